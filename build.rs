@@ -46,9 +46,13 @@ fn main() -> std::io::Result<()> {
 
     let resources_path = env::current_dir()
         .expect("Couldn't find the source directory.")
-        .join("resources")
-        .join("runtime");
-    copy(resources_path, &install_path);
+        .join("core")
+        .join("resources");
+    // quotes in the config directory are read as well as the bundled ones, so copies of those
+    // would come up twice
+    for dir in ["language", "blocklist"] {
+        copy(resources_path.join(dir), install_path.join(dir));
+    }
 
     Ok(())
 }
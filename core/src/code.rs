@@ -0,0 +1,205 @@
+//! Source code split into words which keep its lines and indentation
+
+use crate::test::TestWord;
+
+// Columns a tab indents by
+const TAB_WIDTH: usize = 4;
+
+/// Part of the code a char is in, which the prompt tells apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Syntax {
+    Code,
+    Comment,
+    String,
+}
+
+/// Words of each line in turn, where the first word of a line carries its indentation and
+/// number and the last is followed by Enter. Blank lines are left out, as there's nothing on
+/// them to type.
+pub fn words(text: &str) -> Vec<TestWord> {
+    let mut words = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let mut line_words: Vec<TestWord> = line.split_whitespace().map(TestWord::from).collect();
+        let Some(last) = line_words.last_mut() else {
+            continue;
+        };
+        last.line_end = true;
+        line_words[0].line = Some(number + 1);
+        line_words[0].indent = line
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .map(|c| if c == '\t' { TAB_WIDTH } else { 1 })
+            .sum();
+        words.extend(line_words);
+    }
+    words
+}
+
+/// Mark the comments and strings of words split from code, going by the syntax most languages
+/// share: `//`, `/* */` and `# ` comments, and strings in double quotes or backticks, or in
+/// single quotes closed on the same line
+pub fn highlight(words: &mut [TestWord]) {
+    let mut in_block = false;
+    let mut start = 0;
+    while start < words.len() {
+        let end = words[start..]
+            .iter()
+            .position(|word| word.line_end)
+            .map_or(words.len(), |i| start + i + 1);
+        in_block = highlight_line(&mut words[start..end], in_block);
+        start = end;
+    }
+}
+
+/// Mark the words of one line, returning whether a block comment runs on past its end
+fn highlight_line(words: &mut [TestWord], mut in_block: bool) -> bool {
+    // chars of the line with the word each is in, where a space stands between words
+    let mut chars: Vec<(Option<usize>, char)> = Vec::new();
+    for (i, word) in words.iter().enumerate() {
+        if i > 0 {
+            chars.push((None, ' '));
+        }
+        chars.extend(word.text.chars().map(|c| (Some(i), c)));
+    }
+    let at = |i: usize| chars.get(i).map(|&(_, c)| c);
+
+    let mut syntax = vec![Syntax::Code; chars.len()];
+    let mut quote = None;
+    let mut i = 0;
+    while i < chars.len() {
+        let (c, next) = (chars[i].1, at(i + 1));
+        let after_space = i == 0 || at(i - 1) == Some(' ');
+        if in_block {
+            syntax[i] = Syntax::Comment;
+            if c == '*' && next == Some('/') {
+                syntax[i + 1] = Syntax::Comment;
+                in_block = false;
+                i += 1;
+            }
+        } else if let Some(open) = quote {
+            syntax[i] = Syntax::String;
+            if c == '\\' && next.is_some() {
+                syntax[i + 1] = Syntax::String;
+                i += 1;
+            } else if c == open {
+                quote = None;
+            }
+        } else if (c == '/' && next == Some('/'))
+            || (c == '#' && after_space && matches!(next, None | Some(' ')))
+        {
+            syntax[i..].fill(Syntax::Comment);
+            break;
+        } else if c == '/' && next == Some('*') {
+            syntax[i] = Syntax::Comment;
+            in_block = true;
+        } else if c == '"'
+            || c == '`'
+            // not an apostrophe or a lifetime, which aren't closed
+            || (c == '\''
+                && !i.checked_sub(1).and_then(at).is_some_and(char::is_alphanumeric)
+                && chars[i + 1..].iter().any(|&(_, c)| c == '\''))
+        {
+            syntax[i] = Syntax::String;
+            quote = Some(c);
+        }
+        i += 1;
+    }
+
+    for (&(word, _), syntax) in chars.iter().zip(syntax) {
+        if let Some(word) = word {
+            words[word].syntax.push(syntax);
+        }
+    }
+    in_block
+}
+
+/// Lines a unified diff such as `git diff` prints adds, without their `+`, in the order they
+/// come in
+pub fn added_lines(diff: &str) -> String {
+    let mut added = String::new();
+    // the headers of each file, which come before its first hunk, can start with `+++` too
+    let mut in_hunk = false;
+    for line in diff.lines() {
+        if line.starts_with("diff ") {
+            in_hunk = false;
+        } else if line.starts_with("@@") {
+            in_hunk = true;
+        } else if let Some(line) = line.strip_prefix('+').filter(|_| in_hunk) {
+            added.push_str(line);
+            added.push('\n');
+        }
+    }
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::Test;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    #[test]
+    fn keeps_lines_and_indentation() {
+        let words = words("fn main() {\n\tlet x = 1;\n\n        x\n}\n");
+        let texts: Vec<&str> = words.iter().map(|word| word.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            ["fn", "main()", "{", "let", "x", "=", "1;", "x", "}"]
+        );
+        let ends: Vec<usize> = (0..words.len()).filter(|&i| words[i].line_end).collect();
+        assert_eq!(ends, [2, 6, 7, 8]);
+        assert_eq!(words[3].indent, 4);
+        assert_eq!(words[4].indent, 0);
+        assert_eq!(words[7].indent, 8);
+    }
+
+    #[test]
+    fn highlights_comments_and_strings() {
+        let mut words = words("let s = \"a \\\" b\"; // note\n/* one\ntwo */ x # y\nf('c', 'd)\n");
+        highlight(&mut words);
+        let parts = |word: &TestWord| -> String {
+            word.syntax
+                .iter()
+                .map(|syntax| match syntax {
+                    Syntax::Code => 'c',
+                    Syntax::Comment => '#',
+                    Syntax::String => 's',
+                })
+                .collect()
+        };
+        let parts: Vec<String> = words.iter().map(parts).collect();
+        assert_eq!(
+            parts,
+            [
+                "ccc", "c", "c", "ss", "ss", "ssc", "##", "####", "##", "###", "###", "##", "c",
+                "#", "#", "ccsssc", "ccc"
+            ]
+        );
+        assert_eq!(words[10].line, Some(3));
+    }
+
+    #[test]
+    fn takes_added_lines_from_diffs() {
+        let diff = "diff --git a/x.rs b/x.rs\n--- a/x.rs\n+++ b/x.rs\n@@ -1,2 +1,3 @@\n \
+                    fn x() {\n-    old();\n+    new();\n++++i;\n }\n\\ No newline at end of file\n\
+                    diff --git a/y.rs b/y.rs\nnew file mode 100644\n--- /dev/null\n+++ b/y.rs\n\
+                    @@ -0,0 +1 @@\n+y\n";
+        assert_eq!(added_lines(diff), "    new();\n+++i;\ny\n");
+    }
+
+    #[test]
+    fn lines_end_with_enter() {
+        let mut test = Test::new(words("a b\nc\n"), true, false);
+        let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        for code in [KeyCode::Char('a'), KeyCode::Char(' '), KeyCode::Char('b')] {
+            test.handle_key(press(code));
+        }
+        test.handle_key(press(KeyCode::Char(' ')));
+        assert_eq!(test.current_word, 1);
+        test.handle_key(press(KeyCode::Backspace));
+        test.handle_key(press(KeyCode::Enter));
+        assert_eq!(test.current_word, 2);
+        let submit = test.words[1].events.last().unwrap();
+        assert_eq!((submit.correct, submit.expected), (Some(true), Some('\n')));
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// Words which should never appear in a test
+#[derive(Debug, Default)]
+pub struct Blocklist {
+    words: HashSet<String>,
+}
+
+impl Blocklist {
+    /// Add the words of a blocklist file, one per line, ignoring blank lines and `#` comments
+    pub fn extend_from(&mut self, text: &str) {
+        self.words.extend(
+            text.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_lowercase),
+        );
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Words which are left out, lowercased and in no particular order
+    pub fn words(&self) -> impl Iterator<Item = &str> {
+        self.words.iter().map(String::as_str)
+    }
+
+    /// Whether any word of a language entry is blocked, ignoring case and surrounding punctuation
+    pub fn blocks(&self, entry: &str) -> bool {
+        entry.split(" / ").any(|entry| {
+            let word = entry.split('\t').next().unwrap_or_default();
+            let word = word.trim_matches(|c: char| !c.is_alphanumeric());
+            self.words.contains(&word.to_lowercase())
+        })
+    }
+}
+
+/// Range of word lengths in characters, written like `3..8`, `5..` or `..6`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lengths {
+    pub min: usize,
+    pub max: usize,
+}
+
+impl Lengths {
+    pub fn contains(&self, word: &str) -> bool {
+        (self.min..=self.max).contains(&word.chars().count())
+    }
+}
+
+impl FromStr for Lengths {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bound = |s: &str, default| match s.trim() {
+            "" => Ok(default),
+            s => s.parse().map_err(|_| format!("{} is not a length", s)),
+        };
+        let (min, max) = match s.split_once("..") {
+            Some((min, max)) => (bound(min, 0)?, bound(max, usize::MAX)?),
+            None => {
+                let length = bound(s, 0)?;
+                (length, length)
+            }
+        };
+        if min > max {
+            return Err(format!("{} is longer than {}", min, max));
+        }
+        Ok(Self { min, max })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_length_ranges() {
+        let lengths: Lengths = "3..5".parse().unwrap();
+        assert!(lengths.contains("the") && lengths.contains("étude") && !lengths.contains("to"));
+        assert_eq!(
+            "5..".parse(),
+            Ok(Lengths {
+                min: 5,
+                max: usize::MAX
+            })
+        );
+        assert_eq!("..6".parse(), Ok(Lengths { min: 0, max: 6 }));
+        assert_eq!("4".parse(), Ok(Lengths { min: 4, max: 4 }));
+        assert!("6..3".parse::<Lengths>().is_err());
+        assert!("a..b".parse::<Lengths>().is_err());
+    }
+
+    #[test]
+    fn blocks_listed_words() {
+        let mut blocklist = Blocklist::default();
+        blocklist.extend_from("# comment\n\nDarn\n  heck  \n");
+
+        assert!(blocklist.blocks("darn"));
+        assert!(blocklist.blocks("Heck!"));
+        assert!(blocklist.blocks("\"darn,\""));
+        assert!(blocklist.blocks("heck\texclamation"));
+        assert!(blocklist.blocks("deck\ton a ship / heck\texclamation"));
+        assert!(!blocklist.blocks("darning"));
+        assert!(!blocklist.blocks("comment"));
+        assert!(!blocklist.blocks("# comment"));
+    }
+}
@@ -0,0 +1,261 @@
+//! Fuzzing and benchmarking of the lexers and of how typed keys are lined up with the words of
+//! a test, built with the `harness` feature for working on either
+
+use crate::code;
+use crate::test::{results::Results, Test, TestWord};
+use crate::words;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+
+// pieces random text is put together from, picked for the edge cases they bring out in lexing
+const PIECES: [&str; 24] = [
+    "word",
+    "Ünïcödé",
+    "日本語",
+    "e\u{301}",
+    "🦀",
+    " ",
+    "  ",
+    "\t",
+    "\n",
+    "\r\n",
+    "\n\n",
+    "\u{FEFF}",
+    "\u{200B}",
+    "\u{7}",
+    "//",
+    "/*",
+    "*/",
+    "# ",
+    "\"",
+    "'",
+    "`",
+    "\\",
+    "“quote”",
+    "‘",
+];
+
+/// Input which broke an invariant of a lexer or of the test, with the seed which makes it again
+#[derive(Debug, Clone)]
+pub struct Failure {
+    pub seed: u64,
+    /// Which lexer or part of the test failed
+    pub stage: &'static str,
+    pub input: String,
+    pub problem: String,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} failed on seed {}: {}\ninput: {:?}",
+            self.stage, self.seed, self.problem, self.input
+        )
+    }
+}
+
+/// How long a lexer or the test took over the same input, on average over the runs
+#[derive(Debug, Clone)]
+pub struct Timing {
+    pub stage: &'static str,
+    pub per_run: Duration,
+}
+
+/// Random text made of pieces which tend to trip lexers up
+pub fn text(rng: &mut impl Rng, pieces: usize) -> String {
+    (0..pieces)
+        .map(|_| *PIECES.choose(rng).expect("There are pieces to pick from."))
+        .collect()
+}
+
+/// Keys typing the words with mistakes along the way: wrong, dropped and doubled chars, words
+/// cut short, and corrections with Backspace and Ctrl-W
+pub fn keys(rng: &mut impl Rng, words: &[TestWord]) -> Vec<KeyEvent> {
+    let char_key = |c| KeyEvent::from(KeyCode::Char(c));
+    let mut keys = Vec::new();
+    for word in words {
+        for c in word.text.chars() {
+            match rng.gen_range(0..20) {
+                0 => {}
+                1 => keys.extend([char_key(c), char_key(c)]),
+                2 => keys.push(char_key(rng.gen_range('!'..='~'))),
+                3 => keys.push(KeyEvent::from(KeyCode::Backspace)),
+                4 => keys.push(KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL)),
+                5 => break,
+                _ => keys.push(char_key(c)),
+            }
+        }
+        keys.push(KeyEvent::from(if word.line_end {
+            KeyCode::Enter
+        } else {
+            KeyCode::Char(' ')
+        }));
+    }
+    keys
+}
+
+/// Run one check under a stage's name, turning a panic into a failure
+fn stage<T>(
+    seed: u64,
+    stage: &'static str,
+    input: &str,
+    check: impl FnOnce() -> Result<T, String>,
+) -> Result<T, Failure> {
+    panic::catch_unwind(AssertUnwindSafe(check))
+        .unwrap_or_else(|panic| {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            Err(format!("panicked: {}", message))
+        })
+        .map_err(|problem| Failure {
+            seed,
+            stage,
+            input: input.to_owned(),
+            problem,
+        })
+}
+
+/// Check the lexers and the test against the random input a seed makes
+pub fn check(seed: u64) -> Result<(), Failure> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let pieces = rng.gen_range(0..200);
+    let input = text(&mut rng, pieces);
+
+    stage(seed, "read_list", &input, || {
+        let (list, _) = words::read_list(input.as_bytes());
+        match list.lines().find(|entry| {
+            entry.is_empty() || entry.trim() != *entry || entry.contains(['\r', '\u{FEFF}'])
+        }) {
+            Some(entry) => Err(format!("left the entry {:?} uncleaned", entry)),
+            None => Ok(()),
+        }
+    })?;
+
+    let words = stage(seed, "code::words", &input, || {
+        let words = code::words(&input);
+        if let Some(word) = words
+            .iter()
+            .find(|word| word.text.is_empty() || word.text.contains(char::is_whitespace))
+        {
+            return Err(format!("split off the word {:?}", word.text));
+        }
+        if words.last().is_some_and(|word| !word.line_end) {
+            return Err(String::from("left the last line unended"));
+        }
+        Ok(words)
+    })?;
+
+    stage(seed, "code::highlight", &input, || {
+        let mut words = words.clone();
+        code::highlight(&mut words);
+        match words
+            .iter()
+            .find(|word| word.syntax.len() != word.text.chars().count())
+        {
+            Some(word) => Err(format!("gave {:?} the wrong number of marks", word.text)),
+            None => Ok(()),
+        }
+    })?;
+
+    stage(seed, "align", &input, || {
+        if words.is_empty() {
+            return Ok(());
+        }
+        let mut test = Test::new(words.clone(), true, false);
+        for word in &words {
+            for c in word.text.chars() {
+                test.handle_key(KeyEvent::from(KeyCode::Char(c)));
+            }
+            if !test.complete {
+                test.handle_key(KeyEvent::from(KeyCode::Enter));
+            }
+        }
+        if !test.complete {
+            return Err(String::from(
+                "typing every word right didn't finish the test",
+            ));
+        }
+        if f64::from(Results::from(&test).accuracy.overall) != 1.0 {
+            return Err(String::from(
+                "typing every word right wasn't scored as accurate",
+            ));
+        }
+
+        let mut test = Test::new(words.clone(), true, false);
+        for key in keys(&mut rng, &words) {
+            if test.complete {
+                break;
+            }
+            test.handle_key(key);
+        }
+        let accuracy = f64::from(Results::from(&test).accuracy.overall);
+        if !(0.0..=1.0).contains(&accuracy) && !accuracy.is_nan() {
+            return Err(format!("scored typing with mistakes {} accurate", accuracy));
+        }
+        Ok(())
+    })
+}
+
+/// Check the inputs of a run of seeds, returning the first which fails
+pub fn fuzz(seed: u64, runs: u64) -> Result<(), Failure> {
+    (seed..seed.saturating_add(runs)).try_for_each(check)
+}
+
+/// Time each lexer, and typing with mistakes, over the same random input
+pub fn bench(pieces: usize, runs: u32) -> Vec<Timing> {
+    let mut rng = StdRng::seed_from_u64(0);
+    let input = text(&mut rng, pieces);
+    let words = code::words(&input);
+    let keys = keys(&mut rng, &words);
+
+    let time = |stage, run: &mut dyn FnMut()| {
+        let start = Instant::now();
+        for _ in 0..runs {
+            run();
+        }
+        Timing {
+            stage,
+            per_run: start.elapsed() / runs.max(1),
+        }
+    };
+    vec![
+        time("read_list", &mut || {
+            words::read_list(input.as_bytes());
+        }),
+        time("code::words", &mut || {
+            code::words(&input);
+        }),
+        time("code::highlight", &mut || {
+            code::highlight(&mut words.clone());
+        }),
+        time("align", &mut || {
+            if words.is_empty() {
+                return;
+            }
+            let mut test = Test::new(words.clone(), true, false);
+            for &key in &keys {
+                test.handle_key(key);
+            }
+        }),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzes_cleanly() {
+        if let Err(failure) = fuzz(0, 200) {
+            panic!("{}", failure);
+        }
+    }
+}
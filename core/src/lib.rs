@@ -0,0 +1,29 @@
+//! Test engine of ttyper without its terminal frontend: sampling words from a language,
+//! following keypresses as they're typed, and scoring the test once it's done.
+//!
+//! ```
+//! use ttyper_core::crossterm::event::{KeyCode, KeyEvent};
+//! use ttyper_core::test::{results::Results, Test, TestWord};
+//!
+//! let mut test = Test::new(vec![TestWord::from("hi"), TestWord::from("there")], true, false);
+//! for c in "hi there".chars() {
+//!     test.handle_key(KeyEvent::from(KeyCode::Char(c)));
+//! }
+//! assert!(test.complete);
+//!
+//! let results = Results::from(&test);
+//! assert_eq!(results.words.len(), 2);
+//! assert_eq!(f64::from(results.accuracy.overall), 1.0);
+//! ```
+
+pub mod code;
+pub mod filter;
+#[cfg(feature = "harness")]
+pub mod harness;
+pub mod resources;
+pub mod syllables;
+pub mod test;
+pub mod words;
+
+/// Version of crossterm whose key events tests are typed with
+pub use crossterm;
@@ -0,0 +1,50 @@
+//! Word lists and other data files, bundled into the binary or read from where a package
+//! installed them
+
+use std::borrow::Cow;
+use std::fs;
+use std::path::Path;
+
+#[cfg(feature = "embed-resources")]
+use rust_embed::RustEmbed;
+
+/// Directory resources are installed to, chosen when ttyper is built
+const INSTALL_DIR: &str = match option_env!("TTYPER_RESOURCE_DIR") {
+    Some(dir) => dir,
+    None => "/usr/share/ttyper",
+};
+
+#[cfg(feature = "embed-resources")]
+#[derive(RustEmbed)]
+#[folder = "resources"]
+struct Embedded;
+
+/// Contents of a resource, by a path like `language/english200`
+pub fn get(name: &str) -> Option<Cow<'static, [u8]>> {
+    #[cfg(feature = "embed-resources")]
+    if let Some(file) = Embedded::get(name) {
+        return Some(file.data);
+    }
+    fs::read(Path::new(INSTALL_DIR).join(name))
+        .ok()
+        .map(Cow::Owned)
+}
+
+/// Names of the resources in a directory, like the languages in `language`
+pub fn list(dir: &str) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(Path::new(INSTALL_DIR).join(dir))
+        .into_iter()
+        .flatten()
+        .map_while(Result::ok)
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    #[cfg(feature = "embed-resources")]
+    names.extend(Embedded::iter().filter_map(|name| {
+        name.strip_prefix(dir)?
+            .strip_prefix('/')
+            .map(ToOwned::to_owned)
+    }));
+    names.sort();
+    names.dedup();
+    names
+}
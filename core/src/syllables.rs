@@ -0,0 +1,75 @@
+use hyphenation::{Hyphenator, Language, Load, Standard};
+use std::sync::OnceLock;
+
+// Words shorter than this are never broken across lines
+const MIN_BREAK_CHARS: usize = 8;
+
+/// English hyphenation patterns, which only break words where they read well
+fn hyphenation() -> &'static Standard {
+    static DICTIONARY: OnceLock<Standard> = OnceLock::new();
+    DICTIONARY.get_or_init(|| load().expect("Missing hyphenation patterns."))
+}
+
+#[cfg(feature = "embed-resources")]
+fn load() -> Option<Standard> {
+    Standard::from_embedded(Language::EnglishUS).ok()
+}
+
+/// Patterns installed as a resource, from the hyphenation crate's `en-us.standard.bincode`
+#[cfg(not(feature = "embed-resources"))]
+fn load() -> Option<Standard> {
+    let bytes = crate::resources::get("hyphenation/en-us.standard.bincode")?;
+    Standard::from_reader(Language::EnglishUS, &mut &*bytes).ok()
+}
+
+/// The same patterns allowed to break next to any letter, to find every syllable
+fn syllabification() -> &'static Standard {
+    static DICTIONARY: OnceLock<Standard> = OnceLock::new();
+    DICTIONARY.get_or_init(|| {
+        let mut dictionary = hyphenation().clone();
+        dictionary.minima = (1, 1);
+        dictionary
+    })
+}
+
+/// Estimated number of syllables in a word, counting each run of letters separately
+pub fn count(word: &str) -> usize {
+    word.split(|c: char| !c.is_alphabetic())
+        .filter(|run| !run.is_empty())
+        .map(|run| syllabification().hyphenate(run).breaks.len() + 1)
+        .sum()
+}
+
+/// Char offsets where a long word may be hyphenated at the end of a line
+pub fn breaks(word: &str) -> Vec<usize> {
+    if word.chars().count() < MIN_BREAK_CHARS || !word.chars().all(char::is_alphabetic) {
+        return Vec::new();
+    }
+    hyphenation()
+        .hyphenate(word)
+        .breaks
+        .into_iter()
+        .map(|byte| word[..byte].chars().count())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_syllables() {
+        assert_eq!(count("cat"), 1);
+        assert_eq!(count("typing"), 2);
+        assert_eq!(count("hyphenation"), 4);
+        assert_eq!(count("well-known"), 2);
+        assert_eq!(count("42"), 0);
+    }
+
+    #[test]
+    fn breaks_only_long_words() {
+        assert_eq!(breaks("typing"), Vec::<usize>::new());
+        assert_eq!(breaks("hyphenation"), vec![2, 6, 7]);
+        assert_eq!(breaks("don't-care"), Vec::<usize>::new());
+    }
+}
@@ -0,0 +1,958 @@
+pub mod results;
+
+use crate::code::Syntax;
+use results::{PartialResults, ACCURACY_WINDOW, ROLLING_WINDOW, WPM_PER_CPS};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::{Duration, Instant, SystemTime};
+
+// Longest gap between two presses of a key for the second to be taken as switch chatter
+const CHATTER_THRESHOLD: Duration = Duration::from_millis(15);
+
+/// How strictly mistakes are held against the typist
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Strictness {
+    /// Mistakes are typed over and fixed with Backspace, or left behind
+    #[default]
+    Forgiving,
+    /// The cursor doesn't move past a mistake until the right key is pressed
+    Stop,
+    /// Nothing typed can be deleted, so every mistake stays
+    NoBackspace,
+}
+
+impl Strictness {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Forgiving => "forgiving",
+            Self::Stop => "stop",
+            Self::NoBackspace => "no-backspace",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Self::Forgiving, Self::Stop, Self::NoBackspace]
+            .into_iter()
+            .find(|strictness| strictness.name() == name)
+    }
+}
+
+#[derive(Clone)]
+pub struct TestEvent {
+    /// When the key was pressed on the monotonic clock, which all timing is measured by
+    pub time: Instant,
+    /// When the key was pressed on the wall clock, kept to spot the two clocks drifting apart
+    pub wall: SystemTime,
+    pub key: KeyEvent,
+    pub correct: Option<bool>,
+    /// Char of the text the key was meant to type, if it was meant to type one
+    pub expected: Option<char>,
+}
+
+pub fn is_missed_word_event(event: &TestEvent) -> bool {
+    event.correct != Some(true)
+}
+
+impl fmt::Debug for TestEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestEvent")
+            .field("time", &String::from("Instant { ... }"))
+            .field("wall", &self.wall)
+            .field("key", &self.key)
+            .field("expected", &self.expected)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TestWord {
+    pub text: String,
+    pub definition: Option<String>,
+    pub progress: String,
+    pub events: Vec<TestEvent>,
+    /// Characters of progress removed by backspacing
+    pub deleted: usize,
+    /// Columns of whitespace before the word at the start of its line, which are shown but
+    /// never typed
+    pub indent: usize,
+    /// Whether the word ends its line, so it's followed by Enter instead of a space
+    pub line_end: bool,
+    /// Number of the line of code the word starts, counting from 1
+    pub line: Option<usize>,
+    /// Part of the code each char of the word is in, empty unless it's been highlighted
+    pub syntax: Vec<Syntax>,
+}
+
+impl TestWord {
+    /// Parse a word list entry, which may carry a tab-separated definition or translation
+    pub fn from_entry(entry: &str) -> Self {
+        match entry.split_once('\t') {
+            Some((text, definition)) => TestWord {
+                definition: Some(definition.trim().to_string()).filter(|d| !d.is_empty()),
+                ..Self::from(text)
+            },
+            None => Self::from(entry),
+        }
+    }
+
+    /// Parse a word list entry which may be a group of words separated by ` / `, such as
+    /// easily confused words which are drilled together
+    pub fn from_group(entry: &str) -> Vec<Self> {
+        entry.split(" / ").map(Self::from_entry).collect()
+    }
+}
+
+impl From<String> for TestWord {
+    fn from(string: String) -> Self {
+        TestWord {
+            text: string,
+            definition: None,
+            progress: String::new(),
+            events: Vec::new(),
+            deleted: 0,
+            indent: 0,
+            line_end: false,
+            line: None,
+            syntax: Vec::new(),
+        }
+    }
+}
+
+impl From<&str> for TestWord {
+    fn from(string: &str) -> Self {
+        Self::from(string.to_string())
+    }
+}
+
+/// Whether a straight quote stands for a curly or other typographic quote
+fn stands_for(typed: char, quote: char) -> bool {
+    match typed {
+        '\'' => matches!(quote, '‘' | '’' | 'ʼ' | '‚'),
+        '"' => matches!(quote, '“' | '”' | '„'),
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Test {
+    pub words: Vec<TestWord>,
+    pub current_word: usize,
+    pub complete: bool,
+    pub backtracking_enabled: bool,
+    pub sudden_death_enabled: bool,
+    /// Whether sudden death ends the test at the first error, instead of starting it over
+    pub sudden_death_ends: bool,
+    /// Whether sudden death ended the test at an error
+    pub died: bool,
+    /// Whether typed text is kept out of sight, so mistakes are only seen in the results
+    pub hide_typed: bool,
+    /// Whether definitions and translations are kept out of sight until Tab shows them, so
+    /// learners can test themselves
+    pub hide_definitions: bool,
+    /// Whether more words are added as the test goes on, so it only ends when the typist stops
+    pub endless: bool,
+    /// Words typed correctly after which the test ends, giving an endless test a goal
+    pub stop_after_words: Option<usize>,
+    /// Accuracy from 0 to 1, over the latest keypresses, below which the test ends
+    pub stop_below_accuracy: Option<f64>,
+    /// Number of errors allowed, which is shown counting down
+    pub error_budget: Option<usize>,
+    /// Whether the test ends once the error budget is exceeded
+    pub end_over_budget: bool,
+    /// Presses of each key which came too soon after the last to be intentional
+    pub chatter: BTreeMap<char, usize>,
+    /// Whether chattered keypresses are ignored instead of typed
+    pub filter_chatter: bool,
+    /// Index of the first word and name of each stretch of the test at a difficulty
+    pub segments: Vec<(usize, &'static str)>,
+    /// Whether speed is also scored per syllable, for language learners
+    pub syllables: bool,
+    /// Whether long words may be hyphenated where the prompt wraps
+    pub hyphenate: bool,
+    /// Whether the prompt wraps at exactly its width, splitting words, instead of between them
+    pub soft_wrap: bool,
+    /// Whether straight quotes count as the curly quotes they stand for
+    pub loose_quotes: bool,
+    /// Whether the last word must be submitted with Enter, so it can still be corrected once
+    /// it's typed, instead of the test ending on its last char
+    pub confirm_end: bool,
+    /// Longest gap between keypresses before the test counts as paused
+    pub pause_after: Option<Duration>,
+    /// Time spent paused, which is left out of the timing
+    pub paused: Duration,
+    /// Whether the monotonic and wall clocks are shown with the prompt
+    pub clock_debug: bool,
+    /// Whether speed is kept out of sight, for practice which only looks at accuracy
+    pub hide_speed: bool,
+    /// How long the test runs for from the first keypress, ending mid-word if need be
+    pub time_limit: Option<Duration>,
+    /// Whether the test ended by running out of time
+    pub timed_out: bool,
+    /// Word which the time limit cut off part way through, if any
+    pub cut_off: Option<usize>,
+    /// Whether a word cut off by the time limit is left out of the scoring entirely, instead
+    /// of its keypresses counting towards speed and accuracy
+    pub drop_cut_off: bool,
+    /// Rolling WPM to keep up, below which the stats pulse
+    pub target_wpm: Option<f64>,
+    /// How long the rolling WPM may stay below the target before the stats pulse
+    pub pace_grace: Duration,
+    /// When the rolling WPM last fell below the target
+    pub behind_since: Option<Instant>,
+    /// Who said the quote being typed, for quote tests
+    pub attribution: Option<String>,
+    /// Seed the words were picked with, for seeded tests
+    pub seed: Option<u64>,
+    pub strictness: Strictness,
+    /// Seconds from the first keypress each word of an earlier run of the same words was
+    /// started and finished at, for racing it
+    pub ghost: Option<Vec<(f64, f64)>>,
+    /// Speed of the pace caret, which moves through the text at it from the first keypress
+    pub pace_wpm: Option<f64>,
+}
+
+impl Test {
+    pub fn new(
+        words: Vec<TestWord>,
+        backtracking_enabled: bool,
+        sudden_death_enabled: bool,
+    ) -> Self {
+        Self {
+            words,
+            current_word: 0,
+            complete: false,
+            backtracking_enabled,
+            sudden_death_enabled,
+            sudden_death_ends: false,
+            died: false,
+            hide_typed: false,
+            hide_definitions: false,
+            endless: false,
+            stop_after_words: None,
+            stop_below_accuracy: None,
+            error_budget: None,
+            end_over_budget: false,
+            chatter: BTreeMap::new(),
+            filter_chatter: false,
+            segments: Vec::new(),
+            syllables: false,
+            hyphenate: false,
+            soft_wrap: false,
+            loose_quotes: false,
+            confirm_end: false,
+            pause_after: None,
+            paused: Duration::ZERO,
+            clock_debug: false,
+            hide_speed: false,
+            time_limit: None,
+            timed_out: false,
+            cut_off: None,
+            drop_cut_off: false,
+            target_wpm: None,
+            pace_grace: Duration::ZERO,
+            behind_since: None,
+            attribution: None,
+            seed: None,
+            strictness: Strictness::Forgiving,
+            ghost: None,
+            pace_wpm: None,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        self.handle_key_at(key, Instant::now());
+    }
+
+    /// Handle a key pressed at the given time, such as one replayed from another machine
+    pub fn handle_key_at(&mut self, key: KeyEvent, time: Instant) {
+        if key.kind != KeyEventKind::Press {
+            return;
+        }
+        // time only runs out between keypresses, so it's noticed at the next one
+        if self.time_left_at(time) == Some(Duration::ZERO) {
+            self.time_up();
+            return;
+        }
+
+        // the wall clock reading when the key was pressed, which may have been a while ago
+        let wall = SystemTime::now() - Instant::now().saturating_duration_since(time);
+        // events are timed on a clock which stops while the test is paused
+        let time = time.checked_sub(self.paused).unwrap_or(time);
+        let idle = self
+            .last_event_time()
+            .map(|last| time.saturating_duration_since(last))
+            .filter(|idle| self.pause_after.is_some_and(|limit| *idle > limit));
+
+        let word = &mut self.words[self.current_word];
+        if let KeyCode::Char(c) = key.code {
+            let chattered = word.events.last().is_some_and(|last| {
+                last.key.code == key.code
+                    && last.key.modifiers == key.modifiers
+                    && time.saturating_duration_since(last.time) < CHATTER_THRESHOLD
+            });
+            if chattered {
+                *self.chatter.entry(c).or_default() += 1;
+                if self.filter_chatter {
+                    return;
+                }
+            }
+        }
+
+        // resume as if typing had carried on from the last keypress
+        let time = match idle {
+            Some(idle) => {
+                self.paused += idle;
+                time - idle
+            }
+            None => time,
+        };
+        let last_word = self.current_word == self.words.len() - 1;
+        let word = &mut self.words[self.current_word];
+
+        match key.code {
+            KeyCode::Tab => self.hide_definitions = !self.hide_definitions,
+            // nothing typed can be taken back
+            KeyCode::Backspace | KeyCode::Char('h') | KeyCode::Char('w')
+                if self.strictness == Strictness::NoBackspace
+                    && (key.code == KeyCode::Backspace
+                        || key.modifiers.contains(KeyModifiers::CONTROL)) => {}
+            // a space at the end of a line is typed like any other wrong char
+            KeyCode::Char(' ') | KeyCode::Enter if key.code == KeyCode::Enter || !word.line_end => {
+                if word.text.chars().nth(word.progress.chars().count()) == Some(' ') {
+                    word.progress.push(' ');
+                    word.events.push(TestEvent {
+                        time,
+                        wall,
+                        correct: Some(true),
+                        expected: Some(' '),
+                        key,
+                    })
+                } else if self.confirm_end && last_word && key.code != KeyCode::Enter {
+                    // only Enter ends the test
+                } else if self.strictness == Strictness::Stop
+                    && !word.progress.is_empty()
+                    && word.progress != word.text
+                {
+                    // the word has to be finished before moving on
+                    word.events.push(TestEvent {
+                        time,
+                        wall,
+                        correct: Some(false),
+                        expected: word.text.chars().nth(word.progress.chars().count()),
+                        key,
+                    });
+                } else if !word.progress.is_empty() || word.text.is_empty() {
+                    let correct = word.text == word.progress;
+                    // the rest of the word if it's cut short, or else the space or line break
+                    // after it
+                    let expected = word.text.chars().nth(word.progress.chars().count());
+                    let separator = if word.line_end { '\n' } else { ' ' };
+                    if self.sudden_death_enabled && !correct && !self.sudden_death_ends {
+                        self.reset();
+                    } else {
+                        word.events.push(TestEvent {
+                            time,
+                            wall,
+                            correct: Some(correct),
+                            expected: expected.or(Some(separator)),
+                            key,
+                        });
+                        if self.sudden_death_enabled && !correct {
+                            self.die();
+                        } else {
+                            self.next_word();
+                        }
+                    }
+                }
+            }
+            // CTRL-BackSpace and CTRL-W, where CTRL-BackSpace arrives as CTRL-H unless the
+            // terminal reports keys unambiguously
+            KeyCode::Char('h') | KeyCode::Char('w') | KeyCode::Backspace
+                if key.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                if self.words[self.current_word].progress.is_empty() {
+                    self.last_word();
+                }
+
+                let word = &mut self.words[self.current_word];
+
+                word.events.push(TestEvent {
+                    time,
+                    wall,
+                    correct: None,
+                    expected: None,
+                    key,
+                });
+                word.deleted += word.progress.chars().count();
+                word.progress.clear();
+            }
+            KeyCode::Backspace => {
+                if word.progress.is_empty() && self.backtracking_enabled {
+                    self.last_word();
+                } else {
+                    word.events.push(TestEvent {
+                        time,
+                        wall,
+                        correct: Some(!word.text.starts_with(&word.progress[..])),
+                        expected: None,
+                        key,
+                    });
+                    if word.progress.pop().is_some() {
+                        word.deleted += 1;
+                    }
+                }
+            }
+            KeyCode::Char(c) => {
+                let expected = word.text.chars().nth(word.progress.chars().count());
+                match expected {
+                    // typed as the quote in the text, so it matches exactly from here on
+                    Some(quote) if self.loose_quotes && stands_for(c, quote) => {
+                        word.progress.push(quote)
+                    }
+                    _ => word.progress.push(c),
+                }
+                let correct = word.text.starts_with(&word.progress[..]);
+                // a wrong key is counted, but the cursor waits for the right one
+                if !correct && self.strictness == Strictness::Stop {
+                    word.progress.pop();
+                }
+                if self.sudden_death_enabled && !correct && !self.sudden_death_ends {
+                    self.reset();
+                } else {
+                    word.events.push(TestEvent {
+                        time,
+                        wall,
+                        correct: Some(correct),
+                        expected,
+                        key,
+                    });
+                    if self.sudden_death_enabled && !correct {
+                        self.die();
+                    } else if word.progress == word.text && last_word && !self.confirm_end {
+                        self.complete = true;
+                        self.current_word = 0;
+                    }
+                }
+            }
+            _ => {}
+        };
+
+        if self.end_over_budget
+            && self
+                .remaining_budget()
+                .is_some_and(|remaining| remaining < 0)
+        {
+            self.complete = true;
+            self.current_word = 0;
+        }
+        if !self.complete && self.stop_reached() {
+            self.complete = true;
+            self.current_word = 0;
+        }
+    }
+
+    /// Whether a goal or limit the test stops at has been reached
+    fn stop_reached(&self) -> bool {
+        self.stop_after_words
+            .is_some_and(|words| self.correct_words() >= words)
+            || self.stop_below_accuracy.is_some_and(|min| {
+                self.rolling_accuracy(ACCURACY_WINDOW)
+                    .is_some_and(|accuracy| accuracy < min)
+            })
+    }
+
+    /// Number of words before the current one which were typed exactly
+    pub fn correct_words(&self) -> usize {
+        self.words[..self.current_word]
+            .iter()
+            .filter(|word| word.progress == word.text)
+            .count()
+    }
+
+    /// Whether the last word has been typed and the test only waits for Enter to end
+    pub fn awaiting_confirmation(&self) -> bool {
+        let word = &self.words[self.current_word];
+        self.confirm_end
+            && !self.complete
+            && self.current_word == self.words.len() - 1
+            && word.progress == word.text
+    }
+
+    /// Whether the test has gone longer than its pause limit without a keypress
+    pub fn is_paused(&self) -> bool {
+        let now = Instant::now().checked_sub(self.paused);
+        !self.complete
+            && self
+                .last_event_time()
+                .zip(now)
+                .zip(self.pause_after)
+                .is_some_and(|((last, now), limit)| now.saturating_duration_since(last) > limit)
+    }
+
+    /// Seconds since the first keypress on the monotonic clock, and how far the wall clock has
+    /// run ahead of it, as it does across suspends and clock adjustments
+    pub fn clock_drift(&self) -> Option<(f64, f64)> {
+        let first = self
+            .words
+            .iter()
+            .flat_map(|word| &word.events)
+            .min_by_key(|event| event.time)?;
+        let monotonic = first.time.elapsed().as_secs_f64();
+        let wall = match SystemTime::now().duration_since(first.wall) {
+            Ok(elapsed) => elapsed.as_secs_f64(),
+            Err(behind) => -behind.duration().as_secs_f64(),
+        };
+        Some((monotonic, wall - monotonic))
+    }
+
+    /// Time the test has run for since the first keypress, leaving out pauses
+    fn running_time(&self, now: Instant) -> Option<Duration> {
+        let now = now.checked_sub(self.paused).unwrap_or(now);
+        let first = self.first_event_time()?;
+        let last = self.last_event_time()?;
+        // a pause under way stops the clock at the last keypress, as it's left out once typing
+        // resumes
+        let now = match self.pause_after {
+            Some(limit) if now.saturating_duration_since(last) > limit => last,
+            _ => now,
+        };
+        Some(now.saturating_duration_since(first))
+    }
+
+    /// Time left before the time limit, if there is one
+    pub fn time_left(&self) -> Option<Duration> {
+        self.time_left_at(Instant::now())
+    }
+
+    fn time_left_at(&self, now: Instant) -> Option<Duration> {
+        let limit = self.time_limit?;
+        let ran = self.running_time(now).unwrap_or_default();
+        Some(limit.saturating_sub(ran))
+    }
+
+    /// End the test as time runs out, noting the word it cut off
+    fn time_up(&mut self) {
+        let word = &self.words[self.current_word];
+        if !word.progress.is_empty() && word.progress != word.text {
+            self.cut_off = Some(self.current_word);
+        }
+        self.timed_out = true;
+        self.complete = true;
+        self.current_word = 0;
+    }
+
+    /// Note whether the rolling WPM is below the target, which is checked as time passes
+    pub fn check_pace(&mut self) {
+        let Some(target) = self.target_wpm else {
+            return;
+        };
+        let behind = !self.complete
+            && !self.is_paused()
+            && self
+                .rolling_wpm(ROLLING_WINDOW)
+                .is_some_and(|wpm| wpm < target);
+        if !behind {
+            self.behind_since = None;
+        } else if self.behind_since.is_none() {
+            self.behind_since = Some(Instant::now());
+        }
+    }
+
+    /// How long the rolling WPM has stayed below the target past the grace period
+    pub fn pace_alarm(&self) -> Option<Duration> {
+        self.behind_since?.elapsed().checked_sub(self.pace_grace)
+    }
+
+    /// Word the earlier run being raced has reached and how many of its chars it has typed, as
+    /// if it started at the first keypress of this one
+    pub fn ghost_position(&self) -> Option<(usize, usize)> {
+        self.ghost_position_at(Instant::now())
+    }
+
+    fn ghost_position_at(&self, now: Instant) -> Option<(usize, usize)> {
+        let ghost = self.ghost.as_ref()?;
+        let Some(seconds) = self.running_time(now).map(|time| time.as_secs_f64()) else {
+            return Some((0, 0));
+        };
+        let word = ghost
+            .iter()
+            .rposition(|&(start, _)| start <= seconds)
+            .unwrap_or(0)
+            .min(self.words.len().checked_sub(1)?);
+        let (start, end) = ghost[word];
+        let len = self.words[word].text.chars().count();
+        // the first keypress of a word types its first char, and the rest follow evenly
+        let typed = if seconds < start {
+            0
+        } else if end > start {
+            1 + ((seconds - start) / (end - start) * len.saturating_sub(1) as f64) as usize
+        } else {
+            len
+        };
+        Some((word, typed.min(len)))
+    }
+
+    /// Word the pace caret has reached and how many of its chars it has passed
+    pub fn pace_position(&self) -> Option<(usize, usize)> {
+        self.pace_position_at(Instant::now())
+    }
+
+    fn pace_position_at(&self, now: Instant) -> Option<(usize, usize)> {
+        let wpm = self.pace_wpm?;
+        let seconds = self
+            .running_time(now)
+            .map_or(0.0, |time| time.as_secs_f64());
+        // the space after each word is a char to type like any other
+        let mut chars = (seconds * wpm / WPM_PER_CPS) as usize;
+        for (i, word) in self.words.iter().enumerate() {
+            let len = word.text.chars().count();
+            if chars <= len || i + 1 == self.words.len() {
+                return Some((i, chars.min(len)));
+            }
+            chars -= len + 1;
+        }
+        None
+    }
+
+    fn first_event_time(&self) -> Option<Instant> {
+        self.words
+            .iter()
+            .flat_map(|word| &word.events)
+            .map(|event| event.time)
+            .min()
+    }
+
+    fn last_event_time(&self) -> Option<Instant> {
+        self.words
+            .iter()
+            .flat_map(|word| &word.events)
+            .map(|event| event.time)
+            .max()
+    }
+
+    /// Number of incorrect keypresses so far
+    pub fn errors(&self) -> usize {
+        self.words
+            .iter()
+            .flat_map(|word| &word.events)
+            .filter(|event| event.correct == Some(false))
+            .count()
+    }
+
+    /// Errors left in the budget, which is negative once it has been exceeded
+    pub fn remaining_budget(&self) -> Option<isize> {
+        self.error_budget
+            .map(|budget| budget as isize - self.errors() as isize)
+    }
+
+    fn last_word(&mut self) {
+        if self.current_word != 0 {
+            self.current_word -= 1;
+        }
+    }
+
+    fn next_word(&mut self) {
+        if self.current_word == self.words.len() - 1 {
+            self.complete = true;
+            self.current_word = 0;
+        } else {
+            self.current_word += 1;
+        }
+    }
+
+    /// End the test at an error, as sudden death does
+    fn die(&mut self) {
+        self.died = true;
+        self.complete = true;
+        self.current_word = 0;
+    }
+
+    /// Start the test over with the same words
+    pub fn reset(&mut self) {
+        self.words.iter_mut().for_each(|word: &mut TestWord| {
+            word.progress.clear();
+            word.events.clear();
+            word.deleted = 0;
+        });
+        self.chatter.clear();
+        self.paused = Duration::ZERO;
+        self.cut_off = None;
+        self.behind_since = None;
+        self.current_word = 0;
+        self.timed_out = false;
+        self.died = false;
+        self.complete = false;
+    }
+
+    /// Fresh copy of the test to take again, with the same words in the same order, cut back to
+    /// the last one reached if words were added as it went on
+    pub fn repeat(&self) -> Self {
+        let mut test = self.clone();
+        if test.endless {
+            let reached = test
+                .words
+                .iter()
+                .rposition(|word| !word.events.is_empty())
+                .map_or(1, |last| last + 1);
+            test.words.truncate(reached);
+        }
+        test.reset();
+        test
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeats_timed_tests_up_to_the_words_reached() {
+        let words = ["one", "two", "three", "four", "five"];
+        let mut test = Test::new(words.map(TestWord::from).to_vec(), true, false);
+        test.endless = true;
+        test.time_limit = Some(Duration::from_secs(15));
+        test.attribution = Some(String::from("someone"));
+        test.segments = vec![(0, "easy"), (2, "medium")];
+        for c in "one two th".chars() {
+            test.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        // the time ran out partway through the third word
+        test.timed_out = true;
+        test.complete = true;
+
+        let repeat = test.repeat();
+        let texts: Vec<&str> = repeat.words.iter().map(|word| word.text.as_str()).collect();
+        assert_eq!(texts, ["one", "two", "three"]);
+        assert!(repeat.words.iter().all(|word| word.progress.is_empty()));
+        assert!(!repeat.complete && !repeat.timed_out);
+        assert_eq!(repeat.current_word, 0);
+        assert_eq!(repeat.time_limit, Some(Duration::from_secs(15)));
+        assert_eq!(repeat.attribution.as_deref(), Some("someone"));
+        assert_eq!(repeat.segments, [(0, "easy"), (2, "medium")]);
+
+        // a test of a set number of words keeps them all
+        let mut words_test = Test::new(words.map(TestWord::from).to_vec(), true, false);
+        words_test.handle_key(KeyEvent::from(KeyCode::Char('o')));
+        assert_eq!(words_test.repeat().words.len(), 5);
+    }
+
+    #[test]
+    fn ends_over_the_error_budget() {
+        let mut test = Test::new(
+            vec![TestWord::from("one"), TestWord::from("two")],
+            true,
+            false,
+        );
+        test.error_budget = Some(2);
+        test.end_over_budget = true;
+        for c in "xy".chars() {
+            test.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        // spending the whole budget isn't going over it
+        assert_eq!(test.remaining_budget(), Some(0));
+        assert!(!test.complete);
+
+        test.handle_key(KeyEvent::from(KeyCode::Char('z')));
+        assert_eq!(test.remaining_budget(), Some(-1));
+        assert!(test.complete);
+    }
+
+    #[test]
+    fn leaves_pauses_out_of_the_timing() {
+        let mut test = Test::new(
+            vec![TestWord::from("one"), TestWord::from("two")],
+            true,
+            false,
+        );
+        test.pause_after = Some(Duration::from_secs(2));
+        let start = Instant::now();
+        // a gap of two seconds is kept, and one of five is a pause
+        for (c, ms) in [('o', 0), ('n', 2000), ('e', 7000), (' ', 8000)] {
+            test.handle_key_at(
+                KeyEvent::from(KeyCode::Char(c)),
+                start + Duration::from_millis(ms),
+            );
+        }
+        assert_eq!(test.paused, Duration::from_secs(5));
+
+        let events = &test.words[0].events;
+        let times: Vec<Duration> = events.iter().map(|e| e.time - events[0].time).collect();
+        assert_eq!(times, [0, 2, 2, 3].map(Duration::from_secs));
+
+        let results = results::Results::from(&test);
+        assert_eq!(results.paused, 5.0);
+        assert_eq!(results.duration(), 3.0);
+    }
+
+    #[test]
+    fn waits_for_enter_after_the_last_word() {
+        let mut test = Test::new(
+            vec![TestWord::from("ab"), TestWord::from("cd")],
+            true,
+            false,
+        );
+        test.confirm_end = true;
+        for c in "ab cd ".chars() {
+            test.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        // neither the last char nor a space after it ends the test
+        assert!(!test.complete);
+        assert!(test.awaiting_confirmation());
+        assert_eq!(test.current_word, 1);
+
+        test.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert!(test.complete);
+        assert!(!test.awaiting_confirmation());
+    }
+
+    #[test]
+    fn filters_switch_chatter() {
+        let mut test = Test::new(vec![TestWord::from("aab")], true, false);
+        test.filter_chatter = true;
+        let start = Instant::now();
+        // the second press comes too soon after the first, and the third late enough
+        for (c, ms) in [('a', 0), ('a', 10), ('a', 40), ('b', 45)] {
+            test.handle_key_at(
+                KeyEvent::from(KeyCode::Char(c)),
+                start + Duration::from_millis(ms),
+            );
+        }
+        assert_eq!(test.words[0].progress, "aab");
+        assert_eq!(test.words[0].events.len(), 3);
+        assert_eq!(test.chatter, BTreeMap::from([('a', 1)]));
+        assert!(test.complete);
+    }
+
+    #[test]
+    fn stops_after_enough_correct_words() {
+        let words = ["one", "two", "three", "four"];
+        let mut test = Test::new(words.map(TestWord::from).to_vec(), true, false);
+        test.stop_after_words = Some(2);
+        for c in "one tw ".chars() {
+            test.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        // a word typed wrong doesn't count towards the goal
+        assert_eq!(test.correct_words(), 1);
+        assert!(!test.complete);
+        for c in "three ".chars() {
+            test.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        assert!(test.complete);
+        assert!(test.words[3].events.is_empty());
+    }
+
+    #[test]
+    fn stops_below_the_accuracy_over_the_latest_keypresses() {
+        let mut test = Test::new(vec![TestWord::from("a".repeat(200))], true, false);
+        test.stop_below_accuracy = Some(0.99);
+        // each wrong key is a single event, as the cursor waits for the right one
+        test.strictness = Strictness::Stop;
+        let keys = |test: &mut Test, keys: &str| {
+            keys.chars()
+                .for_each(|c| test.handle_key(KeyEvent::from(KeyCode::Char(c))))
+        };
+        keys(&mut test, &"a".repeat(98));
+        keys(&mut test, "xa");
+        // one wrong in the latest hundred is right at the limit
+        assert_eq!(test.rolling_accuracy(ACCURACY_WINDOW), Some(0.99));
+        assert!(!test.complete);
+        keys(&mut test, "x");
+        assert_eq!(test.rolling_accuracy(ACCURACY_WINDOW), Some(0.98));
+        assert!(test.complete);
+    }
+
+    #[test]
+    fn ends_sudden_death_at_the_first_error() {
+        let words = vec![TestWord::from("one"), TestWord::from("two")];
+        let mut test = Test::new(words, true, true);
+        test.sudden_death_ends = true;
+        for c in "onx".chars() {
+            test.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        // the error ends the test before it can be corrected
+        assert!(test.died && test.complete);
+        assert_eq!(test.words[0].progress, "onx");
+        let results = results::Results::from(&test);
+        assert_eq!(results.sudden_death, Some(results::SuddenDeath::Died));
+
+        // otherwise the test starts over at the error
+        let mut restarting = test.repeat();
+        restarting.sudden_death_ends = false;
+        for c in "onx".chars() {
+            restarting.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        assert!(!restarting.died && !restarting.complete);
+        assert!(restarting.words[0].progress.is_empty());
+        assert!(restarting.words[0].events.is_empty());
+    }
+
+    #[test]
+    fn drops_the_word_cut_off_by_the_time_limit() {
+        let mut test = Test::new(
+            vec![TestWord::from("one"), TestWord::from("two")],
+            true,
+            false,
+        );
+        test.time_limit = Some(Duration::from_secs(1));
+        test.drop_cut_off = true;
+        let start = Instant::now();
+        // the time runs out partway through the second word, with a mistake in it
+        let keys = [
+            ('o', 0),
+            ('n', 100),
+            ('e', 200),
+            (' ', 300),
+            ('t', 400),
+            ('x', 500),
+        ];
+        for (c, ms) in keys.into_iter().chain([('o', 1500)]) {
+            test.handle_key_at(
+                KeyEvent::from(KeyCode::Char(c)),
+                start + Duration::from_millis(ms),
+            );
+        }
+        assert!(test.timed_out);
+        assert_eq!(test.cut_off, Some(1));
+
+        let dropped = results::Results::from(&test);
+        assert_eq!(dropped.accuracy.overall, results::Fraction::new(4, 4));
+        assert_eq!(dropped.timing.per_event.len(), 3);
+        assert_eq!(dropped.raw_wpm(), 3.0 * WPM_PER_CPS);
+
+        test.drop_cut_off = false;
+        let kept = results::Results::from(&test);
+        assert_eq!(kept.accuracy.overall, results::Fraction::new(5, 6));
+        assert_eq!(kept.timing.per_event.len(), 5);
+    }
+
+    #[test]
+    fn ends_when_the_time_limit_runs_out() {
+        let words = ["one", "two", "three"];
+        let mut test = Test::new(words.map(TestWord::from).to_vec(), true, false);
+        test.time_limit = Some(Duration::from_secs(2));
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        for (c, ms) in [('o', 0), ('n', 500), ('e', 1000), (' ', 1500)] {
+            test.handle_key_at(KeyEvent::from(KeyCode::Char(c)), at(ms));
+        }
+        assert_eq!(
+            test.time_left_at(at(1500)),
+            Some(Duration::from_millis(500))
+        );
+        assert!(!test.complete);
+
+        // the key pressed after the time ran out ends the test without being typed
+        test.handle_key_at(KeyEvent::from(KeyCode::Char('t')), at(2500));
+        assert_eq!(test.time_left_at(at(2500)), Some(Duration::ZERO));
+        assert!(test.complete && test.timed_out);
+        assert!(test.words[1].events.is_empty());
+
+        let results = results::Results::from(&test);
+        assert_eq!(results.time_limit, Some(2.0));
+        assert_eq!(results.duration(), 2.0);
+        assert_eq!(results.timing.overall_cps, 1.5);
+    }
+}
@@ -0,0 +1,771 @@
+use super::{is_missed_word_event, Strictness, Test, TestWord};
+use crate::syllables;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Instant, SystemTime};
+use std::{cmp, fmt};
+
+// Convert CPS to WPM (clicks per second)
+pub const WPM_PER_CPS: f64 = 12.0;
+
+// Seconds of typing covered by the rolling WPM of endless mode
+pub const ROLLING_WINDOW: f64 = 30.0;
+
+/// Latest keypresses the live accuracy of an endless test is measured over
+pub const ACCURACY_WINDOW: usize = 100;
+
+// Seconds of the sprint measured by burst WPM
+pub const BURST_WINDOW: f64 = 10.0;
+
+// Width of the moving average window for the WPM chart
+const WPM_SMA_WIDTH: usize = 10;
+
+// Keypresses in each window whose speeds are compared to measure consistency
+const CONSISTENCY_WINDOW: usize = 10;
+
+// Columns of each letter row typed by the left hand
+const HAND_COLUMNS: usize = 5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Fraction {
+    pub numerator: usize,
+    pub denominator: usize,
+}
+
+impl Fraction {
+    pub const fn new(numerator: usize, denominator: usize) -> Self {
+        Self {
+            numerator,
+            denominator,
+        }
+    }
+}
+
+impl From<Fraction> for f64 {
+    fn from(f: Fraction) -> Self {
+        f.numerator as f64 / f.denominator as f64
+    }
+}
+
+impl cmp::Ord for Fraction {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        f64::from(*self).partial_cmp(&f64::from(*other)).unwrap()
+    }
+}
+
+impl PartialOrd for Fraction {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Fraction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+pub trait PartialResults {
+    fn progress(&self) -> Fraction;
+
+    /// WPM over the last few seconds, or since the first keypress if that was more recent
+    fn rolling_wpm(&self, window: f64) -> Option<f64>;
+
+    /// Accuracy from 0 to 1 of the latest keypresses, once there have been that many
+    fn rolling_accuracy(&self, keypresses: usize) -> Option<f64>;
+}
+
+impl PartialResults for Test {
+    fn progress(&self) -> Fraction {
+        Fraction {
+            numerator: self.current_word + 1,
+            denominator: self.words.len(),
+        }
+    }
+
+    fn rolling_wpm(&self, window: f64) -> Option<f64> {
+        let now = Instant::now();
+        let now = now.checked_sub(self.paused).unwrap_or(now);
+        let events: Vec<&super::TestEvent> =
+            self.words.iter().flat_map(|w| w.events.iter()).collect();
+        let start = events.iter().map(|event| event.time).min()?;
+        let span = now.duration_since(start).as_secs_f64().min(window);
+        // too soon to say anything meaningful
+        if span < 1.0 {
+            return None;
+        }
+
+        let recent: Vec<&&super::TestEvent> = events
+            .iter()
+            .filter(|event| now.duration_since(event.time).as_secs_f64() <= span)
+            .collect();
+        Some(window_wpm(recent.iter().map(|event| event.correct), span))
+    }
+
+    fn rolling_accuracy(&self, keypresses: usize) -> Option<f64> {
+        // a word backtracked to is typed after those following it, so events go by time
+        let mut events: Vec<&super::TestEvent> = self
+            .words
+            .iter()
+            .flat_map(|w| w.events.iter())
+            .filter(|event| event.correct.is_some())
+            .collect();
+        if events.len() < keypresses.max(1) {
+            return None;
+        }
+        events.sort_by_key(|event| event.time);
+        let recent = &events[events.len() - keypresses.max(1)..];
+        let correct = recent
+            .iter()
+            .filter(|event| event.correct == Some(true))
+            .count();
+        Some(f64::from(Fraction::new(correct, recent.len())))
+    }
+}
+
+pub struct TimingData {
+    // Instead of storing WPM, we store CPS (clicks per second)
+    pub overall_cps: f64,
+    pub per_event: Vec<f64>,
+    pub per_key: HashMap<KeyEvent, f64>,
+}
+
+pub struct AccuracyData {
+    pub overall: Fraction,
+    pub per_key: HashMap<KeyEvent, Fraction>,
+    /// Keypresses which typed each char of the text correctly, out of all meant to type it
+    pub per_expected: HashMap<char, Fraction>,
+}
+
+/// Keystrokes spent undoing mistakes
+pub struct CorrectionData {
+    /// Presses of backspace and the word-deleting shortcuts
+    pub backspaces: usize,
+    pub deleted: usize,
+    pub keystrokes: usize,
+}
+
+impl CorrectionData {
+    /// Share of keystrokes which were deleted or did the deleting
+    pub fn wasted(&self) -> Fraction {
+        Fraction::new(self.backspaces + self.deleted, self.keystrokes)
+    }
+}
+
+/// One keypress, timed from the first keypress of the test
+pub struct Keystroke {
+    pub time: f64,
+    /// When the key was pressed on the wall clock, if that was kept
+    pub wall: Option<SystemTime>,
+    pub key: KeyEvent,
+    pub correct: Option<bool>,
+    /// Char of the text the key was meant to type, if it was meant to type one
+    pub expected: Option<char>,
+}
+
+/// How one word of the test was typed
+pub struct WordResult {
+    pub text: String,
+    /// Columns of whitespace shown before the word, and whether it ends its line, as in code
+    pub indent: usize,
+    pub line_end: bool,
+    pub errors: usize,
+    /// Average seconds between the word's keypresses, if it took more than one
+    pub seconds_per_key: Option<f64>,
+    /// Seconds from the test's first keypress to the word's first and last
+    pub start: f64,
+    pub end: f64,
+}
+
+impl WordResult {
+    /// Speed the word was typed at between its first and last keypress
+    pub fn wpm(&self) -> Option<f64> {
+        self.seconds_per_key.map(|seconds| WPM_PER_CPS / seconds)
+    }
+}
+
+/// How a word went over every time it was typed, in one test or across many
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WordStats {
+    pub text: String,
+    /// Times the word was typed
+    pub typed: usize,
+    pub errors: usize,
+    // seconds between keypresses summed over the times the word took more than one, and how
+    // many times those were
+    seconds_per_key: f64,
+    timed: usize,
+}
+
+impl WordStats {
+    /// Count another time the word was typed
+    pub fn add(&mut self, word: &WordResult) {
+        self.typed += 1;
+        self.errors += word.errors;
+        if let Some(seconds) = word.seconds_per_key {
+            self.seconds_per_key += seconds;
+            self.timed += 1;
+        }
+    }
+
+    /// Average speed the word was typed at, if any time it was took more than one keypress
+    pub fn wpm(&self) -> Option<f64> {
+        (self.timed > 0).then(|| WPM_PER_CPS * self.timed as f64 / self.seconds_per_key)
+    }
+}
+
+/// Stats of each word among those typed, in the order each was first typed
+pub fn word_stats<'a>(words: impl IntoIterator<Item = &'a WordResult>) -> Vec<WordStats> {
+    let mut stats: Vec<WordStats> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for word in words {
+        let i = *index.entry(&word.text).or_insert_with(|| {
+            stats.push(WordStats {
+                text: word.text.clone(),
+                ..WordStats::default()
+            });
+            stats.len() - 1
+        });
+        stats[i].add(word);
+    }
+    stats
+}
+
+/// How a test in sudden death mode went
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuddenDeath {
+    /// Finished without an error
+    Survived,
+    /// Ended at the first error
+    Died,
+}
+
+impl SuddenDeath {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Survived => "survived",
+            Self::Died => "died",
+        }
+    }
+}
+
+/// How one second of a test was typed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Second {
+    /// Seconds from the first keypress to the end of this one
+    pub end: f64,
+    /// Speed of the keypresses made during this second, ignoring accuracy
+    pub raw_wpm: f64,
+    /// Speed of the test up to the end of this second, adjusted for accuracy
+    pub wpm: f64,
+    /// Wrong keypresses made during this second
+    pub errors: usize,
+}
+
+/// Hand which types a key, going by which half of its letter row it's on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+impl Hand {
+    /// Hand typing a char on a layout with the given letter rows, if it's on one of them
+    pub fn on(rows: &[&str], c: char) -> Option<Self> {
+        let c = c.to_lowercase().next()?;
+        rows.iter().find_map(|row| {
+            let column = row.chars().position(|key| key == c)?;
+            Some(if column < HAND_COLUMNS {
+                Self::Left
+            } else {
+                Self::Right
+            })
+        })
+    }
+}
+
+/// Accuracy on capital letters by the Shift key taking them: the left one for letters of the
+/// right hand, and the right one for the left hand's
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shifts {
+    pub left: Fraction,
+    pub right: Fraction,
+}
+
+/// Chars which come in pairs around code and quotations, including their curly quotes
+pub const DELIMITERS: &str = "()[]{}<>\"'`“”‘’«»";
+
+/// Accuracy and speed on paired delimiters and quotes, which trip up programmers most
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Delimiters {
+    pub accuracy: Fraction,
+    /// Speed of the correct ones from the keypress before, missing if none were timed
+    pub wpm: Option<f64>,
+}
+
+/// Speed of correct pairs of keypresses in a row, typed by both hands in turn or by one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Alternation {
+    pub alternating_wpm: f64,
+    pub same_hand_wpm: f64,
+}
+
+pub struct Results {
+    pub timing: TimingData,
+    pub accuracy: AccuracyData,
+    pub corrections: CorrectionData,
+    /// Every keypress in the order it happened
+    pub keystrokes: Vec<Keystroke>,
+    pub missed_words: Vec<String>,
+    /// Words which were typed, in test order
+    pub words: Vec<WordResult>,
+    /// Keypresses of each key which were likely switch chatter
+    pub chatter: BTreeMap<char, usize>,
+    /// Keypresses before each difficulty segment which was reached, and its name
+    pub segments: Vec<(usize, &'static str)>,
+    /// Syllables in the correctly typed words, if the test was scored by syllable
+    pub syllables: Option<usize>,
+    /// Seconds the test was paused for, which are left out of its timing
+    pub paused: f64,
+    /// Seconds the test was limited to, if it ran out of time
+    pub time_limit: Option<f64>,
+    /// Who said the quote typed, for quote tests
+    pub attribution: Option<String>,
+    /// How the test went, for sudden death tests
+    pub sudden_death: Option<SuddenDeath>,
+    /// Seed the words were picked with, which picks the same words again
+    pub seed: Option<u64>,
+    pub strictness: Strictness,
+}
+
+impl From<&Test> for Results {
+    fn from(test: &Test) -> Self {
+        // a word cut off by the time limit may be left out of the scoring entirely
+        let scored: Vec<&TestWord> = if test.drop_cut_off {
+            finished_words(test).collect()
+        } else {
+            test.words.iter().collect()
+        };
+        let events: Vec<&super::TestEvent> = scored.iter().flat_map(|w| w.events.iter()).collect();
+
+        let time_limit = test
+            .time_limit
+            .filter(|_| test.timed_out)
+            .map(|limit| limit.as_secs_f64());
+        let mut timing = calc_timing(&events);
+        // speed is over the whole time allowed, which runs on after the last keypress
+        if let Some(limit) = time_limit {
+            timing.overall_cps = timing.per_event.len() as f64 / limit;
+        }
+
+        Self {
+            timing,
+            accuracy: calc_accuracy(&events),
+            corrections: calc_corrections(&scored, &events),
+            keystrokes: calc_keystrokes(&events),
+            missed_words: calc_missed_words(test),
+            words: calc_words(test),
+            chatter: test.chatter.clone(),
+            segments: calc_segments(test, &events),
+            syllables: test.syllables.then(|| calc_syllables(test)),
+            paused: test.paused.as_secs_f64(),
+            time_limit,
+            attribution: test.attribution.clone(),
+            seed: test.seed,
+            strictness: test.strictness,
+            sudden_death: test.sudden_death_enabled.then_some(if test.died {
+                SuddenDeath::Died
+            } else {
+                SuddenDeath::Survived
+            }),
+        }
+    }
+}
+
+impl Results {
+    /// Words per minute, adjusted for accuracy
+    pub fn wpm(&self) -> f64 {
+        self.raw_wpm() * f64::from(self.accuracy.overall)
+    }
+
+    /// Words per minute, ignoring accuracy
+    pub fn raw_wpm(&self) -> f64 {
+        self.timing.overall_cps * WPM_PER_CPS
+    }
+
+    /// Moving average of WPM, against the number of keypresses so far
+    pub fn wpm_sma(&self) -> Vec<(f64, f64)> {
+        self.timing
+            .per_event
+            .windows(WPM_SMA_WIDTH)
+            .enumerate()
+            .map(|(i, window)| {
+                (
+                    (i + WPM_SMA_WIDTH) as f64,
+                    window.len() as f64 / window.iter().copied().sum::<f64>() * WPM_PER_CPS,
+                )
+            })
+            .collect()
+    }
+
+    /// Seconds from the first to the last keypress, or the time allowed if it ran out
+    pub fn duration(&self) -> f64 {
+        self.time_limit
+            .unwrap_or_else(|| self.timing.per_event.iter().sum())
+    }
+
+    /// Steadiness of typing speed, from 0 to 1: one minus the coefficient of variation of WPM
+    /// over windows of a few keypresses
+    pub fn consistency(&self) -> f64 {
+        let speeds: Vec<f64> = self
+            .timing
+            .per_event
+            .windows(CONSISTENCY_WINDOW)
+            .map(|window| window.len() as f64 / window.iter().sum::<f64>())
+            .filter(|cps| cps.is_finite())
+            .collect();
+        if speeds.len() < 2 {
+            return 1.0;
+        }
+        let mean = speeds.iter().sum::<f64>() / speeds.len() as f64;
+        let variance = speeds.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / speeds.len() as f64;
+        (1.0 - variance.sqrt() / mean).clamp(0.0, 1.0)
+    }
+
+    /// Syllables typed correctly per minute, if the test was scored by syllable
+    pub fn syllables_per_minute(&self) -> Option<f64> {
+        let minutes = self.duration() / 60.0;
+        self.syllables
+            .filter(|_| minutes > 0.0)
+            .map(|syllables| syllables as f64 / minutes)
+    }
+
+    /// Best WPM over any stretch of the given number of seconds, if the test lasted that long
+    pub fn peak_wpm(&self, window: f64) -> Option<f64> {
+        let mut start = 0;
+        self.keystrokes
+            .iter()
+            .enumerate()
+            .filter(|(_, end)| end.time >= window)
+            .map(|(i, end)| {
+                while self.keystrokes[start].time < end.time - window {
+                    start += 1;
+                }
+                let keystrokes = &self.keystrokes[start..=i];
+                window_wpm(keystrokes.iter().map(|k| k.correct), window)
+            })
+            .reduce(f64::max)
+    }
+
+    /// Each second of typing in turn, where the last runs to the last keypress, taking in any
+    /// fraction of a second too short to measure on its own
+    pub fn seconds(&self) -> Vec<Second> {
+        let last = self
+            .keystrokes
+            .last()
+            .map_or(0.0, |keystroke| keystroke.time);
+        if last <= 0.0 {
+            return Vec::new();
+        }
+        let count = (last.round() as usize).max(1);
+        let mut typed = 0;
+        (1..=count)
+            .map(|second| {
+                let start = typed;
+                let end = if second == count { last } else { second as f64 };
+                // the first keypress is at zero, the start of the first second
+                while self
+                    .keystrokes
+                    .get(typed)
+                    .is_some_and(|keystroke| keystroke.time <= end)
+                {
+                    typed += 1;
+                }
+                let during = &self.keystrokes[start..typed];
+                Second {
+                    end,
+                    raw_wpm: during.len() as f64 / (end - (second - 1) as f64) * WPM_PER_CPS,
+                    wpm: window_wpm(self.keystrokes[..typed].iter().map(|k| k.correct), end),
+                    errors: during
+                        .iter()
+                        .filter(|keystroke| keystroke.correct == Some(false))
+                        .count(),
+                }
+            })
+            .collect()
+    }
+
+    /// Speed of letters typed by alternating hands against letters typed by the same hand, on a
+    /// layout with the given letter rows, if the test had both
+    pub fn alternation(&self, rows: &[&str]) -> Option<Alternation> {
+        // total seconds and count of the pairs typed by alternating hands, then the same hand
+        let mut pairs = [(0.0, 0); 2];
+        let hand = |keystroke: &Keystroke| {
+            keystroke
+                .expected
+                .filter(|_| keystroke.correct == Some(true))
+                .and_then(|c| Hand::on(rows, c))
+        };
+        for pair in self.keystrokes.windows(2) {
+            let (Some(first), Some(second)) = (hand(&pair[0]), hand(&pair[1])) else {
+                continue;
+            };
+            let pair_time = &mut pairs[usize::from(first == second)];
+            pair_time.0 += pair[1].time - pair[0].time;
+            pair_time.1 += 1;
+        }
+        let wpm = |(seconds, count): (f64, usize)| {
+            (count > 0 && seconds > 0.0).then(|| count as f64 / seconds * WPM_PER_CPS)
+        };
+        Some(Alternation {
+            alternating_wpm: wpm(pairs[0])?,
+            same_hand_wpm: wpm(pairs[1])?,
+        })
+    }
+
+    /// Accuracy on capital letters by the Shift key taking them, on a layout with the given
+    /// letter rows, if the test had any. Terminals don't say which Shift was pressed, so each is
+    /// taken to be the one opposite the letter's hand.
+    pub fn shifts(&self, rows: &[&str]) -> Option<Shifts> {
+        let mut shifts = Shifts {
+            left: Fraction::new(0, 0),
+            right: Fraction::new(0, 0),
+        };
+        for keystroke in &self.keystrokes {
+            let (Some(expected), Some(correct)) = (keystroke.expected, keystroke.correct) else {
+                continue;
+            };
+            if !expected.is_uppercase() {
+                continue;
+            }
+            let accuracy = match Hand::on(rows, expected) {
+                Some(Hand::Left) => &mut shifts.right,
+                Some(Hand::Right) => &mut shifts.left,
+                None => continue,
+            };
+            accuracy.denominator += 1;
+            accuracy.numerator += usize::from(correct);
+        }
+        (shifts.left.denominator + shifts.right.denominator > 0).then_some(shifts)
+    }
+
+    /// Accuracy and speed of the keypresses meant to type paired delimiters and quotes, if the
+    /// test had any
+    pub fn delimiters(&self) -> Option<Delimiters> {
+        let mut accuracy = Fraction::new(0, 0);
+        let (mut seconds, mut timed) = (0.0, 0);
+        for (i, keystroke) in self.keystrokes.iter().enumerate() {
+            let (Some(expected), Some(correct)) = (keystroke.expected, keystroke.correct) else {
+                continue;
+            };
+            if !DELIMITERS.contains(expected) {
+                continue;
+            }
+            accuracy.denominator += 1;
+            accuracy.numerator += usize::from(correct);
+            if let Some(before) = i.checked_sub(1).filter(|_| correct) {
+                seconds += keystroke.time - self.keystrokes[before].time;
+                timed += 1;
+            }
+        }
+        (accuracy.denominator > 0).then(|| Delimiters {
+            accuracy,
+            wpm: (seconds > 0.0).then(|| timed as f64 / seconds * WPM_PER_CPS),
+        })
+    }
+}
+
+/// Accuracy-adjusted WPM of keypresses made over the given number of seconds
+fn window_wpm(correct: impl Iterator<Item = Option<bool>>, seconds: f64) -> f64 {
+    let (mut keys, mut accuracy) = (0, Fraction::new(0, 0));
+    for correct in correct {
+        keys += 1;
+        if let Some(correct) = correct {
+            accuracy.denominator += 1;
+            accuracy.numerator += usize::from(correct);
+        }
+    }
+    keys as f64 / seconds * WPM_PER_CPS * f64::from(accuracy)
+}
+
+fn calc_timing(events: &[&super::TestEvent]) -> TimingData {
+    let mut timing = TimingData {
+        overall_cps: -1.0,
+        per_event: Vec::new(),
+        per_key: HashMap::new(),
+    };
+
+    // map of keys to a two-tuple (total time, clicks) for counting average
+    let mut keys: HashMap<KeyEvent, (f64, usize)> = HashMap::new();
+
+    for win in events.windows(2) {
+        let event_dur = win[1]
+            .time
+            .checked_duration_since(win[0].time)
+            .map(|d| d.as_secs_f64());
+
+        if let Some(event_dur) = event_dur {
+            timing.per_event.push(event_dur);
+
+            let key = keys.entry(win[1].key).or_insert((0.0, 0));
+            key.0 += event_dur;
+            key.1 += 1;
+        }
+    }
+
+    timing.per_key = keys
+        .into_iter()
+        .map(|(key, (total, count))| (key, total / count as f64))
+        .collect();
+
+    timing.overall_cps = timing.per_event.len() as f64 / timing.per_event.iter().sum::<f64>();
+
+    timing
+}
+
+fn calc_accuracy(events: &[&super::TestEvent]) -> AccuracyData {
+    let mut acc = AccuracyData {
+        overall: Fraction::new(0, 0),
+        per_key: HashMap::new(),
+        per_expected: HashMap::new(),
+    };
+
+    events
+        .iter()
+        .filter(|event| event.correct.is_some())
+        .for_each(|event| {
+            let key = acc
+                .per_key
+                .entry(event.key)
+                .or_insert_with(|| Fraction::new(0, 0));
+
+            acc.overall.denominator += 1;
+            key.denominator += 1;
+
+            if event.correct.unwrap() {
+                acc.overall.numerator += 1;
+                key.numerator += 1;
+            }
+
+            if let Some(expected) = event.expected {
+                let expected = acc
+                    .per_expected
+                    .entry(expected)
+                    .or_insert_with(|| Fraction::new(0, 0));
+                expected.denominator += 1;
+                if event.correct == Some(true) {
+                    expected.numerator += 1;
+                }
+            }
+        });
+
+    acc
+}
+
+fn calc_corrections(words: &[&TestWord], events: &[&super::TestEvent]) -> CorrectionData {
+    CorrectionData {
+        backspaces: events
+            .iter()
+            .filter(|event| match event.key.code {
+                KeyCode::Backspace => true,
+                KeyCode::Char('h') | KeyCode::Char('w') => {
+                    event.key.modifiers.contains(KeyModifiers::CONTROL)
+                }
+                _ => false,
+            })
+            .count(),
+        deleted: words.iter().map(|word| word.deleted).sum(),
+        keystrokes: events.len(),
+    }
+}
+
+fn calc_keystrokes(events: &[&super::TestEvent]) -> Vec<Keystroke> {
+    // events are grouped by word, which is out of order after backtracking
+    let mut events = events.to_vec();
+    events.sort_by_key(|event| event.time);
+
+    let Some(start) = events.first().map(|event| event.time) else {
+        return Vec::new();
+    };
+    events
+        .iter()
+        .map(|event| Keystroke {
+            time: event.time.duration_since(start).as_secs_f64(),
+            wall: Some(event.wall),
+            key: event.key,
+            correct: event.correct,
+            expected: event.expected,
+        })
+        .collect()
+}
+
+/// Words typed through to the end, leaving out one cut off by the time limit
+fn finished_words(test: &Test) -> impl Iterator<Item = &TestWord> {
+    test.words
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| test.cut_off != Some(*i))
+        .map(|(_, word)| word)
+}
+
+fn calc_missed_words(test: &Test) -> Vec<String> {
+    finished_words(test)
+        .filter(|word| word.events.iter().any(is_missed_word_event))
+        .map(|word| word.text.clone())
+        .collect()
+}
+
+fn calc_words(test: &Test) -> Vec<WordResult> {
+    let Some(started) = test
+        .words
+        .iter()
+        .flat_map(|word| &word.events)
+        .map(|event| event.time)
+        .min()
+    else {
+        return Vec::new();
+    };
+    let since_start = |time: Instant| time.duration_since(started).as_secs_f64();
+
+    finished_words(test)
+        .filter_map(|word| {
+            let first = word.events.iter().map(|event| event.time).min()?;
+            let last = word.events.iter().map(|event| event.time).max()?;
+            Some(WordResult {
+                text: word.text.clone(),
+                indent: word.indent,
+                line_end: word.line_end,
+                errors: word
+                    .events
+                    .iter()
+                    .filter(|event| event.correct == Some(false))
+                    .count(),
+                seconds_per_key: (word.events.len() > 1).then(|| {
+                    last.duration_since(first).as_secs_f64() / (word.events.len() - 1) as f64
+                }),
+                start: since_start(first),
+                end: since_start(last),
+            })
+        })
+        .collect()
+}
+
+fn calc_syllables(test: &Test) -> usize {
+    test.words
+        .iter()
+        .filter(|word| !word.events.is_empty() && word.progress == word.text)
+        .map(|word| syllables::count(&word.text))
+        .sum()
+}
+
+fn calc_segments(test: &Test, events: &[&super::TestEvent]) -> Vec<(usize, &'static str)> {
+    test.segments
+        .iter()
+        .filter_map(|&(word, name)| {
+            let start = test.words.get(word)?.events.iter().map(|e| e.time).min()?;
+            let before = events.iter().filter(|event| event.time < start).count();
+            Some((before, name))
+        })
+        .collect()
+}
@@ -0,0 +1,252 @@
+//! Words of a test, sampled from a language
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::HashMap;
+use std::fmt;
+
+/// How words are drawn from a language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Sampling {
+    /// Shuffle the language, repeating it if more words are requested than it contains
+    Shuffle,
+    /// Never repeat a word within a test, shortening the test if the language is too small
+    Unique,
+    /// Deal every word in the language across consecutive tests before repeating any
+    Deck,
+    /// Draw words as often as they're used in real text, going by the language's frequency file
+    /// or else by Zipf's law
+    Frequency,
+}
+
+/// Entries of a language for one test, in a random order.
+///
+/// With [`Sampling::Deck`], the `deck` holds the words not yet dealt, and should be kept
+/// between tests.
+pub fn sample(
+    mut language: Vec<&str>,
+    count: usize,
+    sampling: Sampling,
+    deck: &mut Vec<String>,
+    rng: &mut impl Rng,
+) -> Vec<String> {
+    if sampling == Sampling::Frequency {
+        return weighted(&language, &zipf(language.len()), count, rng);
+    }
+    language.shuffle(rng);
+
+    let mut contents: Vec<String> = match sampling {
+        Sampling::Shuffle => language
+            .into_iter()
+            .cycle()
+            .take(count)
+            .map(ToOwned::to_owned)
+            .collect(),
+        Sampling::Unique => language
+            .into_iter()
+            .take(count)
+            .map(ToOwned::to_owned)
+            .collect(),
+        // drawn above, before the order of the language is lost
+        Sampling::Frequency => unreachable!(),
+        Sampling::Deck => {
+            let mut contents = Vec::with_capacity(count);
+            while contents.len() < count && !language.is_empty() {
+                // refill the deck once every word has been dealt
+                if deck.is_empty() {
+                    deck.extend(language.iter().map(|&w| w.to_owned()));
+                    deck.shuffle(rng);
+                }
+                contents.extend(deck.pop());
+            }
+            contents
+        }
+    };
+    contents.shuffle(rng);
+    contents
+}
+
+/// Problems with a word list which were fixed as it was read
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Repairs {
+    /// Whether bytes which weren't UTF-8 were replaced
+    pub invalid_utf8: bool,
+    /// Control and zero-width characters removed, which would make words that can't be seen
+    pub hidden_chars: usize,
+}
+
+impl Repairs {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Display for Repairs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut repairs = Vec::new();
+        if self.invalid_utf8 {
+            repairs.push(String::from("replaced text which wasn't UTF-8"));
+        }
+        if self.hidden_chars > 0 {
+            repairs.push(format!(
+                "removed {} hidden character{}",
+                self.hidden_chars,
+                if self.hidden_chars == 1 { "" } else { "s" }
+            ));
+        }
+        f.write_str(&repairs.join(" and "))
+    }
+}
+
+/// Whether a character can't be seen or typed, apart from the tab which separates definitions
+fn is_hidden(c: char) -> bool {
+    (c.is_control() && c != '\t') || matches!(c, '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}')
+}
+
+/// Read a word list leniently, so a file saved by any editor gives one entry per line: invalid
+/// UTF-8 is replaced, a byte order mark, carriage returns and hidden characters are removed, and
+/// blank lines are dropped
+pub fn read_list(bytes: &[u8]) -> (String, Repairs) {
+    let text = String::from_utf8_lossy(bytes);
+    let mut repairs = Repairs {
+        invalid_utf8: matches!(text, std::borrow::Cow::Owned(_)),
+        hidden_chars: 0,
+    };
+
+    let mut list = String::with_capacity(text.len());
+    // a byte order mark only says how the file is encoded, so it isn't counted
+    for line in text.trim_start_matches('\u{FEFF}').lines() {
+        let entry: String = line
+            .chars()
+            .filter(|&c| {
+                let hidden = is_hidden(c) && c != '\r';
+                repairs.hidden_chars += usize::from(hidden);
+                !is_hidden(c)
+            })
+            .collect();
+        let entry = entry.trim();
+        if !entry.is_empty() {
+            list.push_str(entry);
+            list.push('\n');
+        }
+    }
+    (list, repairs)
+}
+
+/// Weights of entries ranked from most to least common, the `n`th being used `1/n` as often as the
+/// first
+pub fn zipf(size: usize) -> Vec<f64> {
+    (1..=size).map(|rank| 1.0 / rank as f64).collect()
+}
+
+/// Entries drawn independently, each in proportion to its weight. Nothing is drawn if no entry
+/// has any weight.
+pub fn weighted(
+    language: &[&str],
+    weights: &[f64],
+    count: usize,
+    rng: &mut impl Rng,
+) -> Vec<String> {
+    let Ok(index) = WeightedIndex::new(weights) else {
+        return Vec::new();
+    };
+    (0..count)
+        .map(|_| language[index.sample(rng)].to_owned())
+        .collect()
+}
+
+/// Parse a frequency list, which gives a word and how often it's used, separated by
+/// whitespace, on each line
+pub fn parse_frequencies(text: &str) -> Result<HashMap<&str, f64>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let (word, weight) = line
+                .trim()
+                .rsplit_once(char::is_whitespace)
+                .ok_or_else(|| format!("line {} has no frequency", i + 1))?;
+            let weight: f64 = weight
+                .parse()
+                .ok()
+                .filter(|weight: &f64| weight.is_finite() && *weight >= 0.0)
+                .ok_or_else(|| format!("line {} has an invalid frequency", i + 1))?;
+            Ok((word.trim_end(), weight))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashSet;
+
+    #[test]
+    fn samples_by_mode() {
+        let language = vec!["a", "b", "c"];
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut deck = Vec::new();
+
+        let shuffled = sample(language.clone(), 7, Sampling::Shuffle, &mut deck, &mut rng);
+        assert_eq!(shuffled.len(), 7);
+        let unique = sample(language.clone(), 7, Sampling::Unique, &mut deck, &mut rng);
+        assert_eq!(unique.len(), 3);
+
+        // two words from the deck leave one, which is dealt before it's refilled
+        let first = sample(language.clone(), 2, Sampling::Deck, &mut deck, &mut rng);
+        assert_eq!(deck.len(), 1);
+        let second = sample(language, 1, Sampling::Deck, &mut deck, &mut rng);
+        let dealt: HashSet<&String> = first.iter().chain(&second).collect();
+        assert_eq!(dealt.len(), 3);
+        assert!(deck.is_empty());
+    }
+
+    #[test]
+    fn reads_dirty_lists() {
+        let (list, repairs) =
+            read_list(b"\xEF\xBB\xBFone\r\n\r\n  \ntw\x07o\xE2\x80\x8B\nth\xFFree\tdef\n");
+        assert_eq!(list, "one\ntwo\nth\u{FFFD}ree\tdef\n");
+        assert!(repairs.invalid_utf8);
+        assert_eq!(repairs.hidden_chars, 2);
+        assert_eq!(
+            repairs.to_string(),
+            "replaced text which wasn't UTF-8 and removed 2 hidden characters"
+        );
+
+        let (list, repairs) = read_list(b"clean\r\nlist\n\n");
+        assert_eq!(list, "clean\nlist\n");
+        assert!(repairs.is_empty());
+    }
+
+    #[test]
+    fn samples_by_frequency() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let frequencies = parse_frequencies("the 900\nof 100\n\nrare words 0\n").unwrap();
+        assert_eq!(frequencies["rare words"], 0.0);
+        assert!(parse_frequencies("the\n").is_err());
+        assert!(parse_frequencies("the -1\n").is_err());
+
+        let language = ["the", "of", "rare words"];
+        let weights: Vec<f64> = language.iter().map(|w| frequencies[w]).collect();
+        let drawn = weighted(&language, &weights, 1000, &mut rng);
+        let common = drawn.iter().filter(|&w| w == "the").count();
+        assert!((850..950).contains(&common));
+        assert!(!drawn.iter().any(|w| w == "rare words"));
+        assert!(weighted(&language, &[0.0; 3], 5, &mut rng).is_empty());
+
+        // without frequencies, the first of two words is used twice as often as the second
+        let zipfian = sample(
+            vec!["a", "b"],
+            900,
+            Sampling::Frequency,
+            &mut Vec::new(),
+            &mut rng,
+        );
+        let first = zipfian.iter().filter(|&w| w == "a").count();
+        assert!((550..650).contains(&first));
+    }
+}
@@ -0,0 +1,286 @@
+use crate::date;
+use crate::signing::{self, hex, Signer};
+use crate::test::results::Results;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Test handed out by a teacher, loaded from a TOML file
+#[derive(Debug, Clone, Deserialize)]
+pub struct Assignment {
+    pub title: String,
+    /// Last day for submissions, in YYYY-MM-DD format
+    pub due: Option<String>,
+    #[serde(default = "default_attempts")]
+    pub attempts: usize,
+    #[serde(default)]
+    pub test: TestDefinition,
+
+    /// Digest of the assignment file, so submissions can be matched against it
+    #[serde(skip)]
+    pub digest: String,
+}
+
+fn default_attempts() -> usize {
+    1
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TestDefinition {
+    pub language: Option<String>,
+    pub words: Option<usize>,
+    /// Exact text to type, instead of words from a language
+    pub text: Option<String>,
+    pub no_backtrack: bool,
+    pub sudden_death: bool,
+}
+
+impl Assignment {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        let text = String::from_utf8_lossy(&bytes);
+        let mut assignment: Self = toml::from_str(&text)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        // a test of no words has nothing to type, and the test can't be started
+        if let Some(text) = assignment.test.text.as_deref() {
+            if text.trim().is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("the text to type in {} is empty", path.display()),
+                ));
+            }
+        }
+        assignment.digest = hex(&Sha256::digest(&bytes));
+        Ok(assignment)
+    }
+}
+
+/// One completed attempt at an assignment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attempt {
+    pub wpm: f64,
+    pub raw_wpm: f64,
+    pub accuracy: f64,
+    pub duration: f64,
+}
+
+impl From<&Results> for Attempt {
+    fn from(results: &Results) -> Self {
+        Self {
+            wpm: results.wpm(),
+            raw_wpm: results.raw_wpm(),
+            accuracy: f64::from(results.accuracy.overall),
+            duration: results.duration(),
+        }
+    }
+}
+
+/// Results file a student hands back to their teacher
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Submission {
+    pub assignment: String,
+    pub assignment_digest: String,
+    pub student: String,
+    pub submitted: String,
+    pub late: bool,
+    pub attempts: Vec<Attempt>,
+    /// Digest of everything above, to catch accidental edits
+    #[serde(default)]
+    pub digest: String,
+
+    /// Key of the installation which signed the submission
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub public_key: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub signature: String,
+}
+
+/// Outcome of checking a submission for tampering
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verification {
+    /// The digest doesn't match the contents
+    Modified,
+    Unsigned,
+    /// A signature is present but wasn't made by the listed key
+    Forged,
+    /// Signed by the given public key
+    Signed(String),
+}
+
+impl Submission {
+    pub fn new(assignment: &Assignment, student: String) -> Self {
+        Self {
+            assignment: assignment.title.clone(),
+            assignment_digest: assignment.digest.clone(),
+            student,
+            ..Self::default()
+        }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        toml::from_str(&fs::read_to_string(path)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+    }
+
+    /// Submission to add attempts to: the one already saved to a file for the assignment, so
+    /// taking it again doesn't reset the attempts, or else a new one
+    pub fn resume(assignment: &Assignment, student: String, path: &Path) -> io::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::new(assignment, student));
+        }
+        let invalid = |msg: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} {}", path.display(), msg),
+            )
+        };
+        let submission =
+            Self::load(path).map_err(|err| invalid(&format!("is damaged: {}", err)))?;
+        if submission.assignment_digest != assignment.digest {
+            return Err(invalid("holds the results of another assignment"));
+        }
+        // saving again would sign over the edit
+        if matches!(
+            submission.verify(),
+            Verification::Modified | Verification::Forged
+        ) {
+            return Err(invalid("has been edited since it was saved"));
+        }
+        Ok(submission)
+    }
+
+    /// Date the submission, sign it if possible, and write it to a file
+    pub fn save(
+        &mut self,
+        assignment: &Assignment,
+        path: &Path,
+        signer: Option<&Signer>,
+    ) -> io::Result<()> {
+        let today = date::today();
+        self.submitted = date::format(today);
+        self.late = assignment
+            .due
+            .as_deref()
+            .and_then(date::parse)
+            .is_some_and(|due| today > due);
+        self.digest = self.compute_digest();
+        if let Some(signer) = signer {
+            self.public_key = signer.public_key();
+            self.signature = signer.sign(self.signed_text().as_bytes());
+        }
+
+        let text = toml::to_string(self)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        fs::write(path, text)
+    }
+
+    pub fn verify(&self) -> Verification {
+        if self.compute_digest() != self.digest {
+            Verification::Modified
+        } else if self.signature.is_empty() {
+            Verification::Unsigned
+        } else if signing::verify(
+            &self.public_key,
+            self.signed_text().as_bytes(),
+            &self.signature,
+        ) {
+            Verification::Signed(self.public_key.clone())
+        } else {
+            Verification::Forged
+        }
+    }
+
+    fn compute_digest(&self) -> String {
+        let undigested = Self {
+            digest: String::new(),
+            ..self.signed()
+        };
+        let text = toml::to_string(&undigested).expect("Submission failed to serialize.");
+        hex(&Sha256::digest(text.as_bytes()))
+    }
+
+    /// Text covered by the signature, which is everything but the signature itself
+    fn signed_text(&self) -> String {
+        toml::to_string(&self.signed()).expect("Submission failed to serialize.")
+    }
+
+    fn signed(&self) -> Self {
+        Self {
+            public_key: String::new(),
+            signature: String::new(),
+            ..self.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_tampering() {
+        let signer = Signer::from_bytes(&[3; 32]);
+        let mut submission = Submission {
+            assignment: String::from("Week 1"),
+            attempts: vec![Attempt {
+                wpm: 40.0,
+                raw_wpm: 42.0,
+                accuracy: 0.95,
+                duration: 30.0,
+            }],
+            ..Submission::default()
+        };
+        assert_eq!(submission.verify(), Verification::Modified);
+
+        submission.digest = submission.compute_digest();
+        assert_eq!(submission.verify(), Verification::Unsigned);
+
+        submission.public_key = signer.public_key();
+        submission.signature = signer.sign(submission.signed_text().as_bytes());
+        assert_eq!(
+            submission.verify(),
+            Verification::Signed(signer.public_key())
+        );
+
+        // re-digesting an edit doesn't fool the signature
+        submission.attempts[0].wpm = 140.0;
+        submission.digest = submission.compute_digest();
+        assert_eq!(submission.verify(), Verification::Forged);
+    }
+
+    #[test]
+    fn resumes_saved_attempts() {
+        let dir = std::env::temp_dir().join(format!("ttyper-assignment-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("week1.toml");
+        fs::write(&path, "title = \"Week 1\"\nattempts = 2\n").unwrap();
+        let assignment = Assignment::load(&path).unwrap();
+
+        let out = dir.join("week1.results.toml");
+        let mut submission = Submission::resume(&assignment, String::from("ada"), &out).unwrap();
+        assert!(submission.attempts.is_empty());
+        submission.attempts.push(Attempt {
+            wpm: 40.0,
+            raw_wpm: 42.0,
+            accuracy: 0.95,
+            duration: 30.0,
+        });
+        submission.save(&assignment, &out, None).unwrap();
+
+        let resumed = Submission::resume(&assignment, String::from("ada"), &out).unwrap();
+        assert_eq!(resumed.attempts.len(), 1);
+
+        let edited = fs::read_to_string(&out).unwrap().replace("40.0", "140.0");
+        fs::write(&out, edited).unwrap();
+        assert!(Submission::resume(&assignment, String::from("ada"), &out).is_err());
+
+        fs::write(&path, "title = \"Week 1\"\n[test]\ntext = \" \\n\"\n").unwrap();
+        let err = Assignment::load(&path).unwrap_err();
+        assert!(err.to_string().contains("week1.toml"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
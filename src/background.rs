@@ -0,0 +1,151 @@
+use crate::config::Background;
+
+use std::env;
+use std::sync::OnceLock;
+
+/// Guess whether the terminal has a light or dark background.
+///
+/// The answer is cached, since the terminal can't be queried once the interface is drawn.
+pub fn detect() -> Background {
+    static DETECTED: OnceLock<Background> = OnceLock::new();
+    *DETECTED.get_or_init(|| {
+        env::var("COLORFGBG")
+            .ok()
+            .and_then(|value| from_colorfgbg(&value))
+            .or_else(query)
+            .unwrap_or(Background::Dark)
+    })
+}
+
+/// Parse a `COLORFGBG` value like `15;0`, whose last field is the background's palette index
+fn from_colorfgbg(value: &str) -> Option<Background> {
+    match value.rsplit(';').next()?.parse::<u8>().ok()? {
+        0..=6 | 8 => Some(Background::Dark),
+        7 | 9..=15 => Some(Background::Light),
+        _ => None,
+    }
+}
+
+/// Parse a reply to the OSC 11 query, like `\x1b]11;rgb:ffff/ffff/dddd\x07`
+fn from_osc11(reply: &str) -> Option<Background> {
+    let (_, color) = reply.split_once("rgb:")?;
+    let end = color
+        .find(|c: char| !c.is_ascii_hexdigit() && c != '/')
+        .unwrap_or(color.len());
+
+    let mut channels = color[..end].split('/').map(|channel| {
+        let value = u32::from_str_radix(channel, 16).ok()?;
+        let max = 16u32.checked_pow(channel.len() as u32)? - 1;
+        Some(f64::from(value) / f64::from(max))
+    });
+    let (r, g, b) = (channels.next()??, channels.next()??, channels.next()??);
+
+    let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    Some(if luminance > 0.5 {
+        Background::Light
+    } else {
+        Background::Dark
+    })
+}
+
+/// Whether a reply holds the whole answer to the device attributes query, like `\x1b[?62;22c`
+fn has_device_attributes(reply: &[u8]) -> bool {
+    (0..reply.len()).any(|start| {
+        let Some(rest) = reply[start..].strip_prefix(b"\x1b[?") else {
+            return false;
+        };
+        let end = rest.iter().position(|&b| !b.is_ascii_digit() && b != b';');
+        end.is_some_and(|end| rest[end] == b'c')
+    })
+}
+
+/// Ask the terminal for its background color.
+///
+/// A device attributes query is sent afterwards, which every terminal answers, so terminals
+/// that ignore OSC 11 are noticed without waiting out the timeout.
+#[cfg(unix)]
+fn query() -> Option<Background> {
+    use crossterm::terminal;
+    use std::fs::OpenOptions;
+    use std::io::{Read, Write};
+    use std::os::unix::io::AsRawFd;
+
+    const TIMEOUT_MS: i32 = 200;
+
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    terminal::enable_raw_mode().ok()?;
+
+    let mut reply = Vec::new();
+    if tty.write_all(b"\x1b]11;?\x1b\\\x1b[c").is_ok() && tty.flush().is_ok() {
+        let mut pollfd = libc::pollfd {
+            fd: tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let mut buffer = [0; 64];
+        // the device attributes reply comes last
+        while !has_device_attributes(&reply) && reply.len() < 256 {
+            // SAFETY: pollfd is a valid, initialized pollfd for the duration of the call
+            if unsafe { libc::poll(&mut pollfd, 1, TIMEOUT_MS) } <= 0 {
+                break;
+            }
+            match tty.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => reply.extend_from_slice(&buffer[..read]),
+            }
+        }
+    }
+
+    let _ = terminal::disable_raw_mode();
+    from_osc11(&String::from_utf8_lossy(&reply))
+}
+
+#[cfg(not(unix))]
+fn query() -> Option<Background> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_colorfgbg() {
+        assert_eq!(from_colorfgbg("15;0"), Some(Background::Dark));
+        assert_eq!(from_colorfgbg("0;15"), Some(Background::Light));
+        assert_eq!(from_colorfgbg("0;default;7"), Some(Background::Light));
+        assert_eq!(from_colorfgbg("7;8"), Some(Background::Dark));
+        assert_eq!(from_colorfgbg("default;default"), None);
+    }
+
+    #[test]
+    fn parses_osc11_replies() {
+        assert_eq!(
+            from_osc11("\x1b]11;rgb:ffff/ffff/dddd\x1b\\\x1b[?62;22c"),
+            Some(Background::Light)
+        );
+        assert_eq!(
+            from_osc11("\x1b]11;rgb:1e1e/1e1e/2e2e\x07"),
+            Some(Background::Dark)
+        );
+        assert_eq!(from_osc11("\x1b]11;rgb:f/f/f\x07"), Some(Background::Light));
+        assert_eq!(from_osc11("\x1b[?62;22c"), None);
+    }
+
+    #[test]
+    fn waits_for_the_device_attributes() {
+        assert!(has_device_attributes(
+            b"\x1b]11;rgb:ffff/ffff/dddd\x07\x1b[?62;22c"
+        ));
+        assert!(has_device_attributes(b"\x1b[?1;2c"));
+        // a color cut off at a hex digit 'c' is not the end of the reply
+        assert!(!has_device_attributes(b"\x1b]11;rgb:ffff/ffff/dddc"));
+        assert!(!has_device_attributes(
+            b"\x1b]11;rgb:ffff/ffff/dddd\x07\x1b[?62;2"
+        ));
+    }
+}
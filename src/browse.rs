@@ -0,0 +1,140 @@
+//! Recorded tests listed from the history, each opened again in the results screen or
+//! compared with another
+
+use crate::config::Config;
+use crate::export::Record;
+use crate::history::{Entry, History};
+use crate::test::results::Results;
+use crate::ui::{Comparison, HistoryBrowser};
+use crate::view;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{backend::Backend, terminal::Terminal};
+use std::io;
+
+// Tests a page up or down moves by
+const PAGE: usize = 10;
+
+pub fn run<B: Backend>(
+    terminal: &mut Terminal<B>,
+    config: &Config,
+    history: &History,
+) -> io::Result<()> {
+    let keys = &config.keybindings;
+    let entries = history.entries().map_err(io::Error::other)?;
+    let last = entries.len().saturating_sub(1);
+    let mut selected = 0;
+    // test marked to be compared with the next one marked
+    let mut marked = None;
+    let mut message = String::new();
+    loop {
+        terminal.draw(|f| {
+            let browser = HistoryBrowser {
+                entries: &entries,
+                selected,
+                marked,
+                keys,
+                message: &message,
+            };
+            f.render_widget(config.theme.apply_to(browser), f.size());
+        })?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        message.clear();
+        match key {
+            KeyEvent {
+                code: KeyCode::Char('c'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => break,
+            key if keys.quit.matches(&key) || keys.end.matches(&key) => break,
+            KeyEvent { code, .. } => match code {
+                KeyCode::Up | KeyCode::Char('k') => selected = selected.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => selected = (selected + 1).min(last),
+                KeyCode::PageUp => selected = selected.saturating_sub(PAGE),
+                KeyCode::PageDown => selected = (selected + PAGE).min(last),
+                KeyCode::Home => selected = 0,
+                KeyCode::End => selected = last,
+                KeyCode::Enter => {
+                    let Some(entry) = entries.get(selected) else {
+                        continue;
+                    };
+                    match results(history, entry)? {
+                        Ok(results) => view::run(terminal, config, &results)?,
+                        Err(problem) => message = problem,
+                    }
+                }
+                KeyCode::Char(' ') if entries.is_empty() => {}
+                KeyCode::Char(' ') => match marked {
+                    None => marked = Some(selected),
+                    Some(first) if first == selected => marked = None,
+                    Some(first) => {
+                        // the earlier test goes first, as the one compared against
+                        let (earlier, later) =
+                            if entries[first].timestamp <= entries[selected].timestamp {
+                                (&entries[first], &entries[selected])
+                            } else {
+                                (&entries[selected], &entries[first])
+                            };
+                        match (results(history, earlier)?, results(history, later)?) {
+                            (Ok(before), Ok(after)) => {
+                                compare(terminal, config, [(earlier, &before), (later, &after)])?;
+                                marked = None;
+                            }
+                            (Err(problem), _) | (_, Err(problem)) => message = problem,
+                        }
+                    }
+                },
+                _ => {}
+            },
+        }
+    }
+    Ok(())
+}
+
+/// Results of a recorded test, or why they can't be shown
+fn results(history: &History, entry: &Entry) -> io::Result<Result<Results, String>> {
+    let details = history.details(entry.id).map_err(io::Error::other)?;
+    if details.is_empty() {
+        return Ok(Err(String::from(
+            "This test was recorded before its details were kept, so only its summary is left",
+        )));
+    }
+    Ok(serde_json::from_str::<Record>(&details)
+        .map(|record| record.results())
+        .map_err(|e| format!("The details of this test are damaged: {}", e)))
+}
+
+fn compare<B: Backend>(
+    terminal: &mut Terminal<B>,
+    config: &Config,
+    tests: [(&Entry, &Results); 2],
+) -> io::Result<()> {
+    let keys = &config.keybindings;
+    loop {
+        terminal.draw(|f| {
+            let comparison = Comparison { tests, keys };
+            f.render_widget(config.theme.apply_to(comparison), f.size());
+        })?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        match key {
+            KeyEvent {
+                code: KeyCode::Char('c'),
+                modifiers: KeyModifiers::CONTROL,
+                kind: KeyEventKind::Press,
+                ..
+            } => break,
+            key if keys.quit.matches(&key) || keys.end.matches(&key) => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,607 @@
+use crate::config::Config;
+use crate::test::{
+    results::{PartialResults, Results},
+    Strictness, Test, TestWord,
+};
+use crate::ui::ClassroomView;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{backend::Backend, terminal::Terminal};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_PORT: u16 = 7880;
+
+// Longest a test may take to send before the student is dropped
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+// How often the dashboard checks for input while waiting for students
+const POLL_RATE: Duration = Duration::from_millis(50);
+
+// How much earlier than it reached the host a student's key may be timed, to make up for network
+// delays, which is also the most a student's clock can shave off their test
+const LATENCY_ALLOWANCE: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum HostMessage {
+    Test {
+        round: usize,
+        words: Vec<(String, Option<String>)>,
+        rules: Rules,
+    },
+}
+
+/// Messages sent by students, which are only ever their keys so the host decides the results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StudentMessage {
+    Hello {
+        name: String,
+    },
+    Key {
+        round: usize,
+        key: Keystroke,
+        /// Milliseconds since the student's first key of the round, by their clock
+        at_ms: u64,
+    },
+}
+
+/// Key which can change a test, as sent over the wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Keystroke {
+    Char(char),
+    Ctrl(char),
+    Backspace,
+    CtrlBackspace,
+    Enter,
+}
+
+impl Keystroke {
+    fn from_event(key: &KeyEvent) -> Option<Self> {
+        if key.kind != KeyEventKind::Press {
+            return None;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char(c) if ctrl => Some(Self::Ctrl(c)),
+            KeyCode::Char(c) => Some(Self::Char(c)),
+            KeyCode::Backspace if ctrl => Some(Self::CtrlBackspace),
+            KeyCode::Backspace => Some(Self::Backspace),
+            KeyCode::Enter => Some(Self::Enter),
+            _ => None,
+        }
+    }
+
+    fn to_event(self) -> KeyEvent {
+        let (code, modifiers) = match self {
+            Self::Char(c) => (KeyCode::Char(c), KeyModifiers::NONE),
+            Self::Ctrl(c) => (KeyCode::Char(c), KeyModifiers::CONTROL),
+            Self::Backspace => (KeyCode::Backspace, KeyModifiers::NONE),
+            Self::CtrlBackspace => (KeyCode::Backspace, KeyModifiers::CONTROL),
+            Self::Enter => (KeyCode::Enter, KeyModifiers::NONE),
+        };
+        KeyEvent::new(code, modifiers)
+    }
+}
+
+/// Options of the teacher's test which change how keys are scored, so students' tests play out
+/// just as the host replays them
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Rules {
+    backtracking: bool,
+    sudden_death: bool,
+    sudden_death_ends: bool,
+    error_budget: Option<usize>,
+    end_over_budget: bool,
+    filter_chatter: bool,
+    loose_quotes: bool,
+    confirm_end: bool,
+    pause_after: Option<Duration>,
+    #[serde(with = "strictness_name")]
+    strictness: Strictness,
+}
+
+/// Strictness sent by its name, as the test engine has no serde
+mod strictness_name {
+    use super::Strictness;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(strictness: &Strictness, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(strictness.name())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Strictness, D::Error> {
+        let name = String::deserialize(d)?;
+        Strictness::from_name(&name)
+            .ok_or_else(|| de::Error::custom(format!("unknown strictness {:?}", name)))
+    }
+}
+
+impl Rules {
+    fn of(test: &Test) -> Self {
+        Self {
+            backtracking: test.backtracking_enabled,
+            sudden_death: test.sudden_death_enabled,
+            sudden_death_ends: test.sudden_death_ends,
+            error_budget: test.error_budget,
+            end_over_budget: test.end_over_budget,
+            filter_chatter: test.filter_chatter,
+            loose_quotes: test.loose_quotes,
+            confirm_end: test.confirm_end,
+            pause_after: test.pause_after,
+            strictness: test.strictness,
+        }
+    }
+
+    pub fn apply(self, mut test: Test) -> Test {
+        test.backtracking_enabled = self.backtracking;
+        test.sudden_death_enabled = self.sudden_death;
+        test.sudden_death_ends = self.sudden_death_ends;
+        test.error_budget = self.error_budget;
+        test.end_over_budget = self.end_over_budget;
+        test.filter_chatter = self.filter_chatter;
+        test.loose_quotes = self.loose_quotes;
+        test.confirm_end = self.confirm_end;
+        test.pause_after = self.pause_after;
+        test.strictness = self.strictness;
+        // the host has no more words to replay keys against, nor a clock to end them by
+        test.endless = false;
+        test.time_limit = None;
+        test
+    }
+}
+
+/// Latest progress of one student on one round of the session
+#[derive(Debug, Clone)]
+pub struct StudentRow {
+    /// Connection the student joined on, which tells apart students with the same name
+    pub student: usize,
+    pub name: String,
+    pub round: usize,
+    pub typed: usize,
+    pub total: usize,
+    /// Missing until enough keys have been pressed to measure
+    pub wpm: Option<f64>,
+    pub accuracy: Option<f64>,
+    pub complete: bool,
+}
+
+fn send_line<T: Serialize>(stream: &mut TcpStream, message: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(message).expect("Message failed to serialize.");
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+}
+
+fn test_message(round: usize, test: &Test) -> HostMessage {
+    HostMessage::Test {
+        round,
+        words: test
+            .words
+            .iter()
+            .map(|w| (w.text.clone(), w.definition.clone()))
+            .collect(),
+        rules: Rules::of(test),
+    }
+}
+
+/// Connection from a student to the teacher's session
+pub struct Student {
+    stream: TcpStream,
+    tests: mpsc::Receiver<(usize, Vec<TestWord>, Rules)>,
+    round: usize,
+    /// When the first key of the round was pressed
+    started: Option<Instant>,
+}
+
+impl Student {
+    /// Join a session, waiting for the teacher's current test
+    pub fn join(addr: &str, name: &str) -> io::Result<(Self, Vec<TestWord>, Rules)> {
+        let mut stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        send_line(
+            &mut stream,
+            &StudentMessage::Hello {
+                name: name.to_owned(),
+            },
+        )?;
+
+        let (tx, rx) = mpsc::channel();
+        let reader = BufReader::new(stream.try_clone()?);
+        thread::spawn(move || {
+            for line in reader.lines().map_while(Result::ok) {
+                if let Ok(HostMessage::Test {
+                    round,
+                    words,
+                    rules,
+                }) = serde_json::from_str(&line)
+                {
+                    let words = words
+                        .into_iter()
+                        .map(|(text, definition)| TestWord {
+                            definition,
+                            ..TestWord::from(text)
+                        })
+                        .collect();
+                    if tx.send((round, words, rules)).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        let (round, words, rules) = rx.recv().map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                "classroom host closed the connection",
+            )
+        })?;
+
+        Ok((
+            Self {
+                stream,
+                tests: rx,
+                round,
+                started: None,
+            },
+            words,
+            rules,
+        ))
+    }
+
+    /// The newest test pushed by the teacher since the last call, if any
+    pub fn next_test(&mut self) -> Option<(Vec<TestWord>, Rules)> {
+        let (round, words, rules) = self.tests.try_iter().last()?;
+        self.round = round;
+        self.started = None;
+        Some((words, rules))
+    }
+
+    /// Send a key pressed during the test to the host, which scores it
+    pub fn report(&mut self, key: &KeyEvent) {
+        let Some(key) = Keystroke::from_event(key) else {
+            return;
+        };
+        let started = *self.started.get_or_insert_with(Instant::now);
+        let _ = send_line(
+            &mut self.stream,
+            &StudentMessage::Key {
+                round: self.round,
+                key,
+                at_ms: started.elapsed().as_millis() as u64,
+            },
+        );
+    }
+}
+
+/// Host's own copy of a student's test, which their keys are replayed into
+struct Replay {
+    student: usize,
+    round: usize,
+    test: Test,
+    /// When the student's first key reached the host
+    started: Instant,
+    /// Time of the latest key since the first
+    last: Duration,
+}
+
+impl Replay {
+    fn press(&mut self, key: Keystroke, at_ms: u64, arrived: Instant) {
+        self.last = reconcile(
+            Duration::from_millis(at_ms),
+            arrived.saturating_duration_since(self.started),
+            self.last,
+        );
+        self.test
+            .handle_key_at(key.to_event(), self.started + self.last);
+    }
+
+    fn row(&self, name: String) -> StudentRow {
+        let results = Results::from(&self.test);
+        let progress = self.test.progress();
+        StudentRow {
+            student: self.student,
+            name,
+            round: self.round,
+            typed: if self.test.complete {
+                progress.denominator
+            } else {
+                progress.numerator - 1
+            },
+            total: progress.denominator,
+            wpm: Some(results.wpm()).filter(|x| x.is_finite()),
+            accuracy: Some(f64::from(results.accuracy.overall)).filter(|x| x.is_finite()),
+            complete: self.test.complete,
+        }
+    }
+}
+
+/// Time of a key since a student's first, trusting the student's own timing only as far as
+/// arriving later than it says could be explained by the network
+fn reconcile(claimed: Duration, observed: Duration, last: Duration) -> Duration {
+    claimed
+        .clamp(observed.saturating_sub(LATENCY_ALLOWANCE), observed)
+        .max(last)
+}
+
+/// Pushes tests to students and collects their keys
+struct Host {
+    students: Arc<Mutex<Vec<TcpStream>>>,
+    current: Arc<Mutex<HostMessage>>,
+    messages: mpsc::Receiver<(usize, Instant, StudentMessage)>,
+}
+
+impl Host {
+    fn bind(port: u16, test: &Test) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, port))?;
+        let students = Arc::new(Mutex::new(Vec::new()));
+        let current = Arc::new(Mutex::new(test_message(0, test)));
+        let (tx, rx) = mpsc::channel();
+
+        let accepted = Arc::clone(&students);
+        let accepted_current = Arc::clone(&current);
+        thread::spawn(move || {
+            for (id, stream) in listener.incoming().map_while(Result::ok).enumerate() {
+                let _ = stream.set_nodelay(true);
+                // a student who stops reading shouldn't stall the others or the dashboard
+                let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+                let Ok(reader) = stream.try_clone() else {
+                    continue;
+                };
+
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    for line in BufReader::new(reader).lines().map_while(Result::ok) {
+                        // timed on arrival, by the host's clock
+                        if let Ok(message) = serde_json::from_str(&line) {
+                            if tx.send((id, Instant::now(), message)).is_err() {
+                                break;
+                            }
+                        }
+                    }
+                });
+
+                // late arrivals get the test everyone else is typing
+                let mut stream = stream;
+                let current = accepted_current.lock().unwrap().clone();
+                if send_line(&mut stream, &current).is_ok() {
+                    accepted.lock().unwrap().push(stream);
+                }
+            }
+        });
+
+        Ok(Self {
+            students,
+            current,
+            messages: rx,
+        })
+    }
+
+    fn push(&self, round: usize, test: &Test) {
+        let message = test_message(round, test);
+        *self.current.lock().unwrap() = message.clone();
+        self.students
+            .lock()
+            .unwrap()
+            .retain_mut(|stream| send_line(stream, &message).is_ok());
+    }
+}
+
+/// Run a classroom session until the teacher quits, then export it as CSV if requested
+pub fn host<B: Backend>(
+    terminal: &mut Terminal<B>,
+    config: &Config,
+    port: u16,
+    csv: Option<&Path>,
+    mut new_test: impl FnMut() -> Test,
+) -> io::Result<()> {
+    let mut tests = vec![new_test()];
+    let host = Host::bind(port, &tests[0])?;
+
+    let mut names: Vec<Option<String>> = Vec::new();
+    let mut replays: Vec<Replay> = Vec::new();
+    let mut rows: Vec<StudentRow> = Vec::new();
+    let mut changed = true;
+    loop {
+        for (id, arrived, message) in host.messages.try_iter() {
+            if names.len() <= id {
+                names.resize(id + 1, None);
+            }
+            match message {
+                StudentMessage::Hello { name } => names[id] = Some(name),
+                StudentMessage::Key { round, key, at_ms } => {
+                    let index = match replays
+                        .iter()
+                        .position(|r| r.student == id && r.round == round)
+                    {
+                        Some(index) => index,
+                        None => {
+                            let Some(test) = tests.get(round) else {
+                                continue;
+                            };
+                            // scored by the same rules the student's own test follows
+                            replays.push(Replay {
+                                student: id,
+                                round,
+                                test: Rules::of(test).apply(test.clone()),
+                                started: arrived,
+                                last: Duration::ZERO,
+                            });
+                            replays.len() - 1
+                        }
+                    };
+                    let replay = &mut replays[index];
+                    if replay.test.complete {
+                        continue;
+                    }
+                    replay.press(key, at_ms, arrived);
+
+                    let name = names[id].clone().unwrap_or_else(|| format!("#{}", id));
+                    update(&mut rows, replay.row(name));
+                }
+            }
+            changed = true;
+        }
+
+        if event::poll(POLL_RATE)? {
+            match event::read()? {
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('c'),
+                    kind: KeyEventKind::Press,
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                })
+                | Event::Key(KeyEvent {
+                    code: KeyCode::Esc | KeyCode::Char('q'),
+                    kind: KeyEventKind::Press,
+                    modifiers: KeyModifiers::NONE,
+                    ..
+                }) => break,
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('n'),
+                    kind: KeyEventKind::Press,
+                    modifiers: KeyModifiers::NONE,
+                    ..
+                }) => {
+                    tests.push(new_test());
+                    host.push(tests.len() - 1, &tests[tests.len() - 1]);
+                }
+                _ => {}
+            }
+            changed = true;
+        }
+
+        if changed {
+            terminal.draw(|f| {
+                let view = ClassroomView {
+                    rows: &rows,
+                    round: tests.len() - 1,
+                    port,
+                };
+                f.render_widget(config.theme.apply_to(view), f.size());
+            })?;
+            changed = false;
+        }
+    }
+
+    if let Some(path) = csv {
+        fs::write(path, to_csv(&rows))?;
+    }
+
+    Ok(())
+}
+
+/// Replace a student's row for the round, or add it if it's their first key of the round
+fn update(rows: &mut Vec<StudentRow>, row: StudentRow) {
+    match rows
+        .iter_mut()
+        .find(|r| r.student == row.student && r.round == row.round)
+    {
+        Some(existing) => *existing = row,
+        None => rows.push(row),
+    }
+}
+
+fn to_csv(rows: &[StudentRow]) -> String {
+    let mut csv = String::from("round,name,typed,total,wpm,accuracy,complete\n");
+    for row in rows {
+        csv.push_str(&format!(
+            "{},\"{}\",{},{},{},{},{}\n",
+            row.round + 1,
+            row.name.replace('"', "\"\""),
+            row.typed,
+            row.total,
+            row.wpm.map(|x| format!("{:.1}", x)).unwrap_or_default(),
+            row.accuracy
+                .map(|x| format!("{:.1}", x * 100.0))
+                .unwrap_or_default(),
+            row.complete
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconciles_student_timing() {
+        let ms = Duration::from_millis;
+        // delays within the allowance keep the student's own timing
+        assert_eq!(reconcile(ms(1000), ms(1200), ms(900)), ms(1000));
+        // keys can't be claimed long before they arrived, or after
+        assert_eq!(reconcile(ms(200), ms(1200), ms(100)), ms(900));
+        assert_eq!(reconcile(ms(1500), ms(1200), ms(900)), ms(1200));
+        // nor before the key that came before them
+        assert_eq!(reconcile(ms(1000), ms(1200), ms(1100)), ms(1100));
+    }
+
+    #[test]
+    fn replays_keys_with_the_students_strictness() {
+        let replay = |strictness: Strictness, keys: &[Keystroke]| {
+            let mut teacher = Test::new(vec![TestWord::from("ab")], true, false);
+            teacher.strictness = strictness;
+            let HostMessage::Test { rules, .. } =
+                serde_json::from_str(&serde_json::to_string(&test_message(0, &teacher)).unwrap())
+                    .unwrap();
+            let mut replay = Replay {
+                student: 0,
+                round: 0,
+                test: rules.apply(Test::new(vec![TestWord::from("ab")], true, false)),
+                started: Instant::now(),
+                last: Duration::ZERO,
+            };
+            for (i, &key) in keys.iter().enumerate() {
+                replay.press(key, i as u64 * 100, replay.started);
+            }
+            replay.test
+        };
+
+        // the wrong key holds the cursor, so the right ones still finish the word
+        let stopped = replay(
+            Strictness::Stop,
+            &[
+                Keystroke::Char('a'),
+                Keystroke::Char('x'),
+                Keystroke::Char('b'),
+            ],
+        );
+        assert!(stopped.complete);
+        assert_eq!(stopped.words[0].progress, "ab");
+
+        // the backspace is refused, so the mistake stays typed
+        let kept = replay(
+            Strictness::NoBackspace,
+            &[Keystroke::Char('x'), Keystroke::Backspace],
+        );
+        assert_eq!(kept.words[0].progress, "x");
+    }
+
+    #[test]
+    fn keeps_students_with_the_same_name_apart() {
+        let row = |student, typed| StudentRow {
+            student,
+            name: String::from("sam"),
+            round: 0,
+            typed,
+            total: 10,
+            wpm: None,
+            accuracy: None,
+            complete: false,
+        };
+        let mut rows = Vec::new();
+        update(&mut rows, row(0, 1));
+        update(&mut rows, row(1, 1));
+        update(&mut rows, row(0, 2));
+        let typed: Vec<(usize, usize)> = rows.iter().map(|r| (r.student, r.typed)).collect();
+        assert_eq!(typed, [(0, 2), (1, 1)]);
+    }
+}
@@ -0,0 +1,109 @@
+use crate::config::ColorDepth;
+
+use ratatui::style::Color;
+use std::env;
+
+/// Guess how many colors the terminal can show from its environment
+pub fn detect() -> ColorDepth {
+    if cfg!(windows) || env::var_os("WT_SESSION").is_some() {
+        return ColorDepth::TrueColor;
+    }
+
+    let colorterm = env::var("COLORTERM").unwrap_or_default();
+    let term = env::var("TERM").unwrap_or_default();
+    if matches!(colorterm.as_str(), "truecolor" | "24bit") || term.ends_with("-direct") {
+        ColorDepth::TrueColor
+    } else if term.contains("256color") {
+        ColorDepth::Ansi256
+    } else {
+        ColorDepth::Ansi16
+    }
+}
+
+/// Red, green, and blue values of the 16 named colors, as xterm shows them
+const ANSI_16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Channel levels of the 6x6x6 color cube in the 256-color palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Replace an RGB color with the nearest one the terminal can show
+pub fn fit(color: Color, depth: ColorDepth) -> Color {
+    let Color::Rgb(r, g, b) = color else {
+        return color;
+    };
+    let rgb = (r, g, b);
+
+    match depth {
+        ColorDepth::Auto | ColorDepth::TrueColor => color,
+        ColorDepth::Ansi256 => {
+            let level = |value: u8| {
+                (0..CUBE_LEVELS.len())
+                    .min_by_key(|&i| CUBE_LEVELS[i].abs_diff(value))
+                    .unwrap_or_default()
+            };
+            let (ri, gi, bi) = (level(r), level(g), level(b));
+            let cube = (CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]);
+
+            // the grayscale ramp runs from 8 to 238 in steps of 10
+            let average = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+            let step = (average.saturating_sub(3) / 10).min(23) as u8;
+            let gray = 8 + step * 10;
+
+            if distance(rgb, (gray, gray, gray)) < distance(rgb, cube) {
+                Color::Indexed(232 + step)
+            } else {
+                Color::Indexed((16 + 36 * ri + 6 * gi + bi) as u8)
+            }
+        }
+        ColorDepth::Ansi16 => ANSI_16
+            .iter()
+            .min_by_key(|(_, named)| distance(rgb, *named))
+            .map(|&(named, _)| named)
+            .unwrap_or(color),
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let channel = |a: u8, b: u8| u32::from(a.abs_diff(b)).pow(2);
+    channel(a.0, b.0) + channel(a.1, b.1) + channel(a.2, b.2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_colors_to_palettes() {
+        let orange = Color::Rgb(0xff, 0x87, 0x00);
+        assert_eq!(fit(orange, ColorDepth::TrueColor), orange);
+        assert_eq!(fit(orange, ColorDepth::Ansi256), Color::Indexed(208));
+        assert_eq!(fit(orange, ColorDepth::Ansi16), Color::Yellow);
+
+        let gray = Color::Rgb(0x80, 0x80, 0x80);
+        assert_eq!(fit(gray, ColorDepth::Ansi256), Color::Indexed(244));
+        assert_eq!(fit(gray, ColorDepth::Ansi16), Color::DarkGray);
+
+        assert_eq!(
+            fit(Color::Rgb(0, 0, 0xff), ColorDepth::Ansi256),
+            Color::Indexed(21)
+        );
+        assert_eq!(fit(Color::Cyan, ColorDepth::Ansi16), Color::Cyan);
+    }
+}
@@ -1,29 +1,829 @@
+use crate::color;
+use crate::date;
+use crate::metrics::Metrics;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
 use ratatui::{
     style::{Color, Modifier, Style},
     widgets::BorderType,
 };
 use serde::{
     de::{self, IntoDeserializer},
-    Deserialize,
+    Deserialize, Serialize,
 };
 
 #[derive(Debug, Deserialize)]
 #[serde(default)]
 pub struct Config {
     pub default_language: String,
+    /// URL `ttyper languages install` downloads word lists from, with each language's name
+    /// appended
+    pub language_index: String,
+    /// Leave words from the bundled profanity list out of tests
+    pub filter_profanity: bool,
+    /// Make the words into sentences, as `--punctuation` does
+    pub punctuation: bool,
+    /// Put numbers among the words, as `--numbers` does
+    pub numbers: bool,
+    /// Chance of the tricky-caps transform capitalizing each letter after a word's first
+    pub tricky_caps: f64,
+    /// Accept straight quotes typed for curly ones, like `'` for `’`
+    pub loose_quotes: bool,
+    /// End tests with Enter after the last word instead of on its last char
+    pub confirm_end: bool,
+    /// Hide speed during and after tests, leaving accuracy and errors
+    pub accuracy_only: bool,
+    /// Seconds without a keypress after which a test pauses, never if unset
+    pub pause_after: Option<f64>,
+    /// How the prompt is broken into lines
+    pub wrap: Wrap,
+    /// Draw the comments and strings of code in their own styles, as an editor would
+    pub highlight_code: bool,
+    /// How a word cut off when a timed test runs out of time is scored
+    pub cut_off_word: CutOffWord,
+    /// What `--sudden-death` does at the first error
+    pub sudden_death: SuddenDeathAction,
+    /// Terminal background the default theme is chosen for
+    pub background: Background,
+    /// Colors the terminal can show, which RGB theme colors are reduced to
+    pub color_depth: ColorDepth,
+    /// How the terminal is asked to pop up a notification once a test is complete
+    pub notify: Notify,
+    pub keyboard: Keyboard,
+    pub accuracy_grades: AccuracyGrades,
+    /// How the punctuation transform writes sentences
+    pub sentences: Sentences,
+    /// How missed days are forgiven in the daily practice streak
+    pub streak: StreakPolicy,
+    pub pace: Pace,
+    pub share: Share,
+    pub missed_words: MissedWords,
+    pub keybindings: Keybindings,
+    /// How `--file` splits files into words, by extension, over the defaults for common
+    /// languages
+    pub lexers: HashMap<String, Lexer>,
+    /// Formulas of extra results, like `score = "wpm * acc^2"`, by the name they're shown under
+    pub metrics: Metrics,
     pub theme: Theme,
 }
 
+/// Where lines of the prompt break
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Wrap {
+    /// Between words, never splitting one across lines
+    #[default]
+    Words,
+    /// At exactly the width of the prompt, splitting words, for code and long tokens
+    Soft,
+}
+
+/// How a file typed through is split into words
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Lexer {
+    /// Words of the whole text, running its lines together
+    Prose,
+    /// Words of each line, keeping its indentation and typing Enter at its end
+    Code,
+}
+
+// Extensions of source files, which are split as code unless the config says otherwise
+const CODE_EXTENSIONS: [&str; 24] = [
+    "c", "cc", "cpp", "cs", "css", "go", "h", "hpp", "hs", "html", "java", "js", "json", "jsx",
+    "kt", "lua", "php", "py", "rb", "rs", "sh", "swift", "toml", "ts",
+];
+
+impl Config {
+    /// Lexer for a file, going by its extension
+    pub fn lexer(&self, path: &Path) -> Lexer {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        match self.lexers.get(&extension) {
+            Some(&lexer) => lexer,
+            None if CODE_EXTENSIONS.contains(&extension.as_str()) => Lexer::Code,
+            None => Lexer::Prose,
+        }
+    }
+}
+
+/// Scoring of the word a timed test ends part way through
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CutOffWord {
+    /// Its keypresses count towards speed and accuracy, but it isn't scored as a word
+    #[default]
+    Prorate,
+    /// It's left out as if it was never started
+    Drop,
+}
+
+/// How sudden death treats the first error of a test
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuddenDeathAction {
+    /// The test starts over from its first word
+    #[default]
+    Restart,
+    /// The test ends there, and its results are recorded as having died
+    End,
+}
+
+/// Live speed to keep up, which the stats with the prompt pulse to push towards
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Pace {
+    /// Rolling WPM to stay above, never checked if unset
+    pub target_wpm: Option<f64>,
+    /// Seconds the rolling WPM may stay below the target before the stats pulse
+    pub grace: f64,
+    /// Speed of a caret moving through the text to keep ahead of, none if unset
+    #[serde(deserialize_with = "deserialize_pace_caret")]
+    pub caret: Option<PaceCaret>,
+}
+
+impl Default for Pace {
+    fn default() -> Self {
+        Self {
+            target_wpm: None,
+            grace: 5.0,
+            caret: None,
+        }
+    }
+}
+
+/// Speed the pace caret moves at, written as a WPM or "best"
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaceCaret {
+    Wpm(f64),
+    /// That of the fastest test in the history
+    Best,
+}
+
+impl std::str::FromStr for PaceCaret {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "best" {
+            return Ok(Self::Best);
+        }
+        match s.parse::<f64>() {
+            Ok(wpm) if wpm > 0.0 && wpm.is_finite() => Ok(Self::Wpm(wpm)),
+            _ => Err(String::from("expected a WPM above 0 or best")),
+        }
+    }
+}
+
+/// Score card shared from the results screen
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Share {
+    /// Text of the card, with placeholders like `{wpm}` for the results
+    pub template: String,
+    pub target: ShareTarget,
+}
+
+impl Default for Share {
+    fn default() -> Self {
+        Self {
+            template: String::from(
+                "ttyper {mode} | {wpm} WPM | {accuracy}% accuracy | {consistency}% consistency | {date}",
+            ),
+            target: ShareTarget::Clipboard,
+        }
+    }
+}
+
+/// Where shared score cards go
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShareTarget {
+    /// Copied with an OSC 52 escape sequence, which most terminals support
+    #[default]
+    Clipboard,
+    /// Printed once ttyper exits
+    Stdout,
+}
+
+/// Where the list of missed words goes when it's copied from the results screen
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct MissedWords {
+    pub target: MissedWordsTarget,
+    /// File the words are appended to, by default `missed-words.txt` in the data directory
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MissedWordsTarget {
+    /// Copied with an OSC 52 escape sequence, like shared score cards
+    #[default]
+    Clipboard,
+    /// Appended to a file, one word per line
+    File,
+}
+
+/// Keys of the test and the screens after it, which can be moved off keys a terminal
+/// multiplexer or window manager takes
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Keybindings {
+    /// End a test early, or leave the results
+    pub end: Key,
+    /// Start a test over with the same words, while it's being typed
+    pub restart: Key,
+    /// New test from the results or stats
+    pub next_test: Key,
+    /// Take the test just finished again, with the same words in the same order
+    pub repeat: Key,
+    pub quit: Key,
+    /// Switch the chart under the results
+    pub chart: Key,
+    pub note: Key,
+    pub share: Key,
+    pub practice: Key,
+    pub missed_words: Key,
+    /// Drill the weakest words from the stats
+    pub drill: Key,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        let key = |key: &str| key.parse().expect("default keybindings are valid");
+        Self {
+            end: key("esc"),
+            restart: key("ctrl-r"),
+            next_test: key("r"),
+            repeat: key("shift-r"),
+            quit: key("q"),
+            chart: key("c"),
+            note: key("n"),
+            share: key("s"),
+            practice: key("p"),
+            missed_words: key("m"),
+            drill: key("d"),
+        }
+    }
+}
+
+/// Key with the modifiers held with it, written like `q`, `esc`, `f5` or `ctrl-r`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl From<&KeyEvent> for Key {
+    fn from(key: &KeyEvent) -> Self {
+        // shift is already in the case of a char, and not always reported with it
+        let modifiers = match key.code {
+            KeyCode::Char(_) => key.modifiers - KeyModifiers::SHIFT,
+            _ => key.modifiers,
+        };
+        Self {
+            code: key.code,
+            modifiers,
+        }
+    }
+}
+
+impl Key {
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        key.kind == KeyEventKind::Press && Self::from(key) == *self
+    }
+}
+
+impl std::str::FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        // a lone `-` is the key itself
+        while let Some((modifier, after)) =
+            rest.split_once('-').filter(|(_, after)| !after.is_empty())
+        {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("{} is not a modifier", modifier)),
+            };
+            rest = after;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_lowercase().as_str() {
+                "esc" => KeyCode::Esc,
+                "enter" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "space" => KeyCode::Char(' '),
+                name => name
+                    .strip_prefix('f')
+                    .and_then(|n| n.parse().ok())
+                    .filter(|n| (1..=12).contains(n))
+                    .map(KeyCode::F)
+                    .ok_or_else(|| format!("{} is not a key", rest))?,
+            },
+        };
+        // as with keypresses, shift only shows in the case of a char
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers -= KeyModifiers::SHIFT;
+                KeyCode::Char(c.to_uppercase().next().unwrap_or(c))
+            }
+            code => code,
+        };
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl TryFrom<String> for Key {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            f.write_str("shift-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Esc => f.write_str("esc"),
+            KeyCode::Enter => f.write_str("enter"),
+            KeyCode::Tab => f.write_str("tab"),
+            KeyCode::Backspace => f.write_str("backspace"),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            _ => f.write_str("?"),
+        }
+    }
+}
+
+/// Days which can be missed without breaking the daily practice streak
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct StreakPolicy {
+    /// Missed days each calendar month which are frozen instead of breaking the streak
+    pub freezes: usize,
+    /// Dates like `2026-12-25`, or inclusive ranges like `2026-12-20..2027-01-03`, which
+    /// neither count towards nor break the streak
+    pub vacations: Vec<String>,
+}
+
+impl Default for StreakPolicy {
+    fn default() -> Self {
+        Self {
+            freezes: 2,
+            vacations: Vec::new(),
+        }
+    }
+}
+
+impl StreakPolicy {
+    pub fn on_vacation(&self, day: i64) -> bool {
+        self.vacations.iter().any(|vacation| {
+            let (start, end) = vacation.split_once("..").unwrap_or((vacation, vacation));
+            match (date::parse(start), date::parse(end)) {
+                (Some(start), Some(end)) => (start..=end).contains(&day),
+                _ => false,
+            }
+        })
+    }
+}
+
+/// Conventions for the sentences made by the punctuation transform
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Sentences {
+    /// Whether each sentence starts with a capital, which has to be typed
+    pub capitals: bool,
+    pub spacing: SentenceSpacing,
+}
+
+impl Default for Sentences {
+    fn default() -> Self {
+        Self {
+            capitals: true,
+            spacing: SentenceSpacing::Single,
+        }
+    }
+}
+
+/// Spaces typed after the punctuation ending a sentence
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SentenceSpacing {
+    #[default]
+    Single,
+    Double,
+}
+
+/// Accuracy percentages which live accuracy is graded by
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct AccuracyGrades {
+    /// Lowest accuracy which counts as good
+    pub good: f64,
+    /// Lowest accuracy which counts as fair, below which it's poor
+    pub fair: f64,
+}
+
+impl Default for AccuracyGrades {
+    fn default() -> Self {
+        Self {
+            good: 95.0,
+            fair: 80.0,
+        }
+    }
+}
+
+/// Keyboard the tests are typed on, which is recorded with each result
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(default)]
+pub struct Keyboard {
+    pub name: String,
+    pub switches: String,
+    pub layout: String,
+}
+
+/// Name of a keyboard layout going by the same names as the key heatmaps, so any Dvorak or
+/// Colemak variant counts as that layout and no layout as qwerty
+pub fn layout_name(layout: &str) -> String {
+    let layout = layout.trim().to_lowercase();
+    if layout.contains("dvorak") {
+        String::from("dvorak")
+    } else if layout.contains("colemak") {
+        String::from("colemak")
+    } else if layout.is_empty() {
+        String::from("qwerty")
+    } else {
+        layout
+    }
+}
+
+impl Keyboard {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_empty() && self.switches.is_empty() && self.layout.is_empty()
+    }
+
+    /// Layout the keys are arranged in, as named by [`layout_name`]
+    pub fn layout_name(&self) -> String {
+        layout_name(&self.layout)
+    }
+
+    /// Letter rows of the layout from the top down, for the key heatmaps, falling back to qwerty
+    pub fn rows(&self) -> [&'static str; 3] {
+        let layout = self.layout.to_lowercase();
+        if layout.contains("dvorak") {
+            ["',.pyfgcrl", "aoeuidhtns", ";qjkxbmwvz"]
+        } else if layout.contains("colemak") {
+            ["qwfpgjluy;", "arstdhneio", "zxcvbkm"]
+        } else {
+            ["qwertyuiop", "asdfghjkl", "zxcvbnm"]
+        }
+    }
+
+    /// Name of the keyboard, followed by whichever details are known
+    pub fn label(&self) -> String {
+        if self.is_empty() {
+            return String::from("no profile");
+        }
+        let name = if self.name.is_empty() {
+            "unnamed"
+        } else {
+            &self.name
+        };
+        let details: Vec<&str> = [&self.switches, &self.layout]
+            .into_iter()
+            .map(String::as_str)
+            .filter(|detail| !detail.is_empty())
+            .collect();
+        if details.is_empty() {
+            name.to_string()
+        } else {
+            format!("{} ({})", name, details.join(", "))
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Background {
+    /// Ask the terminal, falling back to dark
+    #[default]
+    Auto,
+    Dark,
+    Light,
+}
+
+/// Escape sequence a notification is sent to the terminal with
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Notify {
+    #[default]
+    Off,
+    /// Whichever the terminal is known to support, going by the environment, or none
+    Auto,
+    /// OSC 9, as iTerm2, WezTerm and Ghostty show
+    Osc9,
+    /// OSC 777, as foot, Ghostty and rxvt-unicode with its notify extension show
+    Osc777,
+    /// OSC 99, kitty's own, which has a title apart from the body
+    Osc99,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum ColorDepth {
+    /// Guess from the environment
+    #[default]
+    #[serde(rename = "auto")]
+    Auto,
+    #[serde(rename = "truecolor")]
+    TrueColor,
+    #[serde(rename = "256")]
+    Ansi256,
+    #[serde(rename = "16")]
+    Ansi16,
+}
+
+/// Theme defaults which replace those unreadable on a light background
+const LIGHT_THEME: &str = r#"
+title = "black;bold"
+prompt_untyped = "darkgray"
+prompt_definition = "darkgray;italic"
+prompt_line_number = "gray"
+prompt_comment = "gray;italic"
+prompt_string = "darkgray;dim"
+prompt_ghost = "none:gray"
+prompt_sparkline = "darkgray"
+breathing_guide_border = "darkgray"
+sidebar_border = "darkgray"
+sidebar_key_untyped = "gray"
+results_chart_y = "darkgray;bold"
+results_restart_prompt = "darkgray;italic"
+"#;
+
+/// Theme presets, which the file's own theme keys take precedence over
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Preset {
+    /// The default colors, for a dark terminal
+    Dark,
+    /// The default colors, with those unreadable on a light terminal replaced
+    Light,
+    Solarized,
+    Gruvbox,
+    /// Bright colors on black, with errors underlined as well as colored
+    HighContrast,
+}
+
+impl Preset {
+    /// Theme keys which the preset sets over the defaults
+    fn styles(self) -> &'static str {
+        match self {
+            Self::Dark => "",
+            Self::Light => LIGHT_THEME,
+            Self::Solarized => SOLARIZED_THEME,
+            Self::Gruvbox => GRUVBOX_THEME,
+            Self::HighContrast => HIGH_CONTRAST_THEME,
+        }
+    }
+}
+
+const SOLARIZED_THEME: &str = r#"
+default = "839496:002b36"
+title = "93a1a1;bold"
+input_border = "268bd2"
+prompt_border = "859900"
+prompt_correct = "859900"
+prompt_incorrect = "dc322f"
+prompt_untyped = "586e75"
+prompt_current_correct = "859900;bold"
+prompt_current_incorrect = "dc322f;bold"
+prompt_current_untyped = "b58900;bold"
+prompt_definition = "586e75;italic"
+prompt_line_number = "586e75"
+prompt_comment = "586e75;italic"
+prompt_string = "2aa198"
+prompt_ghost = "none:073642"
+prompt_sparkline = "586e75"
+breathing_guide = "268bd2"
+breathing_guide_border = "586e75"
+sidebar_border = "586e75"
+sidebar_stats = "268bd2;bold"
+sidebar_accuracy_good = "859900;bold"
+sidebar_accuracy_fair = "cb4b16;bold"
+sidebar_accuracy_poor = "dc322f;bold"
+sidebar_sparkline = "268bd2"
+sidebar_key_good = "859900"
+sidebar_key_fair = "cb4b16"
+sidebar_key_poor = "dc322f;bold"
+sidebar_key_untyped = "586e75"
+results_overview = "268bd2;bold"
+results_overview_border = "268bd2"
+results_worst_keys = "268bd2;bold"
+results_worst_keys_border = "268bd2"
+results_chart = "268bd2"
+results_chart_x = "268bd2"
+results_chart_y = "586e75;bold"
+results_chart_segments = "d33682"
+results_restart_prompt = "586e75;italic"
+stats_overview = "268bd2;bold"
+stats_overview_border = "268bd2"
+"#;
+
+const GRUVBOX_THEME: &str = r#"
+default = "ebdbb2:282828"
+title = "fbf1c7;bold"
+input_border = "83a598"
+prompt_border = "b8bb26"
+prompt_correct = "b8bb26"
+prompt_incorrect = "fb4934"
+prompt_untyped = "928374"
+prompt_current_correct = "b8bb26;bold"
+prompt_current_incorrect = "fb4934;bold"
+prompt_current_untyped = "fabd2f;bold"
+prompt_definition = "928374;italic"
+prompt_line_number = "7c6f64"
+prompt_comment = "928374;italic"
+prompt_string = "8ec07c"
+prompt_ghost = "none:504945"
+prompt_sparkline = "928374"
+breathing_guide = "83a598"
+breathing_guide_border = "928374"
+sidebar_border = "928374"
+sidebar_stats = "83a598;bold"
+sidebar_accuracy_good = "b8bb26;bold"
+sidebar_accuracy_fair = "fe8019;bold"
+sidebar_accuracy_poor = "fb4934;bold"
+sidebar_sparkline = "83a598"
+sidebar_key_good = "b8bb26"
+sidebar_key_fair = "fe8019"
+sidebar_key_poor = "fb4934;bold"
+sidebar_key_untyped = "928374"
+results_overview = "83a598;bold"
+results_overview_border = "83a598"
+results_worst_keys = "83a598;bold"
+results_worst_keys_border = "83a598"
+results_chart = "83a598"
+results_chart_x = "83a598"
+results_chart_y = "928374;bold"
+results_chart_segments = "d3869b"
+results_restart_prompt = "928374;italic"
+stats_overview = "83a598;bold"
+stats_overview_border = "83a598"
+"#;
+
+const HIGH_CONTRAST_THEME: &str = r#"
+default = "white:black"
+title = "white;bold;underlined"
+input_border = "lightcyan"
+prompt_border = "white"
+prompt_correct = "lightgreen"
+prompt_incorrect = "lightred;underlined"
+prompt_untyped = "white"
+prompt_current_correct = "lightgreen;bold"
+prompt_current_incorrect = "lightred;underlined;bold"
+prompt_current_untyped = "lightyellow;bold"
+prompt_definition = "white;italic"
+prompt_line_number = "white"
+prompt_comment = "white;italic"
+prompt_string = "lightcyan"
+prompt_ghost = "black:gray"
+prompt_sparkline = "white"
+breathing_guide = "lightcyan"
+breathing_guide_border = "white"
+sidebar_border = "white"
+sidebar_stats = "lightcyan;bold"
+sidebar_accuracy_good = "lightgreen;bold"
+sidebar_accuracy_fair = "lightyellow;bold"
+sidebar_accuracy_poor = "lightred;underlined;bold"
+sidebar_sparkline = "lightcyan"
+sidebar_key_good = "lightgreen"
+sidebar_key_fair = "lightyellow"
+sidebar_key_poor = "lightred;underlined;bold"
+sidebar_key_untyped = "white"
+results_overview = "lightcyan;bold"
+results_overview_border = "lightcyan"
+results_worst_keys = "lightcyan;bold"
+results_worst_keys_border = "lightcyan"
+results_chart = "lightcyan"
+results_chart_x = "lightcyan"
+results_chart_y = "white;bold"
+results_chart_segments = "lightmagenta"
+results_restart_prompt = "white;italic"
+stats_overview = "lightcyan;bold"
+stats_overview_border = "lightcyan"
+prompt_cursor = "black:white"
+border_type = "thick"
+"#;
+
+impl Config {
+    /// Parse a configuration file with the given theme preset in place of the one it names, if
+    /// any. Without a preset, the theme suits the terminal background, calling `detect` to find
+    /// it unless one is set.
+    ///
+    /// Keys set in the file's theme always take precedence over the preset.
+    pub fn parse(
+        text: &str,
+        preset: Option<Preset>,
+        detect: impl FnOnce() -> Background,
+    ) -> Result<Self, toml::de::Error> {
+        let mut table: toml::Table = toml::from_str(text)?;
+        let named = table
+            .get("theme")
+            .and_then(|theme| theme.get("preset"))
+            .cloned()
+            .map(toml::Value::try_into::<Preset>)
+            .transpose()?;
+        let preset = match preset.or(named) {
+            Some(preset) => preset,
+            None => match table.get("background").and_then(toml::Value::as_str) {
+                Some("dark") => Preset::Dark,
+                Some("light") => Preset::Light,
+                _ if detect() == Background::Light => Preset::Light,
+                _ => Preset::Dark,
+            },
+        };
+
+        let styles: toml::Table =
+            toml::from_str(preset.styles()).expect("Theme preset was ill-formed.");
+        if let Some(theme) = table
+            .entry("theme")
+            .or_insert_with(|| toml::Table::new().into())
+            .as_table_mut()
+        {
+            for (key, value) in styles {
+                theme.entry(key).or_insert(value);
+            }
+        }
+
+        table.try_into()
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             default_language: "english200".into(),
+            language_index:
+                "https://raw.githubusercontent.com/max-niederman/ttyper/main/core/resources/language"
+                    .into(),
+            filter_profanity: false,
+            punctuation: false,
+            numbers: false,
+            tricky_caps: 0.1,
+            loose_quotes: true,
+            confirm_end: false,
+            accuracy_only: false,
+            pause_after: None,
+            wrap: Wrap::default(),
+            highlight_code: true,
+            cut_off_word: CutOffWord::default(),
+            sudden_death: SuddenDeathAction::default(),
+            background: Background::Auto,
+            notify: Notify::Off,
+            color_depth: ColorDepth::Auto,
+            keyboard: Keyboard::default(),
+            accuracy_grades: AccuracyGrades::default(),
+            sentences: Sentences::default(),
+            streak: StreakPolicy::default(),
+            pace: Pace::default(),
+            share: Share::default(),
+            missed_words: MissedWords::default(),
+            keybindings: Keybindings::default(),
+            lexers: HashMap::new(),
+            metrics: Metrics::new(),
             theme: Theme::default(),
         }
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
 pub struct Theme {
     #[serde(deserialize_with = "deserialize_style")]
@@ -57,6 +857,52 @@ pub struct Theme {
     #[serde(deserialize_with = "deserialize_style")]
     pub prompt_cursor: Style,
 
+    #[serde(deserialize_with = "deserialize_style")]
+    pub prompt_definition: Style,
+
+    #[serde(deserialize_with = "deserialize_style")]
+    pub prompt_line_number: Style,
+    #[serde(deserialize_with = "deserialize_style")]
+    pub prompt_comment: Style,
+    #[serde(deserialize_with = "deserialize_style")]
+    pub prompt_string: Style,
+
+    #[serde(deserialize_with = "deserialize_style")]
+    pub prompt_ghost: Style,
+    #[serde(deserialize_with = "deserialize_style")]
+    pub prompt_pace: Style,
+
+    /// Recent results whose WPM is drawn as a sparkline under the prompt, none if 0
+    pub prompt_sparkline_results: usize,
+    #[serde(deserialize_with = "deserialize_style")]
+    pub prompt_sparkline: Style,
+
+    #[serde(deserialize_with = "deserialize_style")]
+    pub breathing_guide: Style,
+    #[serde(deserialize_with = "deserialize_style")]
+    pub breathing_guide_border: Style,
+
+    #[serde(deserialize_with = "deserialize_style")]
+    pub sidebar_border: Style,
+    #[serde(deserialize_with = "deserialize_style")]
+    pub sidebar_stats: Style,
+    #[serde(deserialize_with = "deserialize_style")]
+    pub sidebar_accuracy_good: Style,
+    #[serde(deserialize_with = "deserialize_style")]
+    pub sidebar_accuracy_fair: Style,
+    #[serde(deserialize_with = "deserialize_style")]
+    pub sidebar_accuracy_poor: Style,
+    #[serde(deserialize_with = "deserialize_style")]
+    pub sidebar_sparkline: Style,
+    #[serde(deserialize_with = "deserialize_style")]
+    pub sidebar_key_good: Style,
+    #[serde(deserialize_with = "deserialize_style")]
+    pub sidebar_key_fair: Style,
+    #[serde(deserialize_with = "deserialize_style")]
+    pub sidebar_key_poor: Style,
+    #[serde(deserialize_with = "deserialize_style")]
+    pub sidebar_key_untyped: Style,
+
     // results widget
     #[serde(deserialize_with = "deserialize_style")]
     pub results_overview: Style,
@@ -74,9 +920,17 @@ pub struct Theme {
     pub results_chart_x: Style,
     #[serde(deserialize_with = "deserialize_style")]
     pub results_chart_y: Style,
+    #[serde(deserialize_with = "deserialize_style")]
+    pub results_chart_segments: Style,
 
     #[serde(deserialize_with = "deserialize_style")]
     pub results_restart_prompt: Style,
+
+    // stats widget
+    #[serde(deserialize_with = "deserialize_style")]
+    pub stats_overview: Style,
+    #[serde(deserialize_with = "deserialize_style")]
+    pub stats_overview_border: Style,
 }
 
 impl Default for Theme {
@@ -107,6 +961,42 @@ impl Default for Theme {
 
             prompt_cursor: Style::default().add_modifier(Modifier::UNDERLINED),
 
+            prompt_definition: Style::default()
+                .fg(Color::Gray)
+                .add_modifier(Modifier::ITALIC),
+
+            prompt_line_number: Style::default().fg(Color::DarkGray),
+            prompt_comment: Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC),
+            prompt_string: Style::default().fg(Color::Gray).add_modifier(Modifier::DIM),
+
+            prompt_ghost: Style::default().bg(Color::DarkGray),
+            prompt_pace: Style::default().add_modifier(Modifier::UNDERLINED),
+
+            prompt_sparkline_results: 0,
+            prompt_sparkline: Style::default().fg(Color::Gray),
+
+            breathing_guide: Style::default().fg(Color::Cyan),
+            breathing_guide_border: Style::default().fg(Color::Gray),
+
+            sidebar_border: Style::default().fg(Color::Gray),
+            sidebar_stats: Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+            sidebar_accuracy_good: Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+            sidebar_accuracy_fair: Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+            sidebar_accuracy_poor: Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            sidebar_sparkline: Style::default().fg(Color::Cyan),
+            sidebar_key_good: Style::default().fg(Color::Green),
+            sidebar_key_fair: Style::default().fg(Color::Yellow),
+            sidebar_key_poor: Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            sidebar_key_untyped: Style::default().fg(Color::DarkGray),
+
             results_overview: Style::default()
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::BOLD),
@@ -122,14 +1012,191 @@ impl Default for Theme {
             results_chart_y: Style::default()
                 .fg(Color::Gray)
                 .add_modifier(Modifier::BOLD),
+            results_chart_segments: Style::default().fg(Color::Magenta),
 
             results_restart_prompt: Style::default()
                 .fg(Color::Gray)
                 .add_modifier(Modifier::ITALIC),
+
+            stats_overview: Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+            stats_overview_border: Style::default().fg(Color::Cyan),
+        }
+    }
+}
+
+impl Theme {
+    /// Every style of the theme, by its key in the config file
+    pub fn styles_mut(&mut self) -> [(&'static str, &mut Style); 41] {
+        [
+            ("default", &mut self.default),
+            ("title", &mut self.title),
+            ("input_border", &mut self.input_border),
+            ("prompt_border", &mut self.prompt_border),
+            ("prompt_correct", &mut self.prompt_correct),
+            ("prompt_incorrect", &mut self.prompt_incorrect),
+            ("prompt_untyped", &mut self.prompt_untyped),
+            ("prompt_current_correct", &mut self.prompt_current_correct),
+            (
+                "prompt_current_incorrect",
+                &mut self.prompt_current_incorrect,
+            ),
+            ("prompt_current_untyped", &mut self.prompt_current_untyped),
+            ("prompt_cursor", &mut self.prompt_cursor),
+            ("prompt_definition", &mut self.prompt_definition),
+            ("prompt_line_number", &mut self.prompt_line_number),
+            ("prompt_comment", &mut self.prompt_comment),
+            ("prompt_string", &mut self.prompt_string),
+            ("prompt_ghost", &mut self.prompt_ghost),
+            ("prompt_pace", &mut self.prompt_pace),
+            ("prompt_sparkline", &mut self.prompt_sparkline),
+            ("breathing_guide", &mut self.breathing_guide),
+            ("breathing_guide_border", &mut self.breathing_guide_border),
+            ("sidebar_border", &mut self.sidebar_border),
+            ("sidebar_stats", &mut self.sidebar_stats),
+            ("sidebar_accuracy_good", &mut self.sidebar_accuracy_good),
+            ("sidebar_accuracy_fair", &mut self.sidebar_accuracy_fair),
+            ("sidebar_accuracy_poor", &mut self.sidebar_accuracy_poor),
+            ("sidebar_sparkline", &mut self.sidebar_sparkline),
+            ("sidebar_key_good", &mut self.sidebar_key_good),
+            ("sidebar_key_fair", &mut self.sidebar_key_fair),
+            ("sidebar_key_poor", &mut self.sidebar_key_poor),
+            ("sidebar_key_untyped", &mut self.sidebar_key_untyped),
+            ("results_overview", &mut self.results_overview),
+            ("results_overview_border", &mut self.results_overview_border),
+            ("results_worst_keys", &mut self.results_worst_keys),
+            (
+                "results_worst_keys_border",
+                &mut self.results_worst_keys_border,
+            ),
+            ("results_chart", &mut self.results_chart),
+            ("results_chart_x", &mut self.results_chart_x),
+            ("results_chart_y", &mut self.results_chart_y),
+            ("results_chart_segments", &mut self.results_chart_segments),
+            ("results_restart_prompt", &mut self.results_restart_prompt),
+            ("stats_overview", &mut self.stats_overview),
+            ("stats_overview_border", &mut self.stats_overview_border),
+        ]
+    }
+
+    /// Replace RGB colors with the nearest ones a terminal of the given depth can show
+    pub fn fit_to(&mut self, depth: ColorDepth) {
+        for (_, style) in self.styles_mut() {
+            style.fg = style.fg.map(|color| color::fit(color, depth));
+            style.bg = style.bg.map(|color| color::fit(color, depth));
         }
     }
 }
 
+// Names of the colors a style string can give, as they're written
+const COLOR_NAMES: [(Color, &str); 17] = [
+    (Color::Reset, "reset"),
+    (Color::Black, "black"),
+    (Color::White, "white"),
+    (Color::Red, "red"),
+    (Color::Green, "green"),
+    (Color::Yellow, "yellow"),
+    (Color::Blue, "blue"),
+    (Color::Magenta, "magenta"),
+    (Color::Cyan, "cyan"),
+    (Color::Gray, "gray"),
+    (Color::DarkGray, "darkgray"),
+    (Color::LightRed, "lightred"),
+    (Color::LightGreen, "lightgreen"),
+    (Color::LightYellow, "lightyellow"),
+    (Color::LightBlue, "lightblue"),
+    (Color::LightMagenta, "lightmagenta"),
+    (Color::LightCyan, "lightcyan"),
+];
+
+/// Modifiers a style string can give, in the order they're written
+pub const MODIFIER_NAMES: [(Modifier, &str); 9] = [
+    (Modifier::BOLD, "bold"),
+    (Modifier::DIM, "dim"),
+    (Modifier::ITALIC, "italic"),
+    (Modifier::UNDERLINED, "underlined"),
+    (Modifier::SLOW_BLINK, "slow_blink"),
+    (Modifier::RAPID_BLINK, "rapid_blink"),
+    (Modifier::REVERSED, "reversed"),
+    (Modifier::HIDDEN, "hidden"),
+    (Modifier::CROSSED_OUT, "crossed_out"),
+];
+
+/// Write a style the way the config file gives it, like `red:black;bold`
+pub fn style_string(style: Style) -> String {
+    let color = |color: Option<Color>| match color {
+        Some(Color::Rgb(r, g, b)) => format!("{:02x}{:02x}{:02x}", r, g, b),
+        Some(color) => COLOR_NAMES
+            .iter()
+            .find(|(named, _)| *named == color)
+            .map_or("none", |(_, name)| name)
+            .to_owned(),
+        None => String::from("none"),
+    };
+    let mut string = color(style.fg);
+    if style.bg.is_some() {
+        string += ":";
+        string += &color(style.bg);
+    }
+    for (modifier, name) in MODIFIER_NAMES {
+        if style.add_modifier.contains(modifier) {
+            string += ";";
+            string += name;
+        }
+    }
+    string
+}
+
+/// Set keys of the `[theme]` table in the text of a config file, keeping the rest of the file and
+/// its comments as they are
+pub fn set_theme_keys(text: &str, values: &[(&str, String)]) -> String {
+    let mut lines: Vec<String> = text.lines().map(ToOwned::to_owned).collect();
+    let is_header = |line: &str| line.trim_start().starts_with('[');
+    let header = lines.iter().position(|line| line.trim() == "[theme]");
+    let (start, mut end) = match header {
+        Some(header) => {
+            let end = lines[header + 1..]
+                .iter()
+                .position(|line| is_header(line))
+                .map_or(lines.len(), |i| header + 1 + i);
+            (header + 1, end)
+        }
+        None => {
+            if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(String::from("[theme]"));
+            (lines.len(), lines.len())
+        }
+    };
+
+    for (key, value) in values {
+        let line = format!("{} = \"{}\"", key, value);
+        let existing = lines[start..end].iter().position(|line| {
+            line.trim_start()
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.trim_start().starts_with('='))
+        });
+        match existing {
+            Some(i) => lines[start + i] = line,
+            None => {
+                // after the table's last key, ahead of any blank lines before the next table
+                let last = lines[start..end]
+                    .iter()
+                    .rposition(|line| !line.trim().is_empty())
+                    .map_or(start, |i| start + i + 1);
+                lines.insert(last, line);
+                end += 1;
+            }
+        }
+    }
+
+    let mut text = lines.join("\n");
+    text.push('\n');
+    text
+}
+
 fn deserialize_style<'de, D>(deserializer: D) -> Result<Style, D::Error>
 where
     D: de::Deserializer<'de>,
@@ -159,23 +1226,13 @@ where
             };
 
             for modifier in modifiers.split_terminator(';') {
-                style = style.add_modifier(match modifier {
-                    "bold" => Modifier::BOLD,
-                    "crossed_out" => Modifier::CROSSED_OUT,
-                    "dim" => Modifier::DIM,
-                    "hidden" => Modifier::HIDDEN,
-                    "italic" => Modifier::ITALIC,
-                    "rapid_blink" => Modifier::RAPID_BLINK,
-                    "slow_blink" => Modifier::SLOW_BLINK,
-                    "reversed" => Modifier::REVERSED,
-                    "underlined" => Modifier::UNDERLINED,
-                    _ => {
-                        return Err(E::invalid_value(
-                            de::Unexpected::Str(modifier),
-                            &"a style modifier",
-                        ))
-                    }
-                });
+                let (modifier, _) = MODIFIER_NAMES
+                    .iter()
+                    .find(|(_, name)| *name == modifier)
+                    .ok_or_else(|| {
+                        E::invalid_value(de::Unexpected::Str(modifier), &"a style modifier")
+                    })?;
+                style = style.add_modifier(*modifier);
             }
 
             Ok(style)
@@ -198,40 +1255,22 @@ where
         }
 
         fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
-            match value {
-                "reset" => Ok(Color::Reset),
-                "black" => Ok(Color::Black),
-                "white" => Ok(Color::White),
-                "red" => Ok(Color::Red),
-                "green" => Ok(Color::Green),
-                "yellow" => Ok(Color::Yellow),
-                "blue" => Ok(Color::Blue),
-                "magenta" => Ok(Color::Magenta),
-                "cyan" => Ok(Color::Cyan),
-                "gray" => Ok(Color::Gray),
-                "darkgray" => Ok(Color::DarkGray),
-                "lightred" => Ok(Color::LightRed),
-                "lightgreen" => Ok(Color::LightGreen),
-                "lightyellow" => Ok(Color::LightYellow),
-                "lightblue" => Ok(Color::LightBlue),
-                "lightmagenta" => Ok(Color::LightMagenta),
-                "lightcyan" => Ok(Color::LightCyan),
-                _ => {
-                    if value.len() == 6 {
-                        let parse_error = |_| E::custom("color code was not valid hexadecimal");
-
-                        Ok(Color::Rgb(
-                            u8::from_str_radix(&value[0..2], 16).map_err(parse_error)?,
-                            u8::from_str_radix(&value[2..4], 16).map_err(parse_error)?,
-                            u8::from_str_radix(&value[4..6], 16).map_err(parse_error)?,
-                        ))
-                    } else {
-                        Err(E::invalid_value(
-                            de::Unexpected::Str(value),
-                            &"a color name or hexadecimal color code",
-                        ))
-                    }
-                }
+            if let Some((color, _)) = COLOR_NAMES.iter().find(|(_, name)| *name == value) {
+                return Ok(*color);
+            }
+            if value.len() == 6 {
+                let parse_error = |_| E::custom("color code was not valid hexadecimal");
+
+                Ok(Color::Rgb(
+                    u8::from_str_radix(&value[0..2], 16).map_err(parse_error)?,
+                    u8::from_str_radix(&value[2..4], 16).map_err(parse_error)?,
+                    u8::from_str_radix(&value[4..6], 16).map_err(parse_error)?,
+                ))
+            } else {
+                Err(E::invalid_value(
+                    de::Unexpected::Str(value),
+                    &"a color name or hexadecimal color code",
+                ))
             }
         }
     }
@@ -239,6 +1278,34 @@ where
     deserializer.deserialize_str(ColorVisitor)
 }
 
+fn deserialize_pace_caret<'de, D>(deserializer: D) -> Result<Option<PaceCaret>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    struct PaceCaretVisitor;
+    impl<'de> de::Visitor<'de> for PaceCaretVisitor {
+        type Value = Option<PaceCaret>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            formatter.write_str("a WPM or \"best\"")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            value.parse().map(Some).map_err(E::custom)
+        }
+
+        fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+            self.visit_str(&value.to_string())
+        }
+
+        fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+            self.visit_str(&value.to_string())
+        }
+    }
+
+    deserializer.deserialize_any(PaceCaretVisitor)
+}
+
 fn deserialize_border_type<'de, D>(deserializer: D) -> Result<BorderType, D::Error>
 where
     D: de::Deserializer<'de>,
@@ -273,6 +1340,9 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test::results::{Fraction, Hand, Results};
+    use crate::test::{Test, TestWord};
+    use clap::ValueEnum;
 
     #[test]
     fn deserializes_basic_colors() {
@@ -332,6 +1402,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn writes_styles_as_they_are_read() {
+        for string in [
+            "none",
+            "red",
+            "none:black",
+            "00ff00:blue;bold;italic",
+            "none;underlined",
+        ] {
+            let style = deserialize_style(
+                de::IntoDeserializer::<de::value::Error>::into_deserializer(string),
+            )
+            .unwrap();
+            assert_eq!(style_string(style), string);
+        }
+    }
+
+    #[test]
+    fn sets_theme_keys_keeping_the_rest() {
+        let values = [
+            ("title", String::from("red;bold")),
+            ("prompt_border", String::from("blue")),
+        ];
+        assert_eq!(
+            set_theme_keys("# mine\n[theme]\n# titles\ntitle = \"white\"\n\n[keyboard]\nname = \"k\"\n", &values),
+            "# mine\n[theme]\n# titles\ntitle = \"red;bold\"\nprompt_border = \"blue\"\n\n[keyboard]\nname = \"k\"\n"
+        );
+        assert_eq!(
+            set_theme_keys("loose_quotes = false\n", &values[..1]),
+            "loose_quotes = false\n\n[theme]\ntitle = \"red;bold\"\n"
+        );
+        assert_eq!(
+            set_theme_keys("", &values[..1]),
+            "[theme]\ntitle = \"red;bold\"\n"
+        );
+    }
+
     #[test]
     fn deserializes_border_types() {
         fn border_type(string: &str) -> BorderType {
@@ -347,4 +1454,173 @@ mod tests {
         assert_eq!(border_type("quadrantinside"), BorderType::QuadrantInside);
         assert_eq!(border_type("quadrantoutside"), BorderType::QuadrantOutside);
     }
+
+    #[test]
+    fn reads_pace_keeping_default_grace() {
+        let config =
+            Config::parse("[pace]\ntarget_wpm = 70.0\n", None, || Background::Dark).unwrap();
+        assert_eq!(
+            config.pace,
+            Pace {
+                target_wpm: Some(70.0),
+                grace: 5.0,
+                caret: None,
+            }
+        );
+
+        let caret = |toml: &str| {
+            Config::parse(&format!("[pace]\ncaret = {}\n", toml), None, || {
+                Background::Dark
+            })
+            .map(|config| config.pace.caret)
+        };
+        assert_eq!(caret("80").unwrap(), Some(PaceCaret::Wpm(80.0)));
+        assert_eq!(caret("62.5").unwrap(), Some(PaceCaret::Wpm(62.5)));
+        assert_eq!(caret("\"best\"").unwrap(), Some(PaceCaret::Best));
+        assert!(caret("0").is_err());
+        assert!(caret("\"fast\"").is_err());
+    }
+
+    #[test]
+    fn reads_keybindings() {
+        let config = Config::parse(
+            "[keybindings]\nquit = \"ctrl-q\"\nnext_test = \"F5\"\nchart = \"-\"\n",
+            None,
+            || Background::Dark,
+        )
+        .unwrap();
+        let keys = config.keybindings;
+        let ctrl_q = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL);
+        assert!(keys.quit.matches(&ctrl_q));
+        assert!(!keys.quit.matches(&KeyEvent::from(KeyCode::Char('q'))));
+        assert!(keys.next_test.matches(&KeyEvent::from(KeyCode::F(5))));
+        assert_eq!(keys.chart.code, KeyCode::Char('-'));
+        assert_eq!(keys.restart.to_string(), "ctrl-r");
+        assert!(keys.repeat.matches(&KeyEvent::from(KeyCode::Char('R'))));
+
+        // shift is part of the char, whether or not the terminal reports it
+        let key: Key = "shift-q".parse().unwrap();
+        assert!(key.matches(&KeyEvent::new(KeyCode::Char('Q'), KeyModifiers::SHIFT)));
+        assert!(key.matches(&KeyEvent::from(KeyCode::Char('Q'))));
+        assert!("hyper-x".parse::<Key>().is_err());
+        assert!("f13".parse::<Key>().is_err());
+    }
+
+    #[test]
+    fn picks_lexers_by_extension() {
+        let config = Config::parse("[lexers]\ntxt = \"code\"\nrs = \"prose\"\n", None, || {
+            Background::Dark
+        })
+        .unwrap();
+        assert_eq!(config.lexer(Path::new("notes.txt")), Lexer::Code);
+        assert_eq!(config.lexer(Path::new("main.rs")), Lexer::Prose);
+        assert_eq!(config.lexer(Path::new("src/App.PY")), Lexer::Code);
+        assert_eq!(config.lexer(Path::new("book.epub")), Lexer::Prose);
+        assert_eq!(config.lexer(Path::new("Makefile")), Lexer::Prose);
+    }
+
+    #[test]
+    fn reads_metrics() {
+        let config = Config::parse("[metrics]\nscore = \"wpm * acc^2\"\n", None, || {
+            Background::Dark
+        });
+        assert!(config.unwrap().metrics.contains_key("score"));
+        let error = Config::parse("[metrics]\nscore = \"wpm *\"\n", None, || Background::Dark);
+        assert!(error
+            .unwrap_err()
+            .to_string()
+            .contains("formula ends too soon"));
+    }
+
+    #[test]
+    fn picks_heatmap_rows_by_layout() {
+        let config = Config::parse("[keyboard]\nlayout = \"Colemak-DH\"\n", None, || {
+            Background::Dark
+        });
+        assert_eq!(config.unwrap().keyboard.rows()[1], "arstdhneio");
+        let dvorak = Keyboard {
+            layout: String::from("dvorak"),
+            ..Keyboard::default()
+        };
+        assert_eq!(dvorak.rows()[0], "',.pyfgcrl");
+        assert_eq!(Keyboard::default().rows()[2], "zxcvbnm");
+    }
+
+    #[test]
+    fn splits_rows_between_hands() {
+        let qwerty = Keyboard::default().rows();
+        assert_eq!(Hand::on(&qwerty, 't'), Some(Hand::Left));
+        assert_eq!(Hand::on(&qwerty, 'Y'), Some(Hand::Right));
+        assert_eq!(Hand::on(&qwerty, 'b'), Some(Hand::Left));
+        assert_eq!(Hand::on(&qwerty, ' '), None);
+        let dvorak = Keyboard {
+            layout: String::from("dvorak"),
+            ..Keyboard::default()
+        };
+        assert_eq!(Hand::on(&dvorak.rows(), 'i'), Some(Hand::Left));
+        assert_eq!(Hand::on(&dvorak.rows(), 'd'), Some(Hand::Right));
+    }
+
+    #[test]
+    fn splits_capitals_between_shifts() {
+        let mut test = Test::new(vec![TestWord::from("aTyP")], true, false);
+        for c in "aTyp".chars() {
+            test.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        test.handle_key(KeyEvent::from(KeyCode::Backspace));
+        test.handle_key(KeyEvent::from(KeyCode::Char('P')));
+        let shifts = Results::from(&test)
+            .shifts(&Keyboard::default().rows())
+            .unwrap();
+        // T is on the left hand, so taken with the right Shift, and the other way for P
+        assert_eq!(shifts.right, Fraction::new(1, 1));
+        assert_eq!(shifts.left, Fraction::new(1, 2));
+
+        let plain = Results::from(&Test::new(vec![TestWord::from("ab")], true, false));
+        assert_eq!(plain.shifts(&Keyboard::default().rows()), None);
+    }
+
+    #[test]
+    fn applies_light_theme_under_explicit_keys() {
+        let dark = Config::parse("", None, || Background::Dark).unwrap();
+        assert_eq!(dark.theme.prompt_untyped, Style::default().fg(Color::Gray));
+
+        let light = Config::parse("[theme]\ntitle = \"red\"", None, || Background::Light).unwrap();
+        assert_eq!(light.theme.title, Style::default().fg(Color::Red));
+        assert_eq!(
+            light.theme.prompt_untyped,
+            Style::default().fg(Color::DarkGray)
+        );
+
+        // an explicit background skips detection
+        let config = Config::parse("background = \"dark\"", None, || Background::Light).unwrap();
+        assert_eq!(
+            config.theme.prompt_untyped,
+            Style::default().fg(Color::Gray)
+        );
+    }
+
+    #[test]
+    fn applies_presets_under_explicit_keys() {
+        let text = "[theme]\npreset = \"gruvbox\"\ntitle = \"red\"";
+        let gruvbox = Config::parse(text, None, || panic!("detected the background")).unwrap();
+        assert_eq!(gruvbox.theme.title, Style::default().fg(Color::Red));
+        assert_eq!(
+            gruvbox.theme.prompt_incorrect,
+            Style::default().fg(Color::Rgb(0xfb, 0x49, 0x34))
+        );
+
+        // a preset given when ttyper is run replaces the one in the file
+        let light = Config::parse(text, Some(Preset::Light), || Background::Dark).unwrap();
+        assert_eq!(light.theme.title, Style::default().fg(Color::Red));
+        assert_eq!(
+            light.theme.prompt_untyped,
+            Style::default().fg(Color::DarkGray)
+        );
+
+        for preset in Preset::value_variants() {
+            assert!(Config::parse("", Some(*preset), || Background::Dark).is_ok());
+        }
+        assert!(Config::parse("[theme]\npreset = \"neon\"", None, || Background::Dark).is_err());
+    }
 }
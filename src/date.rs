@@ -0,0 +1,103 @@
+//! Calendar dates as days since the Unix epoch, in UTC
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Current date
+pub fn today() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64 / SECONDS_PER_DAY)
+        .unwrap_or_default()
+}
+
+/// Days since the epoch of a proleptic Gregorian date
+pub fn from_civil(year: i64, month: u32, day: u32) -> i64 {
+    // see http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Proleptic Gregorian year, month, and day of a date
+pub fn to_civil(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Parse a date in YYYY-MM-DD format
+pub fn parse(date: &str) -> Option<i64> {
+    let mut parts = date.trim().splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts
+        .next()?
+        .parse()
+        .ok()
+        .filter(|m| (1..=12).contains(m))?;
+    let day = parts
+        .next()?
+        .parse()
+        .ok()
+        .filter(|d| (1..=31).contains(d))?;
+    Some(from_civil(year, month, day))
+}
+
+/// Format a date as YYYY-MM-DD
+pub fn format(days: i64) -> String {
+    let (year, month, day) = to_civil(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Format a time as YYYY-MM-DD HH:MM, from seconds since the epoch
+pub fn format_time(timestamp: i64) -> String {
+    let seconds = timestamp.rem_euclid(SECONDS_PER_DAY);
+    format!(
+        "{} {:02}:{:02}",
+        format(timestamp.div_euclid(SECONDS_PER_DAY)),
+        seconds / 3600,
+        seconds % 3600 / 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_civil_dates() {
+        assert_eq!(from_civil(1970, 1, 1), 0);
+        assert_eq!(from_civil(2000, 3, 1), 11017);
+        assert_eq!(from_civil(1969, 12, 31), -1);
+
+        for days in [-800_000, -1, 0, 59, 60, 11016, 20_000, 1_000_000] {
+            let (year, month, day) = to_civil(days);
+            assert_eq!(from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn parses_and_formats_dates() {
+        assert_eq!(parse("1970-01-01"), Some(0));
+        assert_eq!(
+            parse("2024-02-29").map(format).as_deref(),
+            Some("2024-02-29")
+        );
+        assert_eq!(parse("2024-13-01"), None);
+        assert_eq!(parse("yesterday"), None);
+        assert_eq!(format_time(1_700_000_000), "2023-11-14 22:13");
+    }
+}
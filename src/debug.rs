@@ -0,0 +1,133 @@
+//! How keys typed are lined up with the words of a test, shown for working out why a test was
+//! scored the way it was
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ttyper_core::test::{results::Results, Test, TestWord};
+
+// columns the rows of a lined up test are wrapped at
+const WIDTH: usize = 72;
+
+/// Keys of typed text, where `\b` stands for Backspace, `\n` for Enter and `\\` for a backslash
+pub fn keys(typed: &str) -> Vec<KeyEvent> {
+    let mut keys = Vec::new();
+    let mut chars = typed.chars();
+    while let Some(c) = chars.next() {
+        let code = match c {
+            '\\' => match chars.next() {
+                Some('b') => KeyCode::Backspace,
+                Some('n') => KeyCode::Enter,
+                Some(other) if other != '\\' => {
+                    keys.push(KeyEvent::from(KeyCode::Char('\\')));
+                    KeyCode::Char(other)
+                }
+                _ => KeyCode::Char('\\'),
+            },
+            c => KeyCode::Char(c),
+        };
+        keys.push(KeyEvent::from(code));
+    }
+    keys
+}
+
+/// Test of the expected words with the typed keys pressed in turn
+pub fn align(expected: &str, typed: &str) -> Test {
+    let words = expected.split_whitespace().map(TestWord::from).collect();
+    let mut test = Test::new(words, true, false);
+    for key in keys(typed) {
+        if test.complete {
+            break;
+        }
+        test.handle_key(key);
+    }
+    test
+}
+
+/// Each word as expected and as typed, one above the other, with a row under them marking
+/// wrong chars with `^`, missing ones with `-` and extra ones with `+`
+pub fn report(test: &Test) -> String {
+    let mut rows: Vec<[String; 3]> = vec![Default::default()];
+    for word in &test.words {
+        let text: Vec<char> = word.text.chars().collect();
+        let typed: Vec<char> = word.progress.chars().collect();
+        let width = text.len().max(typed.len());
+        let row = rows.last_mut().expect("There is always a row.");
+        if !row[0].is_empty() && row[0].chars().count() + 1 + width > WIDTH {
+            rows.push(Default::default());
+        }
+        let row = rows.last_mut().expect("There is always a row.");
+        if !row[0].is_empty() {
+            row.iter_mut().for_each(|line| line.push(' '));
+        }
+        for i in 0..width {
+            let (expected, got) = (text.get(i), typed.get(i));
+            row[0].push(*expected.unwrap_or(&' '));
+            row[1].push(*got.unwrap_or(&' '));
+            row[2].push(match (expected, got) {
+                (Some(expected), Some(got)) if expected == got => ' ',
+                (Some(_), Some(_)) => '^',
+                (Some(_), None) => '-',
+                _ => '+',
+            });
+        }
+    }
+
+    let mut report = String::new();
+    for [expected, typed, marks] in rows {
+        report.push_str(&format!(
+            "expected  {}\ntyped     {}\n          {}\n",
+            expected,
+            typed.trim_end(),
+            marks.trim_end()
+        ));
+    }
+    let results = Results::from(test);
+    let right = test
+        .words
+        .iter()
+        .filter(|word| word.progress == word.text)
+        .count();
+    report.push_str(&format!(
+        "{} of {} words right, {} of {} keypresses correct\n",
+        right,
+        test.words.len(),
+        results.accuracy.overall.numerator,
+        results.accuracy.overall.denominator
+    ));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_escaped_keys() {
+        assert_eq!(
+            keys(r"a\bb\n\\\x"),
+            [
+                KeyCode::Char('a'),
+                KeyCode::Backspace,
+                KeyCode::Char('b'),
+                KeyCode::Enter,
+                KeyCode::Char('\\'),
+                KeyCode::Char('\\'),
+                KeyCode::Char('x'),
+            ]
+            .map(KeyEvent::from)
+        );
+    }
+
+    #[test]
+    fn lines_up_each_word_on_its_own() {
+        let test = align("the quick fox", "teh quikc\\b\\bck fo");
+        assert_eq!(
+            report(&test).lines().map(str::trim_end).collect::<Vec<_>>(),
+            [
+                "expected  the quick fox",
+                "typed     teh quick fo",
+                "           ^^         -",
+                "1 of 3 words right, 11 of 16 keypresses correct",
+            ]
+        );
+    }
+}
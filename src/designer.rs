@@ -0,0 +1,190 @@
+//! Interactive editor for the theme, which saves changed styles to the config file
+
+use crate::config::{self, ColorDepth, Theme, MODIFIER_NAMES};
+use crate::test::{Test, TestWord};
+use crate::ui::ThemeDesigner;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    backend::Backend,
+    style::{Color, Style},
+    terminal::Terminal,
+};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// Colors cycled through for foregrounds and backgrounds, darkest of each hue first
+const PALETTE: [Option<Color>; 17] = [
+    None,
+    Some(Color::Black),
+    Some(Color::DarkGray),
+    Some(Color::Gray),
+    Some(Color::White),
+    Some(Color::Red),
+    Some(Color::LightRed),
+    Some(Color::Green),
+    Some(Color::LightGreen),
+    Some(Color::Yellow),
+    Some(Color::LightYellow),
+    Some(Color::Blue),
+    Some(Color::LightBlue),
+    Some(Color::Magenta),
+    Some(Color::LightMagenta),
+    Some(Color::Cyan),
+    Some(Color::LightCyan),
+];
+
+/// Test part way through, so that every style of the prompt shows
+fn sample() -> Test {
+    let words = "the quick brown fox jumps over the lazy dog"
+        .split(' ')
+        .map(TestWord::from)
+        .collect();
+    let mut test = Test::new(words, true, false);
+    for (word, progress) in test.words.iter_mut().zip(["the", "quikc", "brown", "fx"]) {
+        word.progress = progress.to_owned();
+    }
+    test.words[3].definition = Some(String::from("a small wild dog"));
+    test.current_word = 3;
+    test.error_budget = Some(3);
+    test
+}
+
+/// Next color of the palette in either direction, starting over from colors outside it
+fn cycle(color: Option<Color>, forward: bool) -> Option<Color> {
+    let len = PALETTE.len();
+    let next = match PALETTE.iter().position(|&c| c == color) {
+        Some(i) if forward => (i + 1) % len,
+        Some(i) => (i + len - 1) % len,
+        None => 0,
+    };
+    PALETTE[next]
+}
+
+/// Write styles into the `[theme]` table of the config file, creating it if there isn't one
+pub fn save_styles(path: &Path, styles: &[(&str, String)]) -> io::Result<()> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, config::set_theme_keys(&text, styles))
+}
+
+/// Edit the theme on a sample test until the user quits, saving to the config file on request
+pub fn edit<B: Backend>(
+    terminal: &mut Terminal<B>,
+    path: &Path,
+    mut theme: Theme,
+    depth: ColorDepth,
+) -> io::Result<()> {
+    let sample = sample();
+    let mut selected = 0;
+    let mut changed = BTreeSet::new();
+    let mut message = String::new();
+    let mut warned = false;
+
+    loop {
+        // previewed as the terminal will show it, but saved as chosen
+        let mut shown = theme.clone();
+        shown.fit_to(depth);
+        let styles: Vec<(&'static str, Style)> = shown
+            .styles_mut()
+            .into_iter()
+            .map(|(key, style)| (key, *style))
+            .collect();
+        let value = config::style_string(*theme.styles_mut()[selected].1);
+        terminal.draw(|f| {
+            let designer = ThemeDesigner {
+                sample: &sample,
+                styles: &styles,
+                selected,
+                value: &value,
+                changed: &changed,
+                message: &message,
+            };
+            f.render_widget(shown.apply_to(designer), f.size());
+        })?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        message.clear();
+
+        let count = styles.len();
+        match key {
+            KeyEvent {
+                code: KeyCode::Char('c'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => break,
+            KeyEvent {
+                code: KeyCode::Esc | KeyCode::Char('q'),
+                ..
+            } => {
+                if changed.is_empty() || warned {
+                    break;
+                }
+                message = String::from("Unsaved changes. Press 'q' again to quit without saving");
+                warned = true;
+                continue;
+            }
+            KeyEvent {
+                code: KeyCode::Up | KeyCode::Char('k'),
+                ..
+            } => selected = selected.checked_sub(1).unwrap_or(count - 1),
+            KeyEvent {
+                code: KeyCode::Down | KeyCode::Char('j'),
+                ..
+            } => selected = (selected + 1) % count,
+            KeyEvent {
+                code: KeyCode::Char('s'),
+                ..
+            } => {
+                let styles: Vec<(&str, String)> = theme
+                    .styles_mut()
+                    .into_iter()
+                    .filter(|(key, _)| changed.contains(key))
+                    .map(|(key, style)| (key, config::style_string(*style)))
+                    .collect();
+                message = match save_styles(path, &styles) {
+                    Ok(()) => {
+                        changed.clear();
+                        format!("Saved to {}", path.display())
+                    }
+                    Err(err) => format!("Couldn't save to {}: {}", path.display(), err),
+                };
+            }
+            _ => {}
+        }
+
+        let mut styles = theme.styles_mut();
+        let (name, style) = &mut styles[selected];
+        let before = **style;
+        match key.code {
+            code @ (KeyCode::Left | KeyCode::Right) => {
+                style.fg = cycle(style.fg, code == KeyCode::Right)
+            }
+            KeyCode::Char(c @ ('[' | ']')) => style.bg = cycle(style.bg, c == ']'),
+            KeyCode::Char(c @ '1'..='9') => {
+                let (modifier, _) = MODIFIER_NAMES[c as usize - '1' as usize];
+                style.add_modifier.toggle(modifier);
+            }
+            _ => {}
+        }
+        if **style != before {
+            changed.insert(*name);
+            warned = false;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+//! Tests built from the spelling dictionaries installed on the system
+
+use std::path::{Path, PathBuf};
+
+/// Word list most Unix systems install
+pub const SYSTEM_WORDS: &str = "/usr/share/dict/words";
+
+// Directories hunspell dictionaries are installed to
+const HUNSPELL_DIRS: [&str; 4] = [
+    "/usr/share/hunspell",
+    "/usr/share/myspell",
+    "/usr/share/myspell/dicts",
+    "/Library/Spelling",
+];
+
+/// Path of a dictionary given as a path, or as the name of a hunspell dictionary like `de_DE`
+pub fn find(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    if path.is_file() {
+        return Some(path.to_owned());
+    }
+    HUNSPELL_DIRS
+        .iter()
+        .map(|dir| Path::new(dir).join(format!("{}.dic", name)))
+        .find(|path| path.is_file())
+}
+
+/// Words of a plain word list or hunspell `.dic` file, leaving out proper nouns, abbreviations
+/// and possessives, which are rarely worth practicing
+pub fn words(text: &str) -> Vec<&str> {
+    let mut words: Vec<&str> = text
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            // hunspell dictionaries start with their number of entries
+            if i == 0 && line.trim().parse::<usize>().is_ok() {
+                return None;
+            }
+            // and follow words with affix flags and morphology, like `walk/USDG po:verb`
+            let word = line.split(['/', '\t', ' ']).next()?.trim();
+            let plain = word.starts_with(char::is_lowercase)
+                && word
+                    .chars()
+                    .all(|c| c.is_alphabetic() || c == '-' || c == '\'')
+                && !word.ends_with("'s");
+            plain.then_some(word)
+        })
+        .collect();
+    words.dedup();
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_plain_and_hunspell_dictionaries() {
+        assert_eq!(
+            words("Aachen\naardvark\naardvark's\nabacus\nNASA\nco-op\n"),
+            ["aardvark", "abacus", "co-op"]
+        );
+        assert_eq!(
+            words("4\nwalk/USDG\tpo:verb\nBerlin/M\nzoo/S\nétude\n"),
+            ["walk", "zoo", "étude"]
+        );
+    }
+}
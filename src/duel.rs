@@ -0,0 +1,227 @@
+//! Asynchronous head-to-head tests, shared as short codes which reproduce the same words
+
+use crate::date;
+use crate::signing::{self, Signer};
+use crate::test::results::Results;
+
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+const BASE36: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+// Test everyone takes as the daily challenge, with a seed which changes each day
+const DAILY_LANGUAGE: &str = "english200";
+const DAILY_WORDS: usize = 50;
+
+/// Test both sides of a duel take, from the language, word count and seed its code encodes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Challenge {
+    pub language: String,
+    pub words: usize,
+    pub seed: u32,
+}
+
+impl Challenge {
+    /// Parse a code like `english200.50.1xkq9z`, whose language may itself contain dots
+    pub fn parse(code: &str) -> Option<Self> {
+        let mut parts = code.trim().rsplitn(3, '.');
+        let seed = parts.next()?;
+        let words = parts.next()?.parse().ok().filter(|&words| words > 0)?;
+        let language = parts.next().filter(|language| !language.is_empty())?;
+        if seed.is_empty() || seed.len() > 7 {
+            return None;
+        }
+        let seed = u32::try_from(u64::from_str_radix(seed, 36).ok()?).ok()?;
+        Some(Self {
+            language: language.to_owned(),
+            words,
+            seed,
+        })
+    }
+
+    /// Challenge of a day since the epoch, the same for everyone on that UTC date
+    pub fn daily(day: i64) -> Self {
+        let digest = Sha256::digest(format!("daily {}", date::format(day)).as_bytes());
+        Self {
+            language: String::from(DAILY_LANGUAGE),
+            words: DAILY_WORDS,
+            seed: u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]),
+        }
+    }
+}
+
+impl fmt::Display for Challenge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut seed = Vec::new();
+        let mut rest = self.seed;
+        loop {
+            seed.push(BASE36[(rest % 36) as usize]);
+            rest /= 36;
+            if rest == 0 {
+                break;
+            }
+        }
+        seed.reverse();
+        write!(
+            f,
+            "{}.{}.{}",
+            self.language,
+            self.words,
+            String::from_utf8_lossy(&seed)
+        )
+    }
+}
+
+/// Score on a challenge, passed back to the opponent as a single line
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+    pub challenge: Challenge,
+    /// Missing if speed was hidden, as it is with `accuracy_only`
+    pub wpm: Option<f64>,
+    /// Percentage of correct keypresses
+    pub accuracy: f64,
+    /// Key of the installation which signed the outcome, empty if it wasn't signed
+    pub public_key: String,
+    pub signature: String,
+}
+
+impl Outcome {
+    pub fn new(
+        challenge: &Challenge,
+        results: &Results,
+        hide_speed: bool,
+        signer: Option<&Signer>,
+    ) -> Self {
+        // rounded as they're shown, so the signature covers exactly what's printed
+        let round = |value: f64| (value * 10.0).round() / 10.0;
+        let mut outcome = Self {
+            challenge: challenge.clone(),
+            wpm: (!hide_speed).then(|| round(results.wpm())),
+            accuracy: round(f64::from(results.accuracy.overall) * 100.0),
+            public_key: String::new(),
+            signature: String::new(),
+        };
+        if let Some(signer) = signer {
+            outcome.public_key = signer.public_key();
+            outcome.signature = signer.sign(outcome.signed_text().as_bytes());
+        }
+        outcome
+    }
+
+    /// Parse an outcome printed at the end of a duel
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut parts = line.trim().split(':');
+        let challenge = Challenge::parse(parts.next()?)?;
+        let wpm = match parts.next()? {
+            "" => None,
+            wpm => Some(wpm.parse().ok()?),
+        };
+        let accuracy = parts.next()?.parse().ok()?;
+        let public_key = parts.next().unwrap_or_default().to_owned();
+        let signature = parts.next().unwrap_or_default().to_owned();
+        if parts.next().is_some() || public_key.is_empty() != signature.is_empty() {
+            return None;
+        }
+        Some(Self {
+            challenge,
+            wpm,
+            accuracy,
+            public_key,
+            signature,
+        })
+    }
+
+    /// Whether the signature is valid, or `None` if the outcome isn't signed
+    pub fn verify(&self) -> Option<bool> {
+        if self.signature.is_empty() {
+            return None;
+        }
+        Some(signing::verify(
+            &self.public_key,
+            self.signed_text().as_bytes(),
+            &self.signature,
+        ))
+    }
+
+    fn signed_text(&self) -> String {
+        let wpm = self
+            .wpm
+            .map(|wpm| format!("{:.1}", wpm))
+            .unwrap_or_default();
+        format!("{}:{}:{:.1}", self.challenge, wpm, self.accuracy)
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.signed_text())?;
+        if !self.signature.is_empty() {
+            write!(f, ":{}:{}", self.public_key, self.signature)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_challenges() {
+        let challenge = Challenge {
+            language: String::from("english200"),
+            words: 50,
+            seed: 1_234_567_890,
+        };
+        assert_eq!(challenge.to_string(), "english200.50.kf12oi");
+        assert_eq!(Challenge::parse("english200.50.kf12oi"), Some(challenge));
+
+        let dotted = Challenge::parse("lang.v2.10.0").unwrap();
+        assert_eq!(
+            (dotted.language.as_str(), dotted.words, dotted.seed),
+            ("lang.v2", 10, 0)
+        );
+
+        assert_eq!(Challenge::parse("english200.0.kf12oi"), None);
+        assert_eq!(Challenge::parse(".50.kf12oi"), None);
+        assert_eq!(Challenge::parse("english200.50.zzzzzzz"), None);
+    }
+
+    #[test]
+    fn picks_a_challenge_each_day() {
+        let today = date::from_civil(2026, 10, 14);
+        assert_eq!(Challenge::daily(today), Challenge::daily(today));
+        assert_ne!(
+            Challenge::daily(today).seed,
+            Challenge::daily(today + 1).seed
+        );
+        assert_eq!(Challenge::daily(today).language, DAILY_LANGUAGE);
+    }
+
+    #[test]
+    fn signs_outcomes() {
+        let signer = Signer::from_bytes(&[5; 32]);
+        let mut outcome = Outcome {
+            challenge: Challenge::parse("english200.50.kf12oi").unwrap(),
+            wpm: Some(72.5),
+            accuracy: 96.1,
+            public_key: signer.public_key(),
+            signature: String::new(),
+        };
+        assert_eq!(outcome.verify(), None);
+        outcome.signature = signer.sign(outcome.signed_text().as_bytes());
+
+        let parsed = Outcome::parse(&outcome.to_string()).unwrap();
+        assert_eq!(parsed, outcome);
+        assert_eq!(parsed.verify(), Some(true));
+
+        let faked = outcome.to_string().replacen(":72.5:", ":92.5:", 1);
+        assert_eq!(Outcome::parse(&faked).unwrap().verify(), Some(false));
+        assert_eq!(Outcome::parse("english200.50.kf12oi:72.5"), None);
+
+        // a duel taken with speed hidden leaves the WPM out
+        let hidden = Outcome::parse("english200.50.kf12oi::96.1").unwrap();
+        assert_eq!(hidden.wpm, None);
+        assert_eq!(hidden.to_string(), "english200.50.kf12oi::96.1");
+    }
+}
@@ -0,0 +1,682 @@
+//! Results written out as JSON for other tools, with word-by-word and keystroke timings
+
+use crate::config::{CutOffWord, Key, Lexer, Sentences};
+use crate::metrics::{self, Metrics};
+use crate::test::results::{
+    self, AccuracyData, CorrectionData, Fraction, Results, SuddenDeath, TimingData, WordResult,
+    WPM_PER_CPS,
+};
+use crate::test::Strictness;
+
+use clap::ValueEnum;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, UNIX_EPOCH};
+
+#[derive(Debug, Serialize)]
+pub struct Export<'a> {
+    /// Seconds since the Unix epoch when the test was completed
+    pub timestamp: i64,
+    pub mode: &'a str,
+    pub language: &'a str,
+    pub wpm: f64,
+    pub raw_wpm: f64,
+    pub accuracy: f64,
+    pub consistency: f64,
+    pub duration: f64,
+    pub setup: &'a Setup,
+    pub words: Vec<Word<'a>>,
+    /// Stats of each key typed, by its name in the config
+    pub keys: BTreeMap<String, KeyStats>,
+    pub keystrokes: Vec<Keystroke>,
+    pub missed_words: &'a [String],
+    /// Chars deleted by backspacing
+    pub deleted: usize,
+    /// Keypresses of each key which were likely switch chatter
+    pub chatter: &'a BTreeMap<char, usize>,
+    pub syllables: Option<usize>,
+    /// Seconds the test was paused for
+    pub paused: f64,
+    /// Seconds the test was limited to, if it ran out of time
+    pub time_limit: Option<f64>,
+    pub attribution: Option<&'a str>,
+    /// Whether a sudden death test was survived or died
+    pub sudden_death: Option<&'static str>,
+    /// Values of the metrics from the config, null where a formula didn't work out to a number
+    pub metrics: BTreeMap<&'a str, Option<f64>>,
+    /// Accuracy and speed on paired delimiters and quotes, if the test had any
+    pub delimiters: Option<DelimiterStats>,
+}
+
+/// Formats results can be printed in once a test is complete
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// The same record as --json writes, on one line
+    Json,
+}
+
+/// Everything that decided which words a test had and how it was scored, so a test can be set
+/// up again the same way after the defaults have changed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Setup {
+    /// Version of ttyper which ran the test
+    pub version: &'static str,
+    /// SHA-256 digest of the language's words, which tells apart edited copies of one language
+    pub language_digest: Option<String>,
+    pub sampling: String,
+    pub transforms: Vec<String>,
+    /// Number of words asked for
+    pub words: usize,
+    pub common: Option<usize>,
+    pub filter: Option<String>,
+    /// Shortest and longest words allowed
+    pub word_length: Option<[usize; 2]>,
+    /// Seed the words were picked with, which only seeded tests such as duels have
+    pub seed: Option<u64>,
+    /// Seconds allowed, for timed tests
+    pub time: Option<u64>,
+    pub backtrack: bool,
+    pub sudden_death: bool,
+    /// Whether sudden death ended the test at its first error, instead of starting it over
+    pub sudden_death_ends: bool,
+    /// How strictly mistakes were held against the typist
+    pub strictness: &'static str,
+    pub loose_quotes: bool,
+    pub cut_off_word: CutOffWord,
+    /// How the file or stdin was split into words, for tests of them
+    pub lexer: Option<Lexer>,
+    pub filter_profanity: bool,
+    /// SHA-256 digest of the words the blocklists left out, if they left any out
+    pub blocklist_digest: Option<String>,
+    pub ramp: bool,
+    /// What the words were drawn to practice
+    pub practice: Option<String>,
+    /// Chance of the tricky-caps transform capitalizing each letter after a word's first
+    pub tricky_caps: f64,
+    /// How the punctuation transform wrote sentences
+    pub sentences: Sentences,
+    /// Whether the test ended with Enter after the last word
+    pub confirm_end: bool,
+    /// Seconds without a keypress after which the test paused, if it did
+    pub pause_after: Option<f64>,
+}
+
+/// How one word was typed, timed in seconds from the test's first keypress
+#[derive(Debug, Serialize)]
+pub struct Word<'a> {
+    pub text: &'a str,
+    pub indent: usize,
+    pub line_end: bool,
+    pub start: f64,
+    pub end: f64,
+    pub errors: usize,
+    /// Speed between the word's first and last keypress, missing for a single keypress
+    pub wpm: Option<f64>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct KeyStats {
+    pub presses: usize,
+    pub correct: usize,
+    /// Speed from the keypress before, averaged over the key's presses
+    pub wpm: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DelimiterStats {
+    pub accuracy: f64,
+    pub wpm: Option<f64>,
+}
+
+/// One keypress, timed in seconds from the test's first
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Keystroke {
+    pub time: f64,
+    /// Seconds since the Unix epoch on the wall clock, which may drift from the timing above
+    #[serde(default)]
+    pub wall: Option<f64>,
+    pub key: String,
+    /// Missing for keys which neither typed nor deleted a char
+    pub correct: Option<bool>,
+    /// Char of the text the key was meant to type, if it was meant to type one
+    #[serde(default)]
+    pub expected: Option<char>,
+}
+
+/// Test read back from an export, with what's needed to show its results again
+#[derive(Debug, Deserialize)]
+pub struct Record {
+    pub raw_wpm: f64,
+    pub words: Vec<RecordWord>,
+    // everything below was added to exports later, so older files may not have it
+    #[serde(default)]
+    pub keystrokes: Vec<Keystroke>,
+    #[serde(default)]
+    pub missed_words: Vec<String>,
+    #[serde(default)]
+    pub deleted: usize,
+    #[serde(default)]
+    pub chatter: BTreeMap<char, usize>,
+    #[serde(default)]
+    pub syllables: Option<usize>,
+    #[serde(default)]
+    pub paused: f64,
+    #[serde(default)]
+    pub time_limit: Option<f64>,
+    #[serde(default)]
+    pub attribution: Option<String>,
+    #[serde(default)]
+    pub sudden_death: Option<String>,
+    #[serde(default)]
+    pub setup: Option<RecordSetup>,
+}
+
+/// What's needed of a record's setup, which older exports don't have, to show its results or
+/// race it again
+#[derive(Debug, Clone, Deserialize)]
+pub struct RecordSetup {
+    pub seed: Option<u64>,
+    // everything below was added to exports later, so it's missing where it wasn't recorded
+    #[serde(default)]
+    pub strictness: Option<String>,
+    #[serde(default)]
+    pub backtrack: Option<bool>,
+    #[serde(default)]
+    pub sudden_death: Option<bool>,
+    #[serde(default)]
+    pub sudden_death_ends: Option<bool>,
+    #[serde(default)]
+    pub loose_quotes: Option<bool>,
+    #[serde(default)]
+    pub ramp: Option<bool>,
+    #[serde(default)]
+    pub confirm_end: Option<bool>,
+    /// Missing where it wasn't recorded, and null for a test which never paused
+    #[serde(default, deserialize_with = "recorded")]
+    pub pause_after: Option<Option<f64>>,
+}
+
+/// Read a field which may be null, telling it apart from one missing altogether
+fn recorded<'de, D: serde::Deserializer<'de>, T: Deserialize<'de>>(
+    d: D,
+) -> Result<Option<T>, D::Error> {
+    T::deserialize(d).map(Some)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecordWord {
+    pub text: String,
+    // added to exports later, so older files may not have them
+    #[serde(default)]
+    pub indent: usize,
+    #[serde(default)]
+    pub line_end: bool,
+    pub start: f64,
+    pub end: f64,
+    pub errors: usize,
+    pub wpm: Option<f64>,
+}
+
+impl<'a> Export<'a> {
+    pub fn new(
+        results: &'a Results,
+        mode: &'a str,
+        language: &'a str,
+        setup: &'a Setup,
+        metrics: &'a Metrics,
+        timestamp: i64,
+    ) -> Self {
+        Self {
+            timestamp,
+            mode,
+            language,
+            wpm: results.wpm(),
+            raw_wpm: results.raw_wpm(),
+            accuracy: f64::from(results.accuracy.overall),
+            consistency: results.consistency(),
+            duration: results.duration(),
+            setup,
+            words: results
+                .words
+                .iter()
+                .map(|word| Word {
+                    text: &word.text,
+                    indent: word.indent,
+                    line_end: word.line_end,
+                    start: word.start,
+                    end: word.end,
+                    errors: word.errors,
+                    wpm: word.wpm(),
+                })
+                .collect(),
+            keys: key_stats(results),
+            keystrokes: results
+                .keystrokes
+                .iter()
+                .map(|keystroke| Keystroke {
+                    time: keystroke.time,
+                    wall: keystroke
+                        .wall
+                        .and_then(|wall| wall.duration_since(UNIX_EPOCH).ok())
+                        .map(|since| since.as_secs_f64()),
+                    key: Key::from(&keystroke.key).to_string(),
+                    correct: keystroke.correct,
+                    expected: keystroke.expected,
+                })
+                .collect(),
+            missed_words: &results.missed_words,
+            deleted: results.corrections.deleted,
+            chatter: &results.chatter,
+            syllables: results.syllables,
+            paused: results.paused,
+            time_limit: results.time_limit,
+            attribution: results.attribution.as_deref(),
+            sudden_death: results.sudden_death.map(SuddenDeath::name),
+            metrics: metrics::values(metrics, results),
+            delimiters: results.delimiters().map(|delimiters| DelimiterStats {
+                accuracy: f64::from(delimiters.accuracy),
+                wpm: delimiters.wpm,
+            }),
+        }
+    }
+
+    /// Results as JSON, where speeds which couldn't be measured are null
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Results failed to serialize.")
+    }
+
+    pub fn to_line(&self) -> String {
+        let mut line = self.to_json();
+        line.push('\n');
+        line
+    }
+}
+
+// keys told apart only by how shift was reported are counted together
+fn key_stats(results: &Results) -> BTreeMap<String, KeyStats> {
+    let mut seconds: BTreeMap<String, (f64, usize)> = BTreeMap::new();
+    for (key, &wait) in &results.timing.per_key {
+        let presses = results
+            .accuracy
+            .per_key
+            .get(key)
+            .map_or(1, |fraction| fraction.denominator.max(1));
+        let total = seconds.entry(Key::from(key).to_string()).or_default();
+        total.0 += wait * presses as f64;
+        total.1 += presses;
+    }
+
+    let mut keys: BTreeMap<String, KeyStats> = BTreeMap::new();
+    for (key, fraction) in &results.accuracy.per_key {
+        let stats = keys.entry(Key::from(key).to_string()).or_default();
+        stats.presses += fraction.denominator;
+        stats.correct += fraction.numerator;
+    }
+    for (name, (total, presses)) in seconds {
+        let wait = total / presses as f64;
+        if wait > 0.0 {
+            keys.entry(name).or_default().wpm = Some(WPM_PER_CPS / wait);
+        }
+    }
+    keys
+}
+
+impl Record {
+    /// Results as they were shown when the test was completed, short of the difficulty segments
+    /// of ramped tests
+    pub fn results(&self) -> Results {
+        // keys with names no longer known still count, as a key which does nothing
+        let key = |name: &str| {
+            name.parse::<Key>()
+                .map_or(KeyEvent::from(KeyCode::Null), |key| {
+                    KeyEvent::new(key.code, key.modifiers)
+                })
+        };
+        let keystrokes: Vec<results::Keystroke> = self
+            .keystrokes
+            .iter()
+            .map(|keystroke| results::Keystroke {
+                time: keystroke.time,
+                wall: keystroke
+                    .wall
+                    .and_then(|wall| Duration::try_from_secs_f64(wall).ok())
+                    .map(|since| UNIX_EPOCH + since),
+                key: key(&keystroke.key),
+                correct: keystroke.correct,
+                expected: keystroke.expected,
+            })
+            .collect();
+
+        let mut timing = TimingData {
+            overall_cps: self.raw_wpm / WPM_PER_CPS,
+            per_event: Vec::new(),
+            per_key: HashMap::new(),
+        };
+        let mut waits: HashMap<KeyEvent, (f64, usize)> = HashMap::new();
+        for pair in keystrokes.windows(2) {
+            let wait = pair[1].time - pair[0].time;
+            timing.per_event.push(wait);
+            let total = waits.entry(pair[1].key).or_insert((0.0, 0));
+            total.0 += wait;
+            total.1 += 1;
+        }
+        timing.per_key = waits
+            .into_iter()
+            .map(|(key, (total, count))| (key, total / count as f64))
+            .collect();
+
+        let mut accuracy = AccuracyData {
+            overall: Fraction::new(0, 0),
+            per_key: HashMap::new(),
+            per_expected: HashMap::new(),
+        };
+        for keystroke in &keystrokes {
+            let Some(correct) = keystroke.correct else {
+                continue;
+            };
+            let fractions = [
+                Some(&mut accuracy.overall),
+                Some(
+                    accuracy
+                        .per_key
+                        .entry(keystroke.key)
+                        .or_insert(Fraction::new(0, 0)),
+                ),
+                keystroke.expected.map(|expected| {
+                    accuracy
+                        .per_expected
+                        .entry(expected)
+                        .or_insert(Fraction::new(0, 0))
+                }),
+            ];
+            for fraction in fractions.into_iter().flatten() {
+                fraction.denominator += 1;
+                fraction.numerator += usize::from(correct);
+            }
+        }
+
+        let corrections = CorrectionData {
+            backspaces: keystrokes
+                .iter()
+                .filter(|keystroke| match keystroke.key.code {
+                    KeyCode::Backspace => true,
+                    KeyCode::Char('h') | KeyCode::Char('w') => {
+                        keystroke.key.modifiers.contains(KeyModifiers::CONTROL)
+                    }
+                    _ => false,
+                })
+                .count(),
+            deleted: self.deleted,
+            keystrokes: keystrokes.len(),
+        };
+
+        Results {
+            timing,
+            accuracy,
+            corrections,
+            keystrokes,
+            missed_words: self.missed_words.clone(),
+            words: self
+                .words
+                .iter()
+                .map(|word| WordResult {
+                    text: word.text.clone(),
+                    indent: word.indent,
+                    line_end: word.line_end,
+                    errors: word.errors,
+                    seconds_per_key: word.wpm.map(|wpm| WPM_PER_CPS / wpm),
+                    start: word.start,
+                    end: word.end,
+                })
+                .collect(),
+            chatter: self.chatter.clone(),
+            segments: Vec::new(),
+            syllables: self.syllables,
+            paused: self.paused,
+            time_limit: self.time_limit,
+            attribution: self.attribution.clone(),
+            sudden_death: match self.sudden_death.as_deref() {
+                Some("survived") => Some(SuddenDeath::Survived),
+                Some("died") => Some(SuddenDeath::Died),
+                _ => None,
+            },
+            seed: self.setup.as_ref().and_then(|setup| setup.seed),
+            strictness: self
+                .setup
+                .as_ref()
+                .and_then(|setup| setup.strictness.as_deref())
+                .and_then(Strictness::from_name)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Every test in an export, whether it's one test or a file of them a line each
+pub fn read(path: &Path) -> io::Result<Vec<Record>> {
+    serde_json::Deserializer::from_str(&fs::read_to_string(path)?)
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), err),
+            )
+        })
+}
+
+/// Add a line of results to the end of a file, so one file holds every test of a session
+pub fn append(path: &Path, export: &Export) -> io::Result<()> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(export.to_line().as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{Test, TestWord};
+
+    use crossterm::event::{KeyCode, KeyEvent};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn exports_word_timings() {
+        let indented = TestWord {
+            indent: 2,
+            ..TestWord::from("c")
+        };
+        let mut test = Test::new(vec![TestWord::from("ab"), indented], true, false);
+        let start = Instant::now();
+        let keys = [
+            KeyCode::Char('a'),
+            KeyCode::Char('x'),
+            KeyCode::Backspace,
+            KeyCode::Char('b'),
+            KeyCode::Char(' '),
+            KeyCode::Char('c'),
+        ];
+        for (i, code) in keys.into_iter().enumerate() {
+            let time = start + Duration::from_millis(250 * i as u64);
+            test.handle_key_at(KeyEvent::from(code), time);
+        }
+        assert!(test.complete);
+
+        let results = Results::from(&test);
+        let setup = Setup {
+            version: "1.6.0",
+            language_digest: None,
+            sampling: String::from("shuffle"),
+            transforms: vec![String::from("capitalize")],
+            words: 2,
+            common: None,
+            filter: None,
+            word_length: Some([1, 2]),
+            seed: Some(7),
+            time: None,
+            backtrack: true,
+            sudden_death: false,
+            sudden_death_ends: false,
+            strictness: "forgiving",
+            loose_quotes: false,
+            cut_off_word: CutOffWord::Drop,
+            lexer: None,
+            filter_profanity: false,
+            blocklist_digest: None,
+            ramp: false,
+            practice: None,
+            tricky_caps: 0.1,
+            sentences: Sentences::default(),
+            confirm_end: false,
+            pause_after: None,
+        };
+        let metrics = Metrics::from([
+            (String::from("none"), "words * 0".parse().unwrap()),
+            (String::from("broken"), "1 / 0".parse().unwrap()),
+        ]);
+        let export = Export::new(&results, "words", "english200", &setup, &metrics, 0);
+        let line = export.to_line();
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(json["words"][0]["text"], "ab");
+        assert_eq!(json["words"][0]["start"], 0.0);
+        assert_eq!(json["words"][0]["end"], 1.0);
+        assert_eq!(json["words"][0]["errors"], 1);
+        assert_eq!(json["words"][0]["wpm"], 48.0);
+        assert_eq!(json["words"][1]["start"], 1.25);
+        assert_eq!(json["words"][1]["indent"], 2);
+        assert_eq!(json["words"][0]["line_end"], false);
+        assert!(json["words"][1]["wpm"].is_null());
+        assert_eq!(json["setup"]["transforms"][0], "capitalize");
+        assert_eq!(json["setup"]["word_length"][1], 2);
+        assert_eq!(json["setup"]["cut_off_word"], "drop");
+        assert!(json["setup"]["lexer"].is_null());
+        assert_eq!(json["setup"]["sentences"]["spacing"], "single");
+        assert!(json["setup"]["pause_after"].is_null());
+        assert!(json["setup"]["time"].is_null());
+        assert_eq!(json["keys"]["b"]["presses"], 1);
+        assert_eq!(json["keys"]["x"]["correct"], 0);
+        assert_eq!(json["keystrokes"][2]["key"], "backspace");
+        assert_eq!(json["metrics"]["none"], 0.0);
+        assert!(json["metrics"]["broken"].is_null());
+        assert_eq!(json["keystrokes"][2]["time"], 0.5);
+        assert_eq!(json["keystrokes"].as_array().unwrap().len(), 6);
+
+        // and reading it back gives the same results
+        let record: Record = serde_json::from_str(&line).unwrap();
+        let read = record.results();
+        assert_eq!(read.accuracy.overall, results.accuracy.overall);
+        assert_eq!(read.accuracy.per_expected, results.accuracy.per_expected);
+        assert_eq!(read.corrections.backspaces, 1);
+        assert_eq!(read.timing.per_event, results.timing.per_event);
+        assert_eq!(read.words[0].wpm(), Some(48.0));
+        assert_eq!(record.words[1].indent, 2);
+        // keystrokes keep the wall clock as well as the monotonic one
+        let wall = json["keystrokes"][0]["wall"].as_f64().unwrap();
+        assert!(wall > 1e9);
+        assert_eq!(record.keystrokes[0].wall, Some(wall));
+        assert_eq!(
+            read.keystrokes[0].wall,
+            Some(UNIX_EPOCH + Duration::from_secs_f64(wall))
+        );
+        assert!((read.wpm() - results.wpm()).abs() < 1e-9);
+        assert!(line.ends_with("}\n"));
+    }
+
+    #[test]
+    fn exports_delimiter_accuracy() {
+        let mut test = Test::new(vec![TestWord::from("f(x)")], true, false);
+        let start = Instant::now();
+        let keys = [
+            KeyCode::Char('f'),
+            KeyCode::Char('['),
+            KeyCode::Backspace,
+            KeyCode::Char('('),
+            KeyCode::Char('x'),
+            KeyCode::Char(')'),
+        ];
+        for (i, code) in keys.into_iter().enumerate() {
+            let time = start + Duration::from_millis(250 * i as u64);
+            test.handle_key_at(KeyEvent::from(code), time);
+        }
+
+        let results = Results::from(&test);
+        let delimiters = results.delimiters().unwrap();
+        assert_eq!(delimiters.accuracy, Fraction::new(2, 3));
+        assert_eq!(delimiters.wpm, Some(48.0));
+
+        let setup = Setup {
+            version: "1.6.0",
+            language_digest: None,
+            sampling: String::from("shuffle"),
+            transforms: Vec::new(),
+            words: 1,
+            common: None,
+            filter: None,
+            word_length: None,
+            seed: None,
+            time: None,
+            backtrack: true,
+            sudden_death: false,
+            sudden_death_ends: false,
+            strictness: "forgiving",
+            loose_quotes: false,
+            cut_off_word: CutOffWord::Drop,
+            lexer: None,
+            filter_profanity: false,
+            blocklist_digest: None,
+            ramp: false,
+            practice: None,
+            tricky_caps: 0.1,
+            sentences: Sentences::default(),
+            confirm_end: false,
+            pause_after: None,
+        };
+        let metrics = Metrics::new();
+        let export = Export::new(&results, "words", "code", &setup, &metrics, 0);
+        let json: serde_json::Value = serde_json::from_str(&export.to_json()).unwrap();
+        assert_eq!(json["delimiters"]["wpm"], 48.0);
+
+        let plain = Results::from(&Test::new(vec![TestWord::from("ab")], true, false));
+        assert_eq!(plain.delimiters(), None);
+    }
+
+    #[test]
+    fn keeps_the_strictness() {
+        let mut test = Test::new(vec![TestWord::from("ab")], true, false);
+        test.strictness = Strictness::Stop;
+        for c in "axb".chars() {
+            test.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        // the wrong key waited for the right one, so the word was still finished
+        assert!(test.complete);
+
+        let results = Results::from(&test);
+        assert_eq!(results.accuracy.overall, Fraction::new(2, 3));
+        let json =
+            r#"{"raw_wpm": 60.0, "words": [], "setup": {"seed": null, "strictness": "stop"}}"#;
+        let record: Record = serde_json::from_str(json).unwrap();
+        assert_eq!(record.results().strictness, Strictness::Stop);
+    }
+
+    #[test]
+    fn tells_unrecorded_options_from_unset_ones() {
+        let setup = |json: &str| serde_json::from_str::<RecordSetup>(json).unwrap();
+        let old = setup(r#"{"seed": null}"#);
+        assert_eq!(old.confirm_end, None);
+        assert_eq!(old.pause_after, None);
+
+        let new = setup(r#"{"seed": 1, "confirm_end": true, "pause_after": null, "ramp": false}"#);
+        assert_eq!(new.confirm_end, Some(true));
+        assert_eq!(new.pause_after, Some(None));
+        assert_eq!(new.ramp, Some(false));
+        assert_eq!(
+            setup(r#"{"seed": 1, "pause_after": 3.0}"#).pause_after,
+            Some(Some(3.0))
+        );
+    }
+}
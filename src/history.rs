@@ -0,0 +1,852 @@
+use crate::config::Keyboard;
+use crate::stats;
+use crate::test::results::{Alternation, Results, SuddenDeath, BURST_WINDOW, ROLLING_WINDOW};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Changes to the schema, each applied once in order and tracked by the user_version pragma
+const MIGRATIONS: [&str; 15] = [
+    "CREATE TABLE IF NOT EXISTS results (
+        id INTEGER PRIMARY KEY,
+        timestamp INTEGER NOT NULL,
+        mode TEXT NOT NULL,
+        language TEXT NOT NULL,
+        duration REAL NOT NULL,
+        wpm REAL NOT NULL,
+        raw_wpm REAL NOT NULL,
+        accuracy REAL NOT NULL
+    );",
+    // comma-separated names of the transforms applied to the words
+    "ALTER TABLE results ADD COLUMN transforms TEXT NOT NULL DEFAULT '';",
+    // zero keystrokes for results recorded before corrections were counted
+    "ALTER TABLE results ADD COLUMN backspaces INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE results ADD COLUMN deleted INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE results ADD COLUMN keystrokes INTEGER NOT NULL DEFAULT 0;",
+    // best WPM over any rolling window, null for tests shorter than the window
+    "ALTER TABLE results ADD COLUMN rolling_peak_wpm REAL;",
+    // best WPM over a short sprint, null for tests shorter than the sprint
+    "ALTER TABLE results ADD COLUMN burst_wpm REAL;",
+    // how badly each word has been typed, as of the last time it was
+    "CREATE TABLE weak_words (
+        word TEXT PRIMARY KEY,
+        weakness REAL NOT NULL,
+        updated INTEGER NOT NULL
+    );",
+    // keyboard profile from the config, empty when none was set
+    "ALTER TABLE results ADD COLUMN keyboard TEXT NOT NULL DEFAULT '';
+    ALTER TABLE results ADD COLUMN switches TEXT NOT NULL DEFAULT '';
+    ALTER TABLE results ADD COLUMN layout TEXT NOT NULL DEFAULT '';",
+    // free-text note added from the results screen
+    "ALTER TABLE results ADD COLUMN note TEXT NOT NULL DEFAULT '';",
+    // seconds the test was paused for after the typist stopped
+    "ALTER TABLE results ADD COLUMN paused REAL NOT NULL DEFAULT 0;",
+    // where the test was typed, empty for results recorded before it was
+    "ALTER TABLE results ADD COLUMN terminal TEXT NOT NULL DEFAULT '';
+    ALTER TABLE results ADD COLUMN term TEXT NOT NULL DEFAULT '';
+    ALTER TABLE results ADD COLUMN columns INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE results ADD COLUMN rows INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE results ADD COLUMN platform TEXT NOT NULL DEFAULT '';
+    ALTER TABLE results ADD COLUMN version TEXT NOT NULL DEFAULT '';
+    ALTER TABLE results ADD COLUMN ssh INTEGER NOT NULL DEFAULT 0;",
+    // "survived" or "died" for sudden death tests, empty for others
+    "ALTER TABLE results ADD COLUMN sudden_death TEXT NOT NULL DEFAULT '';",
+    // word each file typed through with --file was left at
+    "CREATE TABLE file_positions (
+        path TEXT PRIMARY KEY,
+        word INTEGER NOT NULL,
+        updated INTEGER NOT NULL
+    );",
+    // speed of letters typed by alternating hands and by the same hand, null unless the test
+    // had both
+    "ALTER TABLE results ADD COLUMN alternating_wpm REAL;
+    ALTER TABLE results ADD COLUMN same_hand_wpm REAL;",
+    // the test as it's exported, so its results can be opened again, empty for results recorded
+    // before it was kept
+    "ALTER TABLE results ADD COLUMN details TEXT NOT NULL DEFAULT '';",
+    // how strictly mistakes were held against the typist, by the name --strictness takes
+    "ALTER TABLE results ADD COLUMN strictness TEXT NOT NULL DEFAULT 'forgiving';",
+];
+
+// Variables terminal emulators set which name them, for those that don't set TERM_PROGRAM
+const TERMINAL_VARS: [(&str, &str); 6] = [
+    ("KITTY_WINDOW_ID", "kitty"),
+    ("ALACRITTY_WINDOW_ID", "alacritty"),
+    ("WT_SESSION", "Windows Terminal"),
+    ("KONSOLE_VERSION", "konsole"),
+    ("GNOME_TERMINAL_SCREEN", "gnome-terminal"),
+    ("VTE_VERSION", "vte"),
+];
+
+// Weakness below which a word is forgotten
+const MIN_WEAKNESS: f64 = 0.05;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+// Results which can't have come from a test, as recorded tests always have a speed
+const INVALID_RESULTS: &str = "NOT coalesce(
+    duration > 0 AND wpm >= 0 AND raw_wpm >= 0 AND accuracy BETWEEN 0 AND 1, 0
+)";
+
+/// Something wrong with the history, found by `History::check`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    /// Damage reported by SQLite's integrity check
+    Corrupt(String),
+    /// Recorded by a newer ttyper, whose changes to the schema this one doesn't know
+    NewerSchema(usize),
+    /// Table which a migration should have created
+    MissingTable(String),
+    /// Column which a migration should have added, left out by a database edited by hand or
+    /// migrated part way
+    MissingColumn { table: String, column: String },
+    /// Number of results with impossible values
+    InvalidResults(usize),
+}
+
+impl Problem {
+    /// Whether `History::repair` can fix it
+    pub fn repairable(&self) -> bool {
+        !matches!(self, Self::NewerSchema(_))
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Corrupt(message) => write!(f, "damaged: {}", message),
+            Self::NewerSchema(version) => write!(
+                f,
+                "schema version {} is newer than this ttyper's {}, so update ttyper",
+                version,
+                MIGRATIONS.len()
+            ),
+            Self::MissingTable(table) => write!(f, "table {} is missing", table),
+            Self::MissingColumn { table, column } => {
+                write!(f, "column {}.{} is missing", table, column)
+            }
+            Self::InvalidResults(1) => write!(f, "1 result has impossible values"),
+            Self::InvalidResults(count) => write!(f, "{} results have impossible values", count),
+        }
+    }
+}
+
+/// Columns of a table, with the definition `ALTER TABLE` needs to add each one back
+fn columns(conn: &Connection, table: &str) -> rusqlite::Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let rows = stmt.query_map([], |row| {
+        let name: String = row.get("name")?;
+        let kind: String = row.get("type")?;
+        let not_null: bool = row.get("notnull")?;
+        let default: Option<String> = row.get("dflt_value")?;
+        let mut definition = format!("{} {}", name, kind);
+        if not_null {
+            definition.push_str(" NOT NULL");
+        }
+        if let Some(default) = default {
+            definition.push_str(&format!(" DEFAULT {}", default));
+        }
+        Ok((name, definition))
+    })?;
+    rows.collect()
+}
+
+fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+    let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        conn.execute_batch(&format!(
+            "BEGIN; {} PRAGMA user_version = {}; COMMIT;",
+            migration,
+            i + 1
+        ))?;
+    }
+    Ok(())
+}
+
+/// Terminal and machine a test was typed on, so changes in results can be traced to them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Environment {
+    /// Terminal emulator, with its version if it gives one
+    pub terminal: String,
+    /// Value of `TERM`
+    pub term: String,
+    pub columns: u16,
+    pub rows: u16,
+    /// Operating system, such as `linux` or `macos`
+    pub platform: String,
+    /// Version of ttyper
+    pub version: String,
+    /// Whether the test was typed over SSH
+    pub ssh: bool,
+}
+
+impl Environment {
+    /// Environment of this process, in a terminal of the given size
+    pub fn detect(columns: u16, rows: u16) -> Self {
+        Self::from_vars(|name| std::env::var(name).ok(), columns, rows)
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>, columns: u16, rows: u16) -> Self {
+        let terminal = match var("TERM_PROGRAM") {
+            Some(program) => match var("TERM_PROGRAM_VERSION") {
+                Some(version) => format!("{} {}", program, version),
+                None => program,
+            },
+            None => TERMINAL_VARS
+                .iter()
+                .find(|(name, _)| var(name).is_some())
+                .map(|(_, terminal)| terminal.to_string())
+                .unwrap_or_default(),
+        };
+        Self {
+            terminal,
+            term: var("TERM").unwrap_or_default(),
+            columns,
+            rows,
+            platform: std::env::consts::OS.to_owned(),
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            ssh: var("SSH_CONNECTION").is_some() || var("SSH_TTY").is_some(),
+        }
+    }
+}
+
+/// A completed test as stored in the history
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub id: i64,
+    /// Seconds since the Unix epoch when the test was completed
+    pub timestamp: i64,
+    /// How the test was chosen, such as `words` or `practice`
+    pub mode: String,
+    pub language: String,
+    /// Seconds spent typing
+    pub duration: f64,
+    pub wpm: f64,
+    /// Fraction of correct keypresses, from 0 to 1
+    pub accuracy: f64,
+    pub backspaces: usize,
+    /// Keystrokes which were deleted or did the deleting
+    pub wasted: usize,
+    /// Zero for results recorded before corrections were counted
+    pub keystrokes: usize,
+    pub burst_wpm: Option<f64>,
+    /// Speed of letters typed by alternating hands against the same hand, if the test had both
+    pub alternation: Option<Alternation>,
+    pub keyboard: Keyboard,
+    pub note: String,
+    /// "survived" or "died" for sudden death tests, empty for others
+    pub sudden_death: String,
+    /// "forgiving", "stop" or "no-backspace"
+    pub strictness: String,
+    pub environment: Environment,
+    /// Whether the test's details were kept, which `History::details` returns
+    pub detailed: bool,
+}
+
+/// Which results `History::query` returns, all of them by default
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    /// First day, since the epoch, of the results
+    pub since: Option<i64>,
+    /// Last day, since the epoch, of the results
+    pub until: Option<i64>,
+    pub language: Option<String>,
+    pub mode: Option<String>,
+    /// Most results returned, which are the newest
+    pub limit: Option<usize>,
+}
+
+/// Local database of completed tests
+pub struct History {
+    conn: Connection,
+}
+
+impl History {
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+
+        let conn = Connection::open(path)?;
+        migrate(&conn)?;
+        Ok(Self { conn })
+    }
+
+    /// Schema version, which is the number of migrations applied
+    pub fn version(&self) -> rusqlite::Result<usize> {
+        self.conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+    }
+
+    /// Look for damage and for differences from the schema the migrations build
+    pub fn check(&self) -> rusqlite::Result<Vec<Problem>> {
+        let mut problems = Vec::new();
+
+        let mut stmt = self.conn.prepare("PRAGMA integrity_check")?;
+        let messages = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        for message in messages {
+            let message = message?;
+            if message != "ok" {
+                problems.push(Problem::Corrupt(message));
+            }
+        }
+
+        let version = self.version()?;
+        if version > MIGRATIONS.len() {
+            problems.push(Problem::NewerSchema(version));
+        }
+
+        let expected = Connection::open_in_memory()?;
+        migrate(&expected)?;
+        for table in ["results", "weak_words", "file_positions"] {
+            let present = columns(&self.conn, table)?;
+            if present.is_empty() {
+                problems.push(Problem::MissingTable(table.to_owned()));
+                continue;
+            }
+            for (column, _) in columns(&expected, table)? {
+                if !present.iter().any(|(name, _)| *name == column) {
+                    problems.push(Problem::MissingColumn {
+                        table: table.to_owned(),
+                        column,
+                    });
+                }
+            }
+        }
+
+        // columns impossible results are found by, which can't be added back
+        let unreadable = problems.iter().any(|problem| match problem {
+            Problem::MissingTable(table) => table == "results",
+            Problem::MissingColumn { table, column } => {
+                table == "results"
+                    && ["duration", "wpm", "raw_wpm", "accuracy"].contains(&&**column)
+            }
+            _ => false,
+        });
+        if unreadable {
+            return Ok(problems);
+        }
+
+        let invalid: usize = self.conn.query_row(
+            &format!("SELECT count(*) FROM results WHERE {}", INVALID_RESULTS),
+            [],
+            |row| row.get(0),
+        )?;
+        if invalid > 0 {
+            problems.push(Problem::InvalidResults(invalid));
+        }
+
+        Ok(problems)
+    }
+
+    /// Fix problems found by `check`, rebuilding indices, adding back missing columns with their
+    /// defaults, and deleting impossible results
+    pub fn repair(&self, problems: &[Problem]) -> rusqlite::Result<()> {
+        let expected = Connection::open_in_memory()?;
+        migrate(&expected)?;
+
+        for problem in problems {
+            match problem {
+                Problem::Corrupt(_) => self.conn.execute_batch("REINDEX;")?,
+                Problem::NewerSchema(_) => {}
+                Problem::MissingTable(table) => {
+                    let sql: String = expected.query_row(
+                        "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?1",
+                        [table],
+                        |row| row.get(0),
+                    )?;
+                    self.conn.execute_batch(&format!("{};", sql))?;
+                }
+                Problem::MissingColumn { table, column } => {
+                    let definition = columns(&expected, table)?
+                        .into_iter()
+                        .find(|(name, _)| name == column)
+                        .map(|(_, definition)| definition);
+                    if let Some(definition) = definition {
+                        self.conn.execute_batch(&format!(
+                            "ALTER TABLE {} ADD COLUMN {};",
+                            table, definition
+                        ))?;
+                    }
+                }
+                Problem::InvalidResults(_) => {
+                    self.conn.execute(
+                        &format!("DELETE FROM results WHERE {}", INVALID_RESULTS),
+                        [],
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn record(
+        &self,
+        results: &Results,
+        mode: &str,
+        language: &str,
+        transforms: &[String],
+        keyboard: &Keyboard,
+        environment: &Environment,
+    ) -> rusqlite::Result<Option<i64>> {
+        // too few keypresses to measure anything
+        if !results.wpm().is_finite() {
+            return Ok(None);
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        let alternation = results.alternation(&keyboard.rows());
+
+        self.conn.execute(
+            "INSERT INTO results
+             (timestamp, mode, language, duration, wpm, raw_wpm, accuracy, transforms,
+              backspaces, deleted, keystrokes, rolling_peak_wpm, burst_wpm,
+              keyboard, switches, layout, paused,
+              terminal, term, columns, rows, platform, version, ssh, sudden_death,
+              alternating_wpm, same_hand_wpm, strictness)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17,
+              ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28)",
+            params![
+                timestamp,
+                mode,
+                language,
+                results.duration(),
+                results.wpm(),
+                results.raw_wpm(),
+                f64::from(results.accuracy.overall),
+                transforms.join(","),
+                results.corrections.backspaces,
+                results.corrections.deleted,
+                results.corrections.keystrokes,
+                results.peak_wpm(ROLLING_WINDOW),
+                results.peak_wpm(BURST_WINDOW),
+                keyboard.name,
+                keyboard.switches,
+                keyboard.layout,
+                results.paused,
+                environment.terminal,
+                environment.term,
+                environment.columns,
+                environment.rows,
+                environment.platform,
+                environment.version,
+                environment.ssh,
+                results.sudden_death.map_or("", SuddenDeath::name),
+                alternation.map(|a| a.alternating_wpm),
+                alternation.map(|a| a.same_hand_wpm),
+                results.strictness.name(),
+            ],
+        )?;
+        let id = self.conn.last_insert_rowid();
+
+        self.update_weak_words(results, timestamp)?;
+        Ok(Some(id))
+    }
+
+    /// Attach a note to a recorded result, replacing any it had
+    pub fn set_note(&self, id: i64, note: &str) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "UPDATE results SET note = ?1 WHERE id = ?2",
+            params![note, id],
+        )?;
+        Ok(())
+    }
+
+    /// Keep the export of a recorded result, so it can be opened in the results screen again
+    pub fn set_details(&self, id: i64, details: &str) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "UPDATE results SET details = ?1 WHERE id = ?2",
+            params![details, id],
+        )?;
+        Ok(())
+    }
+
+    /// Export of a recorded result, empty if it was recorded before they were kept
+    pub fn details(&self, id: i64) -> rusqlite::Result<String> {
+        self.conn
+            .query_row("SELECT details FROM results WHERE id = ?1", [id], |row| {
+                row.get(0)
+            })
+    }
+
+    /// Add the weakness of each word typed, and let cleanly typed words recover
+    fn update_weak_words(&self, results: &Results, timestamp: i64) -> rusqlite::Result<()> {
+        let average = 1.0 / results.timing.overall_cps;
+        let mut typed: HashMap<&str, f64> = HashMap::new();
+        for word in &results.words {
+            *typed.entry(&word.text).or_default() += stats::weakness(word, average);
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        for (word, weakness) in typed {
+            let previous = tx
+                .query_row(
+                    "SELECT weakness, updated FROM weak_words WHERE word = ?1",
+                    [word],
+                    |row| Ok((row.get::<_, f64>(0)?, row.get::<_, i64>(1)?)),
+                )
+                .optional()?
+                .map_or(0.0, |(previous, updated)| {
+                    stats::decay(previous, (timestamp - updated) as f64)
+                });
+            let current = if weakness > 0.0 {
+                previous + weakness
+            } else {
+                previous / 2.0
+            };
+
+            if current < MIN_WEAKNESS {
+                tx.execute("DELETE FROM weak_words WHERE word = ?1", [word])?;
+            } else {
+                tx.execute(
+                    "INSERT OR REPLACE INTO weak_words (word, weakness, updated)
+                     VALUES (?1, ?2, ?3)",
+                    params![word, current, timestamp],
+                )?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// Days since the epoch on which any test, or any test of a mode, was completed
+    pub fn practice_days(&self, mode: Option<&str>) -> rusqlite::Result<BTreeSet<i64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT timestamp / ?1 FROM results WHERE ?2 IS NULL OR mode = ?2")?;
+        let days = stmt.query_map(params![SECONDS_PER_DAY, mode], |row| row.get(0))?;
+        days.collect()
+    }
+
+    /// Word a file being typed through was left at, which is its first if it hasn't been started
+    pub fn file_position(&self, path: &str) -> rusqlite::Result<usize> {
+        self.conn
+            .query_row(
+                "SELECT word FROM file_positions WHERE path = ?1",
+                [path],
+                |row| row.get(0),
+            )
+            .optional()
+            .map(Option::unwrap_or_default)
+    }
+
+    pub fn set_file_position(&self, path: &str, word: usize) -> rusqlite::Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        self.conn.execute(
+            "INSERT OR REPLACE INTO file_positions (path, word, updated) VALUES (?1, ?2, ?3)",
+            params![path, word, timestamp],
+        )?;
+        Ok(())
+    }
+
+    /// Words with the most weakness as of now, worst first
+    pub fn weakest_words(&self, count: usize) -> rusqlite::Result<Vec<String>> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT word, weakness, updated FROM weak_words")?;
+        let mut words = stmt
+            .query_map([], |row| {
+                let (weakness, updated): (f64, i64) = (row.get(1)?, row.get(2)?);
+                Ok((row.get(0)?, stats::decay(weakness, (now - updated) as f64)))
+            })?
+            .collect::<rusqlite::Result<Vec<(String, f64)>>>()?;
+
+        words.sort_by(|a, b| b.1.total_cmp(&a.1));
+        Ok(words
+            .into_iter()
+            .take(count)
+            .map(|(word, _)| word)
+            .collect())
+    }
+
+    /// All recorded tests, newest first
+    pub fn entries(&self) -> rusqlite::Result<Vec<Entry>> {
+        self.query(&Query::default())
+    }
+
+    /// Recorded tests matching a query, newest first
+    pub fn query(&self, query: &Query) -> rusqlite::Result<Vec<Entry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT timestamp, mode, language, duration, wpm, accuracy, backspaces,
+             backspaces + deleted, keystrokes, burst_wpm, keyboard, switches, layout, note,
+             terminal, term, columns, rows, platform, version, ssh, sudden_death,
+             alternating_wpm, same_hand_wpm, id, details != '', strictness
+             FROM results
+             WHERE (?1 IS NULL OR timestamp >= ?1 * ?6)
+             AND (?2 IS NULL OR timestamp < (?2 + 1) * ?6)
+             AND (?3 IS NULL OR language = ?3)
+             AND (?4 IS NULL OR mode = ?4)
+             ORDER BY timestamp DESC, id DESC LIMIT ?5",
+        )?;
+
+        let limit = query.limit.map_or(-1, |limit| limit as i64);
+        let entries = stmt.query_map(
+            params![
+                query.since,
+                query.until,
+                query.language,
+                query.mode,
+                limit,
+                SECONDS_PER_DAY
+            ],
+            |row| {
+                Ok(Entry {
+                    id: row.get(24)?,
+                    timestamp: row.get(0)?,
+                    mode: row.get(1)?,
+                    language: row.get(2)?,
+                    duration: row.get(3)?,
+                    wpm: row.get(4)?,
+                    accuracy: row.get(5)?,
+                    backspaces: row.get(6)?,
+                    wasted: row.get(7)?,
+                    keystrokes: row.get(8)?,
+                    burst_wpm: row.get(9)?,
+                    alternation: match (row.get(22)?, row.get(23)?) {
+                        (Some(alternating_wpm), Some(same_hand_wpm)) => Some(Alternation {
+                            alternating_wpm,
+                            same_hand_wpm,
+                        }),
+                        _ => None,
+                    },
+                    keyboard: Keyboard {
+                        name: row.get(10)?,
+                        switches: row.get(11)?,
+                        layout: row.get(12)?,
+                    },
+                    note: row.get(13)?,
+                    sudden_death: row.get(21)?,
+                    strictness: row.get(26)?,
+                    environment: Environment {
+                        terminal: row.get(14)?,
+                        term: row.get(15)?,
+                        columns: row.get(16)?,
+                        rows: row.get(17)?,
+                        platform: row.get(18)?,
+                        version: row.get(19)?,
+                        ssh: row.get(20)?,
+                    },
+                    detailed: row.get(25)?,
+                })
+            },
+        )?;
+
+        entries.collect()
+    }
+
+    /// WPM of the last few results, oldest first
+    pub fn recent_wpm(&self, count: usize) -> rusqlite::Result<Vec<f64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT wpm FROM results ORDER BY timestamp DESC, id DESC LIMIT ?1")?;
+
+        let mut wpm = stmt
+            .query_map([count], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<f64>>>()?;
+        wpm.reverse();
+        Ok(wpm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{Test, TestWord};
+    use crossterm::event::{KeyCode, KeyEvent};
+    use std::time::{Duration, Instant};
+
+    fn in_memory() -> History {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn).unwrap();
+        History { conn }
+    }
+
+    fn results_of(text: &str) -> Results {
+        let mut test = Test::new(text.split(' ').map(TestWord::from).collect(), true, false);
+        let start = Instant::now();
+        for (i, c) in text.chars().enumerate() {
+            let time = start + Duration::from_millis(200 * i as u64);
+            test.handle_key_at(KeyEvent::from(KeyCode::Char(c)), time);
+        }
+        assert!(test.complete);
+        Results::from(&test)
+    }
+
+    #[test]
+    fn records_and_queries_results() {
+        let history = in_memory();
+        let keyboard = Keyboard::default();
+        let environment = Environment::default();
+        let record = |text: &str, mode: &str, language: &str| {
+            history
+                .record(
+                    &results_of(text),
+                    mode,
+                    language,
+                    &[],
+                    &keyboard,
+                    &environment,
+                )
+                .unwrap()
+                .unwrap()
+        };
+        let first = record("ab cd", "words", "english200");
+        let second = record("ef gh", "practice", "english200");
+        let third = record("ij kl", "words", "german");
+        history.set_details(second, r#"{"raw_wpm": 60.0}"#).unwrap();
+
+        // newest first, which within a second is the last recorded
+        let ids: Vec<i64> = history.entries().unwrap().iter().map(|e| e.id).collect();
+        assert_eq!(ids, [third, second, first]);
+
+        let query = |language: Option<&str>, mode: Option<&str>| {
+            let query = Query {
+                language: language.map(str::to_owned),
+                mode: mode.map(str::to_owned),
+                ..Query::default()
+            };
+            let entries = history.query(&query).unwrap();
+            entries.iter().map(|e| e.id).collect::<Vec<i64>>()
+        };
+        assert_eq!(query(Some("english200"), None), [second, first]);
+        assert_eq!(query(None, Some("words")), [third, first]);
+        assert!(query(Some("german"), Some("practice")).is_empty());
+        let limited = Query {
+            limit: Some(1),
+            ..Query::default()
+        };
+        assert_eq!(history.query(&limited).unwrap()[0].id, third);
+
+        let entries = history.entries().unwrap();
+        assert!(entries[1].detailed && !entries[0].detailed);
+        assert_eq!(entries[2].mode, "words");
+        assert!(entries[2].wpm > 0.0);
+        assert_eq!(history.details(second).unwrap(), r#"{"raw_wpm": 60.0}"#);
+        assert_eq!(history.details(first).unwrap(), "");
+    }
+
+    #[test]
+    fn migrates_old_histories_forward() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(&format!(
+            "{} PRAGMA user_version = 3;",
+            MIGRATIONS[..3].join("\n")
+        ))
+        .unwrap();
+        conn.execute(
+            "INSERT INTO results (timestamp, mode, language, duration, wpm, raw_wpm, accuracy)
+             VALUES (86400, 'words', 'english200', 30.0, 60.0, 62.0, 0.95)",
+            [],
+        )
+        .unwrap();
+
+        migrate(&conn).unwrap();
+        let history = History { conn };
+        assert_eq!(history.version().unwrap(), MIGRATIONS.len());
+        assert_eq!(history.check().unwrap(), []);
+        // the result from before is kept, with the defaults of the columns added since
+        let entries = history.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].wpm, 60.0);
+        assert_eq!(entries[0].strictness, "forgiving");
+        assert_eq!(entries[0].note, "");
+        assert!(!entries[0].detailed);
+    }
+
+    #[test]
+    fn checks_and_repairs_damage() {
+        let history = in_memory();
+        history
+            .conn
+            .execute_batch(
+                "INSERT INTO results (timestamp, mode, language, duration, wpm, raw_wpm, accuracy)
+                 VALUES (86400, 'words', 'english200', 30.0, 60.0, 62.0, 0.95),
+                        (86400, 'words', 'english200', 0.0, -5.0, 62.0, 1.5);
+                 ALTER TABLE results DROP COLUMN note;",
+            )
+            .unwrap();
+
+        let problems = history.check().unwrap();
+        assert_eq!(
+            problems,
+            [
+                Problem::MissingColumn {
+                    table: String::from("results"),
+                    column: String::from("note"),
+                },
+                Problem::InvalidResults(1),
+            ]
+        );
+        assert!(problems.iter().all(Problem::repairable));
+
+        history.repair(&problems).unwrap();
+        assert_eq!(history.check().unwrap(), []);
+        // only the impossible result is gone, and the others have the column back
+        let entries = history.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].wpm, 60.0);
+        assert_eq!(entries[0].note, "");
+    }
+
+    #[test]
+    fn leaves_newer_schemas_alone() {
+        let history = in_memory();
+        let newer = MIGRATIONS.len() + 1;
+        history
+            .conn
+            .execute_batch(&format!("PRAGMA user_version = {};", newer))
+            .unwrap();
+        let problems = history.check().unwrap();
+        assert_eq!(problems, [Problem::NewerSchema(newer)]);
+        assert!(!problems[0].repairable());
+    }
+
+    #[test]
+    fn detects_terminal_from_env() {
+        let vars = |set: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                set.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        let iterm = Environment::from_vars(
+            vars(&[
+                ("TERM_PROGRAM", "iTerm.app"),
+                ("TERM_PROGRAM_VERSION", "3.5"),
+            ]),
+            80,
+            24,
+        );
+        assert_eq!(iterm.terminal, "iTerm.app 3.5");
+        assert!(!iterm.ssh);
+
+        let remote = Environment::from_vars(
+            vars(&[
+                ("TERM", "xterm-kitty"),
+                ("KITTY_WINDOW_ID", "1"),
+                ("SSH_CONNECTION", "10.0.0.2 51234 10.0.0.1 22"),
+            ]),
+            120,
+            40,
+        );
+        assert_eq!(remote.terminal, "kitty");
+        assert_eq!(remote.term, "xterm-kitty");
+        assert!(remote.ssh);
+        assert_eq!(remote.version, env!("CARGO_PKG_VERSION"));
+    }
+}
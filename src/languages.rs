@@ -0,0 +1,96 @@
+//! Word lists downloaded from an index of languages beyond those bundled, fetched with curl so
+//! no HTTP client is built in
+
+use ttyper_core::words;
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+
+/// Where a language's list is found under the index, and its frequency file beside it
+pub fn url(index: &str, name: &str) -> String {
+    format!("{}/{}", index.trim_end_matches('/'), name)
+}
+
+/// Whether a name is one a language can be saved under, without reaching outside its directory
+pub fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Entries of a word list, as it's read for a test
+pub fn word_count(bytes: &[u8]) -> usize {
+    words::read_list(bytes).0.lines().count()
+}
+
+/// Body of a URL, or an error with what curl said if it couldn't fetch it
+fn download(url: &str) -> io::Result<Vec<u8>> {
+    let output = process::Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location", url])
+        .output()
+        .map_err(|e| io::Error::new(e.kind(), format!("couldn't run curl: {}", e)))?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "couldn't download {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+/// Download a language, and its frequency file if the index has one, into a directory,
+/// returning how many words it has
+pub fn install(index: &str, name: &str, dir: &Path) -> io::Result<usize> {
+    if !valid_name(name) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{:?} isn't a language name", name),
+        ));
+    }
+    let list = download(&url(index, name))?;
+    let count = word_count(&list);
+    if count == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} has no words", url(index, name)),
+        ));
+    }
+    // most languages have no frequency file, so failing to fetch one is expected
+    let frequencies = download(&url(index, &format!("{}.freq", name))).ok();
+
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join(name), list)?;
+    let freq_path = dir.join(format!("{}.freq", name));
+    match frequencies {
+        Some(frequencies) => fs::write(freq_path, frequencies)?,
+        // one left from an earlier install would no longer match the list
+        None if freq_path.exists() => fs::remove_file(freq_path)?,
+        None => {}
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_names_inside_the_directory() {
+        assert_eq!(
+            url("https://example.com/language/", "english1000"),
+            "https://example.com/language/english1000"
+        );
+        assert!(valid_name("english-pirate"));
+        assert!(valid_name("español_1k"));
+        assert!(!valid_name(""));
+        assert!(!valid_name(".."));
+        assert!(!valid_name("../config.toml"));
+        assert!(!valid_name("nested/list"));
+        assert_eq!(word_count(b"\xEF\xBB\xBFone\r\n\n two\nthree\n"), 3);
+    }
+}
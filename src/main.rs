@@ -1,39 +1,148 @@
+mod assignment;
+mod background;
+mod browse;
+mod classroom;
+mod color;
 mod config;
-mod test;
+mod date;
+mod debug;
+mod designer;
+mod dictionary;
+mod duel;
+mod export;
+mod history;
+mod languages;
+mod metrics;
+mod mirror;
+mod notify;
+mod practice;
+mod prompt;
+mod quotes;
+mod rollover;
+mod scheme;
+mod share;
+mod signing;
+mod simulate;
+mod soak;
+mod stats;
 mod ui;
+mod view;
 
-use config::Config;
-use test::{results::Results, Test};
+use assignment::{Assignment, Attempt, Submission, TestDefinition, Verification};
+use config::{
+    ColorDepth, Config, CutOffWord, Lexer, MissedWordsTarget, PaceCaret, Preset, SentenceSpacing,
+    Sentences, ShareTarget, StreakPolicy, SuddenDeathAction, Wrap,
+};
+use duel::{Challenge, Outcome};
+use export::Export;
+use filter::{Blocklist, Lengths};
+use history::{Environment, History, Problem, Query};
+use mirror::Snapshot;
+use quotes::Quote;
+use scheme::{Format, Scheme};
+use sha2::{Digest, Sha256};
+use signing::{hex, Signer};
+use stats::Stats;
+use test::{results::Results, Strictness, Test, TestWord};
+use ttyper_core::words::{self, Sampling};
+use ttyper_core::{code, filter, resources, syllables, test};
 
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 use crossterm::{
     self, cursor,
     event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
     execute, terminal,
 };
-use rand::{seq::SliceRandom, thread_rng};
-use ratatui::{backend::CrosstermBackend, terminal::Terminal};
-use rust_embed::RustEmbed;
+use rand::{rngs::StdRng, seq::SliceRandom, thread_rng, Rng, SeedableRng};
+use ratatui::{
+    backend::{Backend, CrosstermBackend},
+    layout::{Constraint, Direction, Layout},
+    terminal::Terminal,
+};
+use regex::Regex;
 use std::{
+    borrow::Cow,
+    cell::OnceCell,
+    collections::HashMap,
     ffi::OsString,
     fs,
-    io::{self, BufRead},
+    io::{self, IsTerminal, Read, Write},
     num,
-    path::PathBuf,
-    str,
+    path::{Path, PathBuf},
+    process, str,
+    sync::{Arc, OnceLock},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
+use ui::{Breathing, ResultNote, ResultsChart, ResultsView, Sidebar, StatsView, Trend};
+
+// Interval between redraws of animated widgets
+const TICK_RATE: Duration = Duration::from_millis(50);
+
+// Narrowest terminal with room for the breathing guide beside the test
+const BREATHING_MIN_WIDTH: u16 = 50;
+
+// Width of the live stats sidebar, and the narrowest and shortest terminal it's shown in
+const SIDEBAR_WIDTH: u16 = 26;
+const SIDEBAR_MIN_WIDTH: u16 = 100;
+const SIDEBAR_MIN_HEIGHT: u16 = 14;
+
+// Number of recent results in the sidebar sparkline
+const SIDEBAR_RECENT: usize = 5;
+
+// Stretches of a --ramp test, in order of difficulty
+const RAMP_SEGMENTS: [&str; 3] = ["Easy", "Medium", "Hard"];
+
+// Letters a word's rarity is worth in a --ramp test, from most common to least
+const RARITY_WEIGHT: f64 = 4.0;
 
-#[derive(RustEmbed)]
-#[folder = "resources/runtime"]
-struct Resources;
+// Weak words shown on the stats screen and drilled from it
+const WEAKEST_WORDS: usize = 10;
 
-#[derive(Debug, Parser)]
+// Weakest keys and pairs named after practicing them
+const PRACTICED_SHOWN: usize = 5;
+
+// Fewest words in a sentence of the punctuation transform, the chance of ending it after
+// each word past those, and the chance of a comma after any other word
+const SENTENCE_MIN_WORDS: usize = 4;
+const SENTENCE_END_CHANCE: f64 = 0.25;
+const COMMA_CHANCE: f64 = 0.1;
+
+// Chance of the numbers transform putting a number in place of each word
+const NUMBER_CHANCE: f64 = 0.15;
+
+#[derive(Debug, Clone, Parser)]
 #[command(about, version)]
 struct Opt {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Read test contents from the specified file, or "-" for stdin
     #[arg(value_name = "PATH")]
     contents: Option<PathBuf>,
 
+    /// Type through a text file in order, a test's worth of words at a time, picking up where
+    /// the last test left off
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["contents", "language", "language_file", "dictionary", "quotes", "endless", "ramp", "time"]
+    )]
+    file: Option<PathBuf>,
+
+    /// Split the file or stdin as prose or as code [default: by the file's extension, from lexers
+    /// in the config, and prose for stdin]
+    #[arg(long, value_enum, value_name = "LEXER")]
+    lexer: Option<Lexer>,
+
+    /// Retype the lines added by the changes in the current git repository, staged or not, as
+    /// code
+    #[arg(
+        long,
+        conflicts_with_all = ["contents", "file", "language", "language_file", "dictionary", "quotes", "endless", "ramp", "time"]
+    )]
+    from_git_diff: bool,
+
     #[arg(short, long)]
     debug: bool,
 
@@ -45,6 +154,11 @@ struct Opt {
     #[arg(short, long, value_name = "PATH")]
     config: Option<PathBuf>,
 
+    /// Theme preset to start from, under any styles set in the config [default: theme.preset in
+    /// the config, or dark or light to suit the terminal]
+    #[arg(long, value_enum, value_name = "PRESET")]
+    theme: Option<Preset>,
+
     /// Specify test language in file
     #[arg(long, value_name = "PATH")]
     language_file: Option<PathBuf>,
@@ -53,6 +167,16 @@ struct Opt {
     #[arg(short, long, value_name = "LANG")]
     language: Option<String>,
 
+    /// Use words from a system dictionary, given as a path or a hunspell name like de_DE
+    #[arg(
+        long,
+        value_name = "DICT",
+        num_args = 0..=1,
+        default_missing_value = dictionary::SYSTEM_WORDS,
+        conflicts_with_all = ["contents", "language", "language_file"]
+    )]
+    dictionary: Option<String>,
+
     /// List installed languages
     #[arg(long)]
     list_languages: bool,
@@ -61,100 +185,1276 @@ struct Opt {
     #[arg(long)]
     no_backtrack: bool,
 
-    /// Enable sudden death mode to restart on first error
+    /// Enable sudden death mode to restart, or end, on first error
     #[arg(long)]
     sudden_death: bool,
+
+    /// How strictly mistakes are held against you: fixed with Backspace or left behind, stopping
+    /// the cursor until the right key is pressed, or kept for good without Backspace
+    #[arg(long, value_enum, value_name = "MODE", default_value_t = Strictness::Forgiving)]
+    strictness: Strictness,
+
+    /// Allow this many errors per test, showing how many are left
+    #[arg(long, value_name = "ERRORS")]
+    error_budget: Option<usize>,
+
+    /// End the test once the error budget is exceeded
+    #[arg(long, requires = "error_budget")]
+    end_over_budget: bool,
+
+    /// Ignore repeated keypresses a few milliseconds apart, which are likely switch chatter
+    #[arg(long)]
+    filter_chatter: bool,
+
+    /// Show live stats, recent results and a key heatmap beside wide tests
+    #[arg(long)]
+    sidebar: bool,
+
+    /// Keep adding words until the test is ended with Esc, showing a rolling WPM
+    #[arg(long)]
+    endless: bool,
+
+    /// End an endless test once this many words have been typed correctly
+    #[arg(long, value_name = "WORDS", requires = "endless")]
+    stop_after: Option<num::NonZeroUsize>,
+
+    /// End an endless test once the accuracy of the last 100 keypresses drops below this
+    /// percentage
+    #[arg(long, value_name = "PERCENT", requires = "endless", value_parser = parse_percent)]
+    stop_below: Option<f64>,
+
+    /// Order words from short and common to long and rare, in easy, medium and hard stretches
+    #[arg(long, conflicts_with = "endless")]
+    ramp: bool,
+
+    /// Hide speed during and after tests, leaving accuracy and errors, though the history
+    /// still records it
+    #[arg(long, conflicts_with = "target_wpm")]
+    accuracy_only: bool,
+
+    /// Pulse the live WPM once it stays below this for a few seconds [default: pace.target_wpm
+    /// in the config]
+    #[arg(long, value_name = "WPM")]
+    target_wpm: Option<f64>,
+
+    /// Move a second caret through the text at this WPM, or "best" for that of your fastest
+    /// test, to keep ahead of [default: pace.caret in the config]
+    #[arg(long, value_name = "WPM", value_parser = parse_pace, conflicts_with = "accuracy_only")]
+    pace: Option<PaceCaret>,
+
+    /// Type a random quote, of any length unless one is given, instead of words
+    #[arg(
+        short,
+        long,
+        value_name = "LENGTH",
+        num_args = 0..=1,
+        conflicts_with_all = ["contents", "language_file", "dictionary", "endless", "ramp", "time"]
+    )]
+    quotes: Option<Option<quotes::Length>>,
+
+    /// Keep adding words until the given number of seconds have passed since the first keypress
+    #[arg(long, value_name = "SECONDS", conflicts_with_all = ["endless", "ramp"])]
+    time: Option<num::NonZeroU64>,
+
+    /// Also show speed in syllables per minute, for language learners
+    #[arg(long)]
+    syllables: bool,
+
+    /// Hyphenate long words which don't fit at the end of a line of the prompt
+    #[arg(long)]
+    hyphenate: bool,
+
+    /// Hide typed text, only moving the cursor, until the test is over
+    #[arg(long)]
+    hide_typed: bool,
+
+    /// Hide definitions and translations until Tab shows them, to test yourself on them
+    #[arg(long)]
+    hide_definitions: bool,
+
+    /// Show how long the test has run by the monotonic clock which times it and by the wall
+    /// clock, to diagnose timing after suspends or clock adjustments
+    #[arg(long)]
+    clock_debug: bool,
+
+    /// Show a breathing guide in the margin, at the given breaths per minute
+    #[arg(long, value_name = "BPM", num_args = 0..=1, default_missing_value = "6")]
+    breathe: Option<f64>,
+
+    /// Append the results of each test to a file as a line of JSON, with word-by-word timings
+    #[arg(long, value_name = "PATH")]
+    json: Option<PathBuf>,
+
+    /// Print the results of each test once ttyper exits, drawing on stderr instead of stdout
+    /// if stdout isn't a terminal so the results can be piped
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    format: Option<export::Format>,
+
+    /// Leave as soon as the test is complete, without showing the results
+    #[arg(long)]
+    quiet: bool,
+
+    /// Share the test with `ttyper mirror` on a local port [default: the port `ttyper mirror`
+    /// watches]
+    #[arg(long, value_name = "PORT", num_args = 0..=1)]
+    share: Option<Option<u16>>,
+
+    /// Leave words from the bundled profanity list out of tests
+    #[arg(long)]
+    filter_profanity: bool,
+
+    /// Leave words listed in a file out of tests [default: blocklist.txt in the config directory]
+    #[arg(long, value_name = "FILE")]
+    blocklist: Option<PathBuf>,
+
+    /// Only use words from the language which match a regular expression
+    #[arg(long, value_name = "REGEX")]
+    filter: Option<Regex>,
+
+    /// Only use words with a number of characters in a range, like 3..8
+    #[arg(long, value_name = "RANGE")]
+    word_length: Option<Lengths>,
+
+    /// Only use the N most common words of the language, which isn't known for dictionaries
+    #[arg(long, value_name = "N", conflicts_with = "dictionary")]
+    common: Option<usize>,
+
+    /// Change the generated words, applying transforms in the order given
+    #[arg(long, value_enum, value_name = "TRANSFORM", value_delimiter = ',')]
+    transform: Vec<Transform>,
+
+    /// Make the words into sentences, with capitals and punctuation, after any --transform
+    /// [default: punctuation in the config]
+    #[arg(long, overrides_with = "no_punctuation")]
+    punctuation: bool,
+
+    /// Leave the words unpunctuated, even if the config punctuates them
+    #[arg(long, overrides_with = "punctuation")]
+    no_punctuation: bool,
+
+    /// Put numbers here and there among the words, after any --transform [default: numbers in
+    /// the config]
+    #[arg(long, overrides_with = "no_numbers")]
+    numbers: bool,
+
+    /// Leave numbers out, even if the config puts them in
+    #[arg(long, overrides_with = "numbers")]
+    no_numbers: bool,
+
+    /// Specify how words are sampled from the language
+    #[arg(long, value_enum, value_name = "MODE", default_value_t = Sampling::Shuffle)]
+    sampling: Sampling,
+
+    /// Draw the words of the language by what to practice, like "weak" for the keys, pairs of
+    /// keys and words typed worst lately
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FOCUS",
+        conflicts_with_all = ["contents", "file", "from_git_diff", "quotes", "ghost", "sampling", "seed", "ramp"]
+    )]
+    practice: Option<practice::Practice>,
+
+    /// Exact text to type, set by an assignment
+    #[arg(skip)]
+    text: Option<String>,
+
+    /// Pick the words with a seed, so the same seed gives the same test again
+    #[arg(long, value_name = "SEED")]
+    seed: Option<u64>,
+
+    /// Race an earlier test from the history over its words, shown as a second caret: "last",
+    /// "best" or a test's ID from `ttyper history list`
+    #[arg(
+        long,
+        value_name = "TEST",
+        value_parser = parse_ghost,
+        conflicts_with_all = ["contents", "file", "from_git_diff", "language", "language_file", "dictionary", "quotes", "endless", "ramp", "time"]
+    )]
+    ghost: Option<Ghost>,
+
+    /// Earlier test being raced, once it's been read from the history
+    #[arg(skip)]
+    race: Option<Race>,
+
+    /// Configuration, read on first use
+    #[arg(skip)]
+    loaded_config: Arc<OnceLock<Config>>,
+
+    /// Text of the contents file or language, read on first use as lists can be several
+    /// megabytes
+    #[arg(skip)]
+    loaded_words: Arc<OnceLock<Option<(String, words::Repairs)>>>,
+
+    /// What's been typed worst lately, read from the history on first use
+    #[arg(skip)]
+    loaded_weaknesses: Arc<OnceLock<practice::Weaknesses>>,
+
+    /// Speed of the pace caret, looked up in the history on first use for "best"
+    #[arg(skip)]
+    loaded_pace: Arc<OnceLock<Option<f64>>>,
+}
+
+/// Earlier test to race with --ghost
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ghost {
+    Last,
+    Best,
+    Id(i64),
+}
+
+/// Earlier test being raced, as recorded in the history
+#[derive(Debug, Clone)]
+struct Race {
+    id: i64,
+    language: String,
+    wpm: f64,
+    words: Vec<TestWord>,
+    /// Seconds from the first keypress each word was started and finished at
+    times: Vec<(f64, f64)>,
+    /// Options the test was taken with, which the race is typed by
+    setup: Option<export::RecordSetup>,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum Command {
+    /// Show statistics from the history of completed tests
+    Stats {
+        #[command(subcommand)]
+        command: Option<StatsCommand>,
+    },
+    /// Look after the history of completed tests
+    History {
+        #[command(subcommand)]
+        command: HistoryCommand,
+    },
+    /// Show a live, read-only copy of a test started with --share
+    Mirror {
+        /// Local port the test is shared on
+        #[arg(short, long, default_value_t = mirror::DEFAULT_PORT)]
+        port: u16,
+    },
+    /// Run the same test across a classroom of typists
+    Classroom {
+        #[command(subcommand)]
+        command: ClassroomCommand,
+    },
+    /// Complete assignments handed out by a teacher
+    Assignment {
+        #[command(subcommand)]
+        command: AssignmentCommand,
+    },
+    /// Work on the colors and styles of the theme
+    Theme {
+        #[command(subcommand)]
+        command: ThemeCommand,
+    },
+    /// Manage the key used to sign shared results
+    Key {
+        #[command(subcommand)]
+        command: KeyCommand,
+    },
+    /// Take the daily challenge, the same test for everyone on each UTC date
+    Daily,
+    /// Look into how typed keys are lined up with the words, for working out a test's score
+    Debug {
+        #[command(subcommand)]
+        command: DebugCommand,
+    },
+    /// Challenge someone to the same test, without any networking
+    #[command(args_conflicts_with_subcommands = true, arg_required_else_help = true)]
+    Duel {
+        /// Code of the duel to play, from `ttyper duel new`
+        code: Option<String>,
+
+        #[command(subcommand)]
+        command: Option<DuelCommand>,
+    },
+    /// List the languages available, and download more
+    Languages {
+        #[command(subcommand)]
+        command: LanguagesCommand,
+    },
+    /// Show which keys the keyboard reports held at once, and test chords for rollover and
+    /// ghosting
+    Rollover,
+    /// Estimate how hard a word list is by simulating a typist over it
+    Simulate {
+        /// Word list, in the same format as a language file
+        #[arg(long, value_name = "FILE")]
+        list: PathBuf,
+
+        /// Typist to simulate
+        #[arg(long, value_enum, default_value_t)]
+        model: simulate::Model,
+
+        /// Number of words in each test
+        #[arg(short, long, default_value_t = 50)]
+        words: usize,
+
+        /// Number of tests to simulate
+        #[arg(long, default_value_t = 1000)]
+        tests: usize,
+    },
+    /// Type scripted text round after round to soak-test a terminal's input handling, logging
+    /// keys which go missing, arrive twice or arrive mangled, and failures to draw
+    Soak {
+        /// Chars in each round of the script
+        #[arg(long, value_name = "N", default_value_t = soak::ROUND_LENGTH)]
+        length: usize,
+
+        /// File to log problems to [default: soak.log in the data directory]
+        #[arg(long, value_name = "PATH")]
+        log: Option<PathBuf>,
+
+        /// Print the first N rounds of the script, one a line, for a driver to send as keys
+        #[arg(long, value_name = "N")]
+        script: Option<usize>,
+    },
+    /// Print today's best WPM for a shell prompt, from the latest stats file, or nothing if no
+    /// test has been completed today
+    PromptSegment {
+        /// How the segment is colored
+        #[arg(long, value_enum, default_value_t)]
+        style: prompt::Style,
+    },
+    /// Check that a results file is unmodified and see who signed it
+    Verify {
+        /// Results file
+        path: PathBuf,
+
+        /// Fail unless the file was signed by this public key
+        #[arg(long, value_name = "KEY")]
+        key: Option<String>,
+    },
+    /// Open the results of a test exported with --json or --format json
+    View {
+        /// Exported results
+        path: PathBuf,
+
+        /// Which of the file's tests to open, counting from 1 [default: the last]
+        #[arg(long, value_name = "N")]
+        test: Option<num::NonZeroUsize>,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum StatsCommand {
+    /// List the words typed slowest or missed most, across the tests recorded with their
+    /// details
+    Words {
+        /// Which words are listed first
+        #[arg(long, value_enum, default_value_t)]
+        sort: stats::WordSort,
+
+        /// Number of words to list
+        #[arg(short = 'n', long, default_value_t = 20)]
+        count: usize,
+
+        /// Leave out words typed fewer times than this
+        #[arg(long, value_name = "N", default_value_t = 3)]
+        min_typed: usize,
+    },
+    /// Compare the speed on a keyboard layout being learned with that on the old one, and
+    /// estimate when it catches up
+    Layout {
+        /// Layout being learned [default: the layout of the keyboard in the config]
+        #[arg(long)]
+        layout: Option<String>,
+
+        /// Layout switched from
+        #[arg(long, default_value = "qwerty")]
+        baseline: String,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum HistoryCommand {
+    /// List completed tests, newest first
+    List {
+        /// Only tests completed on or after a date, like 2026-01-31
+        #[arg(long, value_name = "DATE", value_parser = parse_date)]
+        since: Option<i64>,
+
+        /// Only tests completed on or before a date
+        #[arg(long, value_name = "DATE", value_parser = parse_date)]
+        until: Option<i64>,
+
+        /// Only tests of a language, as recorded
+        #[arg(short, long)]
+        language: Option<String>,
+
+        /// Only tests of a mode, like words, quote, timed, endless, practice or duel
+        #[arg(long)]
+        mode: Option<String>,
+
+        /// Most tests to list
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+
+        /// List every matching test
+        #[arg(long, conflicts_with = "limit")]
+        all: bool,
+
+        /// Print comma-separated values instead of a table
+        #[arg(long)]
+        csv: bool,
+    },
+    /// Scroll through completed tests and open the results of any of them again
+    Browse,
+    /// Check the history for damage and for changes an upgrade left half done
+    Doctor {
+        /// Fix what can be fixed, after backing up the history
+        #[arg(long)]
+        repair: bool,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum ThemeCommand {
+    /// Adjust the theme on a sample test and save it to the config file
+    Edit,
+    /// Set the theme from a base16 scheme, iTerm2 color preset, terminal.sexy export or Monkeytype
+    /// theme
+    Import {
+        /// Scheme file, a `.yaml`, `.itermcolors`, `.json` or `.css` file
+        path: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum KeyCommand {
+    /// Generate a signing key for this installation
+    Generate {
+        /// Replace an existing key
+        #[arg(long)]
+        force: bool,
+    },
+    /// Print the public key others can verify results against
+    Show,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum LanguagesCommand {
+    /// Show the bundled and installed languages, with how many words each has
+    List,
+    /// Download a language from the index in the config into the data directory
+    Install {
+        /// Name of the language, as it's then given to --language
+        name: String,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum DebugCommand {
+    /// Show how typing some text is lined up with the words expected, char by char
+    Align {
+        /// Words expected
+        expected: String,
+        /// Text typed, where \b stands for Backspace and \n for Enter
+        typed: String,
+    },
+    /// Check the lexers and the lining up of keys against random input
+    #[cfg(feature = "harness")]
+    Fuzz {
+        /// Seed of the first input, each after it taking the next
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+
+        /// Number of inputs to check
+        #[arg(long, default_value_t = 10000)]
+        runs: u64,
+    },
+    /// Time the lexers and the lining up of keys over random input
+    #[cfg(feature = "harness")]
+    Bench {
+        /// Pieces of random text in the input
+        #[arg(long, default_value_t = 1000)]
+        pieces: usize,
+
+        /// Number of times each is timed over it
+        #[arg(long, default_value_t = 100)]
+        runs: u32,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum DuelCommand {
+    /// Print a code for a duel of the language and word count given before `duel`
+    New,
+    /// Check a result sent back by an opponent and see who signed it
+    Check {
+        /// Result printed at the end of the duel
+        result: String,
+
+        /// Fail unless the result was signed by this public key
+        #[arg(long, value_name = "KEY")]
+        key: Option<String>,
+
+        /// Fail unless the result is for this duel
+        #[arg(long, value_name = "CODE")]
+        code: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum AssignmentCommand {
+    /// Complete an assignment, writing a results file to hand back
+    Do {
+        /// Assignment file
+        path: PathBuf,
+
+        /// Write results to the given file [default: <PATH>.results.toml]
+        #[arg(short, long, value_name = "PATH")]
+        out: Option<PathBuf>,
+
+        /// Name put on the results [default: your username]
+        #[arg(short, long)]
+        name: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum ClassroomCommand {
+    /// Host a session, pushing tests to students and showing their progress
+    Host {
+        /// Port students connect to
+        #[arg(short, long, default_value_t = classroom::DEFAULT_PORT)]
+        port: u16,
+
+        /// Export the session to a CSV file when it ends
+        #[arg(long, value_name = "PATH")]
+        csv: Option<PathBuf>,
+    },
+    /// Join a session hosted by a teacher
+    Join {
+        /// Address of the teacher's session, such as 192.168.0.2:7880
+        addr: String,
+
+        /// Name shown to the teacher [default: your username]
+        #[arg(short, long)]
+        name: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Transform {
+    /// Capitalize every letter
+    Uppercase,
+    /// Spell words backwards
+    Reverse,
+    /// Capitalize letters at random
+    RandomCase,
+    /// Replace letters with similar-looking digits
+    Leet,
+    /// Add punctuation, making the words into sentences
+    Punctuation,
+    /// Put numbers in place of some words
+    Numbers,
+    /// Capitalize letters mid-word now and then, at the chance set by tricky_caps in the config
+    TrickyCaps,
+}
+
+impl Transform {
+    fn apply(self, words: &mut [TestWord], config: &Config, rng: &mut impl Rng) {
+        match self {
+            Transform::Punctuation => return punctuate(words, config.sentences, rng),
+            Transform::Numbers => return add_numbers(words, rng),
+            Transform::TrickyCaps => return add_tricky_caps(words, config.tricky_caps, rng),
+            _ => {}
+        }
+        for word in words {
+            word.text = self.apply_to_word(&word.text, rng);
+        }
+    }
+
+    fn apply_to_word(self, word: &str, rng: &mut impl Rng) -> String {
+        match self {
+            Transform::Uppercase => word.to_uppercase(),
+            Transform::Reverse => word.chars().rev().collect(),
+            Transform::RandomCase => word
+                .chars()
+                .map(|c| {
+                    if rng.gen() {
+                        c.to_ascii_uppercase()
+                    } else {
+                        c.to_ascii_lowercase()
+                    }
+                })
+                .collect(),
+            Transform::Leet => word
+                .chars()
+                .map(|c| match c.to_ascii_lowercase() {
+                    'a' => '4',
+                    'e' => '3',
+                    'i' => '1',
+                    'o' => '0',
+                    's' => '5',
+                    't' => '7',
+                    _ => c,
+                })
+                .collect(),
+            Transform::Punctuation | Transform::Numbers | Transform::TrickyCaps => word.to_owned(),
+        }
+    }
+
+    fn name(self) -> String {
+        self.to_possible_value()
+            .map(|value| value.get_name().to_owned())
+            .expect("Transforms are never skipped.")
+    }
 }
 
 impl Opt {
-    fn gen_contents(&self) -> Option<Vec<String>> {
-        match &self.contents {
-            Some(path) => {
-                let lines: Vec<String> = if path.as_os_str() == "-" {
-                    std::io::stdin()
-                        .lock()
-                        .lines()
-                        .map_while(Result::ok)
-                        .collect()
-                } else {
-                    let file = fs::File::open(path).expect("Error reading language file.");
-                    io::BufReader::new(file)
-                        .lines()
-                        .map_while(Result::ok)
-                        .collect()
-                };
+    fn gen_contents(&self, deck: &mut Vec<String>) -> Option<Vec<TestWord>> {
+        // the words are taken as they were, since splitting them up again could move the
+        // boundaries the ghost's timings line up with
+        if let Some(race) = &self.race {
+            return Some(race.words.clone());
+        }
+        if let Some(text) = &self.text {
+            return Some(text.split_whitespace().map(TestWord::from).collect());
+        }
+
+        let mut words = self.gen_words(deck)?;
+        let mut rng = self.rng();
+        for transform in self.transforms() {
+            transform.apply(&mut words, self.config(), &mut rng);
+        }
+        Some(words)
+    }
+
+    /// Text of the contents file or language, which is only read once
+    fn words_text(&self) -> Option<&str> {
+        self.loaded_words
+            .get_or_init(|| match &self.contents {
+                _ if self.from_git_diff => git_added_lines(),
+                _ if self.file.is_some() || self.reads_stdin() => {
+                    let bytes = match &self.file {
+                        Some(path) => fs::read(path).expect("Error reading file."),
+                        None => {
+                            let mut bytes = Vec::new();
+                            io::stdin()
+                                .lock()
+                                .read_to_end(&mut bytes)
+                                .expect("Error reading stdin.");
+                            bytes
+                        }
+                    };
+                    Some(match self.lexer()? {
+                        Lexer::Prose => words::read_list(&bytes),
+                        // code keeps its indentation, which cleaning up a list would trim
+                        Lexer::Code => (
+                            String::from_utf8_lossy(&bytes)
+                                .trim_start_matches('\u{FEFF}')
+                                .to_owned(),
+                            words::Repairs::default(),
+                        ),
+                    })
+                }
+                Some(path) => Some(words::read_list(
+                    &fs::read(path).expect("Error reading language file."),
+                )),
+                None if self.dictionary.is_some() => {
+                    let path = dictionary::find(self.dictionary.as_deref()?)?;
+                    Some(words::read_list(&fs::read(path).ok()?))
+                }
+                None => {
+                    let bytes: Vec<u8> = self
+                        .language_file
+                        .as_ref()
+                        .map(fs::read)
+                        .and_then(Result::ok)
+                        .or_else(|| {
+                            // only the default language needs the config
+                            let lang_name = self
+                                .language
+                                .clone()
+                                .unwrap_or_else(|| self.config().default_language.clone());
+                            self.language_bytes(&lang_name)
+                        })?;
+                    Some(words::read_list(&bytes))
+                }
+            })
+            .as_ref()
+            .map(|(text, _)| text.as_str())
+    }
 
-                Some(lines.iter().map(String::from).collect())
+    /// Whether the words of the test can be read before the config, for the commands which
+    /// type a test of these options' words, so they're read while the config is
+    fn reads_words_without_config(&self) -> bool {
+        let types_words = matches!(
+            self.command,
+            None | Some(Command::Classroom {
+                command: ClassroomCommand::Host { .. }
+            })
+        ) && self.quotes.is_none();
+        // as words_text finds them, where the config gives the lexer of a file and the default
+        // language
+        let needs_config = match &self.contents {
+            _ if self.from_git_diff => false,
+            _ if self.file.is_some() => self.lexer.is_none(),
+            Some(_) => false,
+            None => {
+                self.dictionary.is_none() && self.language.is_none() && self.language_file.is_none()
+            }
+        };
+        types_words && !needs_config
+    }
+
+    /// Problems fixed in the contents file or language, once it's been read
+    fn words_repairs(&self) -> Option<words::Repairs> {
+        let (_, repairs) = self.loaded_words.get()?.as_ref()?;
+        Some(*repairs).filter(|repairs| !repairs.is_empty())
+    }
+
+    /// Frequencies of the language's words from the `.freq` file beside it, if it has one
+    fn frequencies_text(&self) -> Option<String> {
+        if self.contents.is_some() || self.dictionary.is_some() {
+            return None;
+        }
+        let bytes: Vec<u8> = match &self.language_file {
+            Some(path) => {
+                let mut path = path.clone().into_os_string();
+                path.push(".freq");
+                fs::read(path).ok()?
             }
             None => {
                 let lang_name = self
                     .language
                     .clone()
-                    .unwrap_or_else(|| self.config().default_language);
+                    .unwrap_or_else(|| self.config().default_language.clone());
+                let freq_name = format!("{}.freq", lang_name);
+                self.language_bytes(&freq_name)?
+            }
+        };
+        Some(words::read_list(&bytes).0)
+    }
 
-                let bytes: Vec<u8> = self
-                    .language_file
-                    .as_ref()
-                    .map(fs::read)
-                    .and_then(Result::ok)
-                    .or_else(|| fs::read(self.language_dir().join(&lang_name)).ok())
-                    .or_else(|| {
-                        Resources::get(&format!("language/{}", &lang_name))
-                            .map(|f| f.data.into_owned())
-                    })?;
-
-                let mut rng = thread_rng();
-
-                let mut language: Vec<&str> = str::from_utf8(&bytes)
-                    .expect("Language file had non-utf8 encoding.")
-                    .lines()
-                    .collect();
-                language.shuffle(&mut rng);
+    /// Untransformed words from the contents file or language
+    fn gen_words(&self, deck: &mut Vec<String>) -> Option<Vec<TestWord>> {
+        // a review of the changes runs through all of them at once
+        if self.from_git_diff {
+            let mut words = code::words(self.words_text()?);
+            if self.config().highlight_code {
+                code::highlight(&mut words);
+            }
+            return (!words.is_empty()).then_some(words);
+        }
+        if self.file.is_some() {
+            let text = self.words_text()?;
+            let start = self
+                .history()
+                .map_or(0, |history| self.file_start(&history));
+            let mut count = self.words.get();
+            let words = self.lexed(text)?;
+            // a test of code runs on to the end of its last line
+            if self.lexer()? == Lexer::Code {
+                let rest = words.get(start..).unwrap_or_default();
+                count += rest
+                    .iter()
+                    .skip(count - 1)
+                    .position(|word| word.line_end)
+                    .unwrap_or(rest.len());
+            }
+            let words: Vec<TestWord> = words.into_iter().skip(start).take(count).collect();
+            return (!words.is_empty()).then_some(words);
+        }
+        // all of what's piped in is typed, as there's no knowing where to pick up next time
+        if self.reads_stdin() {
+            let words = self.lexed(self.words_text()?)?;
+            return (!words.is_empty()).then_some(words);
+        }
 
-                let mut contents: Vec<_> = language
-                    .into_iter()
-                    .cycle()
-                    .take(self.words.get())
+        match &self.contents {
+            Some(_) => Some(
+                self.words_text()?
+                    .lines()
                     .map(ToOwned::to_owned)
-                    .collect();
-                contents.shuffle(&mut rng);
+                    .map(TestWord::from)
+                    .collect(),
+            ),
+            None => {
+                let mut rng = self.rng();
+
+                let text = self.words_text()?;
+                let mut language: Vec<&str> = match self.dictionary {
+                    Some(_) => dictionary::words(text),
+                    None => text.lines().collect(),
+                };
+                if let Some(common) = self.common {
+                    language.truncate(common);
+                }
+                let blocklist = self.applied_blocklist();
+                if !blocklist.is_empty() {
+                    language.retain(|entry| !blocklist.blocks(entry));
+                }
+                if let Some(filter) = &self.filter {
+                    language.retain(|entry| {
+                        TestWord::from_group(entry)
+                            .iter()
+                            .all(|word| filter.is_match(&word.text))
+                    });
+                }
+                if let Some(lengths) = self.word_length {
+                    language.retain(|entry| {
+                        TestWord::from_group(entry)
+                            .iter()
+                            .all(|word| lengths.contains(&word.text))
+                    });
+                }
+                if language.is_empty() {
+                    return None;
+                }
+                // word lists are ordered from most to least common
+                let ranks: HashMap<&str, usize> =
+                    language.iter().enumerate().map(|(i, &w)| (w, i)).collect();
+                let size = language.len();
+                let frequencies = match self.sampling {
+                    Sampling::Frequency => self.frequencies_text(),
+                    _ => None,
+                };
+                let mut contents = match &frequencies {
+                    _ if self.practice.is_some() => {
+                        let weaknesses = self.weaknesses();
+                        let weights: Vec<f64> = language
+                            .iter()
+                            .map(|entry| weaknesses.weight(entry))
+                            .collect();
+                        words::weighted(&language, &weights, self.words.get(), &mut rng)
+                    }
+                    Some(frequencies) => {
+                        let frequencies = words::parse_frequencies(frequencies)
+                            .expect("Frequency file was ill-formed.");
+                        let weights: Vec<f64> = language
+                            .iter()
+                            .map(|entry| {
+                                let text = entry.split_once('\t').map_or(*entry, |(text, _)| text);
+                                frequencies.get(text).copied().unwrap_or(0.0)
+                            })
+                            .collect();
+                        words::weighted(&language, &weights, self.words.get(), &mut rng)
+                    }
+                    None => {
+                        words::sample(language, self.words.get(), self.sampling, deck, &mut rng)
+                    }
+                };
+                if contents.is_empty() {
+                    return None;
+                }
+                if self.ramp {
+                    let difficulty = |entry: &String| {
+                        let rank = ranks.get(entry.as_str()).copied().unwrap_or(size);
+                        difficulty(entry, rank, size)
+                    };
+                    contents.sort_by(|a, b| difficulty(a).total_cmp(&difficulty(b)));
+                }
+
+                Some(
+                    contents
+                        .iter()
+                        .flat_map(|entry| {
+                            let mut group = TestWord::from_group(entry);
+                            group.shuffle(&mut rng);
+                            group
+                        })
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    /// Random number generator for picking words, which picks the same ones for the same seed
+    fn rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        }
+    }
+
+    /// Transforms of the generated words: those given with --transform, then numbers and
+    /// punctuation if they're turned on by their flags or the config
+    fn transforms(&self) -> Vec<Transform> {
+        let config = self.config();
+        let mut transforms = self.transform.clone();
+        for (transform, on, off, default) in [
+            (
+                Transform::Numbers,
+                self.numbers,
+                self.no_numbers,
+                config.numbers,
+            ),
+            (
+                Transform::Punctuation,
+                self.punctuation,
+                self.no_punctuation,
+                config.punctuation,
+            ),
+        ] {
+            if (on || (default && !off)) && !transforms.contains(&transform) {
+                transforms.push(transform);
+            }
+        }
+        transforms
+    }
+
+    /// Leave the words as they're generated, whatever the flags and config say
+    fn clear_transforms(&mut self) {
+        self.transform.clear();
+        self.punctuation = false;
+        self.no_punctuation = true;
+        self.numbers = false;
+        self.no_numbers = true;
+    }
+
+    /// Options for a duel, which only depend on its code so both sides get the same test
+    fn with_challenge(&self, challenge: &Challenge) -> Self {
+        let mut opt = self.clone();
+        opt.contents = None;
+        opt.language_file = None;
+        opt.language = Some(challenge.language.clone());
+        opt.words = num::NonZeroUsize::new(challenge.words).unwrap_or(opt.words);
+        opt.seed = Some(u64::from(challenge.seed));
+        opt.dictionary = None;
+        opt.word_length = None;
+        opt.common = None;
+        opt.loaded_words = Arc::default();
+        opt.filter = None;
+        opt.clear_transforms();
+        opt.sampling = Sampling::Shuffle;
+        opt.practice = None;
+        opt.ramp = false;
+        opt.endless = false;
+        opt.time = None;
+        opt.quotes = None;
+        opt
+    }
+
+    /// Options for racing an earlier test, which is typed again word for word
+    fn with_race(&self, race: Race) -> Self {
+        let mut opt = self.clone();
+        opt.quotes = None;
+        opt.clear_transforms();
+        if let Some(setup) = &race.setup {
+            opt.no_backtrack = setup
+                .backtrack
+                .map_or(opt.no_backtrack, |backtrack| !backtrack);
+            opt.sudden_death = setup.sudden_death.unwrap_or(opt.sudden_death);
+            opt.strictness = setup
+                .strictness
+                .as_deref()
+                .and_then(Strictness::from_name)
+                .unwrap_or(opt.strictness);
+            opt.ramp = setup.ramp.unwrap_or(opt.ramp);
+        }
+        opt.loaded_words = Arc::default();
+        opt.race = Some(race);
+        opt
+    }
+
+    /// Options overridden by an assignment's test definition
+    fn with_test_definition(&self, test: &TestDefinition) -> Self {
+        let mut opt = self.clone();
+        opt.contents = None;
+        opt.language_file = None;
+        opt.dictionary = None;
+        opt.language = test.language.clone().or(opt.language);
+        opt.text = test.text.clone();
+        opt.clear_transforms();
+        opt.practice = None;
+        opt.words = test
+            .words
+            .and_then(num::NonZeroUsize::new)
+            .unwrap_or(opt.words);
+        opt.no_backtrack |= test.no_backtrack;
+        opt.sudden_death |= test.sudden_death;
+        opt.time = None;
+        opt.quotes = None;
+        opt.loaded_words = Arc::default();
+        opt
+    }
+
+    fn new_test(&self, deck: &mut Vec<String>) -> Test {
+        if let Some(length) = self.quotes {
+            let quotes = self.load_quotes();
+            let quote = quotes::pick(&quotes, length, &mut self.rng()).expect(
+                "Couldn't find a quote of that length. More can be added to the quotes directory under the config directory.",
+            );
+            let mut test = self.test_of(quote.words());
+            test.attribution = Some(quote.attribution());
+            return test;
+        }
+
+        let mut test = self.test_of(self.gen_contents(deck).expect(
+            "Couldn't get test contents. Make sure the specified language actually exists and has words which aren't filtered out.",
+        ));
+        if self.ramp && self.contents.is_none() && self.text.is_none() {
+            let words = test.words.len();
+            test.segments = (0..RAMP_SEGMENTS.len())
+                .map(|i| (i * words / RAMP_SEGMENTS.len(), RAMP_SEGMENTS[i]))
+                .collect();
+        }
+        test
+    }
 
-                Some(contents)
+    /// Test of the given words with the options for how it's typed
+    fn test_of(&self, words: Vec<TestWord>) -> Test {
+        let mut test = Test::new(words, !self.no_backtrack, self.sudden_death);
+        test.hide_typed = self.hide_typed;
+        test.seed = self.seed;
+        test.strictness = self.strictness;
+        test.ghost = self.race.as_ref().map(|race| race.times.clone());
+        test.hide_definitions = self.hide_definitions;
+        test.clock_debug = self.clock_debug;
+        test.endless = self.endless || self.time.is_some();
+        if self.endless {
+            test.stop_after_words = self.stop_after.map(num::NonZeroUsize::get);
+            test.stop_below_accuracy = self.stop_below.map(|percent| percent / 100.0);
+        }
+        test.time_limit = self.time.map(|seconds| Duration::from_secs(seconds.get()));
+        test.drop_cut_off = self.config().cut_off_word == CutOffWord::Drop;
+        test.sudden_death_ends = self.config().sudden_death == SuddenDeathAction::End;
+        test.error_budget = self.error_budget;
+        test.end_over_budget = self.end_over_budget;
+        test.filter_chatter = self.filter_chatter;
+        test.syllables = self.syllables;
+        test.hyphenate = self.hyphenate;
+        test.soft_wrap = self.config().wrap == Wrap::Soft;
+        test.loose_quotes = self.config().loose_quotes;
+        test.confirm_end = self.config().confirm_end;
+        test.pause_after = self
+            .config()
+            .pause_after
+            .filter(|seconds| *seconds > 0.0)
+            .map(Duration::from_secs_f64);
+        // a race ends the way the test it races did, whatever the config says now
+        if let Some(setup) = self.race.as_ref().and_then(|race| race.setup.as_ref()) {
+            test.sudden_death_ends = setup.sudden_death_ends.unwrap_or(test.sudden_death_ends);
+            test.loose_quotes = setup.loose_quotes.unwrap_or(test.loose_quotes);
+            test.confirm_end = setup.confirm_end.unwrap_or(test.confirm_end);
+            if let Some(pause_after) = setup.pause_after {
+                test.pause_after = pause_after
+                    .filter(|seconds| *seconds > 0.0)
+                    .map(Duration::from_secs_f64);
             }
         }
+        let pace = self.config().pace;
+        test.hide_speed = self.config().accuracy_only;
+        // there's no pace to keep without speed
+        test.target_wpm = self
+            .target_wpm
+            .or(pace.target_wpm)
+            .filter(|_| !test.hide_speed);
+        test.pace_grace = Duration::from_secs_f64(pace.grace.max(0.0));
+        test.pace_wpm = self.pace_wpm().filter(|_| !test.hide_speed);
+        test
+    }
+
+    /// Options which decided the words and scoring of the tests, as exported with their results
+    fn setup(&self) -> export::Setup {
+        let words_given = self.quotes.is_some() || self.text.is_some() || self.race.is_some();
+        let config = self.config();
+        export::Setup {
+            version: env!("CARGO_PKG_VERSION"),
+            language_digest: self
+                .words_text()
+                .filter(|_| !words_given)
+                .map(|text| hex(&Sha256::digest(text.as_bytes()))),
+            sampling: self
+                .sampling
+                .to_possible_value()
+                .map(|value| value.get_name().to_owned())
+                .expect("Sampling modes are never skipped."),
+            transforms: self.transforms().iter().map(|t| t.name()).collect(),
+            words: self.words.get(),
+            common: self.common,
+            filter: self
+                .filter
+                .as_ref()
+                .map(|filter| filter.as_str().to_owned()),
+            word_length: self.word_length.map(|lengths| [lengths.min, lengths.max]),
+            seed: self.seed,
+            time: self.time.map(num::NonZeroU64::get),
+            backtrack: !self.no_backtrack,
+            sudden_death: self.sudden_death,
+            sudden_death_ends: config.sudden_death == SuddenDeathAction::End,
+            strictness: self.strictness.name(),
+            loose_quotes: config.loose_quotes,
+            cut_off_word: config.cut_off_word,
+            lexer: self.lexer(),
+            filter_profanity: self.filter_profanity
+                || (self.seed.is_none() && config.filter_profanity),
+            blocklist_digest: Some(self.applied_blocklist())
+                .filter(|blocklist| !words_given && !blocklist.is_empty())
+                .map(|blocklist| {
+                    let mut words: Vec<&str> = blocklist.words().collect();
+                    words.sort_unstable();
+                    hex(&Sha256::digest(words.join("\n").as_bytes()))
+                }),
+            ramp: self.ramp,
+            practice: self.practice.and_then(|practice| {
+                practice
+                    .to_possible_value()
+                    .map(|value| value.get_name().to_owned())
+            }),
+            tricky_caps: config.tricky_caps,
+            sentences: config.sentences,
+            confirm_end: config.confirm_end,
+            pause_after: config.pause_after.filter(|seconds| *seconds > 0.0),
+        }
     }
 
-    /// Configuration
-    fn config(&self) -> Config {
-        fs::read(
-            self.config
+    /// Name of the test contents, as recorded in the history
+    fn language_name(&self, config: &Config) -> String {
+        if self.quotes.is_some() {
+            return String::from("quotes");
+        }
+        if let Some(path) = &self.file {
+            return path.display().to_string();
+        }
+        if self.from_git_diff {
+            return String::from("git diff");
+        }
+        if let Some(race) = &self.race {
+            return race.language.clone();
+        }
+        if self.reads_stdin() {
+            return String::from("stdin");
+        }
+        match (&self.contents, &self.language_file) {
+            (Some(path), _) | (None, Some(path)) => path.display().to_string(),
+            (None, None) => self
+                .dictionary
                 .clone()
-                .unwrap_or_else(|| self.config_dir().join("config.toml")),
+                .or_else(|| self.language.clone())
+                .unwrap_or_else(|| config.default_language.clone()),
+        }
+    }
+
+    /// Configuration, which is only read once
+    fn config(&self) -> &Config {
+        self.loaded_config.get_or_init(|| self.read_config())
+    }
+
+    fn read_config(&self) -> Config {
+        let mut config = self.parse_config();
+        config.accuracy_only |= self.accuracy_only;
+        config.theme.fit_to(color_depth(&config));
+        config
+    }
+
+    /// Config as the file gives it, before its colors are fitted to the terminal
+    fn parse_config(&self) -> Config {
+        let bytes = fs::read(self.config_path()).unwrap_or_default();
+        Config::parse(
+            str::from_utf8(&bytes).unwrap_or_default(),
+            self.theme,
+            background::detect,
         )
-        .map(|bytes| {
-            toml::from_str(str::from_utf8(&bytes).unwrap_or_default())
-                .expect("Configuration was ill-formed.")
-        })
-        .unwrap_or_default()
+        .expect("Configuration was ill-formed.")
+    }
+
+    fn config_path(&self) -> PathBuf {
+        self.config
+            .clone()
+            .unwrap_or_else(|| self.config_dir().join("config.toml"))
+    }
+
+    /// Words left out of generated tests: seeded tests ignore the config's blocklist, so everyone
+    /// with the seed gets the same words, though one given on the command line still applies
+    fn applied_blocklist(&self) -> Blocklist {
+        match self.seed {
+            Some(_) => self.blocklist(None),
+            None => self.blocklist(Some(self.config())),
+        }
+    }
+
+    /// Words to leave out of generated tests, by the flags and, unless it's left out, the config
+    fn blocklist(&self, config: Option<&Config>) -> Blocklist {
+        let mut blocklist = Blocklist::default();
+        if self.filter_profanity || config.is_some_and(|config| config.filter_profanity) {
+            let profanity = resources::get("blocklist/profanity").expect("Missing profanity list.");
+            blocklist.extend_from(str::from_utf8(&profanity).unwrap_or_default());
+        }
+
+        match &self.blocklist {
+            Some(path) => blocklist
+                .extend_from(&fs::read_to_string(path).expect("Error reading blocklist file.")),
+            // the bundled lists are installed beside it, in the `blocklist` directory
+            None if config.is_some() => {
+                match fs::read_to_string(self.config_dir().join("blocklist.txt")) {
+                    Ok(text) => blocklist.extend_from(&text),
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                    Err(err) => panic!("Error reading blocklist file: {}", err),
+                }
+            }
+            None => {}
+        }
+        blocklist
+    }
+
+    /// File of a language by name, from the config directory, then those installed, then those
+    /// bundled
+    fn language_bytes(&self, name: &str) -> Option<Vec<u8>> {
+        fs::read(self.language_dir().join(name))
+            .or_else(|_| fs::read(self.installed_language_dir().join(name)))
+            .ok()
+            .or_else(|| resources::get(&format!("language/{}", name)).map(Cow::into_owned))
     }
 
-    /// Installed languages under config directory
+    /// Bundled languages, those installed by `ttyper languages install`, and those under the
+    /// config directory
     fn languages(&self) -> io::Result<impl Iterator<Item = OsString>> {
-        let builtin = Resources::iter().filter_map(|name| {
-            name.strip_prefix("language/")
-                .map(ToOwned::to_owned)
-                .map(OsString::from)
+        let builtin = resources::list("language").into_iter().map(OsString::from);
+
+        let in_dir = |dir: PathBuf| {
+            dir.read_dir()
+                .into_iter()
+                .flatten()
+                .map_while(Result::ok)
+                .map(|e| e.file_name())
+        };
+
+        // frequency files sit beside their languages without being languages themselves
+        Ok(builtin
+            .chain(in_dir(self.installed_language_dir()))
+            .chain(in_dir(self.language_dir()))
+            .filter(|name| !name.to_string_lossy().ends_with(".freq")))
+    }
+
+    /// Bundled quotes and those in the quotes directory under the config directory, where a file
+    /// named like a bundled one replaces it
+    fn load_quotes(&self) -> Vec<Quote> {
+        let quote_dir = self.config_dir().join("quotes");
+        let bundled = resources::list("quotes").into_iter().filter_map(|name| {
+            if quote_dir.join(&name).exists() {
+                return None;
+            }
+            let format = quotes::Format::of(Path::new(&name))?;
+            let bytes = resources::get(&format!("quotes/{}", name))?;
+            Some(
+                quotes::parse(&String::from_utf8_lossy(&bytes), format)
+                    .expect("Bundled quotes were ill-formed."),
+            )
         });
 
-        let configured = self
-            .language_dir()
+        let configured = quote_dir
             .read_dir()
             .into_iter()
             .flatten()
             .map_while(Result::ok)
-            .map(|e| e.file_name());
+            .filter_map(|entry| {
+                let path = entry.path();
+                let format = quotes::Format::of(&path)?;
+                let text = fs::read_to_string(&path).expect("Error reading quote file.");
+                Some(
+                    quotes::parse(&text, format)
+                        .map_err(|err| format!("{}: {}", path.display(), err))
+                        .expect("Quote file was ill-formed."),
+                )
+            });
 
-        Ok(builtin.chain(configured))
+        bundled.chain(configured).flatten().collect()
     }
 
     /// Config directory
@@ -168,28 +1468,252 @@ impl Opt {
     fn language_dir(&self) -> PathBuf {
         self.config_dir().join("language")
     }
-}
 
-enum State {
-    Test(Test),
-    Results(Results),
-}
+    /// Directory `ttyper languages install` downloads languages into
+    fn installed_language_dir(&self) -> PathBuf {
+        self.data_dir().join("language")
+    }
 
-impl State {
-    fn render_into<B: ratatui::backend::Backend>(
-        &self,
+    /// Data directory
+    fn data_dir(&self) -> PathBuf {
+        dirs::data_dir()
+            .expect("Failed to find data directory.")
+            .join("ttyper")
+    }
+
+    /// Signing key location
+    fn key_path(&self) -> PathBuf {
+        self.data_dir().join("signing.key")
+    }
+
+    /// Signing key, if one has been generated
+    fn signer(&self) -> Option<Signer> {
+        Signer::load(&self.key_path()).ok()
+    }
+
+    /// History database location
+    fn history_path(&self) -> PathBuf {
+        self.data_dir().join("history.sqlite3")
+    }
+
+    /// Stats of the latest test and of today, for other programs to read
+    fn latest_path(&self) -> PathBuf {
+        self.data_dir().join("latest.json")
+    }
+
+    /// History of completed tests, if it can be opened
+    fn history(&self) -> Option<History> {
+        History::open(&self.history_path()).ok()
+    }
+
+    /// Speed of the pace caret given with --pace or in the config, with "best" read from the
+    /// history once
+    fn pace_wpm(&self) -> Option<f64> {
+        *self.loaded_pace.get_or_init(|| {
+            match self.pace.or(self.config().pace.caret)? {
+                PaceCaret::Wpm(wpm) => Some(wpm),
+                // there's nothing to keep ahead of until a test has been recorded
+                PaceCaret::Best => self
+                    .history_path()
+                    .exists()
+                    .then(|| self.history())
+                    .flatten()?
+                    .entries()
+                    .ok()?
+                    .iter()
+                    .map(|entry| entry.wpm)
+                    .max_by(f64::total_cmp),
+            }
+        })
+    }
+
+    /// Keys, pairs of keys and words typed worst in the recent tests of the history, which is
+    /// only read once
+    fn weaknesses(&self) -> &practice::Weaknesses {
+        self.loaded_weaknesses.get_or_init(|| {
+            let mut weaknesses = practice::Weaknesses::default();
+            let Some(history) = self
+                .history_path()
+                .exists()
+                .then(|| self.history())
+                .flatten()
+            else {
+                return weaknesses;
+            };
+            let entries = history.entries().unwrap_or_default();
+            // only tests recorded with their details have their keystrokes
+            for entry in entries
+                .iter()
+                .filter(|entry| entry.detailed)
+                .take(practice::RECENT_TESTS)
+            {
+                let details = history.details(entry.id).unwrap_or_default();
+                if let Ok(record) = serde_json::from_str::<export::Record>(&details) {
+                    weaknesses.add(&record.results());
+                }
+            }
+            weaknesses.words = history
+                .weakest_words(practice::WEAK_WORDS)
+                .unwrap_or_default()
+                .into_iter()
+                .collect();
+            weaknesses
+        })
+    }
+
+    /// Path the position in the file typed through is kept under, the same from any directory
+    fn file_key(&self) -> Option<String> {
+        let path = self.file.as_ref()?;
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+        Some(path.display().to_string())
+    }
+
+    /// Whether the test contents are piped in on stdin
+    fn reads_stdin(&self) -> bool {
+        self.contents
+            .as_ref()
+            .is_some_and(|path| path.as_os_str() == "-")
+    }
+
+    /// How the file typed through or stdin is split into words
+    fn lexer(&self) -> Option<Lexer> {
+        match &self.file {
+            Some(path) => Some(self.lexer.unwrap_or_else(|| self.config().lexer(path))),
+            None if self.reads_stdin() => Some(self.lexer.unwrap_or(Lexer::Prose)),
+            None => None,
+        }
+    }
+
+    /// Words of the text of the file or stdin, as split by the lexer
+    fn lexed(&self, text: &str) -> Option<Vec<TestWord>> {
+        Some(match self.lexer()? {
+            Lexer::Prose => text.split_whitespace().map(TestWord::from).collect(),
+            Lexer::Code => {
+                let mut words = code::words(text);
+                if self.config().highlight_code {
+                    code::highlight(&mut words);
+                }
+                words
+            }
+        })
+    }
+
+    /// Word of the file the next test starts at
+    fn file_start(&self, history: &History) -> usize {
+        let position = self
+            .file_key()
+            .and_then(|key| history.file_position(&key).ok())
+            .unwrap_or_default();
+        let words = self
+            .words_text()
+            .map_or(0, |text| text.split_whitespace().count());
+        // a file typed through to its end starts over
+        if position < words {
+            position
+        } else {
+            0
+        }
+    }
+
+    /// Move the position in the file typed through on by the words of a test typed
+    fn advance_file(&self, history: &History, typed: usize) {
+        if let Some(key) = self.file_key() {
+            let _ = history.set_file_position(&key, self.file_start(history) + typed);
+        }
+    }
+}
+
+enum State {
+    Test(Test),
+    Results(Box<Results>, ResultsChart, ResultNote),
+    /// Stats, with which of their distributions is shown
+    Stats(Stats, usize),
+}
+
+/// Optional panels drawn beside a running test
+struct Panels {
+    breathing: Option<Breathing>,
+    /// WPM of recent results, when the sidebar is shown
+    recent: Option<Vec<f64>>,
+    /// WPM of recent results, when the theme draws them under the prompt
+    trend: Option<Vec<f64>>,
+}
+
+impl State {
+    fn render_into<B: Backend>(
+        &self,
         terminal: &mut Terminal<B>,
         config: &Config,
+        panels: &Panels,
     ) -> io::Result<()> {
         match self {
             State::Test(test) => {
                 terminal.draw(|f| {
-                    f.render_widget(config.theme.apply_to(test), f.size());
+                    let mut area = f.size();
+                    // panels are dropped before they squeeze the test into a narrower tier
+                    if let Some(recent) = panels.recent.as_deref().filter(|_| {
+                        area.width >= SIDEBAR_MIN_WIDTH && area.height >= SIDEBAR_MIN_HEIGHT
+                    }) {
+                        let chunks = Layout::default()
+                            .direction(Direction::Horizontal)
+                            .constraints([Constraint::Min(1), Constraint::Length(SIDEBAR_WIDTH)])
+                            .split(area);
+                        area = chunks[0];
+                        let sidebar = Sidebar {
+                            test,
+                            recent,
+                            grades: &config.accuracy_grades,
+                            rows: config.keyboard.rows(),
+                        };
+                        f.render_widget(config.theme.apply_to(sidebar), chunks[1]);
+                    }
+                    if let Some(breathing) = panels
+                        .breathing
+                        .as_ref()
+                        .filter(|_| area.width >= BREATHING_MIN_WIDTH)
+                    {
+                        let chunks = Layout::default()
+                            .direction(Direction::Horizontal)
+                            .constraints([Constraint::Min(1), Constraint::Length(10)])
+                            .split(area);
+                        area = chunks[0];
+                        f.render_widget(config.theme.apply_to(breathing), chunks[1]);
+                    }
+                    if let Some(recent) = panels.trend.as_deref() {
+                        let chunks = Layout::default()
+                            .direction(Direction::Vertical)
+                            .constraints([Constraint::Min(1), Constraint::Length(1)])
+                            .split(area);
+                        area = chunks[0];
+                        f.render_widget(config.theme.apply_to(Trend { recent }), chunks[1]);
+                    }
+                    f.render_widget(config.theme.apply_to(test), area);
                 })?;
             }
-            State::Results(results) => {
+            State::Results(results, chart, note) => {
                 terminal.draw(|f| {
-                    f.render_widget(config.theme.apply_to(results), f.size());
+                    let view = ResultsView {
+                        results,
+                        chart: *chart,
+                        note,
+                        grades: &config.accuracy_grades,
+                        rows: config.keyboard.rows(),
+                        keys: &config.keybindings,
+                        metrics: &config.metrics,
+                        hide_speed: config.accuracy_only,
+                        viewing: false,
+                    };
+                    f.render_widget(config.theme.apply_to(view), f.size());
+                })?;
+            }
+            State::Stats(stats, shown) => {
+                terminal.draw(|f| {
+                    let view = StatsView {
+                        stats,
+                        keys: &config.keybindings,
+                        shown: *shown,
+                    };
+                    f.render_widget(config.theme.apply_to(view), f.size());
                 })?;
             }
         }
@@ -197,13 +1721,93 @@ impl State {
     }
 }
 
+/// How hard a word list entry is to type, from its length and its rank among the list's words
+fn difficulty(entry: &str, rank: usize, size: usize) -> f64 {
+    let letters: usize = TestWord::from_group(entry)
+        .iter()
+        .map(|word| word.text.chars().count())
+        .sum();
+    letters as f64 + RARITY_WEIGHT * rank as f64 / size as f64
+}
+
+/// Turn words into sentences, with commas and a capital and closing punctuation for each
+fn punctuate(words: &mut [TestWord], sentences: Sentences, rng: &mut impl Rng) {
+    let last = words.len().saturating_sub(1);
+    let mut length = 0;
+    for (i, word) in words.iter_mut().enumerate() {
+        if length == 0 && sentences.capitals {
+            let mut chars = word.text.chars();
+            if let Some(first) = chars.next() {
+                word.text = first.to_uppercase().chain(chars).collect();
+            }
+        }
+        length += 1;
+
+        if i == last || (length >= SENTENCE_MIN_WORDS && rng.gen_bool(SENTENCE_END_CHANCE)) {
+            word.text
+                .push(*['.', '.', '.', '?', '!'].choose(rng).unwrap_or(&'.'));
+            // a second space is part of the word, so it has to be typed before moving on
+            if i != last && sentences.spacing == SentenceSpacing::Double {
+                word.text.push(' ');
+            }
+            length = 0;
+        } else if length > 1 && rng.gen_bool(COMMA_CHANCE) {
+            word.text.push(',');
+        }
+    }
+}
+
+/// Put numbers in place of some words, mostly short ones as prose has, with the odd year
+fn add_numbers(words: &mut [TestWord], rng: &mut impl Rng) {
+    for word in words {
+        if !rng.gen_bool(NUMBER_CHANCE) {
+            continue;
+        }
+        let number = match rng.gen_range(0..10) {
+            0..=3 => rng.gen_range(0..10),
+            4..=6 => rng.gen_range(10..100),
+            7..=8 => rng.gen_range(100..10000),
+            _ => rng.gen_range(1900..2040),
+        };
+        word.text = number.to_string();
+        word.definition = None;
+    }
+}
+
+/// Capitalize letters after the first of each word at the given chance, so both pinkies have
+/// to reach for Shift where a sentence's capitals never ask them to
+fn add_tricky_caps(words: &mut [TestWord], chance: f64, rng: &mut impl Rng) {
+    let chance = chance.clamp(0.0, 1.0);
+    for word in words {
+        let mut chars = word.text.chars();
+        let first = chars.next();
+        word.text = first
+            .into_iter()
+            .chain(chars.map(|c| {
+                if c.is_lowercase() && rng.gen_bool(chance) {
+                    c.to_uppercase().next().unwrap_or(c)
+                } else {
+                    c
+                }
+            }))
+            .collect();
+    }
+}
+
 fn main() -> io::Result<()> {
     let opt = Opt::parse();
     if opt.debug {
         dbg!(&opt);
     }
 
-    let config = opt.config();
+    // the words are read while the config waits for the terminal to tell its background, if
+    // there are words to read and they don't need the config to be found
+    let config = thread::scope(|s| {
+        if opt.reads_words_without_config() {
+            s.spawn(|| opt.words_text());
+        }
+        opt.config()
+    });
     if opt.debug {
         dbg!(&config);
     }
@@ -216,29 +1820,1132 @@ fn main() -> io::Result<()> {
         return Ok(());
     }
 
-    let backend = CrosstermBackend::new(io::stdout());
+    match &opt.command {
+        Some(Command::Key { command }) => return manage_key(&opt, command),
+        Some(Command::Debug { command }) => return debug(command),
+        Some(Command::PromptSegment { style }) => {
+            let latest = prompt::Latest::read(&opt.latest_path());
+            let segment = prompt::segment(latest.as_ref(), date::today(), *style);
+            if !segment.is_empty() {
+                println!("{}", segment);
+            }
+            return Ok(());
+        }
+        Some(Command::Languages { command }) => return manage_languages(&opt, config, command),
+        Some(Command::Stats {
+            command:
+                Some(StatsCommand::Words {
+                    sort,
+                    count,
+                    min_typed,
+                }),
+        }) => return problem_words(&opt, *sort, *count, *min_typed),
+        Some(Command::Stats {
+            command: Some(StatsCommand::Layout { layout, baseline }),
+        }) => {
+            let layout = match layout {
+                Some(layout) => config::layout_name(layout),
+                None => config.keyboard.layout_name(),
+            };
+            return layout_transition(&opt, &config::layout_name(baseline), &layout);
+        }
+        Some(Command::History {
+            command: HistoryCommand::Doctor { repair },
+        }) => return history_doctor(&opt, *repair),
+        Some(Command::History {
+            command:
+                HistoryCommand::List {
+                    since,
+                    until,
+                    language,
+                    mode,
+                    limit,
+                    all,
+                    csv,
+                },
+        }) => {
+            let query = Query {
+                since: *since,
+                until: *until,
+                language: language.clone(),
+                mode: mode.clone(),
+                limit: (!all).then_some(*limit),
+            };
+            return list_history(&opt, &query, *csv);
+        }
+        Some(Command::Verify { path, key }) => return verify(path, key.as_deref()),
+        Some(Command::Theme {
+            command: ThemeCommand::Import { path },
+        }) => return import_theme(&opt, path),
+        Some(Command::Duel {
+            command: Some(DuelCommand::New),
+            ..
+        }) => return new_duel(&opt, config),
+        Some(Command::Duel {
+            command: Some(DuelCommand::Check { result, key, code }),
+            ..
+        }) => return check_duel(result, key.as_deref(), code.as_deref()),
+        Some(Command::Duel {
+            code: Some(code), ..
+        }) if Challenge::parse(code).is_none() => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "not a valid duel code",
+            ))
+        }
+        Some(Command::Simulate {
+            list,
+            model,
+            words,
+            tests,
+        }) => return simulate_list(list, *model, *words, *tests),
+        Some(Command::Soak {
+            length,
+            script: Some(rounds),
+            ..
+        }) => {
+            for round in 0..*rounds {
+                println!("{}", soak::script(round, *length));
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    // read before the terminal is taken over, so a bad file is reported on its own
+    let viewed = match &opt.command {
+        Some(Command::View { path, test }) => Some(exported_results(path, *test)?),
+        _ => None,
+    };
+    let soak_log = match &opt.command {
+        Some(Command::Soak { log, .. }) => {
+            let path = log
+                .clone()
+                .unwrap_or_else(|| opt.data_dir().join("soak.log"));
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)?;
+            Some((file, path))
+        }
+        _ => None,
+    };
+    if opt.from_git_diff && opt.command.is_none() {
+        match opt.words_text() {
+            None => {
+                return Err(io::Error::other(
+                    "couldn't read the changes, as git diff failed to run here",
+                ))
+            }
+            Some(text) if text.trim().is_empty() => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "the changes in this repository add no lines to type",
+                ))
+            }
+            Some(_) => {}
+        }
+    }
+    if opt.lexer.is_some() && opt.file.is_none() && !opt.reads_stdin() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "--lexer only applies to a file typed through with --file, or to stdin",
+        ));
+    }
+    // read before the terminal is taken over, as stdin can only be read once
+    if opt.reads_stdin() && opt.command.is_none() {
+        match opt.words_text() {
+            Some(text) if !text.trim().is_empty() => {}
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "nothing to type was piped in on stdin",
+                ))
+            }
+        }
+    }
+    // a race types the earlier test's words, in place of any others
+    let raced = match (opt.ghost, &opt.command) {
+        (Some(ghost), None) => Some(opt.with_race(race(&opt, ghost)?)),
+        _ => None,
+    };
+    let opt = raced.as_ref().unwrap_or(&opt);
+    let browsed = match &opt.command {
+        Some(Command::History {
+            command: HistoryCommand::Browse,
+        }) => {
+            let path = opt.history_path();
+            if !path.exists() {
+                println!("No history yet at {}.", path.display());
+                return Ok(());
+            }
+            Some(History::open(&path).map_err(io::Error::other)?)
+        }
+        _ => None,
+    };
+
+    // when the results are piped somewhere, the test is drawn on stderr to keep them apart
+    let piped = opt.format.is_some() && !io::stdout().is_terminal();
+    let screen: Box<dyn Write> = if piped {
+        Box::new(io::stderr())
+    } else {
+        Box::new(io::stdout())
+    };
+    let backend = CrosstermBackend::new(screen);
     let mut terminal = Terminal::new(backend)?;
 
     terminal::enable_raw_mode()?;
     execute!(
-        io::stdout(),
+        terminal.backend_mut(),
         cursor::Hide,
         cursor::SavePosition,
         terminal::EnterAlternateScreen,
     )?;
+    // terminals with the enhanced keyboard protocol tell numpad keys apart from the rest
+    // which is asked on stdout, so isn't while the results are piped
+    let enhanced = !piped && terminal::supports_keyboard_enhancement().unwrap_or(false);
+    if enhanced {
+        let mut flags = event::KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES;
+        // and report releases, which the rollover check follows held keys by
+        if matches!(opt.command, Some(Command::Rollover)) {
+            flags |= event::KeyboardEnhancementFlags::REPORT_EVENT_TYPES;
+        }
+        execute!(
+            terminal.backend_mut(),
+            event::PushKeyboardEnhancementFlags(flags)
+        )?;
+    }
     terminal.clear()?;
 
-    let mut state = State::Test(Test::new(
-        opt.gen_contents().expect(
-            "Couldn't get test contents. Make sure the specified language actually exists.",
+    let mut output = Vec::new();
+    let result = match opt.command {
+        Some(Command::Mirror { port }) => mirror::watch(&mut terminal, config, port),
+        Some(Command::Rollover) => rollover::run(&mut terminal, config, enhanced),
+        Some(Command::Soak { length, .. }) => {
+            let (log, path) = soak_log.expect("The soak log is opened first.");
+            soak::run(&mut terminal, config, length, log, &mut output)
+                .map(|()| output.push(format!("Problems are logged to {}.", path.display())))
+        }
+        Some(Command::View { .. }) => view::run(
+            &mut terminal,
+            config,
+            viewed.as_ref().expect("Viewed results are read first."),
         ),
-        !opt.no_backtrack,
-        opt.sudden_death,
-    ));
+        Some(Command::History {
+            command: HistoryCommand::Browse,
+        }) => browse::run(
+            &mut terminal,
+            config,
+            browsed.as_ref().expect("The history is opened first."),
+        ),
+        Some(Command::Theme {
+            command: ThemeCommand::Edit,
+        }) => designer::edit(
+            &mut terminal,
+            &opt.config_path(),
+            opt.parse_config().theme,
+            color_depth(config),
+        ),
+        Some(Command::Classroom {
+            command: ClassroomCommand::Host { port, ref csv },
+        }) => {
+            let mut deck = Vec::new();
+            classroom::host(&mut terminal, config, port, csv.as_deref(), || {
+                opt.new_test(&mut deck)
+            })
+        }
+        _ => run(opt, config, &mut terminal, &mut output),
+    };
+
+    if enhanced {
+        execute!(terminal.backend_mut(), event::PopKeyboardEnhancementFlags)?;
+    }
+    terminal::disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        cursor::RestorePosition,
+        cursor::Show,
+        terminal::LeaveAlternateScreen,
+    )?;
+    // printed after leaving the alternate screen, so they stay in the scrollback
+    for line in output {
+        println!("{}", line);
+    }
+    if let Some(repairs) = opt.words_repairs() {
+        eprintln!("Warning: reading the word list {}.", repairs);
+    }
+
+    result
+}
 
-    state.render_into(&mut terminal, &config)?;
+fn import_theme(opt: &Opt, path: &Path) -> io::Result<()> {
+    let text = fs::read_to_string(path)?;
+    let format = Format::of(path, &text).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "unknown scheme format, expected a .yaml, .itermcolors, .json or .css file",
+        )
+    })?;
+    let scheme = Scheme::parse(&text, format).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            match format {
+                Format::Monkeytype => "theme is missing its bg, main, sub or text color",
+                _ => "scheme is missing some of its sixteen colors",
+            },
+        )
+    })?;
+
+    let config_path = opt.config_path();
+    let styles = scheme.styles();
+    designer::save_styles(&config_path, &styles)?;
+    println!(
+        "Imported {} styles from {} into {}.",
+        styles.len(),
+        path.display(),
+        config_path.display()
+    );
+    Ok(())
+}
+
+/// Lines added by the changes since the last commit of the git repository in the working
+/// directory, or nothing if it isn't in one
+fn git_added_lines() -> Option<(String, words::Repairs)> {
+    let output = process::Command::new("git")
+        .args(["diff", "HEAD", "--no-color", "--no-ext-diff"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let diff = String::from_utf8_lossy(&output.stdout);
+    Some((code::added_lines(&diff), words::Repairs::default()))
+}
+
+fn parse_pace(pace: &str) -> Result<PaceCaret, String> {
+    pace.parse()
+}
+
+fn parse_ghost(test: &str) -> Result<Ghost, String> {
+    match test {
+        "last" => Ok(Ghost::Last),
+        "best" => Ok(Ghost::Best),
+        _ => test
+            .parse()
+            .map(Ghost::Id)
+            .map_err(|_| String::from("expected last, best or the ID of a test")),
+    }
+}
+
+/// Earlier test to race, read from the history before the terminal is taken over
+fn race(opt: &Opt, ghost: Ghost) -> io::Result<Race> {
+    let path = opt.history_path();
+    if !path.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no history yet at {}", path.display()),
+        ));
+    }
+    let history = History::open(&path).map_err(io::Error::other)?;
+    let entries = history.entries().map_err(io::Error::other)?;
+    // only tests recorded with their details have the timings to race
+    let mut detailed = entries.iter().filter(|entry| entry.detailed);
+    let entry = match ghost {
+        Ghost::Last => detailed.next(),
+        Ghost::Best => detailed.max_by(|a, b| a.wpm.total_cmp(&b.wpm)),
+        Ghost::Id(id) => entries.iter().find(|entry| entry.id == id),
+    }
+    .ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            match ghost {
+                Ghost::Id(id) => format!("there's no test {} in the history", id),
+                _ => String::from("no test in the history was recorded with its timings"),
+            },
+        )
+    })?;
+
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+    let details = history.details(entry.id).map_err(io::Error::other)?;
+    if details.is_empty() {
+        return Err(invalid(format!(
+            "test {} was recorded before its timings were kept",
+            entry.id
+        )));
+    }
+    let record: export::Record = serde_json::from_str(&details).map_err(|e| {
+        invalid(format!(
+            "the details of test {} are damaged: {}",
+            entry.id, e
+        ))
+    })?;
+    if record.words.is_empty() {
+        return Err(invalid(format!("test {} has no words to race", entry.id)));
+    }
+    Ok(Race {
+        id: entry.id,
+        language: entry.language.clone(),
+        wpm: entry.wpm,
+        words: record
+            .words
+            .iter()
+            .map(|word| TestWord {
+                indent: word.indent,
+                line_end: word.line_end,
+                ..TestWord::from(word.text.as_str())
+            })
+            .collect(),
+        times: record
+            .words
+            .iter()
+            .map(|word| (word.start, word.end))
+            .collect(),
+        setup: record.setup,
+    })
+}
+
+fn parse_percent(percent: &str) -> Result<f64, String> {
+    percent
+        .trim_end_matches('%')
+        .parse()
+        .ok()
+        .filter(|percent| (0.0..=100.0).contains(percent))
+        .ok_or_else(|| String::from("expected a percentage from 0 to 100, like 95"))
+}
+
+fn parse_date(date: &str) -> Result<i64, String> {
+    date::parse(date).ok_or_else(|| String::from("expected a date like 2026-01-31"))
+}
+
+fn list_history(opt: &Opt, query: &Query, csv: bool) -> io::Result<()> {
+    let path = opt.history_path();
+    if !path.exists() {
+        println!("No history yet at {}.", path.display());
+        return Ok(());
+    }
+    let history = History::open(&path).map_err(io::Error::other)?;
+    let entries = history.query(query).map_err(io::Error::other)?;
+
+    if csv {
+        println!("timestamp,mode,language,duration,wpm,accuracy,note,terminal,term,columns,rows,platform,version,ssh,sudden_death,strictness,id");
+        for entry in &entries {
+            let environment = &entry.environment;
+            println!(
+                "{},{},\"{}\",{:.1},{:.1},{:.1},\"{}\",\"{}\",{},{},{},{},{},{},{},{},{}",
+                entry.timestamp,
+                entry.mode,
+                entry.language.replace('"', "\"\""),
+                entry.duration,
+                entry.wpm,
+                entry.accuracy * 100.0,
+                entry.note.replace('"', "\"\""),
+                environment.terminal.replace('"', "\"\""),
+                environment.term,
+                environment.columns,
+                environment.rows,
+                environment.platform,
+                environment.version,
+                environment.ssh,
+                entry.sudden_death,
+                entry.strictness,
+                entry.id
+            );
+        }
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No tests match.");
+        return Ok(());
+    }
+    println!(
+        "{:>5}  {:<16}  {:<10}  {:<20}  {:>6}  {:>8}  {:>8}  Note",
+        "ID", "Completed (UTC)", "Mode", "Language", "WPM", "Accuracy", "Duration"
+    );
+    for entry in &entries {
+        // sudden death tests are marked ahead of their note
+        let mut note = match entry.sudden_death.as_str() {
+            "" => entry.note.clone(),
+            outcome => format!("[sudden death: {}] {}", outcome, entry.note),
+        };
+        if entry.strictness != Strictness::Forgiving.name() {
+            note = format!("[{}] {}", entry.strictness, note);
+        }
+        println!(
+            "{:>5}  {:<16}  {:<10}  {:<20}  {:>6.1}  {:>7.1}%  {:>7.0}s  {}",
+            entry.id,
+            date::format_time(entry.timestamp),
+            entry.mode,
+            entry.language,
+            entry.wpm,
+            entry.accuracy * 100.0,
+            entry.duration,
+            note.trim_end()
+        );
+    }
+    let count = entries.len() as f64;
+    println!(
+        "{} tests, averaging {:.1} WPM at {:.1}% accuracy.",
+        entries.len(),
+        entries.iter().map(|entry| entry.wpm).sum::<f64>() / count,
+        entries.iter().map(|entry| entry.accuracy).sum::<f64>() / count * 100.0
+    );
+    Ok(())
+}
+
+fn history_doctor(opt: &Opt, repair: bool) -> io::Result<()> {
+    let path = opt.history_path();
+    if !path.exists() {
+        println!("No history yet at {}.", path.display());
+        return Ok(());
+    }
+
+    let history = History::open(&path).map_err(io::Error::other)?;
+    let problems = history.check().map_err(io::Error::other)?;
+    println!(
+        "Checked {} at schema version {}.",
+        path.display(),
+        history.version().map_err(io::Error::other)?
+    );
+    if problems.is_empty() {
+        println!("No problems found.");
+        return Ok(());
+    }
+    for problem in &problems {
+        println!("  {}", problem);
+    }
+
+    if !problems.iter().any(Problem::repairable) {
+        return Ok(());
+    }
+    if !repair {
+        println!("Run `ttyper history doctor --repair` to fix them, after a backup is made.");
+        return Ok(());
+    }
+
+    let mut backup = path.clone().into_os_string();
+    backup.push(".bak");
+    fs::copy(&path, &backup)?;
+    println!(
+        "Backed up the history to {}.",
+        PathBuf::from(backup).display()
+    );
+
+    history.repair(&problems).map_err(io::Error::other)?;
+    let remaining = history.check().map_err(io::Error::other)?;
+    if remaining.iter().any(Problem::repairable) {
+        println!("Some problems couldn't be fixed:");
+        for problem in &remaining {
+            println!("  {}", problem);
+        }
+    } else {
+        println!("Repaired.");
+    }
+    Ok(())
+}
+
+fn manage_key(opt: &Opt, command: &KeyCommand) -> io::Result<()> {
+    let path = opt.key_path();
+    let signer = match command {
+        KeyCommand::Generate { force } => {
+            if path.exists() && !force {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!(
+                        "a signing key already exists at {}; pass --force to replace it",
+                        path.display()
+                    ),
+                ));
+            }
+            Signer::generate(&path)?
+        }
+        KeyCommand::Show => Signer::load(&path)?,
+    };
+
+    println!("{}", signer.public_key());
+    Ok(())
+}
+
+/// Results of one test in an export, the last unless another is picked
+fn exported_results(path: &Path, test: Option<num::NonZeroUsize>) -> io::Result<Results> {
+    let records = export::read(path)?;
+    let index = test.map_or(records.len().checked_sub(1), |n| Some(n.get() - 1));
+    let record = index.and_then(|i| records.get(i)).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} has {} tests", path.display(), records.len()),
+        )
+    })?;
+    if record.keystrokes.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the test has no keystrokes, as it was exported by an older ttyper",
+        ));
+    }
+    Ok(record.results())
+}
+
+fn verify(path: &Path, key: Option<&str>) -> io::Result<()> {
+    let invalid = |msg: String| Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+    match Submission::load(path)?.verify() {
+        Verification::Modified => invalid(String::from("results were modified after saving")),
+        Verification::Forged => invalid(String::from("signature is not valid")),
+        Verification::Unsigned if key.is_some() => invalid(String::from("results are not signed")),
+        Verification::Unsigned => {
+            println!("Results are unmodified but not signed.");
+            Ok(())
+        }
+        Verification::Signed(signer) if key.is_some_and(|key| key != signer) => invalid(format!(
+            "results were signed by a different key, {}",
+            signer
+        )),
+        Verification::Signed(signer) => {
+            println!("Results are unmodified and signed by {}.", signer);
+            Ok(())
+        }
+    }
+}
+
+fn new_duel(opt: &Opt, config: &Config) -> io::Result<()> {
+    if opt.contents.is_some() || opt.language_file.is_some() || opt.dictionary.is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "duels need a language both sides have, chosen with -l",
+        ));
+    }
+    let challenge = Challenge {
+        language: opt.language_name(config),
+        words: opt.words.get(),
+        seed: thread_rng().gen(),
+    };
+    if opt
+        .with_challenge(&challenge)
+        .gen_words(&mut Vec::new())
+        .is_none()
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("couldn't find the language {}", challenge.language),
+        ));
+    }
+
+    println!("{}", challenge);
+    Ok(())
+}
+
+fn check_duel(result: &str, key: Option<&str>, code: Option<&str>) -> io::Result<()> {
+    let invalid = |msg: String| Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+    let Some(outcome) = Outcome::parse(result) else {
+        return invalid(String::from("not a duel result"));
+    };
+    if code.is_some_and(|code| Challenge::parse(code).as_ref() != Some(&outcome.challenge)) {
+        return invalid(format!(
+            "result is for a different duel, {}",
+            outcome.challenge
+        ));
+    }
+
+    let speed = outcome
+        .wpm
+        .map(|wpm| format!("{:.1} WPM at ", wpm))
+        .unwrap_or_default();
+    let score = format!(
+        "{}{:.1}% accuracy on {}",
+        speed, outcome.accuracy, outcome.challenge
+    );
+    match outcome.verify() {
+        Some(false) => invalid(String::from("signature is not valid")),
+        None if key.is_some() => invalid(String::from("result is not signed")),
+        None => {
+            println!("{}, not signed.", score);
+            Ok(())
+        }
+        Some(true) if key.is_some_and(|key| key != outcome.public_key) => invalid(format!(
+            "result was signed by a different key, {}",
+            outcome.public_key
+        )),
+        Some(true) => {
+            println!("{}, signed by {}.", score, outcome.public_key);
+            Ok(())
+        }
+    }
+}
+
+fn layout_transition(opt: &Opt, baseline: &str, layout: &str) -> io::Result<()> {
+    if layout == baseline {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "the layout being learned is {} too, which --layout or the keyboard's layout in the config changes",
+                baseline
+            ),
+        ));
+    }
+    let path = opt.history_path();
+    if !path.exists() {
+        println!("No history yet at {}.", path.display());
+        return Ok(());
+    }
+    let history = History::open(&path).map_err(io::Error::other)?;
+    let entries = history.entries().map_err(io::Error::other)?;
+    let transition = match stats::transition(&entries, baseline, layout) {
+        Ok(transition) => transition,
+        Err(reason) => {
+            println!("Can't compare {} with {}: {}.", layout, baseline, reason);
+            return Ok(());
+        }
+    };
+
+    println!("Switching from {} to {}:", baseline, layout);
+    println!(
+        "{:<10} {:>5.1} WPM at {:>5.1}% accuracy, over {} tests",
+        baseline,
+        transition.baseline_wpm,
+        transition.baseline_accuracy * 100.0,
+        transition.baseline_tests
+    );
+    println!(
+        "{:<10} {:>5.1} WPM at {:>5.1}% accuracy, over {} tests in {:.0} days",
+        layout,
+        transition.current_wpm,
+        transition.current_accuracy * 100.0,
+        transition.tests,
+        transition.days
+    );
+    println!(
+        "{} is at {:.0}% of your {} speed.",
+        layout,
+        transition.current_wpm / transition.baseline_wpm * 100.0,
+        baseline
+    );
+    match (transition.wpm_per_day, transition.crossover) {
+        _ if transition.current_wpm >= transition.baseline_wpm => {
+            println!("You've caught up with your {} speed.", baseline)
+        }
+        (None, _) => println!(
+            "Too few days of tests lately to estimate when you'll catch up."
+        ),
+        (Some(slope), None) => println!(
+            "Your speed changed by {:+.2} WPM a day over the last month, so there's no catching up at this rate.",
+            slope
+        ),
+        (Some(slope), Some(day)) => {
+            let days = day - date::today();
+            println!(
+                "Gaining {:.2} WPM a day over the last month, you'd catch up around {}{}.",
+                slope,
+                date::format(day),
+                match days {
+                    ..=0 => String::new(),
+                    1 => String::from(", tomorrow"),
+                    days => format!(", in {} days", days),
+                }
+            );
+        }
+    }
+    Ok(())
+}
+
+fn problem_words(
+    opt: &Opt,
+    sort: stats::WordSort,
+    count: usize,
+    min_typed: usize,
+) -> io::Result<()> {
+    let path = opt.history_path();
+    if !path.exists() {
+        println!("No history yet at {}.", path.display());
+        return Ok(());
+    }
+    let history = History::open(&path).map_err(io::Error::other)?;
+    let entries = history.entries().map_err(io::Error::other)?;
+    let mut words = Vec::new();
+    let mut tests = 0;
+    // only tests recorded with their details have each word's errors and timing
+    for entry in entries.iter().filter(|entry| entry.detailed) {
+        let details = history.details(entry.id).map_err(io::Error::other)?;
+        let Ok(record) = serde_json::from_str::<export::Record>(&details) else {
+            continue;
+        };
+        words.extend(record.results().words);
+        tests += 1;
+    }
+
+    let words = stats::problem_words(test::results::word_stats(&words), sort, min_typed);
+    if words.is_empty() {
+        println!(
+            "No words typed at least {} times in the {} tests recorded with their details.",
+            min_typed, tests
+        );
+        return Ok(());
+    }
+    let ranked = match sort {
+        stats::WordSort::Slowest => "typed slowest",
+        stats::WordSort::Missed => "missed most",
+    };
+    println!(
+        "Words {}, across {} tests recorded with their details:",
+        ranked, tests
+    );
+    println!("{:<20} {:>7} {:>6} {:>6}", "word", "WPM", "typed", "errors");
+    for word in words.iter().take(count) {
+        println!(
+            "{:<20} {:>7} {:>6} {:>6}",
+            word.text,
+            word.wpm()
+                .map_or_else(|| String::from("-"), |wpm| format!("{:.1}", wpm)),
+            word.typed,
+            word.errors
+        );
+    }
+    Ok(())
+}
+
+fn debug(command: &DebugCommand) -> io::Result<()> {
+    match command {
+        DebugCommand::Align { expected, typed } => {
+            print!("{}", debug::report(&debug::align(expected, typed)));
+        }
+        #[cfg(feature = "harness")]
+        DebugCommand::Fuzz { seed, runs } => {
+            if let Err(failure) = ttyper_core::harness::fuzz(*seed, *runs) {
+                return Err(io::Error::other(failure.to_string()));
+            }
+            println!(
+                "{} inputs checked from seed {}, with no failures.",
+                runs, seed
+            );
+        }
+        #[cfg(feature = "harness")]
+        DebugCommand::Bench { pieces, runs } => {
+            for timing in ttyper_core::harness::bench(*pieces, *runs) {
+                println!(
+                    "{:<16} {:>10.3} ms",
+                    timing.stage,
+                    timing.per_run.as_secs_f64() * 1e3
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn manage_languages(opt: &Opt, config: &Config, command: &LanguagesCommand) -> io::Result<()> {
+    match command {
+        LanguagesCommand::List => {
+            // a language in the config directory is used over one installed or bundled
+            let sources = [
+                ("config", opt.language_dir()),
+                ("installed", opt.installed_language_dir()),
+            ];
+            let mut names: Vec<String> = opt
+                .languages()?
+                .map(|name| name.to_string_lossy().into_owned())
+                .collect();
+            names.sort();
+            names.dedup();
+            println!("{:<24} {:>7}  source", "language", "words");
+            for name in names {
+                let source = sources
+                    .iter()
+                    .find(|(_, dir)| dir.join(&name).is_file())
+                    .map_or("bundled", |&(source, _)| source);
+                let words = opt
+                    .language_bytes(&name)
+                    .map_or(0, |bytes| languages::word_count(&bytes));
+                println!("{:<24} {:>7}  {}", name, words, source);
+            }
+        }
+        LanguagesCommand::Install { name } => {
+            let dir = opt.installed_language_dir();
+            let words = languages::install(&config.language_index, name, &dir)?;
+            println!(
+                "Installed {} with {} words into {}.",
+                name,
+                words,
+                dir.display()
+            );
+            if opt.language_dir().join(name).exists() {
+                println!(
+                    "{} in {} is used over it.",
+                    name,
+                    opt.language_dir().display()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn simulate_list(
+    list: &Path,
+    model: simulate::Model,
+    words: usize,
+    tests: usize,
+) -> io::Result<()> {
+    let (text, repairs) = words::read_list(&fs::read(list)?);
+    if !repairs.is_empty() {
+        eprintln!("Warning: reading the word list {}.", repairs);
+    }
+    let entries: Vec<String> = text
+        .lines()
+        .flat_map(TestWord::from_group)
+        .map(|word| word.text)
+        .filter(|text| !text.is_empty())
+        .collect();
+    if entries.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "word list has no words",
+        ));
+    }
+
+    let report = simulate::simulate(&entries, words, tests, model, &mut thread_rng());
+    println!("{}: {} words", list.display(), entries.len());
+    print!("{}", report);
+    Ok(())
+}
+
+/// Colors the terminal can show, as set in the config or detected
+fn color_depth(config: &Config) -> ColorDepth {
+    match config.color_depth {
+        ColorDepth::Auto => color::detect(),
+        depth => depth,
+    }
+}
+
+/// Name to put on shared results, defaulting to the user's login name
+fn username(name: &Option<String>) -> String {
+    name.clone()
+        .or_else(|| std::env::var("USER").ok())
+        .or_else(|| std::env::var("USERNAME").ok())
+        .unwrap_or_else(|| String::from("student"))
+}
+
+/// WPM of the latest results, or none without a history
+fn recent_wpm(history: &Option<History>, count: usize) -> Vec<f64> {
+    history
+        .as_ref()
+        .and_then(|h| h.recent_wpm(count).ok())
+        .unwrap_or_default()
+}
+
+/// Days in a row up to a day the daily challenge was completed on, which nothing freezes, or
+/// none without a history
+fn daily_streak(history: &Option<History>, day: i64) -> Option<usize> {
+    let completed = history.as_ref()?.practice_days(Some("daily")).ok()?;
+    let policy = StreakPolicy {
+        freezes: 0,
+        vacations: Vec::new(),
+    };
+    Some(stats::streak(&completed, day, &policy).days)
+}
+
+/// Write an escape sequence for the terminal to the stream the test is drawn on, stderr when
+/// stdout isn't a terminal, so results piped from stdout are left alone
+fn write_to_screen(sequence: &str) -> io::Result<()> {
+    let mut screen: Box<dyn Write> = if io::stdout().is_terminal() {
+        Box::new(io::stdout())
+    } else {
+        Box::new(io::stderr())
+    };
+    screen.write_all(sequence.as_bytes())?;
+    screen.flush()
+}
+
+/// Run tests until the typist quits, collecting lines to print once the terminal is restored
+fn run<B: Backend>(
+    opt: &Opt,
+    config: &Config,
+    terminal: &mut Terminal<B>,
+    output: &mut Vec<String>,
+) -> io::Result<()> {
+    let mut assignment = match &opt.command {
+        Some(Command::Assignment {
+            command: AssignmentCommand::Do { path, out, name },
+        }) => {
+            let assignment = Assignment::load(path)?;
+            let out = out
+                .clone()
+                .unwrap_or_else(|| path.with_extension("results.toml"));
+            let submission = Submission::resume(&assignment, username(name), &out)?;
+            if submission.attempts.len() >= assignment.attempts {
+                return Err(io::Error::other(format!(
+                    "all {} attempts at {:?} have been used, as saved in {}",
+                    assignment.attempts,
+                    assignment.title,
+                    out.display()
+                )));
+            }
+            Some((assignment, submission, out))
+        }
+        _ => None,
+    };
+
+    let mut challenge = match &opt.command {
+        Some(Command::Duel {
+            code: Some(code), ..
+        }) => Challenge::parse(code),
+        _ => None,
+    };
+
+    // the day the daily challenge was started on, which it stays the challenge of
+    let mut daily = matches!(opt.command, Some(Command::Daily)).then(date::today);
+    let daily_challenge = daily.map(Challenge::daily);
+
+    // assignments, duels and the daily challenge decide what is being typed
+    let overridden_opt = assignment
+        .as_ref()
+        .map(|(assignment, _, _)| opt.with_test_definition(&assignment.test))
+        .or_else(|| challenge.as_ref().map(|c| opt.with_challenge(c)))
+        .or_else(|| daily_challenge.as_ref().map(|c| opt.with_challenge(c)));
+    let opt = overridden_opt.as_ref().unwrap_or(opt);
+
+    // opened once it's needed, which for most tests is once they're complete
+    let history = OnceCell::new();
+    let history = || history.get_or_init(|| opt.history());
+    let signer = opt.signer();
+    let keys = &config.keybindings;
+    let language = opt.language_name(config);
+    let transforms: Vec<String> = opt.transforms().iter().map(|t| t.name()).collect();
+    let setup = opt.setup();
+    let server = opt
+        .share
+        .map(|port| mirror::Server::bind(port.unwrap_or(mirror::DEFAULT_PORT)))
+        .transpose()?;
+    // what kind of test is running, as recorded in the history
+    let default_mode = if assignment.is_some() {
+        "assignment"
+    } else if daily.is_some() {
+        "daily"
+    } else if challenge.is_some() {
+        "duel"
+    } else if opt.quotes.is_some() {
+        "quote"
+    } else if opt.time.is_some() {
+        "timed"
+    } else if opt.endless {
+        "endless"
+    } else if opt.file.is_some() {
+        "file"
+    } else if opt.from_git_diff {
+        "diff"
+    } else if opt.race.is_some() {
+        "ghost"
+    } else if opt.practice.is_some() {
+        "practice"
+    } else {
+        "words"
+    };
+    let mut mode = default_mode;
+    if opt.practice.is_some() {
+        let weaknesses = opt.weaknesses();
+        let keys = weaknesses.weakest_keys(PRACTICED_SHOWN);
+        let pairs = weaknesses.weakest_pairs(PRACTICED_SHOWN);
+        output.push(if keys.is_empty() && pairs.is_empty() {
+            String::from(
+                "Nothing stands out as weak in the recent history yet, so no keys were favored.",
+            )
+        } else {
+            let list = |items: Vec<String>| {
+                if items.is_empty() {
+                    String::from("-")
+                } else {
+                    items.join(" ")
+                }
+            };
+            format!(
+                "Favored the keys missed most lately: {}, and pairs: {}.",
+                list(keys.iter().map(char::to_string).collect()),
+                list(pairs)
+            )
+        });
+    }
+
+    let mut student = match &opt.command {
+        Some(Command::Classroom {
+            command: ClassroomCommand::Join { addr, name },
+        }) => {
+            mode = "classroom";
+            Some(classroom::Student::join(addr, &username(name))?)
+        }
+        _ => None,
+    };
+
+    let mut deck = Vec::new();
+    // the test just finished, to take it again
+    let mut finished: Option<Test> = None;
+    let mut state = match opt.command {
+        Some(Command::Stats { .. }) => {
+            let mut stats = Stats::from_entries(
+                &history()
+                    .as_ref()
+                    .and_then(|h| h.entries().ok())
+                    .unwrap_or_default(),
+            );
+            stats.weakest_words = history()
+                .as_ref()
+                .and_then(|h| h.weakest_words(WEAKEST_WORDS).ok())
+                .unwrap_or_default();
+            let practiced = history()
+                .as_ref()
+                .and_then(|h| h.practice_days(None).ok())
+                .unwrap_or_default();
+            stats.streak = stats::streak(&practiced, date::today(), &config.streak);
+            stats.daily_streak = daily_streak(history(), date::today()).unwrap_or_default();
+            State::Stats(stats, 0)
+        }
+        _ => match &mut student {
+            Some((_, words, rules)) => State::Test(rules.apply(opt.test_of(std::mem::take(words)))),
+            None => State::Test(opt.new_test(&mut deck)),
+        },
+    };
+    let mut student = student.map(|(student, _, _)| student);
+
+    let mut panels = Panels {
+        breathing: opt.breathe.map(Breathing::new),
+        recent: opt.sidebar.then(|| recent_wpm(history(), SIDEBAR_RECENT)),
+        trend: match config.theme.prompt_sparkline_results {
+            _ if config.accuracy_only => None,
+            0 => None,
+            count => Some(recent_wpm(history(), count)),
+        },
+    };
+
+    state.render_into(terminal, config, &panels)?;
     loop {
-        let event = event::read()?;
+        // the teacher decides when a new test starts
+        if let Some((words, rules)) = student.as_mut().and_then(|s| s.next_test()) {
+            mode = "classroom";
+            state = State::Test(rules.apply(opt.test_of(words)));
+            state.render_into(terminal, config, &panels)?;
+        }
+
+        // keep animations, the classroom, the pause indicator, timers, clocks and the pace alarm
+        // moving while waiting for input
+        if let State::Test(ref mut test) = state {
+            test.check_pace();
+        }
+        let ticking = matches!(&state, State::Test(test) if test.pause_after.is_some()
+            || test.ghost.is_some()
+            || test.pace_wpm.is_some()
+            || test.clock_debug
+            || test.time_limit.is_some()
+            || test.target_wpm.is_some());
+        let timed_out =
+            matches!(&state, State::Test(test) if test.time_left() == Some(Duration::ZERO));
+        let event = if timed_out {
+            // a keypress which does nothing ends the test like any other once time is up
+            Event::Key(KeyEvent::from(KeyCode::Null))
+        } else if (panels.breathing.is_some() || student.is_some() || opt.endless || ticking)
+            && !event::poll(TICK_RATE)?
+        {
+            state.render_into(terminal, config, &panels)?;
+            continue;
+        } else {
+            event::read()?
+        };
 
         // handle exit controls
         match event {
@@ -248,16 +2955,22 @@ fn main() -> io::Result<()> {
                 modifiers: KeyModifiers::CONTROL,
                 ..
             }) => break,
-            Event::Key(KeyEvent {
-                code: KeyCode::Esc,
-                kind: KeyEventKind::Press,
-                modifiers: KeyModifiers::NONE,
-                ..
-            }) => match state {
+            Event::Key(key) if keys.end.matches(&key) => match state {
+                // endless tests have no other way to end, so they're completed like any other
+                State::Test(ref mut test) if test.endless => test.complete = true,
                 State::Test(ref test) => {
-                    state = State::Results(Results::from(test));
+                    if let (Some(history), "file") = (history(), mode) {
+                        opt.advance_file(history, test.current_word);
+                    }
+                    finished = Some(test.clone());
+                    state = State::Results(
+                        Box::new(Results::from(test)),
+                        ResultsChart::default(),
+                        ResultNote::default(),
+                    );
                 }
-                State::Results(_) => break,
+                State::Results(_, _, ref mut note) if note.draft.is_some() => note.draft = None,
+                State::Results(..) | State::Stats(..) => break,
             },
             _ => {}
         }
@@ -265,68 +2978,288 @@ fn main() -> io::Result<()> {
         match state {
             State::Test(ref mut test) => {
                 if let Event::Key(key) = event {
-                    test.handle_key(key);
+                    if keys.restart.matches(&key) && student.is_none() {
+                        test.reset();
+                    } else {
+                        test.handle_key(key);
+                    }
+                    if test.endless && test.words.len() - test.current_word < opt.words.get() {
+                        test.words
+                            .extend(opt.gen_contents(&mut deck).unwrap_or_default());
+                    }
+                    if let Some(student) = &mut student {
+                        student.report(&key);
+                    }
                     if test.complete {
-                        state = State::Results(Results::from(&*test));
+                        if let Some(server) = &server {
+                            server.broadcast(&Snapshot::from(&*test));
+                        }
+                        let results = Results::from(&*test);
+                        let accuracy = f64::from(results.accuracy.overall) * 100.0;
+                        let score = if config.accuracy_only {
+                            format!("Test complete: {:.1}% accuracy", accuracy)
+                        } else {
+                            format!(
+                                "Test complete: {:.1} WPM at {:.1}% accuracy",
+                                results.wpm(),
+                                accuracy
+                            )
+                        };
+                        if let Some(sequence) = notify::sequence(config.notify, &score) {
+                            write_to_screen(&sequence)?;
+                        }
+                        let id = history().as_ref().and_then(|history| {
+                            let size = terminal.size().unwrap_or_default();
+                            let environment = Environment::detect(size.width, size.height);
+                            history
+                                .record(
+                                    &results,
+                                    mode,
+                                    &language,
+                                    &transforms,
+                                    &config.keyboard,
+                                    &environment,
+                                )
+                                .ok()
+                                .flatten()
+                        });
+                        if let (Some(history), "file") = (history(), mode) {
+                            opt.advance_file(history, test.words.len());
+                        }
+                        if panels.recent.is_some() {
+                            panels.recent = Some(recent_wpm(history(), SIDEBAR_RECENT));
+                        }
+                        if let Some(trend) = &mut panels.trend {
+                            *trend = recent_wpm(history(), config.theme.prompt_sparkline_results);
+                        }
+                        let timestamp = SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .map(|d| d.as_secs() as i64)
+                            .unwrap_or_default();
+                        let last = prompt::Last {
+                            timestamp,
+                            mode: mode.to_owned(),
+                            language: language.clone(),
+                            wpm: results.wpm(),
+                            accuracy: f64::from(results.accuracy.overall),
+                        };
+                        let latest_path = opt.latest_path();
+                        let previous = prompt::Latest::read(&latest_path);
+                        // only read by other programs, so failing to write it doesn't end the test
+                        let _ = prompt::Latest::after(previous.as_ref(), last).write(&latest_path);
+                        let export = Export::new(
+                            &results,
+                            mode,
+                            &language,
+                            &setup,
+                            &config.metrics,
+                            timestamp,
+                        );
+                        // kept with the result so it can be opened again from the history
+                        if let (Some(history), Some(id)) = (history(), id) {
+                            let _ = history.set_details(id, &export.to_json());
+                        }
+                        if let Some(path) = &opt.json {
+                            export::append(path, &export)?;
+                        }
+                        if let Some(export::Format::Json) = opt.format {
+                            output.push(export.to_json());
+                        }
+                        if let Some((assignment, submission, out)) = &mut assignment {
+                            if submission.attempts.len() < assignment.attempts {
+                                submission.attempts.push(Attempt::from(&results));
+                                submission.save(assignment, out, signer.as_ref())?;
+                            }
+                        }
+                        // only the first try counts, as anyone could retry until they win
+                        if let Some(challenge) = challenge.take() {
+                            output.push(
+                                Outcome::new(
+                                    &challenge,
+                                    &results,
+                                    config.accuracy_only,
+                                    signer.as_ref(),
+                                )
+                                .to_string(),
+                            );
+                        }
+                        if let Some(day) = daily.take() {
+                            let speed = if config.accuracy_only {
+                                String::new()
+                            } else {
+                                format!("{:.1} WPM at ", results.wpm())
+                            };
+                            let mut line = format!(
+                                "Daily challenge for {}: {}{:.1}% accuracy",
+                                date::format(day),
+                                speed,
+                                f64::from(results.accuracy.overall) * 100.0
+                            );
+                            if let Some(days) = daily_streak(history(), day) {
+                                line += &format!(", a {}-day streak", days);
+                            }
+                            output.push(line + ".");
+                        }
+                        if let Some(race) = &opt.race {
+                            let finish = race.times.last().map_or(0.0, |&(_, end)| end);
+                            let lead = finish - results.duration();
+                            let speeds = if config.accuracy_only {
+                                String::new()
+                            } else {
+                                format!("{:.1} WPM to its {:.1} WPM, ", results.wpm(), race.wpm)
+                            };
+                            output.push(format!(
+                                "Against test {}: {}finishing {:.1}s {}.",
+                                race.id,
+                                speeds,
+                                lead.abs(),
+                                if lead >= 0.0 { "ahead" } else { "behind" }
+                            ));
+                        }
+                        let note = ResultNote {
+                            id,
+                            ..ResultNote::default()
+                        };
+                        if opt.quiet {
+                            break;
+                        }
+                        finished = Some(test.clone());
+                        state = State::Results(Box::new(results), ResultsChart::default(), note);
                     }
                 }
             }
-            State::Results(ref result) => match event {
-                Event::Key(KeyEvent {
-                    code: KeyCode::Char('r'),
+            State::Results(_, _, ref mut note) if note.draft.is_some() => {
+                if let Event::Key(KeyEvent {
+                    code,
                     kind: KeyEventKind::Press,
-                    modifiers: KeyModifiers::NONE,
+                    modifiers,
                     ..
-                }) => {
-                    state = State::Test(Test::new(
-                        opt.gen_contents().expect(
-                            "Couldn't get test contents. Make sure the specified language actually exists.",
-                        ),
-                        !opt.no_backtrack,
-                        opt.sudden_death
-                    ));
+                }) = event
+                {
+                    let draft = note.draft.get_or_insert_with(String::new);
+                    match code {
+                        KeyCode::Char(c) if !modifiers.contains(KeyModifiers::CONTROL) => {
+                            draft.push(c)
+                        }
+                        KeyCode::Backspace => {
+                            draft.pop();
+                        }
+                        KeyCode::Enter => {
+                            note.text = draft.trim().to_string();
+                            note.draft = None;
+                            if let (Some(history), Some(id)) = (history(), note.id) {
+                                let _ = history.set_note(id, &note.text);
+                            }
+                        }
+                        _ => {}
+                    }
                 }
-                Event::Key(KeyEvent {
-                    code: KeyCode::Char('p'),
-                    kind: KeyEventKind::Press,
-                    modifiers: KeyModifiers::NONE,
-                    ..
-                }) => {
+            }
+            State::Results(ref result, ref mut chart, ref mut note) => match event {
+                Event::Key(key) if keys.next_test.matches(&key) && student.is_none() => {
+                    mode = default_mode;
+                    state = State::Test(opt.new_test(&mut deck));
+                }
+                Event::Key(key) if keys.repeat.matches(&key) && student.is_none() => {
+                    if let Some(finished) = &finished {
+                        // the file was already moved on past these words, so it isn't again
+                        if mode == "file" {
+                            mode = "words";
+                        }
+                        state = State::Test(finished.repeat());
+                    }
+                }
+                Event::Key(key) if keys.chart.matches(&key) => *chart = chart.next(),
+                Event::Key(key) if keys.note.matches(&key) && note.id.is_some() => {
+                    note.draft = Some(note.text.clone())
+                }
+                Event::Key(key) if keys.share.matches(&key) => {
+                    let fields = share::fields(result, mode, &language, config.accuracy_only);
+                    let card = share::card(
+                        &share::template(&config.share.template, config.accuracy_only),
+                        &fields,
+                    );
+                    match config.share.target {
+                        ShareTarget::Clipboard => write_to_screen(&share::osc52(&card))?,
+                        ShareTarget::Stdout => output.push(card),
+                    }
+                }
+                Event::Key(key)
+                    if keys.missed_words.matches(&key) && !result.missed_words.is_empty() =>
+                {
+                    let mut words = result.missed_words.clone();
+                    words.sort();
+                    words.dedup();
+                    let mut list = words.join("\n");
+                    list.push('\n');
+                    match config.missed_words.target {
+                        MissedWordsTarget::Clipboard => write_to_screen(&share::osc52(&list))?,
+                        MissedWordsTarget::File => {
+                            let path = config
+                                .missed_words
+                                .file
+                                .clone()
+                                .unwrap_or_else(|| opt.data_dir().join("missed-words.txt"));
+                            if let Some(parent) = path.parent() {
+                                fs::create_dir_all(parent)?;
+                            }
+                            fs::OpenOptions::new()
+                                .create(true)
+                                .append(true)
+                                .open(path)?
+                                .write_all(list.as_bytes())?;
+                        }
+                    }
+                }
+                Event::Key(key) if keys.practice.matches(&key) => {
                     if result.missed_words.is_empty() {
                         continue;
                     }
                     // repeat each missed word 5 times
-                    let mut practice_words: Vec<String> = (result.missed_words)
+                    let mut practice_words: Vec<TestWord> = (result.missed_words)
                         .iter()
                         .flat_map(|w| vec![w.clone(); 5])
+                        .map(TestWord::from)
                         .collect();
                     practice_words.shuffle(&mut thread_rng());
-                    state = State::Test(Test::new(
-                        practice_words,
-                        !opt.no_backtrack,
-                        opt.sudden_death,
-                    ));
+                    mode = "practice";
+                    state = State::Test(opt.test_of(practice_words));
                 }
-                Event::Key(KeyEvent {
-                    code: KeyCode::Char('q'),
-                    kind: KeyEventKind::Press,
-                    modifiers: KeyModifiers::NONE,
-                    ..
-                }) => break,
+                Event::Key(key) if keys.quit.matches(&key) => break,
+                _ => {}
+            },
+            State::Stats(ref stats, ref mut shown) => match event {
+                Event::Key(key) if keys.next_test.matches(&key) => {
+                    mode = default_mode;
+                    state = State::Test(opt.new_test(&mut deck));
+                }
+                Event::Key(key) if keys.drill.matches(&key) => {
+                    if stats.weakest_words.is_empty() {
+                        continue;
+                    }
+                    // drill each weak word 5 times, like missed words
+                    let mut drill_words: Vec<TestWord> = (stats.weakest_words)
+                        .iter()
+                        .flat_map(|w| vec![w.clone(); 5])
+                        .map(TestWord::from)
+                        .collect();
+                    drill_words.shuffle(&mut thread_rng());
+                    mode = "practice";
+                    state = State::Test(opt.test_of(drill_words));
+                }
+                Event::Key(key) if keys.chart.matches(&key) => {
+                    *shown = (*shown + 1) % stats.distributions.len().max(1);
+                }
+                Event::Key(key) if keys.quit.matches(&key) => break,
                 _ => {}
             },
         }
 
-        state.render_into(&mut terminal, &config)?;
+        state.render_into(terminal, config, &panels)?;
+        if let (Some(server), State::Test(test)) = (&server, &state) {
+            server.broadcast(&Snapshot::from(test));
+        }
     }
 
-    terminal::disable_raw_mode()?;
-    execute!(
-        io::stdout(),
-        cursor::RestorePosition,
-        cursor::Show,
-        terminal::LeaveAlternateScreen,
-    )?;
-
     Ok(())
 }
@@ -0,0 +1,326 @@
+//! Metrics worked out from results with formulas from the config, like `score = wpm * acc^2`
+
+use crate::test::results::{Results, BURST_WINDOW};
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Formulas from the config by the name they're shown under
+pub type Metrics = BTreeMap<String, Formula>;
+
+/// Value of a result which a formula can use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Variable {
+    Wpm,
+    RawWpm,
+    BurstWpm,
+    /// Fraction of correct keypresses, from 0 to 1
+    Acc,
+    Consistency,
+    /// Seconds spent typing
+    Duration,
+    CorrectChars,
+    TotalKeystrokes,
+    Backspaces,
+    Errors,
+    Words,
+    /// Fraction of paired delimiters and quotes typed correctly, from 0 to 1
+    DelimiterAcc,
+    DelimiterWpm,
+}
+
+const VARIABLES: [(&str, Variable); 13] = [
+    ("wpm", Variable::Wpm),
+    ("raw_wpm", Variable::RawWpm),
+    ("burst_wpm", Variable::BurstWpm),
+    ("acc", Variable::Acc),
+    ("consistency", Variable::Consistency),
+    ("duration", Variable::Duration),
+    ("correct_chars", Variable::CorrectChars),
+    ("total_keystrokes", Variable::TotalKeystrokes),
+    ("backspaces", Variable::Backspaces),
+    ("errors", Variable::Errors),
+    ("words", Variable::Words),
+    ("delimiter_acc", Variable::DelimiterAcc),
+    ("delimiter_wpm", Variable::DelimiterWpm),
+];
+
+impl Variable {
+    fn value(self, results: &Results) -> f64 {
+        let accuracy = results.accuracy.overall;
+        match self {
+            Self::Wpm => results.wpm(),
+            Self::RawWpm => results.raw_wpm(),
+            Self::BurstWpm => results.peak_wpm(BURST_WINDOW).unwrap_or(f64::NAN),
+            Self::Acc => f64::from(accuracy),
+            Self::Consistency => results.consistency(),
+            Self::Duration => results.duration(),
+            Self::CorrectChars => accuracy.numerator as f64,
+            Self::TotalKeystrokes => results.corrections.keystrokes as f64,
+            Self::Backspaces => results.corrections.backspaces as f64,
+            Self::Errors => (accuracy.denominator - accuracy.numerator) as f64,
+            Self::Words => results.words.len() as f64,
+            Self::DelimiterAcc => results
+                .delimiters()
+                .map_or(f64::NAN, |delimiters| f64::from(delimiters.accuracy)),
+            Self::DelimiterWpm => results
+                .delimiters()
+                .and_then(|delimiters| delimiters.wpm)
+                .unwrap_or(f64::NAN),
+        }
+    }
+
+    fn is_speed(self) -> bool {
+        matches!(
+            self,
+            Self::Wpm | Self::RawWpm | Self::BurstWpm | Self::DelimiterWpm
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Variable(Variable),
+    Negate(Box<Expr>),
+    Binary(Operator, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, value: &impl Fn(Variable) -> f64) -> f64 {
+        match self {
+            Self::Number(n) => *n,
+            Self::Variable(variable) => value(*variable),
+            Self::Negate(expr) => -expr.eval(value),
+            Self::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.eval(value), rhs.eval(value));
+                match op {
+                    Operator::Add => lhs + rhs,
+                    Operator::Subtract => lhs - rhs,
+                    Operator::Multiply => lhs * rhs,
+                    Operator::Divide => lhs / rhs,
+                    Operator::Power => lhs.powf(rhs),
+                }
+            }
+        }
+    }
+
+    fn uses(&self, test: &impl Fn(Variable) -> bool) -> bool {
+        match self {
+            Self::Number(_) => false,
+            Self::Variable(variable) => test(*variable),
+            Self::Negate(expr) => expr.uses(test),
+            Self::Binary(_, lhs, rhs) => lhs.uses(test) || rhs.uses(test),
+        }
+    }
+}
+
+/// Arithmetic on the values of a result, with `+`, `-`, `*`, `/`, `^` and parentheses
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Formula(Expr);
+
+impl Formula {
+    /// Value of the formula for a test, if it works out to a number
+    pub fn eval(&self, results: &Results) -> Option<f64> {
+        Some(self.0.eval(&|variable| variable.value(results))).filter(|value| value.is_finite())
+    }
+
+    /// Whether the formula uses a speed, which accuracy-only tests keep out of sight
+    pub fn uses_speed(&self) -> bool {
+        self.0.uses(&Variable::is_speed)
+    }
+}
+
+impl std::str::FromStr for Formula {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            chars: s.chars().peekable(),
+        };
+        let expr = parser.sum()?;
+        match parser.next_token() {
+            None => Ok(Self(expr)),
+            Some(c) => Err(format!("unexpected `{}`", c)),
+        }
+    }
+}
+
+impl TryFrom<String> for Formula {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Values of each metric for a test, missing where a formula doesn't work out to a number, such
+/// as when it divides by zero
+pub fn values<'a>(metrics: &'a Metrics, results: &Results) -> BTreeMap<&'a str, Option<f64>> {
+    metrics
+        .iter()
+        .map(|(name, formula)| (name.as_str(), formula.eval(results)))
+        .collect()
+}
+
+// recursive descent, from the loosest binding operators to the tightest
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+    /// Next char which isn't whitespace, without taking it
+    fn peek_token(&mut self) -> Option<char> {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+        self.chars.peek().copied()
+    }
+
+    fn next_token(&mut self) -> Option<char> {
+        self.peek_token()?;
+        self.chars.next()
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut expr = self.product()?;
+        while let Some(op) = self.peek_token().and_then(|c| match c {
+            '+' => Some(Operator::Add),
+            '-' => Some(Operator::Subtract),
+            _ => None,
+        }) {
+            self.chars.next();
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.product()?));
+        }
+        Ok(expr)
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        while let Some(op) = self.peek_token().and_then(|c| match c {
+            '*' => Some(Operator::Multiply),
+            '/' => Some(Operator::Divide),
+            _ => None,
+        }) {
+            self.chars.next();
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    // `-x^2` is `-(x^2)`, as in mathematics
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.peek_token() == Some('-') {
+            self.chars.next();
+            return Ok(Expr::Negate(Box::new(self.unary()?)));
+        }
+        self.power()
+    }
+
+    // powers group from the right, so `2^3^2` is `2^9`
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.atom()?;
+        if self.peek_token() == Some('^') {
+            self.chars.next();
+            let exponent = self.unary()?;
+            return Ok(Expr::Binary(
+                Operator::Power,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        match self.peek_token() {
+            Some('(') => {
+                self.chars.next();
+                let expr = self.sum()?;
+                match self.next_token() {
+                    Some(')') => Ok(expr),
+                    _ => Err(String::from("missing `)`")),
+                }
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let number = self.take_while(|c| c.is_ascii_digit() || c == '.');
+                number
+                    .parse()
+                    .map(Expr::Number)
+                    .map_err(|_| format!("{} is not a number", number))
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let name = self.take_while(|c| c.is_alphanumeric() || c == '_');
+                VARIABLES
+                    .iter()
+                    .find(|(known, _)| *known == name)
+                    .map(|&(_, variable)| Expr::Variable(variable))
+                    .ok_or_else(|| format!("{} is not a variable", name))
+            }
+            Some(c) => Err(format!("unexpected `{}`", c)),
+            None => Err(String::from("formula ends too soon")),
+        }
+    }
+
+    fn take_while(&mut self, mut keep: impl FnMut(char) -> bool) -> String {
+        let mut taken = String::new();
+        while let Some(c) = self.chars.next_if(|&c| keep(c)) {
+            taken.push(c);
+        }
+        taken
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(formula: &str) -> f64 {
+        let formula: Formula = formula.parse().unwrap();
+        formula.0.eval(&|variable| match variable {
+            Variable::Wpm => 80.0,
+            Variable::Acc => 0.5,
+            Variable::CorrectChars => 30.0,
+            Variable::TotalKeystrokes => 40.0,
+            _ => 0.0,
+        })
+    }
+
+    #[test]
+    fn evaluates_with_precedence() {
+        assert_eq!(eval("wpm * acc^2"), 20.0);
+        assert_eq!(eval("correct_chars / total_keystrokes"), 0.75);
+        assert_eq!(eval("1 + 2 * 3 - 4 / 2"), 5.0);
+        assert_eq!(eval("(1 + 2) * 3"), 9.0);
+        assert_eq!(eval("2^3^2"), 512.0);
+        assert_eq!(eval("-2^2"), -4.0);
+        assert_eq!(eval("2 ^ -1"), 0.5);
+    }
+
+    #[test]
+    fn rejects_bad_formulas() {
+        let error = |formula: &str| formula.parse::<Formula>().unwrap_err();
+        assert_eq!(error("wpm * speed"), "speed is not a variable");
+        assert_eq!(error("(wpm"), "missing `)`");
+        assert_eq!(error("wpm acc"), "unexpected `a`");
+        assert_eq!(error("wpm *"), "formula ends too soon");
+        assert_eq!(error("1.2.3"), "1.2.3 is not a number");
+    }
+
+    #[test]
+    fn knows_which_formulas_use_speed() {
+        let formula = |formula: &str| formula.parse::<Formula>().unwrap();
+        assert!(formula("-(raw_wpm / 2)").uses_speed());
+        assert!(!formula("acc^2 * duration").uses_speed());
+    }
+}
@@ -0,0 +1,175 @@
+use crate::config::Config;
+use crate::test::{results::Results, Test, TestWord};
+use crate::ui::Mirror;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{backend::Backend, terminal::Terminal};
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+pub const DEFAULT_PORT: u16 = 7879;
+
+// Longest a snapshot may take to send before the mirror is dropped
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+// How often the mirror checks for input while waiting for snapshots
+const POLL_RATE: Duration = Duration::from_millis(50);
+
+/// Read-only copy of a running test, sent to mirrors after every change
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub words: Vec<(String, String)>,
+    pub current_word: usize,
+    pub complete: bool,
+    /// Missing until enough keys have been pressed to measure
+    pub wpm: Option<f64>,
+    pub accuracy: Option<f64>,
+    /// Whether the test hides speed, which is then never sent
+    #[serde(default)]
+    pub hide_speed: bool,
+}
+
+impl From<&Test> for Snapshot {
+    fn from(test: &Test) -> Self {
+        let results = Results::from(test);
+        Self {
+            words: test
+                .words
+                .iter()
+                .map(|w| (w.text.clone(), w.progress.clone()))
+                .collect(),
+            current_word: test.current_word,
+            complete: test.complete,
+            wpm: Some(results.wpm()).filter(|x| x.is_finite() && !test.hide_speed),
+            accuracy: Some(f64::from(results.accuracy.overall)).filter(|x| x.is_finite()),
+            hide_speed: test.hide_speed,
+        }
+    }
+}
+
+impl Snapshot {
+    /// Rebuild a test which renders like the one being mirrored
+    pub fn to_test(&self) -> Test {
+        let mut test = Test::new(
+            self.words
+                .iter()
+                .map(|(text, progress)| TestWord {
+                    progress: progress.clone(),
+                    ..TestWord::from(text.as_str())
+                })
+                .collect(),
+            true,
+            false,
+        );
+        test.current_word = self.current_word.min(self.words.len().saturating_sub(1));
+        test.complete = self.complete;
+        test.hide_speed = self.hide_speed;
+        test
+    }
+}
+
+/// Accepts mirrors on a local port and sends them snapshots
+pub struct Server {
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+impl Server {
+    pub fn bind(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+
+        let accepted = Arc::clone(&clients);
+        thread::spawn(move || {
+            for stream in listener.incoming().map_while(Result::ok) {
+                let _ = stream.set_nodelay(true);
+                // a mirror which stops reading shouldn't stall the test
+                let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+                accepted.lock().unwrap().push(stream);
+            }
+        });
+
+        Ok(Self { clients })
+    }
+
+    pub fn broadcast(&self, snapshot: &Snapshot) {
+        let mut line = serde_json::to_string(snapshot).expect("Snapshot failed to serialize.");
+        line.push('\n');
+
+        // drop mirrors which have disconnected
+        self.clients
+            .lock()
+            .unwrap()
+            .retain_mut(|stream| stream.write_all(line.as_bytes()).is_ok());
+    }
+}
+
+/// Display snapshots from a test running on the given local port until the user quits
+pub fn watch<B: Backend>(terminal: &mut Terminal<B>, config: &Config, port: u16) -> io::Result<()> {
+    let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port))?;
+
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines().map_while(Result::ok) {
+            if let Ok(snapshot) = serde_json::from_str::<Snapshot>(&line) {
+                if tx.send(snapshot).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+
+    let mut snapshot: Option<Snapshot> = None;
+    let mut connected = true;
+    let mut changed = true;
+    loop {
+        loop {
+            match rx.try_recv() {
+                Ok(received) => {
+                    snapshot = Some(received);
+                    changed = true;
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    changed |= connected;
+                    connected = false;
+                    break;
+                }
+            }
+        }
+
+        if event::poll(POLL_RATE)? {
+            match event::read()? {
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('c'),
+                    kind: KeyEventKind::Press,
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                })
+                | Event::Key(KeyEvent {
+                    code: KeyCode::Esc | KeyCode::Char('q'),
+                    kind: KeyEventKind::Press,
+                    modifiers: KeyModifiers::NONE,
+                    ..
+                }) => break,
+                _ => changed = true,
+            }
+        }
+
+        if changed {
+            terminal.draw(|f| {
+                let mirror = Mirror {
+                    snapshot: snapshot.as_ref(),
+                    connected,
+                };
+                f.render_widget(config.theme.apply_to(mirror), f.size());
+            })?;
+            changed = false;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+//! Notifications the terminal pops up on the desktop once a test is complete, so they're seen
+//! even while its window is in the background
+
+use crate::config::Notify;
+
+use std::env;
+
+// title of notifications which have one apart from the body
+const TITLE: &str = "ttyper";
+
+/// Escape sequence of the terminal this process runs in, if it's one known to show them
+pub fn detect() -> Notify {
+    from_vars(|name| env::var(name).ok())
+}
+
+fn from_vars(var: impl Fn(&str) -> Option<String>) -> Notify {
+    let term = var("TERM").unwrap_or_default();
+    if var("KITTY_WINDOW_ID").is_some() || term == "xterm-kitty" {
+        return Notify::Osc99;
+    }
+    match var("TERM_PROGRAM").as_deref() {
+        Some("iTerm.app" | "WezTerm" | "ghostty") => Notify::Osc9,
+        // terminals a multiplexer runs in aren't known, and it would swallow the sequence
+        Some(_) => Notify::Off,
+        None if term.starts_with("foot") || term.starts_with("rxvt-unicode") => Notify::Osc777,
+        None => Notify::Off,
+    }
+}
+
+/// Escape sequence popping up a notification of the given text, or none when they're off
+pub fn sequence(notify: Notify, body: &str) -> Option<String> {
+    let notify = match notify {
+        Notify::Auto => detect(),
+        notify => notify,
+    };
+    // a control char would end the sequence early, and OSC 777 separates its fields with `;`
+    let body: String = body.chars().filter(|c| !c.is_control()).collect();
+    match notify {
+        Notify::Off | Notify::Auto => None,
+        Notify::Osc9 => Some(format!("\x1b]9;{}: {}\x07", TITLE, body)),
+        Notify::Osc777 => Some(format!(
+            "\x1b]777;notify;{};{}\x07",
+            TITLE,
+            body.replace(';', ",")
+        )),
+        Notify::Osc99 => Some(format!(
+            "\x1b]99;i=1:d=0;{}\x1b\\\x1b]99;i=1:d=1:p=body;{}\x1b\\",
+            TITLE, body
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_the_terminals_sequence() {
+        let vars = |pairs: &'static [(&str, &str)]| {
+            move |name: &str| {
+                pairs
+                    .iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        assert_eq!(from_vars(vars(&[("TERM", "xterm-kitty")])), Notify::Osc99);
+        assert_eq!(
+            from_vars(vars(&[
+                ("TERM_PROGRAM", "WezTerm"),
+                ("TERM", "xterm-256color")
+            ])),
+            Notify::Osc9
+        );
+        assert_eq!(from_vars(vars(&[("TERM", "foot")])), Notify::Osc777);
+        assert_eq!(
+            from_vars(vars(&[("TERM_PROGRAM", "tmux"), ("TERM", "foot")])),
+            Notify::Off
+        );
+        assert_eq!(from_vars(vars(&[("TERM", "xterm-256color")])), Notify::Off);
+
+        assert_eq!(sequence(Notify::Off, "done"), None);
+        assert_eq!(
+            sequence(Notify::Osc9, "72 WPM\x07"),
+            Some(String::from("\x1b]9;ttyper: 72 WPM\x07"))
+        );
+        assert_eq!(
+            sequence(Notify::Osc777, "72 WPM; 98%"),
+            Some(String::from("\x1b]777;notify;ttyper;72 WPM, 98%\x07"))
+        );
+    }
+}
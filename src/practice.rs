@@ -0,0 +1,184 @@
+//! Practice biased toward the keys, pairs of keys and words typed worst lately, going by the
+//! history, in the spirit of keybr's adaptive lessons
+
+use crate::test::results::Results;
+
+use clap::ValueEnum;
+use crossterm::event::{KeyCode, KeyModifiers};
+use std::collections::{HashMap, HashSet};
+
+/// Recent tests with their details the weak keys and pairs are worked out from
+pub const RECENT_TESTS: usize = 50;
+
+/// Weakest words of the history which are favored
+pub const WEAK_WORDS: usize = 25;
+
+// keystrokes of the average a key's misses start from, so a key typed a few times isn't taken
+// as weak or strong from those alone
+const PRIOR_KEYSTROKES: f64 = 20.0;
+
+// weight a word from the history's weakest words gains, on top of its keys and pairs
+const WEAK_WORD_WEIGHT: f64 = 2.0;
+
+/// What practice focuses on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Practice {
+    /// The keys, pairs of keys and words typed worst lately
+    Weak,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Misses {
+    missed: usize,
+    typed: usize,
+}
+
+impl Misses {
+    fn add(&mut self, correct: bool) {
+        self.missed += usize::from(!correct);
+        self.typed += 1;
+    }
+}
+
+/// Misses of each key and pair of keys over recent tests, and the weakest words
+#[derive(Debug, Clone, Default)]
+pub struct Weaknesses {
+    keys: HashMap<char, Misses>,
+    pairs: HashMap<(char, char), Misses>,
+    overall: Misses,
+    pub words: HashSet<String>,
+}
+
+impl Weaknesses {
+    /// Count the keystrokes of a test, where a pair is a key due straight after another. Once a
+    /// key is missed, the keys typed after it are wrong whatever they are, so they aren't counted
+    /// until the mistake is deleted.
+    pub fn add(&mut self, results: &Results) {
+        let mut previous = None;
+        // wrong chars typed and not yet deleted
+        let mut wrong = 0usize;
+        for keystroke in &results.keystrokes {
+            match keystroke.key.code {
+                KeyCode::Backspace | KeyCode::Char('h') | KeyCode::Char('w')
+                    if keystroke.key.modifiers.contains(KeyModifiers::CONTROL) =>
+                {
+                    wrong = 0;
+                }
+                KeyCode::Backspace => wrong = wrong.saturating_sub(1),
+                _ => {}
+            }
+            let (Some(expected), Some(correct)) = (keystroke.expected, keystroke.correct) else {
+                previous = None;
+                continue;
+            };
+            if expected.is_whitespace() {
+                wrong = 0;
+                previous = None;
+                continue;
+            }
+            if wrong > 0 {
+                wrong += usize::from(!correct);
+                previous = None;
+                continue;
+            }
+            wrong += usize::from(!correct);
+            self.keys.entry(expected).or_default().add(correct);
+            self.overall.add(correct);
+            if let Some(previous) = previous {
+                self.pairs
+                    .entry((previous, expected))
+                    .or_default()
+                    .add(correct);
+            }
+            previous = Some(expected);
+        }
+    }
+
+    /// How much more often than average a key or pair is missed, as a multiple of the average
+    /// above 1, or 0 if it's missed less
+    fn excess(&self, misses: Option<&Misses>) -> f64 {
+        let Some(misses) = misses.filter(|_| self.overall.missed > 0) else {
+            return 0.0;
+        };
+        let average = self.overall.missed as f64 / self.overall.typed as f64;
+        let rate = (misses.missed as f64 + PRIOR_KEYSTROKES * average)
+            / (misses.typed as f64 + PRIOR_KEYSTROKES);
+        (rate / average - 1.0).max(0.0)
+    }
+
+    /// Weight an entry of a language is drawn with, which is 1 for an entry with nothing weak
+    /// in it and grows with each weak key, pair and word
+    pub fn weight(&self, entry: &str) -> f64 {
+        let text = entry.split_once('\t').map_or(entry, |(text, _)| text);
+        let chars: Vec<char> = text.chars().collect();
+        let keys: f64 = chars.iter().map(|c| self.excess(self.keys.get(c))).sum();
+        let pairs: f64 = chars
+            .windows(2)
+            .map(|pair| self.excess(self.pairs.get(&(pair[0], pair[1]))))
+            .sum();
+        let word = if self.words.contains(text) {
+            WEAK_WORD_WEIGHT
+        } else {
+            0.0
+        };
+        1.0 + keys + pairs + word
+    }
+
+    /// Keys missed more than average, worst first
+    pub fn weakest_keys(&self, count: usize) -> Vec<char> {
+        let mut keys: Vec<(char, f64)> = self
+            .keys
+            .iter()
+            .filter(|(c, _)| !c.is_whitespace())
+            .map(|(&c, misses)| (c, self.excess(Some(misses))))
+            .filter(|&(_, excess)| excess > 0.0)
+            .collect();
+        keys.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        keys.into_iter().take(count).map(|(c, _)| c).collect()
+    }
+
+    /// Pairs of keys missed more than average, worst first
+    pub fn weakest_pairs(&self, count: usize) -> Vec<String> {
+        let mut pairs: Vec<((char, char), f64)> = self
+            .pairs
+            .iter()
+            .map(|(&pair, misses)| (pair, self.excess(Some(misses))))
+            .filter(|&(_, excess)| excess > 0.0)
+            .collect();
+        pairs.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        pairs
+            .into_iter()
+            .take(count)
+            .map(|((a, b), _)| format!("{}{}", a, b))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::debug;
+    use crate::test::{Test, TestWord};
+
+    #[test]
+    fn weighs_words_by_what_was_missed() {
+        let words = ["quiet", "quote", "dune", "dune", "dune", "tide"];
+        let mut test = Test::new(words.map(TestWord::from).to_vec(), true, false);
+        // the q of each word starting with it is missed, then corrected, and the e after the
+        // missed d of tide is wrong only because of it
+        for key in debug::keys(r"w\bquiet w\bquote dune dune dune tixe\b\bde") {
+            test.handle_key(key);
+        }
+
+        let mut weaknesses = Weaknesses::default();
+        weaknesses.add(&Results::from(&test));
+        assert_eq!(weaknesses.weakest_keys(3), ['q', 'd']);
+        assert_eq!(weaknesses.weakest_pairs(3), ["id"]);
+        assert!(weaknesses.weight("queen") > weaknesses.weight("tune"));
+        assert_eq!(weaknesses.weight("tune"), 1.0);
+
+        weaknesses.words.insert(String::from("tune"));
+        assert_eq!(weaknesses.weight("tune"), 1.0 + WEAK_WORD_WEIGHT);
+        assert_eq!(Weaknesses::default().weight("queen"), 1.0);
+    }
+}
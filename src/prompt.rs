@@ -0,0 +1,160 @@
+//! File of the latest stats, kept up to date after every test for other programs to read, and
+//! the shell prompt segment made from it
+
+use crate::date;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Version of the latest stats file, raised whenever a field changes meaning
+pub const VERSION: u32 = 1;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Stats of the latest test and of the day it was taken on, as written to `latest.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Latest {
+    pub version: u32,
+    pub last: Last,
+    pub today: Today,
+}
+
+/// The latest test
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Last {
+    /// Seconds since the Unix epoch when the test was completed
+    pub timestamp: i64,
+    pub mode: String,
+    pub language: String,
+    pub wpm: f64,
+    /// Fraction of keypresses which were correct, from 0 to 1
+    pub accuracy: f64,
+}
+
+/// Tests completed on the UTC date of the latest one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Today {
+    /// UTC date, as YYYY-MM-DD
+    pub date: String,
+    pub tests: usize,
+    pub best_wpm: f64,
+    pub average_wpm: f64,
+}
+
+impl Latest {
+    /// Stats after a test, carrying on today's from the previous stats if they're of the same day
+    pub fn after(previous: Option<&Latest>, last: Last) -> Self {
+        let day = date::format(last.timestamp.div_euclid(SECONDS_PER_DAY));
+        let today = match previous.map(|previous| &previous.today) {
+            Some(today) if today.date == day => {
+                let tests = today.tests + 1;
+                Today {
+                    date: day,
+                    tests,
+                    best_wpm: today.best_wpm.max(last.wpm),
+                    average_wpm: (today.average_wpm * today.tests as f64 + last.wpm) / tests as f64,
+                }
+            }
+            _ => Today {
+                date: day,
+                tests: 1,
+                best_wpm: last.wpm,
+                average_wpm: last.wpm,
+            },
+        };
+        Self {
+            version: VERSION,
+            last,
+            today,
+        }
+    }
+
+    /// Stats read from a file, or none if there isn't one or it's of another version
+    pub fn read(path: &Path) -> Option<Self> {
+        let latest: Self = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+        (latest.version == VERSION).then_some(latest)
+    }
+
+    /// Write the stats to a file, replacing it whole so a prompt never reads it half written
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let partial = path.with_extension("json.partial");
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(&partial, json + "\n")?;
+        fs::rename(partial, path)
+    }
+}
+
+/// How the prompt segment is colored
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Style {
+    /// ANSI colors, as starship and most prompts take them
+    #[default]
+    Ansi,
+    /// ANSI colors marked as taking no space with `%{…%}`, for zsh's PROMPT with prompt_subst
+    Zsh,
+    /// No colors, for prompts which style the segment themselves
+    Plain,
+}
+
+/// Today's best WPM for a shell prompt, or nothing if no test has been completed today
+pub fn segment(latest: Option<&Latest>, today: i64, style: Style) -> String {
+    let Some(today) = latest
+        .map(|latest| &latest.today)
+        .filter(|stats| stats.date == date::format(today))
+    else {
+        return String::new();
+    };
+    let text = format!("{:.0} wpm", today.best_wpm);
+    match style {
+        Style::Ansi => format!("\x1b[1;36m{}\x1b[0m", text),
+        Style::Zsh => format!("%{{\x1b[1;36m%}}{}%{{\x1b[0m%}}", text),
+        Style::Plain => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_at(timestamp: i64, wpm: f64) -> Last {
+        Last {
+            timestamp,
+            mode: String::from("words"),
+            language: String::from("english200"),
+            wpm,
+            accuracy: 0.98,
+        }
+    }
+
+    #[test]
+    fn keeps_todays_best() {
+        let day = date::from_civil(2024, 3, 1);
+        let noon = day * SECONDS_PER_DAY + SECONDS_PER_DAY / 2;
+        let first = Latest::after(None, test_at(noon, 60.0));
+        let second = Latest::after(Some(&first), test_at(noon + 60, 80.0));
+        let third = Latest::after(Some(&second), test_at(noon + 120, 70.0));
+        assert_eq!(third.today.date, "2024-03-01");
+        assert_eq!(third.today.tests, 3);
+        assert_eq!(third.today.best_wpm, 80.0);
+        assert_eq!(third.today.average_wpm, 70.0);
+        assert_eq!(third.last.wpm, 70.0);
+
+        let tomorrow = Latest::after(Some(&third), test_at(noon + SECONDS_PER_DAY, 50.0));
+        assert_eq!(tomorrow.today.tests, 1);
+        assert_eq!(tomorrow.today.best_wpm, 50.0);
+
+        assert_eq!(segment(Some(&third), day, Style::Plain), "80 wpm");
+        assert_eq!(
+            segment(Some(&third), day, Style::Ansi),
+            "\x1b[1;36m80 wpm\x1b[0m"
+        );
+        assert_eq!(segment(Some(&third), day + 1, Style::Plain), "");
+        assert_eq!(segment(None, day, Style::Plain), "");
+    }
+}
@@ -0,0 +1,140 @@
+//! Quotes to type with who said them, from the bundled collection and the config directory
+
+use crate::test::TestWord;
+
+use clap::ValueEnum;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::Deserialize;
+use std::path::Path;
+
+// Characters a quote must reach to count as medium or long
+const MEDIUM: usize = 100;
+const LONG: usize = 300;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Quote {
+    pub text: String,
+    pub author: String,
+    /// Book, speech or other work the quote is from
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+impl Quote {
+    pub fn words(&self) -> Vec<TestWord> {
+        self.text.split_whitespace().map(TestWord::from).collect()
+    }
+
+    /// Who said the quote, and where if that's known
+    pub fn attribution(&self) -> String {
+        match &self.source {
+            Some(source) => format!("{}, {}", self.author, source),
+            None => self.author.clone(),
+        }
+    }
+}
+
+/// How long a quote is, by its number of characters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Length {
+    /// Under 100 characters
+    Short,
+    /// From 100 to 300 characters
+    Medium,
+    /// 300 characters or more
+    Long,
+}
+
+impl Length {
+    pub fn of(text: &str) -> Self {
+        match text.chars().count() {
+            ..MEDIUM => Self::Short,
+            MEDIUM..LONG => Self::Medium,
+            _ => Self::Long,
+        }
+    }
+}
+
+/// Format of a quote file, told by its extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Toml,
+}
+
+impl Format {
+    pub fn of(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+}
+
+/// Quote file, which lists its quotes under `quotes` in either format
+#[derive(Deserialize)]
+struct QuoteFile {
+    quotes: Vec<Quote>,
+}
+
+pub fn parse(text: &str, format: Format) -> Result<Vec<Quote>, String> {
+    let file: QuoteFile = match format {
+        Format::Json => serde_json::from_str(text).map_err(|err| err.to_string())?,
+        Format::Toml => toml::from_str(text).map_err(|err| err.to_string())?,
+    };
+    Ok(file.quotes)
+}
+
+/// Random quote of the given length, or of any length
+pub fn pick<'a>(
+    quotes: &'a [Quote],
+    length: Option<Length>,
+    rng: &mut impl Rng,
+) -> Option<&'a Quote> {
+    let fitting: Vec<&Quote> = quotes
+        .iter()
+        .filter(|quote| length.is_none_or(|length| Length::of(&quote.text) == length))
+        .collect();
+    fitting.choose(rng).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn reads_both_formats() {
+        let json = r#"{"quotes": [{"text": "Call me Ishmael.", "author": "Herman Melville", "source": "Moby-Dick"}]}"#;
+        let toml = "[[quotes]]\ntext = \"Call me Ishmael.\"\nauthor = \"Herman Melville\"\nsource = \"Moby-Dick\"\n";
+        let quotes = parse(json, Format::Json).unwrap();
+        assert_eq!(parse(toml, Format::Toml).unwrap(), quotes);
+        assert_eq!(quotes[0].attribution(), "Herman Melville, Moby-Dick");
+        assert_eq!(quotes[0].words().len(), 3);
+        assert!(parse("[[quotes]]\ntext = \"anonymous\"\n", Format::Toml).is_err());
+    }
+
+    #[test]
+    fn picks_quotes_of_a_length() {
+        let quote = |text: String| Quote {
+            text,
+            author: String::from("someone"),
+            source: None,
+        };
+        let quotes = [
+            quote("a".repeat(99)),
+            quote("b ".repeat(50)),
+            quote("c".repeat(300)),
+        ];
+        assert_eq!(Length::of(&quotes[1].text), Length::Medium);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let long = pick(&quotes, Some(Length::Long), &mut rng).unwrap();
+        assert_eq!(long.text, quotes[2].text);
+        assert!(pick(&quotes[..1], Some(Length::Medium), &mut rng).is_none());
+        assert!(pick(&quotes, None, &mut rng).is_some());
+    }
+}
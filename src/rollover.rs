@@ -0,0 +1,208 @@
+//! Keyboard rollover and ghosting checks, from which keys the terminal reports held at once
+
+use crate::config::Config;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{backend::Backend, terminal::Terminal};
+use std::io;
+
+/// Keys pressed together in turn, from pairs on the home row to both hands at once
+pub const CHORDS: [&str; 10] = [
+    "df", "jk", "sdf", "jkl", "asdf", "jkl;", "qaz", "wsx", "asdfg", "asdfjkl;",
+];
+
+/// Keys registered while one chord was held
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attempt {
+    pub chord: &'static str,
+    /// Keys of the chord which arrived, in the order they did
+    pub registered: String,
+    /// Keys outside the chord which arrived, as ghosting makes up
+    pub ghosts: String,
+}
+
+impl Attempt {
+    /// Keys of the chord which never arrived
+    pub fn missed(&self) -> String {
+        self.chord
+            .chars()
+            .filter(|&c| !self.registered.contains(c))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Rollover {
+    /// Whether the terminal reports key releases, without which held keys can't be followed
+    pub releases: bool,
+    /// Keys held now, in the order they were pressed
+    pub held: Vec<char>,
+    pub most_held: usize,
+    /// Keys of the current chord so far
+    pub current: Attempt,
+    pub attempts: Vec<Attempt>,
+}
+
+impl Rollover {
+    pub fn new(releases: bool) -> Self {
+        Self {
+            releases,
+            held: Vec::new(),
+            most_held: 0,
+            current: Self::attempt(0),
+            attempts: Vec::new(),
+        }
+    }
+
+    fn attempt(i: usize) -> Attempt {
+        Attempt {
+            chord: CHORDS[i.min(CHORDS.len() - 1)],
+            registered: String::new(),
+            ghosts: String::new(),
+        }
+    }
+
+    pub fn complete(&self) -> bool {
+        self.attempts.len() == CHORDS.len()
+    }
+
+    /// Start the current chord over
+    pub fn retry(&mut self) {
+        self.current = Self::attempt(self.attempts.len());
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        let KeyCode::Char(c) = key.code else {
+            return;
+        };
+        let c = c.to_ascii_lowercase();
+        match key.kind {
+            KeyEventKind::Press => {
+                if self.releases && !self.held.contains(&c) {
+                    self.held.push(c);
+                    self.most_held = self.most_held.max(self.held.len());
+                }
+                if self.complete() {
+                    return;
+                }
+                let attempt = &mut self.current;
+                let seen = attempt.registered.contains(c) || attempt.ghosts.contains(c);
+                if !seen && attempt.chord.contains(c) {
+                    attempt.registered.push(c);
+                } else if !seen {
+                    attempt.ghosts.push(c);
+                }
+                // without releases, a chord ends once each of its keys is in
+                if !self.releases && attempt.registered.len() == attempt.chord.len() {
+                    self.finish();
+                }
+            }
+            KeyEventKind::Release => {
+                self.held.retain(|&held| held != c);
+                // the chord ends once it's let go of
+                if self.held.is_empty() && !self.current.registered.is_empty() && !self.complete() {
+                    self.finish();
+                }
+            }
+            KeyEventKind::Repeat => {}
+        }
+    }
+
+    /// Score the current chord and move on to the next
+    pub fn finish(&mut self) {
+        let next = Self::attempt(self.attempts.len() + 1);
+        self.attempts
+            .push(std::mem::replace(&mut self.current, next));
+    }
+}
+
+pub fn run<B: Backend>(
+    terminal: &mut Terminal<B>,
+    config: &Config,
+    releases: bool,
+) -> io::Result<()> {
+    let mut rollover = Rollover::new(releases);
+    loop {
+        terminal.draw(|f| {
+            f.render_widget(config.theme.apply_to(&rollover), f.size());
+        })?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        match key {
+            KeyEvent {
+                code: KeyCode::Esc,
+                kind: KeyEventKind::Press,
+                ..
+            }
+            | KeyEvent {
+                code: KeyCode::Char('c'),
+                modifiers: KeyModifiers::CONTROL,
+                kind: KeyEventKind::Press,
+                ..
+            } => break,
+            KeyEvent {
+                code: KeyCode::Backspace,
+                kind: KeyEventKind::Press,
+                ..
+            } => rollover.retry(),
+            KeyEvent {
+                code: KeyCode::Enter,
+                kind: KeyEventKind::Press,
+                ..
+            } if !rollover.complete() && !rollover.current.registered.is_empty() => {
+                rollover.finish()
+            }
+            _ => rollover.handle_key(key),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(c: char, kind: KeyEventKind) -> KeyEvent {
+        KeyEvent {
+            kind,
+            ..KeyEvent::from(KeyCode::Char(c))
+        }
+    }
+
+    #[test]
+    fn follows_held_keys() {
+        let mut rollover = Rollover::new(true);
+        for c in ['d', 'x', 'd'] {
+            rollover.handle_key(key(c, KeyEventKind::Press));
+        }
+        assert_eq!(rollover.held, ['d', 'x']);
+        rollover.handle_key(key('x', KeyEventKind::Release));
+        assert_eq!(rollover.held, ['d']);
+        assert!(rollover.attempts.is_empty());
+
+        // letting go of every key ends the chord, with the key it never got
+        rollover.handle_key(key('d', KeyEventKind::Release));
+        assert_eq!(rollover.most_held, 2);
+        assert_eq!(rollover.attempts[0].missed(), "f");
+        assert_eq!(rollover.attempts[0].ghosts, "x");
+        assert_eq!(rollover.current.chord, "jk");
+    }
+
+    #[test]
+    fn ends_chords_without_releases() {
+        let mut rollover = Rollover::new(false);
+        for c in "dfjkj".chars() {
+            rollover.handle_key(key(c, KeyEventKind::Press));
+        }
+        assert_eq!(rollover.attempts.len(), 2);
+        assert_eq!(rollover.attempts[1].missed(), "");
+        for _ in 2..CHORDS.len() {
+            rollover.finish();
+        }
+        assert!(rollover.complete());
+        rollover.handle_key(key('a', KeyEventKind::Press));
+        assert_eq!(rollover.attempts.len(), CHORDS.len());
+    }
+}
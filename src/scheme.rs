@@ -0,0 +1,481 @@
+//! Color schemes from other tools, mapped onto the keys of the theme
+
+use crate::config;
+
+use ratatui::style::{Color, Modifier, Style};
+use regex::Regex;
+use serde::Deserialize;
+use std::path::Path;
+
+type Rgb = (u8, u8, u8);
+
+/// Sixteen colors in the roles base16 gives them, which other formats are mapped onto
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scheme {
+    /// `base00` to `base0F`: backgrounds to foregrounds, then red, orange, yellow, green, cyan,
+    /// blue, magenta and brown
+    pub base: [Rgb; 16],
+}
+
+/// Format of a scheme file, told by its extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Base16,
+    /// iTerm2 `.itermcolors` property list
+    ITerm,
+    /// JSON exported by terminal.sexy
+    TerminalSexy,
+    /// Monkeytype theme, as the CSS of its themes folder or the JSON of its custom themes
+    Monkeytype,
+}
+
+impl Format {
+    /// Format of a scheme file, by its extension and, for JSON, by its keys
+    pub fn of(path: &Path, text: &str) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "yaml" | "yml" => Some(Self::Base16),
+            "itermcolors" => Some(Self::ITerm),
+            "json" if serde_json::from_str::<SexyExport>(text).is_ok() => Some(Self::TerminalSexy),
+            "json" | "css" => Some(Self::Monkeytype),
+            _ => None,
+        }
+    }
+}
+
+/// Terminal palette, which the base16 roles are taken from
+struct Palette {
+    background: Rgb,
+    foreground: Rgb,
+    ansi: [Rgb; 16],
+}
+
+impl Palette {
+    fn to_scheme(&self) -> Scheme {
+        let ansi = self.ansi;
+        Scheme {
+            base: [
+                self.background,
+                ansi[0],
+                ansi[8],
+                ansi[8],
+                ansi[7],
+                self.foreground,
+                ansi[15],
+                ansi[15],
+                ansi[1],
+                ansi[9],
+                ansi[3],
+                ansi[2],
+                ansi[6],
+                ansi[4],
+                ansi[5],
+                ansi[11],
+            ],
+        }
+    }
+}
+
+/// Colors of a Monkeytype theme, by the parts they play in it
+struct Monkeytype {
+    bg: Rgb,
+    main: Rgb,
+    caret: Rgb,
+    sub: Rgb,
+    sub_alt: Rgb,
+    text: Rgb,
+    error: Rgb,
+    error_extra: Rgb,
+}
+
+impl Monkeytype {
+    // Monkeytype's own error colors, for themes which leave them out
+    const ERROR: Rgb = (0xca, 0x47, 0x54);
+    const ERROR_EXTRA: Rgb = (0x7e, 0x2a, 0x33);
+
+    /// Colors by their names without `-color`, like `bg` or `sub-alt`, where only the background,
+    /// main, sub and text colors are needed
+    fn from_names(color: impl Fn(&str) -> Option<Rgb>) -> Option<Self> {
+        let main = color("main")?;
+        let bg = color("bg")?;
+        let error = color("error").unwrap_or(Self::ERROR);
+        Some(Self {
+            bg,
+            main,
+            caret: color("caret").unwrap_or(main),
+            sub: color("sub")?,
+            sub_alt: color("sub-alt").unwrap_or(bg),
+            text: color("text")?,
+            error,
+            error_extra: color("error-extra").unwrap_or(Self::ERROR_EXTRA),
+        })
+    }
+
+    fn to_scheme(&self) -> Scheme {
+        Scheme {
+            base: [
+                self.bg,
+                self.sub_alt,
+                self.sub_alt,
+                self.sub,
+                self.sub,
+                self.text,
+                self.text,
+                self.text,
+                self.error,
+                self.error_extra,
+                self.caret,
+                self.text,
+                self.main,
+                self.main,
+                self.main,
+                self.error_extra,
+            ],
+        }
+    }
+}
+
+// Colors of a Monkeytype custom theme in the order it lists them, after which come the colorful
+// error colors, which aren't used
+const MONKEYTYPE_ORDER: [&str; 8] = [
+    "bg",
+    "main",
+    "caret",
+    "sub",
+    "sub-alt",
+    "text",
+    "error",
+    "error-extra",
+];
+
+#[derive(Deserialize)]
+struct SexyExport {
+    color: Vec<String>,
+    foreground: String,
+    background: String,
+}
+
+fn hex(text: &str) -> Option<Rgb> {
+    let text = text.trim().trim_start_matches('#');
+    // CSS shorthand, where each digit is doubled
+    if text.len() == 3 {
+        let doubled: String = text.chars().flat_map(|c| [c, c]).collect();
+        return hex(&doubled);
+    }
+    if text.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(text.get(i..i + 2)?, 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// All sixteen colors, if none are missing
+fn complete(slots: [Option<Rgb>; 16]) -> Option<[Rgb; 16]> {
+    let mut colors = [(0, 0, 0); 16];
+    for (color, slot) in colors.iter_mut().zip(slots) {
+        *color = slot?;
+    }
+    Some(colors)
+}
+
+impl Scheme {
+    pub fn parse(text: &str, format: Format) -> Option<Self> {
+        match format {
+            Format::Base16 => Self::parse_base16(text),
+            Format::ITerm => Self::parse_iterm(text),
+            Format::TerminalSexy => Self::parse_terminal_sexy(text),
+            Format::Monkeytype => Self::parse_monkeytype(text),
+        }
+    }
+
+    /// Read a base16 YAML scheme, with its colors at the top level or under `palette`
+    fn parse_base16(text: &str) -> Option<Self> {
+        let line = Regex::new(r#"(?m)^\s*base0([0-9A-Fa-f])\s*:\s*["']?(#?[0-9A-Fa-f]{6})"#)
+            .expect("Base16 pattern was ill-formed.");
+        let mut base: [Option<Rgb>; 16] = [None; 16];
+        for captures in line.captures_iter(text) {
+            let slot = usize::from_str_radix(&captures[1], 16).ok()?;
+            base[slot] = hex(&captures[2]);
+        }
+        Some(Self {
+            base: complete(base)?,
+        })
+    }
+
+    fn parse_iterm(text: &str) -> Option<Self> {
+        let entry = Regex::new(r"(?s)<key>([^<]+) Color</key>\s*<dict>(.*?)</dict>")
+            .expect("iTerm pattern was ill-formed.");
+        let component =
+            Regex::new(r"(?s)<key>(Red|Green|Blue) Component</key>\s*<real>([^<]+)</real>")
+                .expect("iTerm pattern was ill-formed.");
+
+        let mut background = None;
+        let mut foreground = None;
+        let mut ansi: [Option<Rgb>; 16] = [None; 16];
+        for captures in entry.captures_iter(text) {
+            let mut rgb = (0, 0, 0);
+            for channel in component.captures_iter(&captures[2]) {
+                let value: f64 = channel[2].trim().parse().ok()?;
+                let value = (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+                match &channel[1] {
+                    "Red" => rgb.0 = value,
+                    "Green" => rgb.1 = value,
+                    _ => rgb.2 = value,
+                }
+            }
+            match captures[1].trim() {
+                "Background" => background = Some(rgb),
+                "Foreground" => foreground = Some(rgb),
+                name => {
+                    let index = name.strip_prefix("Ansi ").and_then(|i| i.parse().ok());
+                    if let Some(slot) = index.and_then(|i: usize| ansi.get_mut(i)) {
+                        *slot = Some(rgb);
+                    }
+                }
+            }
+        }
+
+        Some(
+            Palette {
+                background: background?,
+                foreground: foreground?,
+                ansi: complete(ansi)?,
+            }
+            .to_scheme(),
+        )
+    }
+
+    fn parse_terminal_sexy(text: &str) -> Option<Self> {
+        let export: SexyExport = serde_json::from_str(text).ok()?;
+        let colors: Vec<Rgb> = export.color.iter().map(|c| hex(c)).collect::<Option<_>>()?;
+        Some(
+            Palette {
+                background: hex(&export.background)?,
+                foreground: hex(&export.foreground)?,
+                ansi: colors.try_into().ok()?,
+            }
+            .to_scheme(),
+        )
+    }
+
+    /// Read a Monkeytype theme from the `--bg-color` and other variables of its CSS, or from
+    /// JSON: either a custom theme's array of colors, or an object like `{"bgColor": "#323437"}`
+    fn parse_monkeytype(text: &str) -> Option<Self> {
+        let theme = match serde_json::from_str::<serde_json::Value>(text) {
+            Ok(serde_json::Value::Array(colors)) => {
+                let colors: Vec<Rgb> = colors
+                    .iter()
+                    .map(|color| hex(color.as_str()?))
+                    .collect::<Option<_>>()?;
+                Monkeytype::from_names(|name| {
+                    let i = MONKEYTYPE_ORDER.iter().position(|&known| known == name)?;
+                    colors.get(i).copied()
+                })?
+            }
+            Ok(serde_json::Value::Object(keys)) => Monkeytype::from_names(|name| {
+                // `sub-alt` is `subAltColor`
+                let mut key = String::new();
+                for (i, part) in name.split('-').enumerate() {
+                    let mut chars = part.chars();
+                    if i > 0 {
+                        key.extend(chars.next().map(|c| c.to_ascii_uppercase()));
+                    }
+                    key.extend(chars);
+                }
+                hex(keys.get(&(key + "Color"))?.as_str()?)
+            })?,
+            Ok(_) => return None,
+            Err(_) => {
+                let variable = Regex::new(r"--([a-z-]+)-color\s*:\s*(#[0-9A-Fa-f]{3,6})\b")
+                    .expect("Monkeytype pattern was ill-formed.");
+                let colors: Vec<(String, Rgb)> = variable
+                    .captures_iter(text)
+                    .filter_map(|captures| Some((captures[1].to_owned(), hex(&captures[2])?)))
+                    .collect();
+                Monkeytype::from_names(|name| {
+                    let (_, color) = colors.iter().find(|(known, _)| known == name)?;
+                    Some(*color)
+                })?
+            }
+        };
+        Some(theme.to_scheme())
+    }
+
+    /// Style strings for the theme keys, chosen by the role of each base16 color
+    pub fn styles(&self) -> Vec<(&'static str, String)> {
+        let color = |slot: usize| {
+            let (r, g, b) = self.base[slot];
+            Color::Rgb(r, g, b)
+        };
+        let fg = |slot: usize| Style::default().fg(color(slot));
+        let bold = |slot: usize| fg(slot).add_modifier(Modifier::BOLD);
+        let italic = |slot: usize| fg(slot).add_modifier(Modifier::ITALIC);
+
+        [
+            ("default", fg(0x05).bg(color(0x00))),
+            ("title", bold(0x0D)),
+            ("input_border", fg(0x0C)),
+            ("prompt_border", fg(0x0B)),
+            ("prompt_correct", fg(0x0B)),
+            ("prompt_incorrect", fg(0x08)),
+            ("prompt_untyped", fg(0x03)),
+            ("prompt_current_correct", bold(0x0B)),
+            ("prompt_current_incorrect", bold(0x08)),
+            ("prompt_current_untyped", bold(0x0D)),
+            ("prompt_definition", italic(0x04)),
+            ("prompt_line_number", fg(0x03)),
+            ("prompt_comment", italic(0x03)),
+            ("prompt_string", fg(0x0C)),
+            ("prompt_ghost", Style::default().bg(color(0x02))),
+            (
+                "prompt_pace",
+                Style::default().add_modifier(Modifier::UNDERLINED),
+            ),
+            ("prompt_sparkline", fg(0x03)),
+            ("breathing_guide", fg(0x0C)),
+            ("breathing_guide_border", fg(0x03)),
+            ("sidebar_border", fg(0x03)),
+            ("sidebar_stats", bold(0x0C)),
+            ("sidebar_accuracy_good", bold(0x0B)),
+            ("sidebar_accuracy_fair", bold(0x0A)),
+            ("sidebar_accuracy_poor", bold(0x08)),
+            ("sidebar_sparkline", fg(0x0C)),
+            ("sidebar_key_good", fg(0x0B)),
+            ("sidebar_key_fair", fg(0x0A)),
+            ("sidebar_key_poor", bold(0x08)),
+            ("sidebar_key_untyped", fg(0x03)),
+            ("results_overview", bold(0x0C)),
+            ("results_overview_border", fg(0x0C)),
+            ("results_worst_keys", bold(0x0A)),
+            ("results_worst_keys_border", fg(0x0A)),
+            ("results_chart", fg(0x0C)),
+            ("results_chart_x", fg(0x0C)),
+            ("results_chart_y", bold(0x03)),
+            ("results_chart_segments", fg(0x03)),
+            ("results_restart_prompt", italic(0x03)),
+            ("stats_overview", bold(0x0C)),
+            ("stats_overview_border", fg(0x0C)),
+        ]
+        .into_iter()
+        .map(|(key, style)| (key, config::style_string(style)))
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Background, Config};
+
+    fn base16() -> String {
+        (0..16)
+            .map(|i| format!("base0{:X}: \"{:02x}{:02x}{:02x}\"\n", i, i, i * 8, 255 - i))
+            .collect()
+    }
+
+    #[test]
+    fn reads_base16_schemes() {
+        let text = format!("scheme: \"Test\"\nauthor: \"me\"\n{}", base16());
+        let scheme = Scheme::parse(&text, Format::Base16).unwrap();
+        assert_eq!(scheme.base[0x0D], (13, 104, 242));
+
+        // newer schemes nest the colors under a palette, with hashes
+        let nested = format!(
+            "palette:\n{}",
+            base16()
+                .replace(": \"", ":   \"#")
+                .replace("base", "  base")
+        );
+        assert_eq!(Scheme::parse(&nested, Format::Base16), Some(scheme));
+
+        let missing = base16().replace("base0F", "# base0F");
+        assert_eq!(Scheme::parse(&missing, Format::Base16), None);
+    }
+
+    #[test]
+    fn reads_terminal_palettes() {
+        let color = |name: String, level: f64| {
+            format!(
+                "<key>{} Color</key>\n<dict>\n<key>Blue Component</key>\n<real>{}</real>\n\
+                 <key>Green Component</key>\n<real>0</real>\n\
+                 <key>Red Component</key>\n<real>1</real>\n</dict>\n",
+                name, level
+            )
+        };
+        let mut plist = String::from("<plist><dict>\n");
+        for i in 0..16 {
+            plist += &color(format!("Ansi {}", i), f64::from(i) / 15.0);
+        }
+        plist += &color(String::from("Background"), 0.0);
+        plist += &color(String::from("Foreground"), 1.0);
+        plist += "</dict></plist>";
+        let scheme = Scheme::parse(&plist, Format::ITerm).unwrap();
+        assert_eq!(scheme.base[0x00], (255, 0, 0));
+        assert_eq!(scheme.base[0x05], (255, 0, 255));
+        assert_eq!(scheme.base[0x08], (255, 0, 17));
+
+        let export = format!(
+            r##"{{"name": "x", "color": [{}], "foreground": "#ffffff", "background": "#000000"}}"##,
+            (0..16)
+                .map(|i| format!("\"#0000{:02x}\"", i))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        let scheme = Scheme::parse(&export, Format::TerminalSexy).unwrap();
+        assert_eq!(scheme.base[0x00], (0, 0, 0));
+        assert_eq!(scheme.base[0x0D], (0, 0, 4));
+        assert_eq!(Scheme::parse("{}", Format::TerminalSexy), None);
+    }
+
+    #[test]
+    fn reads_monkeytype_themes() {
+        let css = ":root {\n  --bg-color: #323437;\n  --main-color: #e2b714;\n  \
+                   --sub-color: #646669;\n  --sub-alt-color: #2c2e31;\n  --text-color: #fff;\n}";
+        assert_eq!(
+            Format::of(Path::new("serika_dark.css"), css),
+            Some(Format::Monkeytype)
+        );
+        let scheme = Scheme::parse(css, Format::Monkeytype).unwrap();
+        assert_eq!(scheme.base[0x00], (0x32, 0x34, 0x37));
+        assert_eq!(scheme.base[0x01], (0x2c, 0x2e, 0x31));
+        assert_eq!(scheme.base[0x05], (255, 255, 255));
+        // the caret falls back to the main color, and errors to Monkeytype's
+        assert_eq!(scheme.base[0x0A], (0xe2, 0xb7, 0x14));
+        assert_eq!(scheme.base[0x08], (0xca, 0x47, 0x54));
+
+        let custom = r##"["#323437", "#e2b714", "#e2b714", "#646669", "#2c2e31", "#ffffff",
+                          "#ca4754", "#7e2a33", "#ca4754", "#7e2a33"]"##;
+        assert_eq!(
+            Format::of(Path::new("custom.json"), custom),
+            Some(Format::Monkeytype)
+        );
+        assert_eq!(
+            Scheme::parse(custom, Format::Monkeytype),
+            Some(scheme.clone())
+        );
+
+        let listed = r##"{"name": "serika_dark", "bgColor": "#323437", "mainColor": "#e2b714",
+                          "subColor": "#646669", "subAltColor": "#2c2e31", "textColor": "#fff"}"##;
+        assert_eq!(Scheme::parse(listed, Format::Monkeytype), Some(scheme));
+        assert_eq!(
+            Scheme::parse(&css.replace("--text", "--x"), Format::Monkeytype),
+            None
+        );
+    }
+
+    #[test]
+    fn maps_onto_theme_keys() {
+        let scheme = Scheme::parse(&base16(), Format::Base16).unwrap();
+        let text = config::set_theme_keys("", &scheme.styles());
+        let config = Config::parse(&text, None, || Background::Dark).unwrap();
+        assert_eq!(
+            config.theme.prompt_incorrect,
+            Style::default().fg(Color::Rgb(8, 64, 247))
+        );
+        assert_eq!(
+            config.theme.default,
+            Style::default()
+                .fg(Color::Rgb(5, 40, 250))
+                .bg(Color::Rgb(0, 0, 255))
+        );
+    }
+}
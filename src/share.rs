@@ -0,0 +1,145 @@
+//! Score cards for pasting results into chats
+
+use crate::date;
+use crate::test::results::Results;
+
+// Fields left out of the card when speed is hidden, as it is with `accuracy_only`
+const SPEED_FIELDS: [&str; 2] = ["wpm", "raw_wpm"];
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Values for the placeholders of a share card template
+pub fn fields(
+    results: &Results,
+    mode: &str,
+    language: &str,
+    hide_speed: bool,
+) -> Vec<(&'static str, String)> {
+    let mut fields = vec![
+        ("mode", mode.to_owned()),
+        ("language", language.to_owned()),
+        ("wpm", format!("{:.1}", results.wpm())),
+        ("raw_wpm", format!("{:.1}", results.raw_wpm())),
+        (
+            "accuracy",
+            format!("{:.1}", f64::from(results.accuracy.overall) * 100.0),
+        ),
+        (
+            "consistency",
+            format!("{:.0}", results.consistency() * 100.0),
+        ),
+        ("duration", format!("{:.0}", results.duration())),
+        ("date", date::format(date::today())),
+    ];
+    if hide_speed {
+        fields.retain(|(name, _)| !SPEED_FIELDS.contains(name));
+    }
+    fields
+}
+
+/// Template to fill in, leaving out the `|`-separated parts which show speed if it's hidden
+pub fn template(template: &str, hide_speed: bool) -> String {
+    if !hide_speed {
+        return template.to_owned();
+    }
+    template
+        .split('|')
+        .filter(|part| {
+            !SPEED_FIELDS
+                .iter()
+                .any(|name| part.contains(&format!("{{{}}}", name)))
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+        .trim()
+        .to_owned()
+}
+
+/// Fill in a template, replacing each `{name}` with the value of that field.
+///
+/// Placeholders without a field are left as they are, so mistakes show up in the card.
+pub fn card(template: &str, fields: &[(&str, String)]) -> String {
+    let mut card = template.to_owned();
+    for (name, value) in fields {
+        card = card.replace(&format!("{{{}}}", name), value);
+    }
+    card
+}
+
+/// Escape sequence asking the terminal to put text on the clipboard
+pub fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64(text.as_bytes()))
+}
+
+fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | u32::from(byte) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(char::from(BASE64[(group >> (18 - 6 * i) & 0x3f) as usize]));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{Test, TestWord};
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    #[test]
+    fn fills_templates() {
+        let fields = [
+            ("wpm", String::from("72.5")),
+            ("mode", String::from("words")),
+        ];
+        assert_eq!(
+            card("ttyper {mode}: **{wpm} WPM** {unknown}", &fields),
+            "ttyper words: **72.5 WPM** {unknown}"
+        );
+    }
+
+    #[test]
+    fn leaves_speed_out_when_hidden() {
+        let mut test = Test::new(vec![TestWord::from("ab")], true, false);
+        for c in "ab".chars() {
+            test.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        let results = Results::from(&test);
+        let names = |hide_speed| -> Vec<&str> {
+            fields(&results, "words", "english200", hide_speed)
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect()
+        };
+        assert!(names(false).contains(&"wpm"));
+        assert!(!names(true).contains(&"wpm"));
+        assert!(!names(true).contains(&"raw_wpm"));
+        assert!(names(true).contains(&"accuracy"));
+
+        let default = "ttyper {mode} | {wpm} WPM | {accuracy}% accuracy | {date}";
+        assert_eq!(template(default, false), default);
+        assert_eq!(
+            template(default, true),
+            "ttyper {mode} | {accuracy}% accuracy | {date}"
+        );
+        assert_eq!(template("{raw_wpm} raw | {mode}", true), "{mode}");
+    }
+
+    #[test]
+    fn encodes_clipboard_requests() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64("72 WPM ✓".as_bytes()), "NzIgV1BNIOKckw==");
+        assert_eq!(osc52("hi"), "\x1b]52;c;aGk=\x07");
+    }
+}
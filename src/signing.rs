@@ -0,0 +1,105 @@
+use ed25519_dalek::{Signature, Signer as _, SigningKey, Verifier, VerifyingKey};
+use rand::rngs::OsRng;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Private key identifying this installation, used to sign shared results
+pub struct Signer {
+    key: SigningKey,
+}
+
+impl Signer {
+    /// Create a new key and save it to the given path
+    pub fn generate(path: &Path) -> io::Result<Self> {
+        let key = SigningKey::generate(&mut OsRng);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, hex(&key.to_bytes()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+        Ok(Self { key })
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let bytes = unhex(fs::read_to_string(path)?.trim())
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "signing key was ill-formed")
+            })?;
+        Ok(Self::from_bytes(&bytes))
+    }
+
+    pub fn from_bytes(bytes: &[u8; 32]) -> Self {
+        Self {
+            key: SigningKey::from_bytes(bytes),
+        }
+    }
+
+    pub fn public_key(&self) -> String {
+        hex(self.key.verifying_key().as_bytes())
+    }
+
+    pub fn sign(&self, message: &[u8]) -> String {
+        hex(&self.key.sign(message).to_bytes())
+    }
+}
+
+/// Check a signature made by [`Signer::sign`]
+pub fn verify(public_key: &str, message: &[u8], signature: &str) -> bool {
+    let Some(key) = unhex(public_key)
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+    else {
+        return false;
+    };
+    let Some(signature) = unhex(signature)
+        .and_then(|bytes| <[u8; 64]>::try_from(bytes).ok())
+        .map(|bytes| Signature::from_bytes(&bytes))
+    else {
+        return false;
+    };
+
+    key.verify(message, &signature).is_ok()
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn unhex(string: &str) -> Option<Vec<u8>> {
+    if !string.len().is_multiple_of(2) || !string.is_ascii() {
+        return None;
+    }
+    (0..string.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&string[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifies_signatures() {
+        let signer = Signer::from_bytes(&[7; 32]);
+        let signature = signer.sign(b"98.5 wpm");
+
+        assert!(verify(&signer.public_key(), b"98.5 wpm", &signature));
+        assert!(!verify(&signer.public_key(), b"198.5 wpm", &signature));
+        assert!(!verify("not a key", b"98.5 wpm", &signature));
+    }
+
+    #[test]
+    fn round_trips_hex() {
+        assert_eq!(hex(&[0, 0xab, 0xff]), "00abff");
+        assert_eq!(unhex("00abff"), Some(vec![0, 0xab, 0xff]));
+        assert_eq!(unhex("0"), None);
+        assert_eq!(unhex("zz"), None);
+    }
+}
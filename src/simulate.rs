@@ -0,0 +1,248 @@
+use crate::test::results::WPM_PER_CPS;
+
+use clap::ValueEnum;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::fmt;
+
+// Words shown in the list of hardest words
+const HARDEST_WORDS: usize = 10;
+
+/// Typist whose speed and error rate are simulated
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Model {
+    /// 25 WPM with frequent errors
+    Beginner,
+    /// 40 WPM, about one error every thirty keypresses
+    #[default]
+    Average,
+    /// 80 WPM with rare errors
+    Fast,
+}
+
+impl Model {
+    /// Unadjusted WPM on plain lowercase text and chance of mistyping a plain key
+    fn profile(self) -> (f64, f64) {
+        match self {
+            Self::Beginner => (25.0, 0.06),
+            Self::Average => (40.0, 0.03),
+            Self::Fast => (80.0, 0.01),
+        }
+    }
+}
+
+// Keys typed by each finger on a QWERTY keyboard, from the left pinky to the right
+const FINGERS: [&str; 8] = ["qaz", "wsx", "edc", "rfvtgb", "yhnujm", "ik", "ol", "p"];
+
+// Rows away from the home row, which take longer to reach
+const TOP_ROW: &str = "qwertyuiop";
+const BOTTOM_ROW: &str = "zxcvbnm";
+
+/// How much slower and more error-prone a character is than a plain home row letter, given the
+/// character typed before it
+fn key_cost(previous: Option<char>, c: char) -> f64 {
+    let lower = c.to_ascii_lowercase();
+    let reach = if TOP_ROW.contains(lower) {
+        1.1
+    } else if BOTTOM_ROW.contains(lower) {
+        1.25
+    } else {
+        1.0
+    };
+    // the same finger pressing two different keys in a row has to travel
+    let finger = |c: char| FINGERS.iter().position(|keys| keys.contains(c));
+    let same_finger = previous
+        .map(|p| p.to_ascii_lowercase())
+        .is_some_and(|p| p != lower && finger(p).is_some() && finger(p) == finger(lower));
+
+    let cost = match c {
+        ' ' => 0.8,
+        'a'..='z' => reach,
+        'A'..='Z' => reach * 1.6,
+        '0'..='9' => 1.5,
+        _ if c.is_ascii_punctuation() => 1.8,
+        _ if c.is_alphabetic() => 2.0,
+        _ => 2.2,
+    };
+    if same_finger {
+        cost * 1.4
+    } else {
+        cost
+    }
+}
+
+/// Cost of each character of a word
+fn key_costs(word: &str) -> impl Iterator<Item = f64> + '_ {
+    let previous = std::iter::once(None).chain(word.chars().map(Some));
+    previous.zip(word.chars()).map(|(p, c)| key_cost(p, c))
+}
+
+/// Average effort of typing each character of a word, relative to a home row letter and
+/// counting the corrections of expected errors
+pub fn word_difficulty(word: &str, model: Model) -> f64 {
+    let (_, error_rate) = model.profile();
+    let chars = word.chars().count().max(1) as f64;
+    key_costs(word)
+        .map(|cost| {
+            // an error costs the wrong key, a backspace, and the right key again
+            cost * (1.0 + 2.0 * (error_rate * cost).min(1.0))
+        })
+        .sum::<f64>()
+        / chars
+}
+
+/// Outcome of simulating many tests over a word list
+#[derive(Debug)]
+pub struct Report {
+    pub model: Model,
+    pub tests: usize,
+    pub words: usize,
+    pub wpm: f64,
+    pub min_wpm: f64,
+    pub max_wpm: f64,
+    /// Fraction of correct keypresses, from 0 to 1
+    pub accuracy: f64,
+    pub average_length: f64,
+    /// Fractions of characters which are capitals, digits, and punctuation
+    pub mix: (f64, f64, f64),
+    /// Hardest words with their difficulty, hardest first
+    pub hardest: Vec<(String, f64)>,
+}
+
+/// Simulate a typist taking tests of the given number of words drawn at random from the list
+pub fn simulate(
+    list: &[String],
+    words: usize,
+    tests: usize,
+    model: Model,
+    rng: &mut impl Rng,
+) -> Report {
+    let (wpm, error_rate) = model.profile();
+    let seconds_per_key = 1.0 / (wpm / WPM_PER_CPS);
+
+    let mut results = Vec::with_capacity(tests);
+    let (mut correct, mut keypresses) = (0usize, 0usize);
+    for _ in 0..tests {
+        let (mut seconds, mut test_correct, mut test_keypresses) = (0.0, 0, 0);
+        let text: Vec<&String> = (0..words).filter_map(|_| list.choose(rng)).collect();
+        for (i, word) in text.iter().enumerate() {
+            let separator = (i > 0).then_some(key_cost(None, ' '));
+            for cost in separator.into_iter().chain(key_costs(word)) {
+                let jitter = rng.gen_range(0.7..1.3);
+                if rng.gen_bool((error_rate * cost).min(1.0)) {
+                    // the wrong key, then a backspace, which counts as correct
+                    seconds += seconds_per_key * cost * jitter * 2.0;
+                    test_keypresses += 2;
+                    test_correct += 1;
+                }
+                seconds += seconds_per_key * cost * jitter;
+                test_keypresses += 1;
+                test_correct += 1;
+            }
+        }
+
+        if seconds > 0.0 {
+            let accuracy = test_correct as f64 / test_keypresses as f64;
+            results.push(test_keypresses as f64 / seconds * WPM_PER_CPS * accuracy);
+        }
+        correct += test_correct;
+        keypresses += test_keypresses;
+    }
+
+    let chars: Vec<char> = list.iter().flat_map(|word| word.chars()).collect();
+    let share = |f: fn(&char) -> bool| {
+        chars.iter().filter(|c| f(c)).count() as f64 / chars.len().max(1) as f64
+    };
+
+    let mut hardest: Vec<(String, f64)> = list
+        .iter()
+        .map(|word| (word.clone(), word_difficulty(word, model)))
+        .collect();
+    hardest.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    hardest.dedup_by(|a, b| a.0 == b.0);
+    hardest.truncate(HARDEST_WORDS);
+
+    Report {
+        model,
+        tests,
+        words,
+        wpm: results.iter().sum::<f64>() / results.len().max(1) as f64,
+        min_wpm: results.iter().copied().reduce(f64::min).unwrap_or_default(),
+        max_wpm: results.iter().copied().reduce(f64::max).unwrap_or_default(),
+        accuracy: correct as f64 / keypresses.max(1) as f64,
+        average_length: chars.len() as f64 / list.len().max(1) as f64,
+        mix: (
+            share(char::is_ascii_uppercase),
+            share(char::is_ascii_digit),
+            share(char::is_ascii_punctuation),
+        ),
+        hardest,
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Simulated {} tests of {} words by {} typist",
+            self.tests,
+            self.words,
+            match self.model {
+                Model::Average => "an average",
+                Model::Beginner => "a beginner",
+                Model::Fast => "a fast",
+            }
+        )?;
+        writeln!(
+            f,
+            "Expected WPM: {:.1} (from {:.1} to {:.1})",
+            self.wpm, self.min_wpm, self.max_wpm
+        )?;
+        writeln!(f, "Expected accuracy: {:.1}%", self.accuracy * 100.0)?;
+        writeln!(f, "Average word length: {:.1}", self.average_length)?;
+        writeln!(
+            f,
+            "Capitals: {:.1}%, digits: {:.1}%, punctuation: {:.1}%",
+            self.mix.0 * 100.0,
+            self.mix.1 * 100.0,
+            self.mix.2 * 100.0
+        )?;
+        writeln!(f, "Hardest words (effort per character):")?;
+        for (word, difficulty) in &self.hardest {
+            writeln!(f, "  {} ({:.2})", word, difficulty)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn rates_symbols_harder_than_letters() {
+        let plain = word_difficulty("flash", Model::Average);
+        assert!(plain > 1.0 && plain < 1.1);
+        assert!(word_difficulty("Flash!", Model::Average) > plain);
+        assert!(word_difficulty("flash", Model::Beginner) > plain);
+        // the same finger reaches from the bottom row to the top
+        assert!(word_difficulty("much", Model::Average) > word_difficulty("mush", Model::Average));
+    }
+
+    #[test]
+    fn simulates_the_model_speed() {
+        let list: Vec<String> = ["the", "of", "and", "to", "in", "is", "you", "that"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        let report = simulate(&list, 8, 200, Model::Average, &mut StdRng::seed_from_u64(1));
+
+        assert_eq!(report.words, 8);
+        assert!(report.wpm > 30.0 && report.wpm < 45.0, "{}", report.wpm);
+        assert!(report.accuracy > 0.9 && report.accuracy < 1.0);
+        assert_eq!(report.mix, (0.0, 0.0, 0.0));
+        assert_eq!(report.hardest.len(), 8);
+    }
+}
@@ -0,0 +1,282 @@
+//! Soak tests of a terminal's input handling, which type scripted text round after round and
+//! log every key that goes missing, arrives twice or arrives mangled
+
+use crate::config::Config;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use ratatui::{backend::Backend, terminal::Terminal};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Write};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Chars in each round of the script, unless another length is asked for
+pub const ROUND_LENGTH: usize = 200;
+
+// chars the script is written in, which between them cover every printable ASCII key
+const SCRIPT_CHARS: &str =
+    "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+// how far ahead a key is looked for in the script, past which it's taken as mangled instead
+const LOOKAHEAD: usize = 8;
+
+/// Text of a round of the script, which is the same every time
+pub fn script(round: usize, length: usize) -> String {
+    let chars: Vec<char> = SCRIPT_CHARS.chars().collect();
+    let mut rng = StdRng::seed_from_u64(round as u64);
+    let mut text = String::new();
+    while text.len() < length {
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        for _ in 0..rng.gen_range(1..=8) {
+            text.push(chars[rng.gen_range(0..chars.len())]);
+        }
+    }
+    text.truncate(length);
+    text.trim_end().to_owned()
+}
+
+/// Something which went wrong between a key being sent and it being drawn
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// Chars of the script which never arrived
+    Dropped(String),
+    /// Key which arrived again straight after itself
+    Repeated(char),
+    /// Key which arrived in place of another, or after the end of the round
+    Unexpected {
+        expected: Option<char>,
+        received: char,
+    },
+    /// The screen failed to draw
+    Render(String),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Dropped(chars) => write!(f, "dropped {:?}", chars),
+            Self::Repeated(c) => write!(f, "repeated {:?}", c),
+            Self::Unexpected {
+                expected: Some(expected),
+                received,
+            } => write!(f, "received {:?} for {:?}", received, expected),
+            Self::Unexpected {
+                expected: None,
+                received,
+            } => write!(f, "received {:?} after the end of the round", received),
+            Self::Render(error) => write!(f, "failed to draw: {}", error),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Soak {
+    /// Chars in each round
+    pub length: usize,
+    /// Rounds are counted from 0, which is the first line `--script` prints
+    pub round: usize,
+    pub text: Vec<char>,
+    /// Chars of the round which have arrived or been dropped
+    pub position: usize,
+    pub keys: usize,
+    pub dropped: usize,
+    pub repeated: usize,
+    pub unexpected: usize,
+    pub render_errors: usize,
+    pub started: Instant,
+}
+
+impl Soak {
+    pub fn new(length: usize) -> Self {
+        Self {
+            length,
+            round: 0,
+            text: script(0, length).chars().collect(),
+            position: 0,
+            keys: 0,
+            dropped: 0,
+            repeated: 0,
+            unexpected: 0,
+            render_errors: 0,
+            started: Instant::now(),
+        }
+    }
+
+    /// Keys arrived per second since the soak test started
+    pub fn keys_per_second(&self) -> f64 {
+        self.keys as f64 / self.started.elapsed().as_secs_f64().max(1.0)
+    }
+
+    /// Check a key against the script, returning what went wrong if it wasn't the next char.
+    /// Enter ends the round, dropping whatever of it never arrived.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<Problem> {
+        if key.kind != KeyEventKind::Press {
+            return None;
+        }
+        match key.code {
+            KeyCode::Enter => {
+                self.keys += 1;
+                let rest: String = self.text[self.position..].iter().collect();
+                self.round += 1;
+                self.text = script(self.round, self.length).chars().collect();
+                self.position = 0;
+                (!rest.is_empty()).then(|| {
+                    self.dropped += rest.chars().count();
+                    Problem::Dropped(rest)
+                })
+            }
+            KeyCode::Char(c) => {
+                self.keys += 1;
+                let expected = self.text.get(self.position).copied();
+                if expected == Some(c) {
+                    self.position += 1;
+                    return None;
+                }
+                let ahead = self.text.iter().skip(self.position + 1).take(LOOKAHEAD);
+                if let Some(skipped) = ahead.clone().position(|&later| later == c) {
+                    let missing: String = self.text[self.position..=self.position + skipped]
+                        .iter()
+                        .collect();
+                    self.dropped += skipped + 1;
+                    self.position += skipped + 2;
+                    return Some(Problem::Dropped(missing));
+                }
+                if self.position > 0 && self.text[self.position - 1] == c {
+                    self.repeated += 1;
+                    return Some(Problem::Repeated(c));
+                }
+                self.unexpected += 1;
+                Some(Problem::Unexpected {
+                    expected,
+                    received: c,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "Soak test: {} rounds, {} keys, {} dropped, {} repeated, {} unexpected, {} render errors.",
+            self.round, self.keys, self.dropped, self.repeated, self.unexpected, self.render_errors
+        )
+    }
+}
+
+/// Line of the log for a problem, stamped with when it happened and where in the script
+fn log_line(round: usize, position: usize, problem: &Problem) -> String {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0.0, |time| time.as_secs_f64());
+    format!(
+        "{:.3}\tround {}\tchar {}\t{}\n",
+        time, round, position, problem
+    )
+}
+
+pub fn run<B: Backend>(
+    terminal: &mut Terminal<B>,
+    config: &Config,
+    length: usize,
+    mut log: File,
+    output: &mut Vec<String>,
+) -> io::Result<()> {
+    let mut soak = Soak::new(length);
+    writeln!(
+        log,
+        "# soak test of {} chars a round, started at round 0",
+        length
+    )?;
+    loop {
+        // a failed draw is logged along with the keys, as it's what's being tested
+        let drawn = terminal.draw(|f| {
+            f.render_widget(config.theme.apply_to(&soak), f.size());
+        });
+        if let Err(e) = drawn {
+            soak.render_errors += 1;
+            let problem = Problem::Render(e.to_string());
+            log.write_all(log_line(soak.round, soak.position, &problem).as_bytes())?;
+        }
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        match key {
+            KeyEvent {
+                code: KeyCode::Esc,
+                kind: KeyEventKind::Press,
+                ..
+            }
+            | KeyEvent {
+                code: KeyCode::Char('c'),
+                modifiers: KeyModifiers::CONTROL,
+                kind: KeyEventKind::Press,
+                ..
+            } => break,
+            _ => {
+                // logged where the problem started, before the key moved on through the script
+                let (round, position) = (soak.round, soak.position);
+                if let Some(problem) = soak.handle_key(key) {
+                    log.write_all(log_line(round, position, &problem).as_bytes())?;
+                }
+            }
+        }
+    }
+    output.push(soak.summary());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_text(soak: &mut Soak, text: &str) -> Vec<Problem> {
+        text.chars()
+            .filter_map(|c| soak.handle_key(KeyEvent::from(KeyCode::Char(c))))
+            .collect()
+    }
+
+    #[test]
+    fn scripts_the_same_rounds() {
+        assert_eq!(script(3, 50), script(3, 50));
+        assert_ne!(script(3, 50), script(4, 50));
+        assert!(script(3, 50).chars().count() <= 50);
+        assert!(!script(3, 50).ends_with(' '));
+    }
+
+    #[test]
+    fn spots_missing_and_mangled_keys() {
+        let mut soak = Soak::new(ROUND_LENGTH);
+        soak.text = "abcdef".chars().collect();
+        let problems = type_text(&mut soak, "abbdzf");
+        assert_eq!(
+            problems,
+            [
+                Problem::Repeated('b'),
+                Problem::Dropped(String::from("c")),
+                Problem::Unexpected {
+                    expected: Some('e'),
+                    received: 'z'
+                },
+                Problem::Dropped(String::from("e")),
+            ]
+        );
+        assert_eq!(soak.position, 6);
+        assert_eq!(type_text(&mut soak, "g").len(), 1);
+
+        soak.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(soak.round, 1);
+        assert_eq!(
+            soak.text,
+            script(1, ROUND_LENGTH).chars().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            soak.handle_key(KeyEvent::from(KeyCode::Enter)),
+            Some(Problem::Dropped(script(1, ROUND_LENGTH)))
+        );
+        assert_eq!(soak.keys, 9);
+    }
+}
@@ -0,0 +1,828 @@
+use crate::config::{Keyboard, StreakPolicy};
+use crate::date;
+use crate::history::Entry;
+use crate::test::results::{Alternation, WordResult, WordStats};
+
+use clap::ValueEnum;
+use std::collections::{BTreeSet, HashMap};
+
+// Share of typists below a given WPM, approximated from large public typing test datasets
+const WPM_PERCENTILES: [(f64, f64); 12] = [
+    (0.0, 0.0),
+    (10.0, 2.0),
+    (20.0, 9.0),
+    (30.0, 25.0),
+    (40.0, 48.0),
+    (50.0, 68.0),
+    (60.0, 82.0),
+    (70.0, 90.0),
+    (80.0, 95.0),
+    (90.0, 97.5),
+    (120.0, 99.6),
+    (160.0, 100.0),
+];
+
+// Share of typists below a given accuracy percentage
+const ACCURACY_PERCENTILES: [(f64, f64); 8] = [
+    (0.0, 0.0),
+    (80.0, 5.0),
+    (88.0, 15.0),
+    (92.0, 35.0),
+    (95.0, 60.0),
+    (97.0, 80.0),
+    (99.0, 96.0),
+    (100.0, 100.0),
+];
+
+// Seconds for the weakness of a word to halve while it isn't typed
+const WEAKNESS_HALF_LIFE: f64 = 7.0 * 24.0 * 60.0 * 60.0;
+
+// Most that being slow can add to the weakness of a single typing of a word
+const MAX_SLOWNESS: f64 = 2.0;
+
+// Notes shown from the latest results which have them
+const RECENT_NOTES: usize = 3;
+
+// Latest tests on the old layout whose average is the baseline to reach, and on the new layout
+// whose average is the current speed
+const TRANSITION_TESTS: usize = 20;
+
+// Days before the latest test on the new layout which the trend is fitted over, as progress
+// slows the longer a layout has been practiced
+const TREND_DAYS: f64 = 30.0;
+
+// Tests on the new layout within the trend's days needed to fit it
+const TREND_TESTS: usize = 5;
+
+const SECONDS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+
+const RANKS: [(f64, &str); 6] = [
+    (0.0, "Hunt and peck"),
+    (25.0, "Casual typist"),
+    (40.0, "Average typist"),
+    (60.0, "Proficient typist"),
+    (80.0, "Fast typist"),
+    (110.0, "Competitive typist"),
+];
+
+/// Aggregate statistics over the history of completed tests
+#[derive(Debug, Default)]
+pub struct Stats {
+    pub tests: usize,
+    pub average_wpm: f64,
+    pub best_wpm: f64,
+    /// Best WPM over a short sprint, if any test was long enough to measure one
+    pub best_burst_wpm: Option<f64>,
+    /// Average fraction of correct keypresses, from 0 to 1
+    pub average_accuracy: f64,
+    /// Seconds spent typing across all tests
+    pub total_duration: f64,
+    pub favorite_language: Option<String>,
+    /// Average backspaces per test and share of wasted keystrokes, when any tests counted them
+    pub corrections: Option<(f64, f64)>,
+    /// Average speed of letters typed by alternating hands and by the same hand, when any tests
+    /// measured it
+    pub alternation: Option<Alternation>,
+    /// Words with the most weakness, worst first
+    pub weakest_words: Vec<String>,
+    /// Results on each keyboard, most used first, when any tests recorded one
+    pub keyboards: Vec<KeyboardStats>,
+    /// WPM and note of the latest results with notes, newest first
+    pub recent_notes: Vec<(f64, String)>,
+    pub streak: Streak,
+    /// Days in a row the daily challenge was completed, up to today or yesterday
+    pub daily_streak: usize,
+    /// WPM of every test, then of the tests of each mode, most tested first
+    pub distributions: Vec<Distribution>,
+}
+
+/// WPM of a group of tests, for the shape of their spread
+#[derive(Debug, PartialEq)]
+pub struct Distribution {
+    /// Mode of the tests, or `all` for every test
+    pub mode: String,
+    /// Sorted from slowest to fastest
+    pub wpm: Vec<f64>,
+}
+
+impl Distribution {
+    fn new(mode: &str, entries: &[&Entry]) -> Self {
+        let mut wpm: Vec<f64> = entries.iter().map(|e| e.wpm).collect();
+        wpm.sort_by(f64::total_cmp);
+        Self {
+            mode: mode.to_owned(),
+            wpm,
+        }
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.wpm.iter().sum::<f64>() / self.wpm.len() as f64
+    }
+
+    pub fn median(&self) -> f64 {
+        let middle = self.wpm.len() / 2;
+        if self.wpm.len().is_multiple_of(2) {
+            (self.wpm[middle - 1] + self.wpm[middle]) / 2.0
+        } else {
+            self.wpm[middle]
+        }
+    }
+
+    /// Standard deviation, over every test rather than a sample of them
+    pub fn std_dev(&self) -> f64 {
+        let mean = self.mean();
+        let variance =
+            self.wpm.iter().map(|wpm| (wpm - mean).powi(2)).sum::<f64>() / self.wpm.len() as f64;
+        variance.sqrt()
+    }
+
+    /// Tests in each of the given number of equal bins from the slowest WPM to the fastest
+    pub fn histogram(&self, bins: usize) -> Vec<usize> {
+        let mut counts = vec![0; bins];
+        let (min, max) = self.range();
+        for wpm in &self.wpm {
+            let bin = ((wpm - min) / (max - min) * bins as f64) as usize;
+            counts[bin.min(bins - 1)] += 1;
+        }
+        counts
+    }
+
+    /// Slowest and fastest WPM, spread apart when every test was as fast
+    pub fn range(&self) -> (f64, f64) {
+        let (min, max) = (self.wpm[0], self.wpm[self.wpm.len() - 1]);
+        if max - min < 1.0 {
+            (min - 0.5, max + 0.5)
+        } else {
+            (min, max)
+        }
+    }
+}
+
+/// Daily practice streak, as of today
+#[derive(Debug, Default, PartialEq)]
+pub struct Streak {
+    /// Days practiced since the streak began, not counting frozen or vacation days
+    pub days: usize,
+    /// Missed days within the streak which were frozen
+    pub frozen: usize,
+    /// Freezes not yet used this month, unless freezes are turned off
+    pub freezes_left: Option<usize>,
+    pub on_vacation: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct KeyboardStats {
+    pub label: String,
+    pub tests: usize,
+    pub average_wpm: f64,
+    pub average_accuracy: f64,
+}
+
+impl Stats {
+    pub fn from_entries(entries: &[Entry]) -> Self {
+        if entries.is_empty() {
+            return Self::default();
+        }
+
+        let mut languages: HashMap<&str, usize> = HashMap::new();
+        for entry in entries {
+            *languages.entry(&entry.language).or_default() += 1;
+        }
+
+        let counted: Vec<&Entry> = entries.iter().filter(|e| e.keystrokes > 0).collect();
+        let corrections = (!counted.is_empty()).then(|| {
+            let sum = |field: fn(&Entry) -> usize| counted.iter().map(|&e| field(e)).sum::<usize>();
+            (
+                sum(|e| e.backspaces) as f64 / counted.len() as f64,
+                sum(|e| e.wasted) as f64 / sum(|e| e.keystrokes) as f64,
+            )
+        });
+
+        let measured: Vec<Alternation> = entries.iter().filter_map(|e| e.alternation).collect();
+        let alternation = (!measured.is_empty()).then(|| {
+            let average = |field: fn(&Alternation) -> f64| {
+                measured.iter().map(field).sum::<f64>() / measured.len() as f64
+            };
+            Alternation {
+                alternating_wpm: average(|a| a.alternating_wpm),
+                same_hand_wpm: average(|a| a.same_hand_wpm),
+            }
+        });
+
+        let tests = entries.len();
+        Self {
+            tests,
+            average_wpm: entries.iter().map(|e| e.wpm).sum::<f64>() / tests as f64,
+            best_wpm: entries.iter().map(|e| e.wpm).fold(0.0, f64::max),
+            best_burst_wpm: entries.iter().filter_map(|e| e.burst_wpm).reduce(f64::max),
+            average_accuracy: entries.iter().map(|e| e.accuracy).sum::<f64>() / tests as f64,
+            total_duration: entries.iter().map(|e| e.duration).sum(),
+            favorite_language: languages
+                .into_iter()
+                .max_by_key(|&(name, count)| (count, std::cmp::Reverse(name)))
+                .map(|(name, _)| name.to_owned()),
+            corrections,
+            alternation,
+            weakest_words: Vec::new(),
+            keyboards: keyboard_stats(entries),
+            recent_notes: entries
+                .iter()
+                .filter(|e| !e.note.is_empty())
+                .take(RECENT_NOTES)
+                .map(|e| (e.wpm, e.note.clone()))
+                .collect(),
+            streak: Streak::default(),
+            daily_streak: 0,
+            distributions: distributions(entries),
+        }
+    }
+}
+
+fn distributions(entries: &[Entry]) -> Vec<Distribution> {
+    let mut modes: HashMap<&str, Vec<&Entry>> = HashMap::new();
+    for entry in entries {
+        modes.entry(&entry.mode).or_default().push(entry);
+    }
+    let mut modes: Vec<(&str, Vec<&Entry>)> = modes.into_iter().collect();
+    modes.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(b.0)));
+
+    let all: Vec<&Entry> = entries.iter().collect();
+    let mut distributions = vec![Distribution::new("all", &all)];
+    // with a single mode, its tests are all of them
+    if modes.len() > 1 {
+        distributions.extend(
+            modes
+                .iter()
+                .map(|(mode, entries)| Distribution::new(mode, entries)),
+        );
+    }
+    distributions
+}
+
+/// Walk back from today through the days practiced, freezing missed days while the policy
+/// allows and skipping vacations, until a missed day breaks the streak
+pub fn streak(practiced: &BTreeSet<i64>, today: i64, policy: &StreakPolicy) -> Streak {
+    let month = |day: i64| {
+        let (year, month, _) = date::to_civil(day);
+        (year, month)
+    };
+    let mut used: HashMap<(i64, u32), usize> = HashMap::new();
+    // days frozen since the last day practiced, which are only kept if the streak goes on
+    let mut pending: Vec<i64> = Vec::new();
+    let mut streak = Streak::default();
+
+    // today can still be practiced, so missing it doesn't break the streak yet
+    let mut day = if practiced.contains(&today) {
+        today
+    } else {
+        today - 1
+    };
+    let first = practiced.first().copied().unwrap_or(today);
+    while day >= first {
+        if practiced.contains(&day) {
+            streak.days += 1;
+            for frozen in pending.drain(..) {
+                *used.entry(month(frozen)).or_default() += 1;
+                streak.frozen += 1;
+            }
+        } else if !policy.on_vacation(day) {
+            let frozen_in_month = used.get(&month(day)).copied().unwrap_or_default()
+                + pending.iter().filter(|&&d| month(d) == month(day)).count();
+            if frozen_in_month >= policy.freezes {
+                break;
+            }
+            pending.push(day);
+        }
+        day -= 1;
+    }
+
+    streak.freezes_left = (policy.freezes > 0).then(|| {
+        let used = used.get(&month(today)).copied().unwrap_or_default();
+        policy.freezes.saturating_sub(used)
+    });
+    streak.on_vacation = policy.on_vacation(today);
+    streak
+}
+
+fn keyboard_stats(entries: &[Entry]) -> Vec<KeyboardStats> {
+    if entries.iter().all(|e| e.keyboard.is_empty()) {
+        return Vec::new();
+    }
+
+    let mut keyboards: HashMap<&Keyboard, Vec<&Entry>> = HashMap::new();
+    for entry in entries {
+        keyboards.entry(&entry.keyboard).or_default().push(entry);
+    }
+
+    let mut keyboards: Vec<KeyboardStats> = keyboards
+        .into_iter()
+        .map(|(keyboard, entries)| {
+            let tests = entries.len();
+            KeyboardStats {
+                label: keyboard.label(),
+                tests,
+                average_wpm: entries.iter().map(|e| e.wpm).sum::<f64>() / tests as f64,
+                average_accuracy: entries.iter().map(|e| e.accuracy).sum::<f64>() / tests as f64,
+            }
+        })
+        .collect();
+    keyboards.sort_by(|a, b| b.tests.cmp(&a.tests).then_with(|| a.label.cmp(&b.label)));
+    keyboards
+}
+
+/// Progress on a new keyboard layout toward the speed typed on an old one
+#[derive(Debug, PartialEq)]
+pub struct Transition {
+    /// Tests on the old layout, of which the latest make the baseline
+    pub baseline_tests: usize,
+    pub baseline_wpm: f64,
+    pub baseline_accuracy: f64,
+    /// Tests on the new layout, of which the latest make its current speed
+    pub tests: usize,
+    /// Days from the first test on the new layout to the latest
+    pub days: f64,
+    pub current_wpm: f64,
+    pub current_accuracy: f64,
+    /// WPM gained a day on the new layout lately, if there are enough days of tests to tell
+    pub wpm_per_day: Option<f64>,
+    /// Date the trend reaches the baseline, if it's rising toward it
+    pub crossover: Option<i64>,
+}
+
+/// Compare the latest tests on a new layout with those on the old, extending the trend of the
+/// new layout's last month to the date it catches up
+pub fn transition(entries: &[Entry], baseline: &str, layout: &str) -> Result<Transition, String> {
+    let on_layout = |name: &str| {
+        let mut tests: Vec<&Entry> = entries
+            .iter()
+            .filter(|e| e.keyboard.layout_name() == name)
+            .collect();
+        tests.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+        tests
+    };
+    let average = |tests: &[&Entry], field: fn(&Entry) -> f64| {
+        let latest = &tests[..tests.len().min(TRANSITION_TESTS)];
+        latest.iter().map(|&e| field(e)).sum::<f64>() / latest.len() as f64
+    };
+
+    let old = on_layout(baseline);
+    let new = on_layout(layout);
+    for (name, tests) in [(baseline, &old), (layout, &new)] {
+        if tests.is_empty() {
+            return Err(format!("no tests on the {} layout yet", name));
+        }
+    }
+
+    let latest = new[0].timestamp as f64 / SECONDS_PER_DAY;
+    let first = new[new.len() - 1].timestamp as f64 / SECONDS_PER_DAY;
+    let baseline_wpm = average(&old, |e| e.wpm);
+    let current_wpm = average(&new, |e| e.wpm);
+
+    // least squares line through the WPM of each test against its day
+    let points: Vec<(f64, f64)> = new
+        .iter()
+        .map(|e| (e.timestamp as f64 / SECONDS_PER_DAY, e.wpm))
+        .take_while(|&(day, _)| latest - day <= TREND_DAYS)
+        .collect();
+    let n = points.len() as f64;
+    let mean_day = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_wpm = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let spread: f64 = points.iter().map(|p| (p.0 - mean_day).powi(2)).sum();
+    let spans_days = points
+        .first()
+        .zip(points.last())
+        .is_some_and(|(a, b)| a.0 - b.0 >= 1.0);
+    let wpm_per_day = (points.len() >= TREND_TESTS && spans_days).then(|| {
+        points
+            .iter()
+            .map(|p| (p.0 - mean_day) * (p.1 - mean_wpm))
+            .sum::<f64>()
+            / spread
+    });
+
+    let crossover = wpm_per_day.filter(|&slope| slope > 0.0).and_then(|slope| {
+        let trend_wpm = mean_wpm + slope * (latest - mean_day);
+        (trend_wpm < baseline_wpm)
+            .then(|| (latest + (baseline_wpm - trend_wpm) / slope).floor() as i64)
+    });
+
+    Ok(Transition {
+        baseline_tests: old.len(),
+        baseline_wpm,
+        baseline_accuracy: average(&old, |e| e.accuracy),
+        tests: new.len(),
+        days: latest - first,
+        current_wpm,
+        current_accuracy: average(&new, |e| e.accuracy),
+        wpm_per_day,
+        crossover,
+    })
+}
+
+/// How badly a word was typed: its errors, plus how much slower its keypresses were than the
+/// test's average
+pub fn weakness(word: &WordResult, average_seconds_per_key: f64) -> f64 {
+    let slowness = word.seconds_per_key.map_or(0.0, |seconds| {
+        (seconds / average_seconds_per_key - 1.0).clamp(0.0, MAX_SLOWNESS)
+    });
+    let slowness = if slowness.is_finite() { slowness } else { 0.0 };
+    word.errors as f64 + slowness
+}
+
+/// Which of the words typed come first when listing problem words
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WordSort {
+    /// Lowest average WPM first
+    #[default]
+    Slowest,
+    /// Most errors first
+    Missed,
+}
+
+/// Words typed at least `min_typed` times, worst first by the given sort. Words never timed
+/// aren't slow, and words never missed aren't missed, so each sort leaves those out.
+pub fn problem_words(
+    mut words: Vec<WordStats>,
+    sort: WordSort,
+    min_typed: usize,
+) -> Vec<WordStats> {
+    words.retain(|word| word.typed >= min_typed);
+    match sort {
+        WordSort::Slowest => {
+            words.retain(|word| word.wpm().is_some());
+            words.sort_by(|a, b| {
+                a.wpm()
+                    .unwrap_or_default()
+                    .total_cmp(&b.wpm().unwrap_or_default())
+            });
+        }
+        WordSort::Missed => {
+            words.retain(|word| word.errors > 0);
+            words.sort_by(|a, b| b.errors.cmp(&a.errors).then(a.typed.cmp(&b.typed)));
+        }
+    }
+    words
+}
+
+/// Weakness of a word after the given number of seconds without being typed
+pub fn decay(weakness: f64, elapsed: f64) -> f64 {
+    weakness * 0.5f64.powf(elapsed.max(0.0) / WEAKNESS_HALF_LIFE)
+}
+
+/// Estimated percentage of typists slower than the given WPM
+pub fn wpm_percentile(wpm: f64) -> f64 {
+    interpolate(&WPM_PERCENTILES, wpm)
+}
+
+/// Estimated percentage of typists less accurate than the given accuracy, from 0 to 1
+pub fn accuracy_percentile(accuracy: f64) -> f64 {
+    interpolate(&ACCURACY_PERCENTILES, accuracy * 100.0)
+}
+
+/// Name for the skill level of a typist at the given WPM
+pub fn rank(wpm: f64) -> &'static str {
+    RANKS
+        .iter()
+        .rev()
+        .find(|(min, _)| wpm >= *min)
+        .map_or(RANKS[0].1, |(_, name)| name)
+}
+
+/// Piecewise linear interpolation over a table sorted by x
+fn interpolate(table: &[(f64, f64)], x: f64) -> f64 {
+    let (first, last) = (table[0], table[table.len() - 1]);
+    if x.is_nan() || x <= first.0 {
+        return first.1;
+    }
+    if x >= last.0 {
+        return last.1;
+    }
+
+    table
+        .windows(2)
+        .find(|w| x < w[1].0)
+        .map(|w| {
+            let ((x0, y0), (x1, y1)) = (w[0], w[1]);
+            y0 + (x - x0) / (x1 - x0) * (y1 - y0)
+        })
+        .unwrap_or(last.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::Environment;
+
+    #[test]
+    fn interpolates_percentiles() {
+        assert_eq!(wpm_percentile(-5.0), 0.0);
+        assert_eq!(wpm_percentile(f64::NAN), 0.0);
+        assert_eq!(wpm_percentile(40.0), 48.0);
+        assert_eq!(wpm_percentile(45.0), 58.0);
+        assert_eq!(wpm_percentile(500.0), 100.0);
+
+        assert_eq!(accuracy_percentile(0.95), 60.0);
+        assert_eq!(accuracy_percentile(1.0), 100.0);
+    }
+
+    #[test]
+    fn ignores_uncounted_corrections() {
+        let entry = |backspaces, wasted, keystrokes| Entry {
+            id: 0,
+            timestamp: 0,
+            mode: String::from("words"),
+            language: String::from("english200"),
+            duration: 30.0,
+            wpm: 50.0,
+            accuracy: 0.95,
+            backspaces,
+            wasted,
+            keystrokes,
+            burst_wpm: None,
+            alternation: None,
+            keyboard: Keyboard::default(),
+            note: String::new(),
+            sudden_death: String::new(),
+            strictness: String::from("forgiving"),
+            environment: Environment::default(),
+            detailed: false,
+        };
+
+        assert_eq!(Stats::from_entries(&[entry(0, 0, 0)]).corrections, None);
+        assert_eq!(
+            Stats::from_entries(&[entry(4, 10, 100), entry(0, 0, 0), entry(2, 5, 50)]).corrections,
+            Some((3.0, 0.1))
+        );
+    }
+
+    #[test]
+    fn ranks_by_wpm() {
+        assert_eq!(rank(0.0), "Hunt and peck");
+        assert_eq!(rank(f64::NAN), "Hunt and peck");
+        assert_eq!(rank(45.0), "Average typist");
+        assert_eq!(rank(200.0), "Competitive typist");
+    }
+
+    #[test]
+    fn weighs_and_decays_weakness() {
+        let word = |errors, seconds_per_key| WordResult {
+            text: String::from("their"),
+            indent: 0,
+            line_end: false,
+            errors,
+            seconds_per_key,
+            start: 0.0,
+            end: 0.0,
+        };
+
+        assert_eq!(weakness(&word(0, Some(0.2)), 0.25), 0.0);
+        assert_eq!(weakness(&word(2, None), 0.25), 2.0);
+        assert_eq!(weakness(&word(1, Some(0.5)), 0.25), 2.0);
+        assert_eq!(weakness(&word(0, Some(5.0)), 0.25), MAX_SLOWNESS);
+        assert_eq!(weakness(&word(0, Some(0.5)), f64::NAN), 0.0);
+
+        assert_eq!(decay(4.0, 0.0), 4.0);
+        assert_eq!(decay(4.0, WEAKNESS_HALF_LIFE), 2.0);
+        assert_eq!(decay(4.0, 2.0 * WEAKNESS_HALF_LIFE), 1.0);
+    }
+
+    #[test]
+    fn lists_problem_words_worst_first() {
+        let word = |text: &str, errors, seconds_per_key| WordResult {
+            text: text.to_owned(),
+            indent: 0,
+            line_end: false,
+            errors,
+            seconds_per_key,
+            start: 0.0,
+            end: 0.0,
+        };
+        let typed = [
+            word("the", 0, Some(0.1)),
+            word("rhythm", 2, Some(0.4)),
+            word("the", 0, Some(0.1)),
+            word("a", 1, None),
+            word("rhythm", 1, Some(0.2)),
+            word("queue", 0, Some(0.2)),
+        ];
+        let texts = |words: Vec<WordStats>| -> Vec<String> {
+            words.into_iter().map(|word| word.text).collect()
+        };
+
+        let words = crate::test::results::word_stats(&typed);
+        assert_eq!(
+            texts(problem_words(words.clone(), WordSort::Slowest, 1)),
+            ["rhythm", "queue", "the"]
+        );
+        assert_eq!(
+            texts(problem_words(words.clone(), WordSort::Missed, 1)),
+            ["rhythm", "a"]
+        );
+        assert_eq!(
+            texts(problem_words(words, WordSort::Slowest, 2)),
+            ["rhythm", "the"]
+        );
+    }
+
+    #[test]
+    fn spreads_wpm_by_mode() {
+        let entry = |mode: &str, wpm| Entry {
+            id: 0,
+            timestamp: 0,
+            mode: mode.to_string(),
+            language: String::from("english200"),
+            duration: 30.0,
+            wpm,
+            accuracy: 0.9,
+            backspaces: 0,
+            wasted: 0,
+            keystrokes: 0,
+            burst_wpm: None,
+            alternation: None,
+            keyboard: Keyboard::default(),
+            note: String::new(),
+            sudden_death: String::new(),
+            strictness: String::from("forgiving"),
+            environment: Environment::default(),
+            detailed: false,
+        };
+
+        let stats = Stats::from_entries(&[
+            entry("timed", 40.0),
+            entry("words", 60.0),
+            entry("words", 50.0),
+            entry("timed", 70.0),
+            entry("words", 80.0),
+        ]);
+        let modes: Vec<&str> = stats
+            .distributions
+            .iter()
+            .map(|d| d.mode.as_str())
+            .collect();
+        assert_eq!(modes, ["all", "words", "timed"]);
+        let all = &stats.distributions[0];
+        assert_eq!(all.wpm, [40.0, 50.0, 60.0, 70.0, 80.0]);
+        assert_eq!(all.mean(), 60.0);
+        assert_eq!(all.median(), 60.0);
+        assert_eq!(all.std_dev(), 200f64.sqrt());
+        assert_eq!(all.histogram(4), [1, 1, 1, 2]);
+        assert_eq!(stats.distributions[2].median(), 55.0);
+
+        let single = Stats::from_entries(&[entry("words", 60.0)]);
+        assert_eq!(single.distributions.len(), 1);
+        assert_eq!(single.distributions[0].range(), (59.5, 60.5));
+    }
+
+    #[test]
+    fn compares_keyboards() {
+        let entry = |name: &str, wpm| Entry {
+            id: 0,
+            timestamp: 0,
+            mode: String::from("words"),
+            language: String::from("english200"),
+            duration: 30.0,
+            wpm,
+            accuracy: 0.9,
+            backspaces: 0,
+            wasted: 0,
+            keystrokes: 0,
+            burst_wpm: None,
+            alternation: None,
+            keyboard: Keyboard {
+                name: name.to_string(),
+                switches: String::from("browns"),
+                ..Keyboard::default()
+            },
+            note: String::new(),
+            sudden_death: String::new(),
+            strictness: String::from("forgiving"),
+            environment: Environment::default(),
+            detailed: false,
+        };
+
+        assert_eq!(
+            Stats::from_entries(&[entry("", 40.0)]).keyboards,
+            vec![KeyboardStats {
+                label: String::from("unnamed (browns)"),
+                tests: 1,
+                average_wpm: 40.0,
+                average_accuracy: 0.9,
+            }]
+        );
+
+        let stats = Stats::from_entries(&[
+            entry("planck", 60.0),
+            entry("tkl", 50.0),
+            entry("planck", 70.0),
+        ]);
+        let labels: Vec<(&str, usize, f64)> = stats
+            .keyboards
+            .iter()
+            .map(|k| (k.label.as_str(), k.tests, k.average_wpm))
+            .collect();
+        assert_eq!(
+            labels,
+            [("planck (browns)", 2, 65.0), ("tkl (browns)", 1, 50.0)]
+        );
+    }
+
+    #[test]
+    fn freezes_missed_days_and_skips_vacations() {
+        let day = |date: &str| date::parse(date).unwrap();
+        let days = |dates: &[&str]| dates.iter().map(|d| day(d)).collect::<BTreeSet<i64>>();
+        let today = day("2026-10-14");
+        let policy = StreakPolicy::default();
+
+        // two freezes a month cover the 12th and the 9th, but not the 7th
+        let practiced = days(&[
+            "2026-10-06",
+            "2026-10-08",
+            "2026-10-10",
+            "2026-10-11",
+            "2026-10-13",
+        ]);
+        assert_eq!(
+            streak(&practiced, today, &policy),
+            Streak {
+                days: 4,
+                frozen: 2,
+                freezes_left: Some(0),
+                on_vacation: false,
+            }
+        );
+
+        // freezes spent on a gap which breaks the streak anyway are given back
+        let practiced = days(&["2026-10-01", "2026-10-14"]);
+        let broken = streak(&practiced, today, &policy);
+        assert_eq!(
+            (broken.days, broken.frozen, broken.freezes_left),
+            (1, 0, Some(2))
+        );
+
+        let policy = StreakPolicy {
+            freezes: 0,
+            vacations: vec![
+                String::from("2026-10-07..2026-10-12"),
+                String::from("2026-10-14"),
+            ],
+        };
+        let practiced = days(&["2026-10-05", "2026-10-06", "2026-10-13"]);
+        assert_eq!(
+            streak(&practiced, today, &policy),
+            Streak {
+                days: 3,
+                frozen: 0,
+                freezes_left: None,
+                on_vacation: true,
+            }
+        );
+    }
+
+    #[test]
+    fn estimates_layout_crossover() {
+        let start = date::from_civil(2026, 1, 1);
+        let entry = |layout: &str, day: i64, wpm| Entry {
+            id: 0,
+            timestamp: (start + day) * 24 * 60 * 60 + 12 * 60 * 60,
+            mode: String::from("words"),
+            language: String::from("english200"),
+            duration: 30.0,
+            wpm,
+            accuracy: 0.95,
+            backspaces: 0,
+            wasted: 0,
+            keystrokes: 0,
+            burst_wpm: None,
+            alternation: None,
+            keyboard: Keyboard {
+                layout: layout.to_string(),
+                ..Keyboard::default()
+            },
+            note: String::new(),
+            sudden_death: String::new(),
+            strictness: String::from("forgiving"),
+            environment: Environment::default(),
+            detailed: false,
+        };
+
+        // a profile without a layout counts as qwerty
+        let mut entries = vec![entry("", -5, 80.0), entry("QWERTY", -4, 80.0)];
+        entries.extend((0..=10).map(|day| entry("Colemak-DH", day, 30.0 + 2.0 * day as f64)));
+        let colemak = transition(&entries, "qwerty", "colemak").unwrap();
+        assert_eq!(colemak.baseline_tests, 2);
+        assert_eq!(colemak.baseline_wpm, 80.0);
+        assert_eq!(colemak.tests, 11);
+        assert_eq!(colemak.days, 10.0);
+        assert_eq!(colemak.current_wpm, 40.0);
+        assert!((colemak.wpm_per_day.unwrap() - 2.0).abs() < 1e-9);
+        assert_eq!(colemak.crossover, Some(start + 25));
+
+        entries.extend((0..=10).map(|day| entry("dvorak", day, 50.0)));
+        let dvorak = transition(&entries, "qwerty", "dvorak").unwrap();
+        assert_eq!(dvorak.crossover, None);
+
+        let early = transition(&entries[..4], "qwerty", "colemak").unwrap();
+        assert_eq!(early.wpm_per_day, None);
+        assert!(transition(&entries, "qwerty", "workman").is_err());
+    }
+}
@@ -1,23 +1,57 @@
-use crate::config::Theme;
+use crate::classroom::StudentRow;
+use crate::config::{AccuracyGrades, Keybindings, Theme};
+use crate::date;
+use crate::history::Entry;
+use crate::metrics::Metrics;
+use crate::mirror::Snapshot;
+use crate::rollover::{Rollover, CHORDS};
+use crate::soak::Soak;
+use crate::stats::{self, Stats};
+use crate::syllables;
 
-use super::test::{results, Test, TestWord};
+use super::test::{results, results::PartialResults, Strictness, Test, TestWord};
+use ttyper_core::code::Syntax;
 
 use crossterm::event::KeyCode;
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyEvent, KeyEventState};
 use ratatui::{
     buffer::Buffer,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::{Modifier, Style},
     symbols::Marker,
     text::{Line, Span, Text},
-    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph, Widget},
+    widgets::{
+        Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph, Row, Sparkline, Table, Widget,
+        Wrap,
+    },
 };
-use results::Fraction;
+use results::{
+    Fraction, Results, WordStats, ACCURACY_WINDOW, BURST_WINDOW, ROLLING_WINDOW, WPM_PER_CPS,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::f64::consts::TAU;
+use std::time::Instant;
 
-// Convert CPS to WPM (clicks per second)
-const WPM_PER_CPS: f64 = 12.0;
+// Five-row glyphs for drawing large numbers
+const BIG_GLYPH_HEIGHT: u16 = 5;
+const BIG_GLYPHS: [(char, [&str; 5]); 13] = [
+    ('0', ["███", "█ █", "█ █", "█ █", "███"]),
+    ('1', [" █ ", "██ ", " █ ", " █ ", "███"]),
+    ('2', ["███", "  █", "███", "█  ", "███"]),
+    ('3', ["███", "  █", "███", "  █", "███"]),
+    ('4', ["█ █", "█ █", "███", "  █", "  █"]),
+    ('5', ["███", "█  ", "███", "  █", "███"]),
+    ('6', ["███", "█  ", "███", "█ █", "███"]),
+    ('7', ["███", "  █", "  █", "  █", "  █"]),
+    ('8', ["███", "█ █", "███", "█ █", "███"]),
+    ('9', ["███", "█ █", "███", "  █", "███"]),
+    ('.', ["   ", "   ", "   ", "   ", " █ "]),
+    ('%', ["█ █", "  █", " █ ", "█  ", "█ █"]),
+    (' ', ["   ", "   ", "   ", "   ", "   "]),
+];
 
-// Width of the moving average window for the WPM chart
-const WPM_SMA_WIDTH: usize = 10;
+// Eighths of a block, from empty to full, for drawing fractional bar heights
+const BAR_EIGHTHS: [&str; 9] = [" ", "▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
 
 #[derive(Clone)]
 struct SizedBlock<'a> {
@@ -74,63 +108,470 @@ impl Theme {
     }
 }
 
+/// How much chrome a screen can afford in the space it's given
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LayoutTier {
+    /// Bordered sections with titles
+    Full,
+    /// Unbordered sections
+    Compact,
+    /// A single line of the most important information
+    Minimal,
+}
+
+impl LayoutTier {
+    /// Pick the richest tier whose minimum (width, height) fits in the area
+    fn for_area(area: Rect, full: (u16, u16), compact: (u16, u16)) -> Self {
+        let fits = |(width, height)| area.width >= width && area.height >= height;
+        if fits(full) {
+            Self::Full
+        } else if fits(compact) {
+            Self::Compact
+        } else {
+            Self::Minimal
+        }
+    }
+}
+
+// Smallest areas the test screen can be drawn in with each tier
+const TEST_FULL_SIZE: (u16, u16) = (20, 10);
+const TEST_COMPACT_SIZE: (u16, u16) = (10, 3);
+
 impl ThemedWidget for &Test {
     fn render(self, area: Rect, buf: &mut Buffer, theme: &Theme) {
         buf.set_style(area, theme.default);
 
-        // Chunks
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Length(3), Constraint::Length(6)])
-            .split(area);
+        let definition = self.words[self.current_word]
+            .definition
+            .as_deref()
+            .map(|definition| {
+                if self.hide_definitions {
+                    "(Tab to show)"
+                } else {
+                    definition
+                }
+            })
+            .map(|definition| Span::styled(definition, theme.prompt_definition));
+        let progress = if self.hide_typed {
+            String::new()
+        } else {
+            self.words[self.current_word].progress.clone()
+        };
+        let input = Line::from(progress.clone());
 
-        // Sections
-        let input = SizedBlock {
-            block: Block::default()
-                .title(Line::from(vec![Span::styled("Input", theme.title)]))
-                .borders(Borders::ALL)
-                .border_type(theme.border_type)
-                .border_style(theme.input_border),
-            area: chunks[0],
+        match LayoutTier::for_area(area, TEST_FULL_SIZE, TEST_COMPACT_SIZE) {
+            LayoutTier::Full => {
+                // Chunks
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([
+                        Constraint::Length(3),
+                        Constraint::Length(6),
+                        Constraint::Length(1),
+                    ])
+                    .split(area);
+
+                // Sections
+                let input_block = SizedBlock {
+                    block: Block::default()
+                        .title(Line::from(vec![Span::styled("Input", theme.title)]))
+                        .borders(Borders::ALL)
+                        .border_type(theme.border_type)
+                        .border_style(theme.input_border),
+                    area: chunks[0],
+                };
+                input_block.draw_inner(&input, buf);
+                input_block.render(buf);
+
+                let mut title = vec![Span::styled("Prompt", theme.title)];
+                let status = self.status(theme);
+                if !status.is_empty() {
+                    title.push(Span::raw(" "));
+                    title.extend(status);
+                }
+                let prompt_block = Block::default()
+                    .title(Line::from(title))
+                    .borders(Borders::ALL)
+                    .border_type(theme.border_type)
+                    .border_style(theme.prompt_border);
+                let prompt_area = prompt_block.inner(chunks[1]);
+                prompt_block.render(chunks[1], buf);
+                self.render_prompt(prompt_area, buf, theme);
+
+                if let Some(definition) = definition {
+                    buf.set_span(
+                        chunks[2].x + 1,
+                        chunks[2].y,
+                        &definition,
+                        chunks[2].width.saturating_sub(1),
+                    );
+                }
+            }
+            LayoutTier::Compact => {
+                let has_definition = definition.is_some() && area.height >= 4;
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([
+                        Constraint::Length(1),
+                        Constraint::Min(1),
+                        Constraint::Length(u16::from(has_definition)),
+                    ])
+                    .split(area);
+
+                let input = Line::from(vec![
+                    Span::styled("> ", theme.input_border),
+                    Span::raw(progress),
+                ]);
+                buf.set_line(chunks[0].x, chunks[0].y, &input, chunks[0].width);
+                let status = Line::from(self.status(theme));
+                if status.width() > 0 {
+                    let width = status.width() as u16;
+                    let x = chunks[0].right().saturating_sub(width).max(chunks[0].x);
+                    buf.set_line(x, chunks[0].y, &status, width);
+                }
+                self.render_prompt(chunks[1], buf, theme);
+
+                if let Some(definition) = definition.filter(|_| has_definition) {
+                    buf.set_span(chunks[2].x, chunks[2].y, &definition, chunks[2].width);
+                }
+            }
+            // the prompt alone still shows what has been typed through its colors
+            LayoutTier::Minimal => self.render_prompt(area, buf, theme),
+        }
+    }
+}
+
+/// Parts of the test screen drawn from the test
+trait TestScreen {
+    fn status(&self, theme: &Theme) -> Vec<Span<'static>>;
+    fn pause_title(&self, theme: &Theme) -> Option<Span<'static>>;
+    fn confirm_title(&self, theme: &Theme) -> Option<Span<'static>>;
+    fn timer_title(&self, theme: &Theme) -> Option<Span<'static>>;
+    fn clock_title(&self, theme: &Theme) -> Option<Span<'static>>;
+    fn budget_title(&self, theme: &Theme) -> Option<Span<'static>>;
+    fn rolling_title(&self, theme: &Theme) -> Option<Span<'static>>;
+    fn stop_title(&self, theme: &Theme) -> Option<Span<'static>>;
+    fn render_prompt(&self, area: Rect, buf: &mut Buffer, theme: &Theme);
+}
+
+impl TestScreen for Test {
+    /// Live figures shown with the prompt
+    fn status(&self, theme: &Theme) -> Vec<Span<'static>> {
+        let spans = [
+            self.pause_title(theme),
+            self.confirm_title(theme),
+            self.timer_title(theme),
+            self.rolling_title(theme),
+            self.stop_title(theme),
+            self.budget_title(theme),
+            self.clock_title(theme),
+        ];
+        let mut status = Vec::new();
+        for span in spans.into_iter().flatten() {
+            if !status.is_empty() {
+                status.push(Span::raw(" "));
+            }
+            status.push(span);
+        }
+        status
+    }
+
+    /// Shown once the test has paused for lack of keypresses
+    fn pause_title(&self, theme: &Theme) -> Option<Span<'static>> {
+        self.is_paused()
+            .then(|| Span::styled("Paused", theme.results_restart_prompt))
+    }
+
+    /// Shown once the last word is typed, if the test only ends on Enter
+    fn confirm_title(&self, theme: &Theme) -> Option<Span<'static>> {
+        self.awaiting_confirmation()
+            .then(|| Span::styled("Enter to finish", theme.results_restart_prompt))
+    }
+
+    /// Seconds left of a timed test, which starts counting down at the first keypress
+    fn timer_title(&self, theme: &Theme) -> Option<Span<'static>> {
+        let left = self.time_left()?;
+        Some(Span::styled(
+            format!("{:.0}s left", left.as_secs_f64().ceil()),
+            theme.results_overview,
+        ))
+    }
+
+    /// Time since the first keypress by both clocks, when debugging timing
+    fn clock_title(&self, theme: &Theme) -> Option<Span<'static>> {
+        if !self.clock_debug {
+            return None;
+        }
+        let text = match self.clock_drift() {
+            Some((monotonic, drift)) => format!(
+                "monotonic {:.1}s, wall {:.1}s, drift {:+.3}s",
+                monotonic,
+                monotonic + drift,
+                drift
+            ),
+            None => String::from("clocks start at the first keypress"),
         };
-        input.draw_inner(
-            &Line::from(self.words[self.current_word].progress.clone()),
-            buf,
-        );
-        input.render(buf);
+        Some(Span::styled(text, theme.results_restart_prompt))
+    }
+
+    /// Errors left in the budget, if there is one
+    fn budget_title(&self, theme: &Theme) -> Option<Span<'static>> {
+        let remaining = self.remaining_budget()?;
+        Some(match remaining {
+            ..=-1 => Span::styled(
+                format!("{} over error budget", -remaining),
+                theme.prompt_incorrect,
+            ),
+            1 => Span::styled("1 error left", theme.results_overview),
+            _ => Span::styled(format!("{} errors left", remaining), theme.results_overview),
+        })
+    }
+
+    /// Live WPM over the rolling window, for endless tests and tests with a pace to keep
+    fn rolling_title(&self, theme: &Theme) -> Option<Span<'static>> {
+        if self.hide_speed || (!self.endless && self.target_wpm.is_none()) {
+            return None;
+        }
+        let wpm = self
+            .rolling_wpm(ROLLING_WINDOW)
+            .filter(|wpm| wpm.is_finite());
+        let text = match wpm {
+            Some(wpm) => format!("{:.1} WPM (last {:.0}s)", wpm, ROLLING_WINDOW),
+            None => String::from("- WPM"),
+        };
+        // pulses twice a second once the pace has been too slow for too long
+        let style = match self.pace_alarm() {
+            Some(behind) if behind.as_millis() / 250 % 2 == 0 => {
+                theme.results_overview.add_modifier(Modifier::REVERSED)
+            }
+            _ => theme.results_overview,
+        };
+        Some(Span::styled(text, style))
+    }
+
+    /// How far an endless test is from the goal or limit it stops at
+    fn stop_title(&self, theme: &Theme) -> Option<Span<'static>> {
+        let mut parts = Vec::new();
+        if let Some(words) = self.stop_after_words {
+            let left = words.saturating_sub(self.correct_words());
+            parts.push(match left {
+                1 => String::from("1 word to go"),
+                _ => format!("{} words to go", left),
+            });
+        }
+        if let Some(min) = self.stop_below_accuracy {
+            parts.push(match self.rolling_accuracy(ACCURACY_WINDOW) {
+                Some(accuracy) => format!(
+                    "{:.1}% acc (last {}, ends below {:.0}%)",
+                    accuracy * 100.0,
+                    ACCURACY_WINDOW,
+                    min * 100.0
+                ),
+                None => format!("ends below {:.0}% acc", min * 100.0),
+            });
+        }
+        (!parts.is_empty()).then(|| Span::styled(parts.join(", "), theme.results_overview))
+    }
+
+    /// Draw the wrapped prompt, scrolled so the current word is visible
+    fn render_prompt(&self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let mut words = if self.hide_typed {
+            hidden_words_to_spans(&self.words, self.current_word, theme)
+        } else {
+            words_to_spans(&self.words, self.current_word, theme)
+        };
+        // the pace caret, then the earlier test being raced, as second carets
+        for (position, style) in [
+            (self.pace_position(), theme.prompt_pace),
+            (self.ghost_position(), theme.prompt_ghost),
+        ] {
+            let Some((i, typed)) = position.filter(|_| !self.complete) else {
+                continue;
+            };
+            let word = &self.words[i];
+            let gutter = if word.line.is_some() {
+                gutter_width(&self.words)
+            } else {
+                0
+            };
+            let spans = std::mem::take(&mut words[i]);
+            words[i] = mark_char(spans, gutter + word.indent + typed, style);
+        }
+        let width = area.width as usize;
+        let (lines, current_line) = if self.soft_wrap {
+            // the cursor sits at the first untyped char, which may be lines into a long word
+            let word = &self.words[self.current_word];
+            let gutter = if word.line.is_some() {
+                gutter_width(&self.words)
+            } else {
+                0
+            };
+            let cursor = gutter + word.indent + word.progress.chars().count();
+            soft_wrap(words, self.current_word, cursor, width)
+        } else {
+            let breaks: Vec<Vec<usize>> = if self.hyphenate {
+                self.words
+                    .iter()
+                    .map(|w| syllables::breaks(&w.text))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            wrap_words(words, &breaks, self.current_word, width)
+        };
+
+        // keep the line before the current one in view for context
+        let max_scroll = lines.len().saturating_sub(area.height as usize);
+        let scroll = current_line.saturating_sub(1).min(max_scroll) as u16;
+        Paragraph::new(lines).scroll((scroll, 0)).render(area, buf);
+    }
+}
+
+/// Break words into lines no wider than the given width, also returning the line
+/// containing the current word. Words which don't fit may be hyphenated at the given char
+/// offsets instead of moving whole to the next line.
+fn wrap_words<'a>(
+    words: Vec<Vec<Span<'a>>>,
+    breaks: &[Vec<usize>],
+    current_word: usize,
+    width: usize,
+) -> (Vec<Line<'a>>, usize) {
+    let mut lines: Vec<Line> = Vec::new();
+    let mut current_line: Vec<Span> = Vec::new();
+    let mut current_width = 0;
+    let mut current_word_line = 0;
+    for (i, mut word) in words.into_iter().enumerate() {
+        let mut word_width: usize = word.iter().map(|s| s.width()).sum();
+
+        if current_width > 0 && current_width + word_width > width {
+            // the latest break leaving room for the hyphen
+            let room = width.saturating_sub(current_width);
+            let hyphenated = breaks.get(i).into_iter().flatten().rev().find_map(|&at| {
+                let (head, tail) = split_spans(word.clone(), at);
+                let head_width: usize = head.iter().map(|s| s.width()).sum();
+                (head_width < room).then_some((head, tail))
+            });
+            if let Some((head, tail)) = hyphenated {
+                let style = head.last().map(|s| s.style).unwrap_or_default();
+                current_line.extend(head);
+                current_line.push(Span::styled("-", style));
+                word = tail;
+                word_width = word.iter().map(|s| s.width()).sum();
+            }
+
+            current_line.push(Span::raw("\n"));
+            lines.push(Line::from(std::mem::take(&mut current_line)));
+            current_width = 0;
+        }
 
-        let target_lines: Vec<Line> = {
-            let words = words_to_spans(&self.words, self.current_word, theme);
+        if i == current_word {
+            current_word_line = lines.len();
+        }
+        let ends_line = word.last().is_some_and(|span| span.content == "\n");
+        current_line.extend(word);
+        current_width += word_width;
+        if ends_line {
+            lines.push(Line::from(std::mem::take(&mut current_line)));
+            current_width = 0;
+        }
+    }
+    lines.push(Line::from(current_line));
 
-            let mut lines: Vec<Line> = Vec::new();
-            let mut current_line: Vec<Span> = Vec::new();
-            let mut current_width = 0;
-            for word in words {
-                let word_width: usize = word.iter().map(|s| s.width()).sum();
+    (lines, current_word_line)
+}
 
-                if current_width + word_width > chunks[1].width as usize - 2 {
+/// Break words into lines of exactly the given width, splitting words where they reach the
+/// edge, also returning the line holding the cursor, which is the given number of chars into
+/// the current word
+fn soft_wrap<'a>(
+    words: Vec<Vec<Span<'a>>>,
+    current_word: usize,
+    cursor: usize,
+    width: usize,
+) -> (Vec<Line<'a>>, usize) {
+    let mut lines: Vec<Line> = Vec::new();
+    let mut current_line: Vec<Span> = Vec::new();
+    let mut current_width = 0;
+    let mut cursor_line = 0;
+    for (i, word) in words.into_iter().enumerate() {
+        let mut offset = 0;
+        for span in word {
+            let mut piece = String::new();
+            for c in span.content.chars() {
+                if c == '\n' {
+                    if !piece.is_empty() {
+                        current_line.push(Span::styled(std::mem::take(&mut piece), span.style));
+                    }
                     current_line.push(Span::raw("\n"));
-                    lines.push(Line::from(current_line.clone()));
-                    current_line.clear();
+                    lines.push(Line::from(std::mem::take(&mut current_line)));
                     current_width = 0;
+                    offset += 1;
+                    continue;
                 }
-
-                current_line.extend(word);
-                current_width += word_width;
+                let char_width = Span::raw(c.to_string()).width();
+                if current_width > 0 && current_width + char_width > width {
+                    if !piece.is_empty() {
+                        current_line.push(Span::styled(std::mem::take(&mut piece), span.style));
+                    }
+                    current_line.push(Span::raw("\n"));
+                    lines.push(Line::from(std::mem::take(&mut current_line)));
+                    current_width = 0;
+                }
+                // past the end of an overtyped word, the cursor stays on the word's first line
+                if i == current_word && (offset == 0 || offset == cursor) {
+                    cursor_line = lines.len();
+                }
+                piece.push(c);
+                current_width += char_width;
+                offset += 1;
+            }
+            if !piece.is_empty() {
+                current_line.push(Span::styled(piece, span.style));
             }
-            lines.push(Line::from(current_line));
+        }
+    }
+    lines.push(Line::from(current_line));
 
-            lines
-        };
-        let target = Paragraph::new(target_lines).block(
-            Block::default()
-                .title(Span::styled("Prompt", theme.title))
-                .borders(Borders::ALL)
-                .border_type(theme.border_type)
-                .border_style(theme.prompt_border),
-        );
-        target.render(chunks[1], buf);
+    (lines, cursor_line)
+}
+
+/// Split spans after the given number of chars, keeping the style of each part
+fn split_spans(spans: Vec<Span<'_>>, at: usize) -> (Vec<Span<'_>>, Vec<Span<'_>>) {
+    let (mut head, mut tail) = (Vec::new(), Vec::new());
+    let mut remaining = at;
+    for span in spans {
+        let chars = span.content.chars().count();
+        if remaining >= chars {
+            remaining -= chars;
+            head.push(span);
+        } else if remaining == 0 {
+            tail.push(span);
+        } else {
+            let byte = span
+                .content
+                .char_indices()
+                .nth(remaining)
+                .map_or(span.content.len(), |(byte, _)| byte);
+            let (before, after) = span.content.split_at(byte);
+            head.push(Span::styled(before.to_string(), span.style));
+            tail.push(Span::styled(after.to_string(), span.style));
+            remaining = 0;
+        }
     }
+    (head, tail)
+}
+
+/// Spans with the char at an offset patched with a style
+fn mark_char(spans: Vec<Span<'_>>, at: usize, style: Style) -> Vec<Span<'_>> {
+    let (mut head, tail) = split_spans(spans, at);
+    let (marked, rest) = split_spans(tail, 1);
+    head.extend(
+        marked
+            .into_iter()
+            .map(|span| Span::styled(span.content, span.style.patch(style))),
+    );
+    head.extend(rest);
+    head
 }
 
 fn words_to_spans<'a>(
@@ -139,22 +580,100 @@ fn words_to_spans<'a>(
     theme: &'a Theme,
 ) -> Vec<Vec<Span<'a>>> {
     let mut spans = Vec::new();
+    let gutter = gutter_width(words);
 
     for word in &words[..current_word] {
         let parts = split_typed_word(word);
-        spans.push(word_parts_to_spans(parts, theme));
+        spans.push(lay_out(
+            word,
+            word_parts_to_spans(parts, &word.syntax, theme),
+            gutter,
+            theme,
+        ));
     }
 
-    let parts_current = split_current_word(&words[current_word]);
-    spans.push(word_parts_to_spans(parts_current, theme));
+    let current = &words[current_word];
+    let parts_current = split_current_word(current);
+    spans.push(lay_out(
+        current,
+        word_parts_to_spans(parts_current, &current.syntax, theme),
+        gutter,
+        theme,
+    ));
 
     for word in &words[current_word + 1..] {
         let parts = vec![(word.text.clone(), Status::Untyped)];
-        spans.push(word_parts_to_spans(parts, theme));
+        spans.push(lay_out(
+            word,
+            word_parts_to_spans(parts, &word.syntax, theme),
+            gutter,
+            theme,
+        ));
+    }
+    spans
+}
+
+/// Columns taken by the line numbers of code and the space after them, none for other tests
+fn gutter_width(words: &[TestWord]) -> usize {
+    words
+        .iter()
+        .filter_map(|word| word.line)
+        .max()
+        .map_or(0, |last| last.to_string().len() + 1)
+}
+
+/// Spans of a word placed as in its text, after its line's number and indentation and, at the
+/// end of a line, with a mark for the Enter which follows it before the line breaks
+fn lay_out<'a>(
+    word: &TestWord,
+    mut spans: Vec<Span<'a>>,
+    gutter: usize,
+    theme: &'a Theme,
+) -> Vec<Span<'a>> {
+    if word.line_end {
+        spans.pop();
+        spans.push(Span::styled("↵", theme.prompt_untyped));
+        spans.push(Span::raw("\n"));
+    }
+    if word.indent > 0 {
+        spans.insert(
+            0,
+            Span::styled(" ".repeat(word.indent), theme.prompt_untyped),
+        );
+    }
+    if let Some(line) = word.line {
+        let number = format!("{:>1$} ", line, gutter.saturating_sub(1));
+        spans.insert(0, Span::styled(number, theme.prompt_line_number));
     }
     spans
 }
 
+/// Spans of words which don't reveal what's been typed, except for the cursor position
+fn hidden_words_to_spans<'a>(
+    words: &'a [TestWord],
+    current_word: usize,
+    theme: &'a Theme,
+) -> Vec<Vec<Span<'a>>> {
+    let gutter = gutter_width(words);
+    words
+        .iter()
+        .enumerate()
+        .map(|(i, word)| {
+            let parts = if i == current_word {
+                split_hidden_word(word)
+            } else {
+                vec![(word.text.clone(), Status::Untyped)]
+            };
+            lay_out(
+                word,
+                word_parts_to_spans(parts, &word.syntax, theme),
+                gutter,
+                theme,
+            )
+        })
+        .collect()
+}
+
 #[derive(PartialEq, Clone, Copy, Debug)]
 enum Status {
     Correct,
@@ -210,6 +729,25 @@ fn split_current_word(word: &TestWord) -> Vec<(String, Status)> {
     parts
 }
 
+fn split_hidden_word(word: &TestWord) -> Vec<(String, Status)> {
+    let typed = word.progress.chars().count();
+    let before: String = word.text.chars().take(typed).collect();
+    let mut after = word.text.chars().skip(typed);
+
+    let mut parts = Vec::new();
+    if !before.is_empty() {
+        parts.push((before, Status::CurrentUntyped));
+    }
+    if let Some(cursor) = after.next() {
+        parts.push((cursor.to_string(), Status::Cursor));
+    }
+    let after: String = after.collect();
+    if !after.is_empty() {
+        parts.push((after, Status::CurrentUntyped));
+    }
+    parts
+}
+
 fn split_typed_word(word: &TestWord) -> Vec<(String, Status)> {
     let mut parts = Vec::new();
     let mut cur_string = String::new();
@@ -248,9 +786,35 @@ fn split_typed_word(word: &TestWord) -> Vec<(String, Status)> {
     parts
 }
 
-fn word_parts_to_spans(parts: Vec<(String, Status)>, theme: &Theme) -> Vec<Span<'_>> {
+/// Spans of the parts of a word, where untyped chars of code are styled by the syntax they're in
+fn word_parts_to_spans<'a>(
+    parts: Vec<(String, Status)>,
+    syntax: &[Syntax],
+    theme: &'a Theme,
+) -> Vec<Span<'a>> {
     let mut spans = Vec::new();
+    let mut offset = 0;
     for (text, status) in parts {
+        let start = offset;
+        offset += text.chars().count();
+        if status == Status::Untyped && syntax.len() >= offset {
+            // runs of chars in the same part of the code
+            let mut run = String::new();
+            let mut run_syntax = Syntax::Code;
+            for (c, &part) in text.chars().zip(&syntax[start..]) {
+                if part != run_syntax && !run.is_empty() {
+                    spans.push(Span::styled(
+                        std::mem::take(&mut run),
+                        syntax_style(run_syntax, theme),
+                    ));
+                }
+                run_syntax = part;
+                run.push(c);
+            }
+            spans.push(Span::styled(run, syntax_style(run_syntax, theme)));
+            continue;
+        }
+
         let style = match status {
             Status::Correct => theme.prompt_correct,
             Status::Incorrect => theme.prompt_incorrect,
@@ -268,136 +832,1670 @@ fn word_parts_to_spans(parts: Vec<(String, Status)>, theme: &Theme) -> Vec<Span<
     spans
 }
 
-impl ThemedWidget for &results::Results {
+/// Style of untyped chars in a part of the code
+fn syntax_style(syntax: Syntax, theme: &Theme) -> Style {
+    match syntax {
+        Syntax::Code => theme.prompt_untyped,
+        Syntax::Comment => theme.prompt_comment,
+        Syntax::String => theme.prompt_string,
+    }
+}
+
+/// Pacing animation which slowly fills and empties to guide relaxed breathing
+pub struct Breathing {
+    /// Breaths per minute
+    pub rate: f64,
+    pub start: Instant,
+}
+
+impl Breathing {
+    pub fn new(rate: f64) -> Self {
+        Self {
+            rate,
+            start: Instant::now(),
+        }
+    }
+
+    /// Position within the current breath, from 0 to 1
+    fn phase(&self) -> f64 {
+        (self.start.elapsed().as_secs_f64() * self.rate / 60.0).fract()
+    }
+}
+
+impl ThemedWidget for &Breathing {
+    fn render(self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        buf.set_style(area, theme.default);
+
+        let phase = self.phase();
+        let block = Block::default()
+            .title(Span::styled(
+                if phase < 0.5 { "In" } else { "Out" },
+                theme.title,
+            ))
+            .borders(Borders::ALL)
+            .border_type(theme.border_type)
+            .border_style(theme.breathing_guide_border);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        // ease in and out so the fill rests briefly when full and empty
+        let level = (1.0 - (phase * TAU).cos()) / 2.0;
+        let mut eighths = (level * f64::from(inner.height) * 8.0).round() as usize;
+        for y in (inner.top()..inner.bottom()).rev() {
+            let cell = BAR_EIGHTHS[eighths.min(8)].repeat(inner.width as usize);
+            buf.set_string(inner.x, y, cell, theme.breathing_guide);
+            eighths = eighths.saturating_sub(8);
+        }
+    }
+}
+
+/// WPM of recent results on one line under the prompt
+pub struct Trend<'a> {
+    /// WPM of the most recent completed tests, oldest first
+    pub recent: &'a [f64],
+}
+
+impl ThemedWidget for Trend<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let Some(latest) = self.recent.last() else {
+            return;
+        };
+        if area.height == 0 || area.width == 0 {
+            return;
+        }
+        let label = format!("Last {} ({:.0} WPM) ", self.recent.len(), latest);
+        let width = area.width as usize;
+        let line = match width.checked_sub(label.len()) {
+            Some(rest) if rest > 0 => label + &braille_sparkline(self.recent, rest),
+            _ => braille_sparkline(self.recent, width),
+        };
+        buf.set_stringn(area.x, area.y, line, width, theme.prompt_sparkline);
+    }
+}
+
+// Width of the list of theme keys in the designer
+const DESIGNER_LIST_WIDTH: u16 = 44;
+
+/// Editor for the theme's styles, previewed on a sample test drawn with them
+pub struct ThemeDesigner<'a> {
+    pub sample: &'a Test,
+    /// Keys of the theme and their styles, in the order they're listed
+    pub styles: &'a [(&'static str, Style)],
+    pub selected: usize,
+    /// How the selected style is written in the config file
+    pub value: &'a str,
+    /// Keys changed since the theme was last saved
+    pub changed: &'a BTreeSet<&'static str>,
+    /// Result of the last action, shown in place of the controls
+    pub message: &'a str,
+}
+
+impl ThemedWidget for ThemeDesigner<'_> {
     fn render(self, area: Rect, buf: &mut Buffer, theme: &Theme) {
         buf.set_style(area, theme.default);
 
-        // Chunks
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([Constraint::Min(1), Constraint::Length(1)])
             .split(area);
-        let res_chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .margin(1) // Graph looks tremendously better with just a little margin
-            .constraints([Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)])
-            .split(chunks[0]);
-        let info_chunks = Layout::default()
+        let columns = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
-            .split(res_chunks[0]);
+            .constraints([Constraint::Length(DESIGNER_LIST_WIDTH), Constraint::Min(1)])
+            .split(chunks[0]);
+
+        let block = Block::default()
+            .title(Span::styled("Theme", theme.title))
+            .borders(Borders::ALL)
+            .border_type(theme.border_type)
+            .border_style(theme.prompt_border);
+        let list = block.inner(columns[0]);
+        block.render(columns[0], buf);
+
+        // keep the selected key in view
+        let scroll = (self.selected + 1).saturating_sub(list.height as usize);
+        for (row, (i, (key, style))) in self
+            .styles
+            .iter()
+            .enumerate()
+            .skip(scroll)
+            .take(list.height as usize)
+            .enumerate()
+        {
+            let marker = if self.changed.contains(key) { "*" } else { " " };
+            let name_style = if i == self.selected {
+                theme.default.add_modifier(Modifier::REVERSED)
+            } else {
+                theme.default
+            };
+            let line = Line::from(vec![
+                Span::raw(marker),
+                Span::styled(format!("{:<30}", key), name_style),
+                Span::raw(" "),
+                Span::styled("Sample", *style),
+            ]);
+            buf.set_line(list.x, list.y + row as u16, &line, list.width);
+        }
+
+        let preview = columns[1];
+        self.sample.render(preview, buf, theme);
+        if let Some((key, _)) = self.styles.get(self.selected) {
+            let y = preview.y + TEST_FULL_SIZE.1 + 1;
+            if y < preview.bottom() {
+                let line = Span::styled(format!("{} = \"{}\"", key, self.value), theme.title);
+                buf.set_span(preview.x + 1, y, &line, preview.width.saturating_sub(1));
+            }
+        }
 
-        let msg = if self.missed_words.is_empty() {
-            "Press 'q' to quit or 'r' for another test"
+        let footer = if self.message.is_empty() {
+            "↑↓ key, ←→ foreground, [] background, 1-9 modifiers, 's' to save, 'q' to quit"
         } else {
-            "Press 'q' to quit, 'r' for another test or 'p' to practice missed words"
+            self.message
         };
+        let footer = Span::styled(footer, theme.results_restart_prompt);
+        buf.set_span(chunks[1].x, chunks[1].y, &footer, chunks[1].width);
+    }
+}
 
-        let exit = Span::styled(msg, theme.results_restart_prompt);
-        buf.set_span(chunks[1].x, chunks[1].y, &exit, chunks[1].width);
+impl ThemedWidget for &Rollover {
+    fn render(self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        buf.set_style(area, theme.default);
 
-        // Sections
-        let mut overview_text = Text::styled("", theme.results_overview);
-        overview_text.extend([
-            Line::from(format!(
-                "Adjusted WPM: {:.1}",
-                self.timing.overall_cps * WPM_PER_CPS * f64::from(self.accuracy.overall)
-            )),
-            Line::from(format!(
-                "Accuracy: {:.1}%",
-                f64::from(self.accuracy.overall) * 100f64
-            )),
-            Line::from(format!(
-                "Raw WPM: {:.1}",
-                self.timing.overall_cps * WPM_PER_CPS
-            )),
-            Line::from(format!("Correct Keypresses: {}", self.accuracy.overall)),
-        ]);
-        let overview = Paragraph::new(overview_text).block(
-            Block::default()
-                .title(Span::styled("Overview", theme.title))
-                .borders(Borders::ALL)
-                .border_type(theme.border_type)
-                .border_style(theme.results_overview_border),
-        );
-        overview.render(info_chunks[0], buf);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(4),
+                Constraint::Length(3),
+                Constraint::Min(1),
+                Constraint::Length(1),
+            ])
+            .split(area);
 
-        let mut worst_keys: Vec<(&KeyEvent, &Fraction)> = self
-            .accuracy
-            .per_key
-            .iter()
-            .filter(|(key, _)| matches!(key.code, KeyCode::Char(_)))
-            .collect();
-        worst_keys.sort_unstable_by_key(|x| x.1);
+        let held = if self.releases {
+            Text::from(vec![
+                Line::from(format!("Held: {}", spaced(self.held.iter().copied()))),
+                Line::from(format!("Most keys held at once: {}", self.most_held)),
+            ])
+        } else {
+            Text::from(vec![
+                Line::from(
+                    "This terminal doesn't report key releases, so held keys can't be shown.",
+                ),
+                Line::from("Each chord ends once all of its keys arrive, or with Enter."),
+            ])
+        };
+        Paragraph::new(held)
+            .style(theme.results_overview)
+            .block(
+                Block::default()
+                    .title(Span::styled("Rollover", theme.title))
+                    .borders(Borders::ALL)
+                    .border_type(theme.border_type)
+                    .border_style(theme.results_overview_border),
+            )
+            .render(chunks[0], buf);
 
-        let mut worst_text = Text::styled("", theme.results_worst_keys);
-        worst_text.extend(
-            worst_keys
-                .iter()
-                .filter_map(|(key, acc)| {
-                    if let KeyCode::Char(character) = key.code {
-                        let key_accuracy = f64::from(**acc) * 100.0;
-                        if key_accuracy != 100.0 {
-                            Some(format!("- {} at {:.1}% accuracy", character, key_accuracy))
-                        } else {
-                            None
-                        }
+        let chord = if self.complete() {
+            Line::from(Span::styled("Done", theme.prompt_correct))
+        } else {
+            let current = &self.current;
+            let mut spans: Vec<Span> = current
+                .chord
+                .chars()
+                .map(|c| {
+                    let style = if current.registered.contains(c) {
+                        theme.prompt_current_correct
                     } else {
-                        None
-                    }
+                        theme.prompt_current_untyped
+                    };
+                    Span::styled(format!("{} ", c), style)
                 })
-                .take(5)
-                .map(Line::from),
-        );
-        let worst = Paragraph::new(worst_text).block(
-            Block::default()
-                .title(Span::styled("Worst Keys", theme.title))
-                .borders(Borders::ALL)
-                .border_type(theme.border_type)
-                .border_style(theme.results_worst_keys_border),
-        );
-        worst.render(info_chunks[1], buf);
+                .collect();
+            if !current.ghosts.is_empty() {
+                spans.push(Span::styled(
+                    format!(" ghosts: {}", spaced(current.ghosts.chars())),
+                    theme.prompt_current_incorrect,
+                ));
+            }
+            Line::from(spans)
+        };
+        Paragraph::new(chord)
+            .block(
+                Block::default()
+                    .title(Span::styled(
+                        format!(
+                            "Press together ({}/{})",
+                            (self.attempts.len() + 1).min(CHORDS.len()),
+                            CHORDS.len()
+                        ),
+                        theme.title,
+                    ))
+                    .borders(Borders::ALL)
+                    .border_type(theme.border_type)
+                    .border_style(theme.prompt_border),
+            )
+            .render(chunks[1], buf);
 
-        let wpm_sma: Vec<(f64, f64)> = self
-            .timing
-            .per_event
-            .windows(WPM_SMA_WIDTH)
-            .enumerate()
-            .map(|(i, window)| {
-                (
-                    (i + WPM_SMA_WIDTH) as f64,
-                    window.len() as f64 / window.iter().copied().sum::<f64>() * WPM_PER_CPS,
-                )
+        let attempts: Vec<Line> = self
+            .attempts
+            .iter()
+            .map(|attempt| {
+                let missed = attempt.missed();
+                let mut spans = vec![Span::styled(
+                    format!(
+                        "{:<10} {}/{}",
+                        attempt.chord,
+                        attempt.registered.len(),
+                        attempt.chord.len()
+                    ),
+                    theme.results_overview,
+                )];
+                if !missed.is_empty() {
+                    spans.push(Span::styled(
+                        format!("  missed {}", spaced(missed.chars())),
+                        theme.prompt_incorrect,
+                    ));
+                }
+                if !attempt.ghosts.is_empty() {
+                    spans.push(Span::styled(
+                        format!("  ghosts {}", spaced(attempt.ghosts.chars())),
+                        theme.prompt_incorrect,
+                    ));
+                }
+                Line::from(spans)
             })
             .collect();
+        Paragraph::new(attempts)
+            .block(
+                Block::default()
+                    .title(Span::styled("Chords", theme.title))
+                    .borders(Borders::ALL)
+                    .border_type(theme.border_type)
+                    .border_style(theme.results_worst_keys_border),
+            )
+            .render(chunks[2], buf);
 
-        // Render the chart if possible
-        if !wpm_sma.is_empty() {
-            let wpm_sma_min = wpm_sma
-                .iter()
-                .map(|(_, x)| x)
-                .fold(f64::INFINITY, |a, &b| a.min(b));
-            let wpm_sma_max = wpm_sma
-                .iter()
-                .map(|(_, x)| x)
-                .fold(f64::NEG_INFINITY, |a, &b| a.max(b));
-
-            let wpm_datasets = vec![Dataset::default()
+        let exit = if self.releases {
+            "Hold each chord's keys together, then let go. Backspace to retry a chord, Esc to quit"
+        } else {
+            "Press each chord's keys together. Backspace to retry a chord, Esc to quit"
+        };
+        let exit = Span::styled(exit, theme.results_restart_prompt);
+        buf.set_span(chunks[3].x, chunks[3].y, &exit, chunks[3].width);
+    }
+}
+
+impl ThemedWidget for &Soak {
+    fn render(self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        buf.set_style(area, theme.default);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(5),
+                Constraint::Min(3),
+                Constraint::Length(1),
+            ])
+            .split(area);
+
+        let counts = Text::from(vec![
+            Line::from(format!(
+                "Round {}, {} keys at {:.1} keys/s",
+                self.round + 1,
+                self.keys,
+                self.keys_per_second()
+            )),
+            Line::from(format!(
+                "Dropped: {}   Repeated: {}   Unexpected: {}",
+                self.dropped, self.repeated, self.unexpected
+            )),
+            Line::from(format!("Render errors: {}", self.render_errors)),
+        ]);
+        Paragraph::new(counts)
+            .style(theme.results_overview)
+            .block(
+                Block::default()
+                    .title(Span::styled("Soak test", theme.title))
+                    .borders(Borders::ALL)
+                    .border_type(theme.border_type)
+                    .border_style(theme.results_overview_border),
+            )
+            .render(chunks[0], buf);
+
+        let (sent, rest) = self.text.split_at(self.position.min(self.text.len()));
+        let script = Line::from(vec![
+            Span::styled(sent.iter().collect::<String>(), theme.prompt_correct),
+            Span::styled(rest.iter().collect::<String>(), theme.prompt_untyped),
+        ]);
+        Paragraph::new(script)
+            .wrap(Wrap { trim: false })
+            .block(
+                Block::default()
+                    .title(Span::styled(
+                        format!("{}/{}", self.position, self.text.len()),
+                        theme.title,
+                    ))
+                    .borders(Borders::ALL)
+                    .border_type(theme.border_type)
+                    .border_style(theme.prompt_border),
+            )
+            .render(chunks[1], buf);
+
+        let exit = Span::styled(
+            "Send each line of `ttyper soak --script N` followed by Enter. Esc to stop",
+            theme.results_restart_prompt,
+        );
+        buf.set_span(chunks[2].x, chunks[2].y, &exit, chunks[2].width);
+    }
+}
+
+/// Keys separated by spaces, or a dash if there are none
+fn spaced(keys: impl Iterator<Item = char>) -> String {
+    let keys: Vec<String> = keys.map(String::from).collect();
+    if keys.is_empty() {
+        String::from("-")
+    } else {
+        keys.join(" ")
+    }
+}
+
+/// Live stats, recent results, and key accuracy shown beside a test
+pub struct Sidebar<'a> {
+    pub test: &'a Test,
+    /// WPM of the most recent completed tests, oldest first
+    pub recent: &'a [f64],
+    pub grades: &'a AccuracyGrades,
+    /// Letter rows of the keyboard layout, from the top down
+    pub rows: [&'a str; 3],
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Grade {
+    Good,
+    Fair,
+    Poor,
+}
+
+impl AccuracyGrades {
+    /// Grade a fraction of correct keypresses, from 0 to 1
+    fn grade(&self, accuracy: f64) -> Grade {
+        if accuracy >= self.good / 100.0 {
+            Grade::Good
+        } else if accuracy >= self.fair / 100.0 {
+            Grade::Fair
+        } else {
+            Grade::Poor
+        }
+    }
+}
+
+impl ThemedWidget for Sidebar<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        buf.set_style(area, theme.default);
+
+        let block = Block::default()
+            .title(Span::styled("Stats", theme.title))
+            .borders(Borders::ALL)
+            .border_type(theme.border_type)
+            .border_style(theme.sidebar_border);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(4),
+                Constraint::Length(4),
+                Constraint::Length(4),
+                Constraint::Min(0),
+            ])
+            .split(inner);
+
+        let results = results::Results::from(self.test);
+        let progress = self.test.progress();
+        let or_dash = |x: f64, text: String| if x.is_finite() { text } else { "-".into() };
+        let wpm = results.wpm();
+        let accuracy = f64::from(results.accuracy.overall) * 100.0;
+        let accuracy_style = match self.grades.grade(accuracy / 100.0) {
+            _ if !accuracy.is_finite() => theme.sidebar_stats,
+            Grade::Good => theme.sidebar_accuracy_good,
+            Grade::Fair => theme.sidebar_accuracy_fair,
+            Grade::Poor => theme.sidebar_accuracy_poor,
+        };
+        let mut stats_text = Text::styled("", theme.sidebar_stats);
+        if !self.test.hide_speed {
+            stats_text.extend([Line::from(format!(
+                "WPM: {}",
+                or_dash(wpm, format!("{:.1}", wpm))
+            ))]);
+        }
+        stats_text.extend([
+            Line::from(vec![
+                Span::raw("Accuracy: "),
+                Span::styled(
+                    or_dash(accuracy, format!("{:.1}%", accuracy)),
+                    accuracy_style,
+                ),
+            ]),
+            Line::from(format!(
+                "Word: {}/{}",
+                progress.numerator, progress.denominator
+            )),
+        ]);
+        Paragraph::new(stats_text).render(chunks[0], buf);
+
+        if !self.test.hide_speed && !chunks[1].is_empty() {
+            buf.set_span(
+                chunks[1].x,
+                chunks[1].y,
+                &Span::styled("Recent", theme.title),
+                chunks[1].width,
+            );
+            let recent: Vec<u64> = self.recent.iter().map(|&wpm| wpm.round() as u64).collect();
+            Sparkline::default()
+                .data(&recent)
+                .style(theme.sidebar_sparkline)
+                .render(chunks[1].inner(&Margin::new(0, 1)), buf);
+        }
+
+        if chunks[2].is_empty() {
+            return;
+        }
+        buf.set_span(
+            chunks[2].x,
+            chunks[2].y,
+            &Span::styled("Keys", theme.title),
+            chunks[2].width,
+        );
+        let mut per_key: HashMap<char, Fraction> = HashMap::new();
+        for (key, accuracy) in &results.accuracy.per_key {
+            if let KeyCode::Char(character) = key.code {
+                let total = per_key
+                    .entry(character.to_ascii_lowercase())
+                    .or_insert(Fraction::new(0, 0));
+                total.numerator += accuracy.numerator;
+                total.denominator += accuracy.denominator;
+            }
+        }
+        KeyHeatmap {
+            rows: self.rows,
+            keys: &per_key,
+            grades: self.grades,
+            misses: false,
+        }
+        .render(chunks[2].inner(&Margin::new(0, 1)), buf, theme);
+    }
+}
+
+/// Keyboard rows colored by the accuracy of each key, each row indented a little further than the last
+struct KeyHeatmap<'a> {
+    rows: [&'a str; 3],
+    keys: &'a HashMap<char, Fraction>,
+    grades: &'a AccuracyGrades,
+    /// Whether to count each key's misses under it, on wider keys
+    misses: bool,
+}
+
+impl ThemedWidget for KeyHeatmap<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let (cell, lines) = if self.misses { (4, 2) } else { (2, 1) };
+        for (i, row) in self.rows.iter().enumerate() {
+            let y = area.y + (i * lines) as u16;
+            if y + lines as u16 > area.bottom() {
+                break;
+            }
+            let indent = (i * cell / 2) as u16;
+            let (keys, misses): (Vec<Span>, Vec<Span>) = row
+                .chars()
+                .map(|character| {
+                    let accuracy = self.keys.get(&character);
+                    let style = match accuracy.map(|&f| self.grades.grade(f64::from(f))) {
+                        None => theme.sidebar_key_untyped,
+                        Some(Grade::Good) => theme.sidebar_key_good,
+                        Some(Grade::Fair) => theme.sidebar_key_fair,
+                        Some(Grade::Poor) => theme.sidebar_key_poor,
+                    };
+                    let missed = accuracy.map_or(0, |f| f.denominator - f.numerator);
+                    let missed = if missed > 0 {
+                        missed.to_string()
+                    } else {
+                        String::new()
+                    };
+                    (
+                        Span::styled(format!("{:^1$}", character, cell), style),
+                        Span::styled(format!("{:^1$}", missed, cell), style),
+                    )
+                })
+                .unzip();
+            let width = area.width.saturating_sub(indent);
+            buf.set_line(area.x + indent, y, &Line::from(keys), width);
+            if self.misses {
+                buf.set_line(area.x + indent, y + 1, &Line::from(misses), width);
+            }
+        }
+    }
+}
+
+// Braille dots which fill each column of a cell upwards, from the bottom row
+const BRAILLE_BLANK: u32 = 0x2800;
+const BRAILLE_LEFT: [u32; 4] = [0x40, 0x04, 0x02, 0x01];
+const BRAILLE_RIGHT: [u32; 4] = [0x80, 0x20, 0x10, 0x08];
+
+/// One-line chart of values, two per character, for places a full chart doesn't fit
+fn braille_sparkline(values: &[f64], width: usize) -> String {
+    let values: Vec<f64> = values.iter().copied().filter(|x| x.is_finite()).collect();
+    let columns = (width * 2).min(values.len());
+    if columns == 0 {
+        return String::new();
+    }
+
+    // average values into as many columns as fit
+    let buckets: Vec<f64> = (0..columns)
+        .map(|c| {
+            let bucket = &values[c * values.len() / columns..(c + 1) * values.len() / columns];
+            bucket.iter().sum::<f64>() / bucket.len() as f64
+        })
+        .collect();
+    let min = buckets.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = buckets.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    // every column gets at least one dot, so a flat trend is still visible
+    let height = |x: f64| {
+        let scaled = if max > min {
+            (x - min) / (max - min)
+        } else {
+            0.5
+        };
+        1 + (scaled * 3.0).round() as usize
+    };
+    let fill = |dots: &[u32; 4], x: f64| dots[..height(x)].iter().sum::<u32>();
+
+    buckets
+        .chunks(2)
+        .map(|pair| {
+            let left = fill(&BRAILLE_LEFT, pair[0]);
+            let right = pair.get(1).map_or(0, |&x| fill(&BRAILLE_RIGHT, x));
+            char::from_u32(BRAILLE_BLANK + left + right).expect("Braille pattern out of range.")
+        })
+        .collect()
+}
+
+/// Lines of large text, for stats which should be legible from across a room
+fn big_text(text: &str) -> Vec<String> {
+    (0..BIG_GLYPH_HEIGHT as usize)
+        .map(|row| {
+            text.chars()
+                .filter_map(|c| BIG_GLYPHS.iter().find(|(g, _)| *g == c))
+                .map(|(_, glyph)| glyph[row])
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+/// Live view of a test being run in another terminal
+pub struct Mirror<'a> {
+    pub snapshot: Option<&'a Snapshot>,
+    pub connected: bool,
+}
+
+// Smallest areas the mirror can be drawn in with large stats, and with a line of them
+const MIRROR_FULL_SIZE: (u16, u16) = (20, BIG_GLYPH_HEIGHT + 4);
+const MIRROR_COMPACT_SIZE: (u16, u16) = (10, 3);
+
+impl ThemedWidget for Mirror<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        buf.set_style(area, theme.default);
+        if area.is_empty() {
+            return;
+        }
+        let tier = LayoutTier::for_area(area, MIRROR_FULL_SIZE, MIRROR_COMPACT_SIZE);
+
+        // Chunks
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(if tier == LayoutTier::Full {
+                    BIG_GLYPH_HEIGHT + 2
+                } else {
+                    1
+                }),
+                Constraint::Min(1),
+                Constraint::Length(1),
+            ])
+            .split(area);
+        let hide_speed = self.snapshot.is_some_and(|snapshot| snapshot.hide_speed);
+        let stats_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(if hide_speed {
+                vec![Constraint::Ratio(1, 1)]
+            } else {
+                vec![Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)]
+            })
+            .split(chunks[0]);
+
+        let msg = match (self.connected, self.snapshot) {
+            (false, _) => "Test disconnected. Press 'q' to quit",
+            (true, None) => "Waiting for the test to start. Press 'q' to quit",
+            (true, Some(_)) => "Mirroring. Press 'q' to quit",
+        };
+        if chunks[2].height > 0 {
+            let exit = Span::styled(msg, theme.results_restart_prompt);
+            buf.set_span(chunks[2].x, chunks[2].y, &exit, chunks[2].width);
+        }
+
+        let Some(snapshot) = self.snapshot else {
+            return;
+        };
+
+        let wpm = format!("{:.0}", snapshot.wpm.unwrap_or(0.0));
+        let accuracy = format!("{:.0}%", snapshot.accuracy.unwrap_or(1.0) * 100.0);
+        let stats = if hide_speed {
+            vec![("Accuracy", accuracy)]
+        } else {
+            vec![("WPM", wpm), ("Accuracy", accuracy)]
+        };
+        if tier != LayoutTier::Full {
+            if chunks[0].height == 0 {
+                return;
+            }
+            let line = stats
+                .iter()
+                .map(|(title, value)| format!("{}: {}", title, value))
+                .collect::<Vec<_>>()
+                .join("  ");
+            let line = Span::styled(line, theme.results_overview);
+            buf.set_span(chunks[0].x, chunks[0].y, &line, chunks[0].width);
+        } else {
+            for (&chunk, (title, value)) in stats_chunks.iter().zip(stats) {
+                let lines: Vec<Line> = big_text(&value).into_iter().map(Line::from).collect();
+                Paragraph::new(lines)
+                    .style(theme.results_overview)
+                    .block(
+                        Block::default()
+                            .title(Span::styled(title, theme.title))
+                            .borders(Borders::ALL)
+                            .border_type(theme.border_type)
+                            .border_style(theme.results_overview_border),
+                    )
+                    .render(chunk, buf);
+            }
+        }
+
+        if !snapshot.words.is_empty() && chunks[1].height > 0 {
+            theme.apply_to(&snapshot.to_test()).render(chunks[1], buf);
+        }
+    }
+}
+
+/// Teacher's table of every student's progress on the current round
+pub struct ClassroomView<'a> {
+    pub rows: &'a [StudentRow],
+    pub round: usize,
+    pub port: u16,
+}
+
+impl ThemedWidget for ClassroomView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        buf.set_style(area, theme.default);
+        // the controls and at least one row above them
+        if area.height < 2 || area.width == 0 {
+            return;
+        }
+
+        // Chunks
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(area);
+
+        let exit = Span::styled(
+            format!(
+                "Students join with `ttyper classroom join <host>:{}`. Press 'n' to push a new test or 'q' to quit",
+                self.port
+            ),
+            theme.results_restart_prompt,
+        );
+        buf.set_span(chunks[1].x, chunks[1].y, &exit, chunks[1].width);
+
+        let rows: Vec<Row> = self
+            .rows
+            .iter()
+            .filter(|row| row.round == self.round)
+            .map(|row| {
+                Row::new(vec![
+                    row.name.clone(),
+                    format!("{}/{}", row.typed, row.total),
+                    row.wpm.map_or_else(|| "-".into(), |x| format!("{:.1}", x)),
+                    row.accuracy
+                        .map_or_else(|| "-".into(), |x| format!("{:.1}%", x * 100.0)),
+                    String::from(if row.complete { "yes" } else { "" }),
+                ])
+            })
+            .collect();
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Min(12),
+                Constraint::Length(10),
+                Constraint::Length(8),
+                Constraint::Length(10),
+                Constraint::Length(6),
+            ],
+        )
+        .style(theme.results_overview)
+        .header(Row::new(vec!["Student", "Words", "WPM", "Accuracy", "Done"]).style(theme.title))
+        .block(
+            Block::default()
+                .title(Span::styled(
+                    format!("Classroom - Test {}", self.round + 1),
+                    theme.title,
+                ))
+                .borders(Borders::ALL)
+                .border_type(theme.border_type)
+                .border_style(theme.results_overview_border),
+        );
+        table.render(chunks[0], buf);
+    }
+}
+
+/// Recorded tests to pick one from and open its results
+pub struct HistoryBrowser<'a> {
+    pub entries: &'a [Entry],
+    pub selected: usize,
+    /// Test marked to be compared with the next one marked
+    pub marked: Option<usize>,
+    pub keys: &'a Keybindings,
+    /// Result of the last action, shown in place of the controls
+    pub message: &'a str,
+}
+
+impl ThemedWidget for HistoryBrowser<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        buf.set_style(area, theme.default);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(area);
+
+        let footer = if !self.message.is_empty() {
+            self.message.to_owned()
+        } else if self.marked.is_some() {
+            String::from("Space on another test to compare them, or on the marked one to unmark it")
+        } else {
+            format!(
+                "↑↓ test, Enter to open its results, Space to compare two, '{}' to quit",
+                self.keys.quit
+            )
+        };
+        let footer = Span::styled(footer, theme.results_restart_prompt);
+        buf.set_span(chunks[1].x, chunks[1].y, &footer, chunks[1].width);
+
+        let block = Block::default()
+            .title(Span::styled(
+                match self.entries.len() {
+                    1 => String::from("History - 1 test"),
+                    count => format!("History - {} tests", count),
+                },
+                theme.title,
+            ))
+            .borders(Borders::ALL)
+            .border_type(theme.border_type)
+            .border_style(theme.stats_overview_border);
+        let list = block.inner(chunks[0]);
+        block.render(chunks[0], buf);
+        if list.height < 2 {
+            return;
+        }
+
+        let header = Span::styled(
+            format!(
+                "  {:<16}  {:<10}  {:<20}  {:>6}  {:>8}  {:>8}",
+                "Completed (UTC)", "Mode", "Language", "WPM", "Accuracy", "Duration"
+            ),
+            theme.title,
+        );
+        buf.set_span(list.x, list.y, &header, list.width);
+
+        // keep the selected test in view, below the header
+        let height = list.height as usize - 1;
+        let scroll = (self.selected + 1).saturating_sub(height);
+        for (row, (i, entry)) in self
+            .entries
+            .iter()
+            .enumerate()
+            .skip(scroll)
+            .take(height)
+            .enumerate()
+        {
+            // tests recorded before their details were kept can't be opened
+            let mut style = if entry.detailed {
+                theme.stats_overview
+            } else {
+                theme.prompt_untyped
+            };
+            if i == self.selected {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            let line = Span::styled(
+                format!(
+                    "{} {:<16}  {:<10}  {:<20}  {:>6.1}  {:>7.1}%  {:>7.0}s",
+                    if self.marked == Some(i) { '*' } else { ' ' },
+                    date::format_time(entry.timestamp),
+                    entry.mode,
+                    entry.language,
+                    entry.wpm,
+                    entry.accuracy * 100.0,
+                    entry.duration
+                ),
+                style,
+            );
+            buf.set_span(list.x, list.y + 1 + row as u16, &line, list.width);
+        }
+    }
+}
+
+/// Two recorded tests side by side, the earlier first, with what changed from one to the other
+pub struct Comparison<'a> {
+    pub tests: [(&'a Entry, &'a Results); 2],
+    pub keys: &'a Keybindings,
+}
+
+impl ThemedWidget for Comparison<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        buf.set_style(area, theme.default);
+        let [(before_entry, before), (after_entry, after)] = self.tests;
+        let labels = self
+            .tests
+            .map(|(entry, _)| date::format_time(entry.timestamp));
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(11),
+                Constraint::Min(1),
+                Constraint::Length(1),
+            ])
+            .split(area);
+        let info_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
+            .split(chunks[0]);
+
+        let footer = Span::styled(
+            format!("'{}' to go back to the history", self.keys.quit),
+            theme.results_restart_prompt,
+        );
+        buf.set_span(chunks[2].x, chunks[2].y, &footer, chunks[2].width);
+
+        let row = |name: &str, before: f64, after: f64, unit: &str| {
+            Line::from(format!(
+                "{:<11}{:>17}{:>17}{:>+8.1}",
+                name,
+                format!("{:.1}{}", before, unit),
+                format!("{:.1}{}", after, unit),
+                after - before
+            ))
+        };
+        let percent = |fraction: Fraction| f64::from(fraction) * 100.0;
+        let mut overview_text = Text::styled("", theme.results_overview);
+        overview_text.extend([
+            Line::from(format!(
+                "{:<11}{:>17}{:>17}{:>8}",
+                "", "Before", "After", "Change"
+            )),
+            Line::from(format!(
+                "{:<11}{:>17}{:>17}",
+                "Completed", labels[0], labels[1]
+            )),
+            Line::from(format!(
+                "{:<11}{:>17}{:>17}",
+                "Language", before_entry.language, after_entry.language
+            )),
+            row("WPM", before.wpm(), after.wpm(), ""),
+            row("Raw WPM", before.raw_wpm(), after.raw_wpm(), ""),
+            row(
+                "Accuracy",
+                percent(before.accuracy.overall),
+                percent(after.accuracy.overall),
+                "%",
+            ),
+            row(
+                "Consistency",
+                before.consistency() * 100.0,
+                after.consistency() * 100.0,
+                "%",
+            ),
+            row("Duration", before.duration(), after.duration(), "s"),
+        ]);
+        Paragraph::new(overview_text)
+            .block(
+                Block::default()
+                    .title(Span::styled("Comparison", theme.title))
+                    .borders(Borders::ALL)
+                    .border_type(theme.border_type)
+                    .border_style(theme.results_overview_border),
+            )
+            .render(info_chunks[0], buf);
+
+        // keys typed in both tests, by how much their accuracy and then their speed changed
+        let mut changes: Vec<KeyChange> = after
+            .accuracy
+            .per_key
+            .iter()
+            .filter_map(|(key, &now)| {
+                let speed = |results: &Results| {
+                    results
+                        .timing
+                        .per_key
+                        .get(key)
+                        .filter(|&&wait| wait > 0.0)
+                        .map(|wait| WPM_PER_CPS / wait)
+                };
+                Some(KeyChange {
+                    label: key_label(key)?,
+                    before: *before.accuracy.per_key.get(key)?,
+                    after: now,
+                    speedup: speed(after)
+                        .zip(speed(before))
+                        .map(|(now, then)| now - then),
+                })
+            })
+            .collect();
+        changes.sort_by(|a, b| {
+            let accuracy =
+                |change: &KeyChange| (percent(change.after) - percent(change.before)).abs();
+            let speed = |change: &KeyChange| change.speedup.map_or(0.0, f64::abs);
+            accuracy(b)
+                .total_cmp(&accuracy(a))
+                .then(speed(b).total_cmp(&speed(a)))
+        });
+        let mut keys_text = Text::styled("", theme.results_worst_keys);
+        keys_text.extend(changes.iter().take(7).map(|change| {
+            let mut line = format!(
+                "- {} {:.1}% to {:.1}% accuracy",
+                change.label,
+                percent(change.before),
+                percent(change.after)
+            );
+            if let Some(speedup) = change.speedup {
+                line += &format!(", {:+.1} WPM", speedup);
+            }
+            Line::from(line)
+        }));
+        Paragraph::new(keys_text)
+            .block(
+                Block::default()
+                    .title(Span::styled("Key Changes", theme.title))
+                    .borders(Borders::ALL)
+                    .border_type(theme.border_type)
+                    .border_style(theme.results_worst_keys_border),
+            )
+            .render(info_chunks[1], buf);
+
+        // both tests' speed over their keypresses, one over the other
+        let trends = [before.wpm_sma(), after.wpm_sma()];
+        let points = || trends.iter().flatten().map(|&(_, wpm)| wpm);
+        if points().next().is_none() {
+            return;
+        }
+        let min = points().fold(f64::INFINITY, f64::min);
+        let max = points().fold(f64::NEG_INFINITY, f64::max);
+        let keypresses = before
+            .timing
+            .per_event
+            .len()
+            .max(after.timing.per_event.len());
+        let datasets = trends
+            .iter()
+            .zip(["Before", "After"])
+            .zip([theme.results_chart_segments, theme.results_chart])
+            .map(|((trend, name), style)| {
+                Dataset::default()
+                    .name(name)
+                    .marker(Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(style)
+                    .data(trend)
+            })
+            .collect();
+        let y_label_min = min as u16;
+        let y_label_max = (max as u16).max(y_label_min + 6);
+        Chart::new(datasets)
+            .block(Block::default().title(vec![Span::styled("Chart", theme.title)]))
+            .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)))
+            .x_axis(
+                Axis::default()
+                    .title(Span::styled("Keypresses", theme.results_chart_x))
+                    .bounds([0.0, keypresses as f64]),
+            )
+            .y_axis(
+                Axis::default()
+                    .title(Span::styled(
+                        "WPM (10-keypress rolling average)",
+                        theme.results_chart_y,
+                    ))
+                    .bounds([min, max])
+                    .labels(
+                        (y_label_min..y_label_max)
+                            .step_by(5)
+                            .map(|n| Span::raw(format!("{}", n)))
+                            .collect(),
+                    ),
+            )
+            .render(chunks[1], buf);
+    }
+}
+
+/// How the accuracy and speed of a key changed between two tests
+struct KeyChange {
+    label: String,
+    before: Fraction,
+    after: Fraction,
+    speedup: Option<f64>,
+}
+
+// Smallest areas the results screen can be drawn in with each tier
+const RESULTS_FULL_SIZE: (u16, u16) = (40, 20);
+const RESULTS_COMPACT_SIZE: (u16, u16) = (20, 3);
+
+/// Count of a noun, like `1 day` or `3 days`
+fn plural(count: usize, noun: &str) -> String {
+    format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" })
+}
+
+/// Days in the streak, with how it's being kept up
+fn streak_summary(streak: &stats::Streak) -> String {
+    let mut details = Vec::new();
+    if streak.on_vacation {
+        details.push(String::from("on vacation"));
+    }
+    if streak.frozen > 0 {
+        details.push(format!("{} frozen", plural(streak.frozen, "day")));
+    }
+    if let Some(left) = streak.freezes_left {
+        details.push(format!("{} left this month", plural(left, "freeze")));
+    }
+
+    let days = plural(streak.days, "day");
+    if details.is_empty() {
+        days
+    } else {
+        format!("{} ({})", days, details.join(", "))
+    }
+}
+
+/// Name of a typed key, telling numpad digits apart from the number row where the terminal
+/// reports which was used
+fn key_label(key: &KeyEvent) -> Option<String> {
+    let KeyCode::Char(character) = key.code else {
+        return None;
+    };
+    Some(if key.state.contains(KeyEventState::KEYPAD) {
+        format!("numpad {}", character)
+    } else {
+        character.to_string()
+    })
+}
+
+/// How much faster letters are typed by alternating hands than by the same hand
+fn alternation_summary(alternation: results::Alternation) -> String {
+    format!(
+        "Hand alternation: {:+.1} WPM over same hand",
+        alternation.alternating_wpm - alternation.same_hand_wpm
+    )
+}
+
+/// Accuracy on capitals with each Shift key, or a dash for one which had none to type
+fn shifts_summary(shifts: results::Shifts) -> String {
+    let accuracy = |fraction: results::Fraction| {
+        if fraction.denominator == 0 {
+            String::from("-")
+        } else {
+            format!("{:.1}%", f64::from(fraction) * 100.0)
+        }
+    };
+    format!(
+        "Capitals: {} left Shift, {} right",
+        accuracy(shifts.left),
+        accuracy(shifts.right)
+    )
+}
+
+/// Count of chattered keypresses, followed by the keys they came from, most first
+fn chatter_summary(chatter: &BTreeMap<char, usize>) -> String {
+    let total: usize = chatter.values().sum();
+    if total == 0 {
+        return String::from("none");
+    }
+
+    let mut keys: Vec<(&char, &usize)> = chatter.iter().collect();
+    keys.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+    let keys: Vec<String> = keys
+        .iter()
+        .map(|(key, count)| format!("{} ×{}", key, count))
+        .collect();
+    format!("{} ({})", total, keys.join(", "))
+}
+
+/// Words of a test typed slowest, and those with the most errors, side by side
+struct WordsPanel<'a>(&'a [WordStats]);
+
+impl ThemedWidget for WordsPanel<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let halves = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
+            .split(area);
+        let times = |word: &WordStats| {
+            if word.typed > 1 {
+                format!(" ×{}", word.typed)
+            } else {
+                String::new()
+            }
+        };
+
+        let ranked = |sort| stats::problem_words(self.0.to_vec(), sort, 1);
+        let slowest: Vec<Line> = ranked(stats::WordSort::Slowest)
+            .iter()
+            .map(|word| {
+                Line::from(format!(
+                    "{:<16} {:>6.1} WPM{}",
+                    word.text,
+                    word.wpm().unwrap_or_default(),
+                    times(word)
+                ))
+            })
+            .collect();
+        let missed: Vec<Line> = ranked(stats::WordSort::Missed)
+            .iter()
+            .map(|word| {
+                let errors = if word.errors == 1 { "error" } else { "errors" };
+                Line::from(format!(
+                    "{:<16} {:>3} {}{}",
+                    word.text,
+                    word.errors,
+                    errors,
+                    times(word)
+                ))
+            })
+            .collect();
+
+        for (lines, title, half) in [
+            (slowest, "Slowest Words", halves[0]),
+            (missed, "Most Missed Words", halves[1]),
+        ] {
+            Paragraph::new(lines)
+                .style(theme.results_worst_keys)
+                .block(
+                    Block::default()
+                        .title(Span::styled(title, theme.title))
+                        .borders(Borders::ALL)
+                        .border_type(theme.border_type)
+                        .border_style(theme.results_worst_keys_border),
+                )
+                .render(half, buf);
+        }
+    }
+}
+
+/// Chart shown under the results overview
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResultsChart {
+    #[default]
+    Wpm,
+    Timeline,
+    Rhythm,
+    Keys,
+    Words,
+}
+
+impl ResultsChart {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Wpm => Self::Timeline,
+            Self::Timeline => Self::Rhythm,
+            Self::Rhythm => Self::Keys,
+            Self::Keys => Self::Words,
+            Self::Words => Self::Wpm,
+        }
+    }
+}
+
+/// Free-text note attached to a result from the results screen
+#[derive(Debug, Default)]
+pub struct ResultNote {
+    /// Row of the result in the history, if it was recorded
+    pub id: Option<i64>,
+    pub text: String,
+    /// Text being typed, while the note is edited
+    pub draft: Option<String>,
+}
+
+pub struct ResultsView<'a> {
+    pub results: &'a results::Results,
+    pub chart: ResultsChart,
+    pub note: &'a ResultNote,
+    pub grades: &'a AccuracyGrades,
+    /// Letter rows of the keyboard layout, from the top down
+    pub rows: [&'a str; 3],
+    pub keys: &'a Keybindings,
+    /// Formulas of extra results from the config
+    pub metrics: &'a Metrics,
+    /// Whether speed is left out, leaving accuracy and errors
+    pub hide_speed: bool,
+    /// Whether the results are from an exported file, which can only be looked at
+    pub viewing: bool,
+}
+
+impl ThemedWidget for ResultsView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        buf.set_style(area, theme.default);
+        let results = self.results;
+        let keys = self.keys;
+
+        let accuracy = f64::from(results.accuracy.overall) * 100f64;
+        let trend: Vec<f64> = results.wpm_sma().into_iter().map(|(_, wpm)| wpm).collect();
+        let alternation = results.alternation(&self.rows).filter(|_| !self.hide_speed);
+        let delimiters = results.delimiters();
+        let shifts = results.shifts(&self.rows);
+        let metrics: Vec<(&String, Option<f64>)> = self
+            .metrics
+            .iter()
+            .filter(|(_, formula)| !(self.hide_speed && formula.uses_speed()))
+            .map(|(name, formula)| (name, formula.eval(results)))
+            .collect();
+        match LayoutTier::for_area(area, RESULTS_FULL_SIZE, RESULTS_COMPACT_SIZE) {
+            LayoutTier::Full => {}
+            LayoutTier::Compact => {
+                let hint = match &self.note.draft {
+                    Some(draft) => format!("note: {}", draft),
+                    None if self.viewing => format!("{} quit, {} charts", keys.quit, keys.chart),
+                    None if results.missed_words.is_empty() => {
+                        format!(
+                            "{} quit, {} new, {} again",
+                            keys.quit, keys.next_test, keys.repeat
+                        )
+                    }
+                    None => format!(
+                        "{} quit, {} new, {} again, {} practice",
+                        keys.quit, keys.next_test, keys.repeat, keys.practice
+                    ),
+                };
+                let accuracy = Span::styled(
+                    format!("Accuracy: {:.1}%", accuracy),
+                    theme.results_overview,
+                );
+                let lines = if self.hide_speed {
+                    vec![
+                        accuracy,
+                        Span::styled(
+                            format!("Correct Keypresses: {}", results.accuracy.overall),
+                            theme.results_overview,
+                        ),
+                        Span::styled(
+                            format!(
+                                "Backspaces: {} ({} deleted)",
+                                results.corrections.backspaces, results.corrections.deleted
+                            ),
+                            theme.results_overview,
+                        ),
+                    ]
+                } else {
+                    vec![
+                        Span::styled(format!("WPM: {:.1}", results.wpm()), theme.results_overview),
+                        accuracy,
+                        Span::styled(
+                            format!(
+                                "Trend: {}",
+                                braille_sparkline(&trend, area.width as usize - 7)
+                            ),
+                            theme.results_chart,
+                        ),
+                        Span::styled(
+                            format!("Raw WPM: {:.1}", results.raw_wpm()),
+                            theme.results_overview,
+                        ),
+                    ]
+                };
+                // the keys always take the last line, with as many stats above it as fit
+                let shown = lines.len().min(area.height as usize - 1);
+                for (y, line) in (area.y..).zip(&lines[..shown]) {
+                    buf.set_span(area.x, y, line, area.width);
+                }
+                let hint = Span::styled(hint, theme.results_restart_prompt);
+                buf.set_span(area.x, area.bottom() - 1, &hint, area.width);
+                return;
+            }
+            LayoutTier::Minimal => {
+                if area.is_empty() {
+                    return;
+                }
+                let speed = if self.hide_speed {
+                    String::new()
+                } else {
+                    format!("{:.0} wpm ", results.wpm())
+                };
+                let summary = if self.viewing {
+                    format!("{}{:.0}% {} ", speed, accuracy, keys.quit)
+                } else {
+                    format!(
+                        "{}{:.0}% {}/{} ",
+                        speed, accuracy, keys.quit, keys.next_test
+                    )
+                };
+                let summary = Span::styled(summary, theme.results_overview);
+                let (x, _) = buf.set_span(area.x, area.y, &summary, area.width);
+                let rest = area.right().saturating_sub(x);
+                // the summary can fill the whole line, leaving no room for the trend
+                if self.hide_speed || rest == 0 {
+                    return;
+                }
+                let trend = Span::styled(
+                    braille_sparkline(&trend, rest as usize),
+                    theme.results_chart,
+                );
+                buf.set_span(x, area.y, &trend, rest);
+                return;
+            }
+        }
+
+        // Chunks
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(area);
+        let res_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1) // Graph looks tremendously better with just a little margin
+            // room for every line of the overview, leaving the rest to the chart
+            .constraints([
+                Constraint::Length(
+                    if self.hide_speed { 8 } else { 11 }
+                        + u16::from(results.syllables.is_some() && !self.hide_speed)
+                        + u16::from(alternation.is_some())
+                        + u16::from(delimiters.is_some())
+                        + u16::from(shifts.is_some())
+                        + metrics.len() as u16
+                        + u16::from(results.paused > 0.0)
+                        + u16::from(results.attribution.is_some())
+                        + u16::from(results.seed.is_some())
+                        + u16::from(results.sudden_death.is_some())
+                        + u16::from(results.strictness != Strictness::Forgiving),
+                ),
+                Constraint::Min(1),
+            ])
+            .split(chunks[0]);
+        let info_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
+            .split(res_chunks[0]);
+
+        let mut actions = vec![format!("'{}' to quit", keys.quit)];
+        if !self.viewing {
+            actions.push(format!("'{}' for another test", keys.next_test));
+            actions.push(format!("'{}' to take it again", keys.repeat));
+        }
+        // the chart of misses by key is the only one without speed
+        if !self.hide_speed {
+            actions.push(format!("'{}' for charts", keys.chart));
+        }
+        if !self.viewing {
+            actions.push(format!("'{}' to share", keys.share));
+        }
+        if !self.viewing && !results.missed_words.is_empty() {
+            actions.push(format!("'{}' to practice", keys.practice));
+            actions.push(format!("'{}' to copy missed words", keys.missed_words));
+        }
+        let msg = match actions.split_last() {
+            Some((last, [])) => format!("Press {}", last),
+            Some((last, rest)) => format!("Press {} or {}", rest.join(", "), last),
+            None => String::new(),
+        };
+
+        if let Some(draft) = &self.note.draft {
+            let editor = Line::from(vec![
+                Span::styled("Note: ", theme.input_border),
+                Span::raw(draft.as_str()),
+                Span::styled(" ", theme.prompt_cursor),
+                Span::styled(
+                    format!("  (Enter to save, {} to cancel)", keys.end),
+                    theme.results_restart_prompt,
+                ),
+            ]);
+            buf.set_line(chunks[1].x, chunks[1].y, &editor, chunks[1].width);
+        } else {
+            let exit = Span::styled(msg, theme.results_restart_prompt);
+            let (x, _) = buf.set_span(chunks[1].x, chunks[1].y, &exit, chunks[1].width);
+
+            // the note, or how to add one, goes on the right if there's room
+            let hint = match (self.note.id, self.note.text.as_str()) {
+                (None, _) => None,
+                (Some(_), "") => Some(Span::styled(
+                    format!("'{}' for a note", keys.note),
+                    theme.results_restart_prompt,
+                )),
+                (Some(_), text) => Some(Span::styled(
+                    format!("Note: {}", text),
+                    theme.results_overview,
+                )),
+            };
+            if let Some(hint) = hint {
+                let width = hint.width() as u16;
+                if chunks[1].right() >= x + width + 2 {
+                    buf.set_span(chunks[1].right() - width, chunks[1].y, &hint, width);
+                }
+            }
+        }
+
+        // Sections
+        let mut overview_text = Text::styled("", theme.results_overview);
+        if self.hide_speed {
+            overview_text.extend([Line::from(format!("Accuracy: {:.1}%", accuracy))]);
+        } else {
+            overview_text.extend([
+                Line::from(format!("Adjusted WPM: {:.1}", results.wpm())),
+                Line::from(format!("Accuracy: {:.1}%", accuracy)),
+                Line::from(format!("Raw WPM: {:.1}", results.raw_wpm())),
+                Line::from(format!(
+                    "Burst WPM ({:.0}s): {}",
+                    BURST_WINDOW,
+                    results
+                        .peak_wpm(BURST_WINDOW)
+                        .map_or_else(|| String::from("-"), |wpm| format!("{:.1}", wpm))
+                )),
+            ]);
+        }
+        overview_text.extend([
+            Line::from(format!("Correct Keypresses: {}", results.accuracy.overall)),
+            Line::from(format!(
+                "Backspaces: {} ({} deleted)",
+                results.corrections.backspaces, results.corrections.deleted
+            )),
+            Line::from(format!(
+                "Wasted Keystrokes: {:.1}%",
+                f64::from(results.corrections.wasted()) * 100.0
+            )),
+            Line::from(format!("Chatter: {}", chatter_summary(&results.chatter))),
+        ]);
+        if let Some(attribution) = &results.attribution {
+            overview_text.extend([Line::from(format!("Quote by {}", attribution))]);
+        }
+        if let Some(seed) = results.seed {
+            overview_text.extend([Line::from(format!("Seed: {}", seed))]);
+        }
+        if let Some(sudden_death) = results.sudden_death {
+            overview_text.extend([Line::from(format!("Sudden death: {}", sudden_death.name()))]);
+        }
+        if results.strictness != Strictness::Forgiving {
+            overview_text.extend([Line::from(format!(
+                "Strictness: {}",
+                results.strictness.name()
+            ))]);
+        }
+        if results.paused > 0.0 {
+            overview_text.extend([Line::from(format!("Paused: {:.0}s", results.paused))]);
+        }
+        if let Some(syllables) = results.syllables_per_minute().filter(|_| !self.hide_speed) {
+            overview_text.extend([Line::from(format!(
+                "Syllables per minute: {:.1}",
+                syllables
+            ))]);
+        }
+        if let Some(alternation) = alternation {
+            overview_text.extend([Line::from(alternation_summary(alternation))]);
+        }
+        if let Some(delimiters) = delimiters {
+            let mut line = format!(
+                "Delimiters and quotes: {:.1}%",
+                f64::from(delimiters.accuracy) * 100.0
+            );
+            if let Some(wpm) = delimiters.wpm.filter(|_| !self.hide_speed) {
+                line += &format!(" at {:.1} WPM", wpm);
+            }
+            overview_text.extend([Line::from(line)]);
+        }
+        if let Some(shifts) = shifts {
+            overview_text.extend([Line::from(shifts_summary(shifts))]);
+        }
+        overview_text.extend(metrics.iter().map(|(name, value)| {
+            Line::from(format!(
+                "{}: {}",
+                name,
+                value.map_or_else(|| String::from("-"), |value| format!("{:.2}", value))
+            ))
+        }));
+        let overview = Paragraph::new(overview_text).block(
+            Block::default()
+                .title(Span::styled("Overview", theme.title))
+                .borders(Borders::ALL)
+                .border_type(theme.border_type)
+                .border_style(theme.results_overview_border),
+        );
+        overview.render(info_chunks[0], buf);
+
+        let mut worst_keys: Vec<(&KeyEvent, &Fraction)> = results
+            .accuracy
+            .per_key
+            .iter()
+            .filter(|(key, _)| matches!(key.code, KeyCode::Char(_)))
+            .collect();
+        worst_keys.sort_unstable_by_key(|x| x.1);
+
+        let mut worst_text = Text::styled("", theme.results_worst_keys);
+        worst_text.extend(
+            worst_keys
+                .iter()
+                .filter_map(|(key, acc)| {
+                    let key_accuracy = f64::from(**acc) * 100.0;
+                    if key_accuracy != 100.0 {
+                        key_label(key)
+                            .map(|label| format!("- {} at {:.1}% accuracy", label, key_accuracy))
+                    } else {
+                        None
+                    }
+                })
+                .take(5)
+                .map(Line::from),
+        );
+        let worst = Paragraph::new(worst_text).block(
+            Block::default()
+                .title(Span::styled("Worst Keys", theme.title))
+                .borders(Borders::ALL)
+                .border_type(theme.border_type)
+                .border_style(theme.results_worst_keys_border),
+        );
+        worst.render(info_chunks[1], buf);
+
+        let chart = if self.hide_speed {
+            ResultsChart::Keys
+        } else {
+            self.chart
+        };
+        if chart == ResultsChart::Timeline {
+            Timeline(&results.seconds()).render(res_chunks[1], buf, theme);
+            return;
+        }
+        if chart == ResultsChart::Rhythm {
+            Rhythm(&results.keystrokes).render(res_chunks[1], buf, theme);
+            return;
+        }
+        if chart == ResultsChart::Words {
+            WordsPanel(&results::word_stats(&results.words)).render(res_chunks[1], buf, theme);
+            return;
+        }
+
+        if chart == ResultsChart::Keys {
+            let block = Block::default().title(Span::styled("Misses by Key", theme.title));
+            let inner = block.inner(res_chunks[1]);
+            block.render(res_chunks[1], buf);
+            let mut per_key: HashMap<char, Fraction> = HashMap::new();
+            for (character, accuracy) in &results.accuracy.per_expected {
+                let total = per_key
+                    .entry(character.to_ascii_lowercase())
+                    .or_insert(Fraction::new(0, 0));
+                total.numerator += accuracy.numerator;
+                total.denominator += accuracy.denominator;
+            }
+            KeyHeatmap {
+                rows: self.rows,
+                keys: &per_key,
+                grades: self.grades,
+                misses: true,
+            }
+            .render(inner, buf, theme);
+            return;
+        }
+
+        let wpm_sma = results.wpm_sma();
+
+        // Render the chart if possible
+        if !wpm_sma.is_empty() {
+            let wpm_sma_min = wpm_sma
+                .iter()
+                .map(|(_, x)| x)
+                .fold(f64::INFINITY, |a, &b| a.min(b));
+            let wpm_sma_max = wpm_sma
+                .iter()
+                .map(|(_, x)| x)
+                .fold(f64::NEG_INFINITY, |a, &b| a.max(b));
+
+            // vertical lines where the difficulty steps up, named in the legend from left to right
+            let segments: Vec<(&str, [(f64, f64); 2])> = results
+                .segments
+                .iter()
+                .filter(|&&(start, _)| start > 0)
+                .map(|&(start, name)| {
+                    let x = start as f64;
+                    (name, [(x, wpm_sma_min), (x, wpm_sma_max)])
+                })
+                .collect();
+
+            let mut wpm_datasets = vec![Dataset::default()
                 .name("WPM")
                 .marker(Marker::Braille)
                 .graph_type(GraphType::Line)
                 .style(theme.results_chart)
                 .data(&wpm_sma)];
+            wpm_datasets.extend(segments.iter().map(|(name, line)| {
+                Dataset::default()
+                    .name(*name)
+                    .marker(Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(theme.results_chart_segments)
+                    .data(line)
+            }));
 
             let y_label_min = wpm_sma_min as u16;
             let y_label_max = (wpm_sma_max as u16).max(y_label_min + 6);
 
             let wpm_chart = Chart::new(wpm_datasets)
                 .block(Block::default().title(vec![Span::styled("Chart", theme.title)]))
+                .hidden_legend_constraints((Constraint::Ratio(1, 4), Constraint::Ratio(1, 2)))
                 .x_axis(
                     Axis::default()
                         .title(Span::styled("Keypresses", theme.results_chart_x))
-                        .bounds([0.0, self.timing.per_event.len() as f64]),
+                        .bounds([0.0, results.timing.per_event.len() as f64]),
                 )
                 .y_axis(
                     Axis::default()
@@ -418,9 +2516,439 @@ impl ThemedWidget for &results::Results {
     }
 }
 
+/// Speed over each second of a test, with the raw speed of the second and its errors
+struct Timeline<'a>(&'a [results::Second]);
+
+impl ThemedWidget for Timeline<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let Some(last) = self.0.last() else {
+            Block::default()
+                .title(Span::styled("WPM over Time", theme.title))
+                .render(area, buf);
+            return;
+        };
+        let wpm: Vec<(f64, f64)> = self.0.iter().map(|s| (s.end, s.wpm)).collect();
+        let raw: Vec<(f64, f64)> = self.0.iter().map(|s| (s.end, s.raw_wpm)).collect();
+        let errors: Vec<(f64, f64)> = self
+            .0
+            .iter()
+            .filter(|s| s.errors > 0)
+            .map(|s| (s.end, s.raw_wpm))
+            .collect();
+        let max = raw
+            .iter()
+            .chain(&wpm)
+            .map(|&(_, wpm)| wpm)
+            .filter(|wpm| wpm.is_finite())
+            .fold(0.0, f64::max)
+            .max(10.0);
+
+        let datasets = vec![
+            Dataset::default()
+                .name("WPM")
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(theme.results_chart)
+                .data(&wpm),
+            Dataset::default()
+                .name("Raw")
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(theme.prompt_untyped)
+                .data(&raw),
+            Dataset::default()
+                .name("Errors")
+                .marker(Marker::Dot)
+                .graph_type(GraphType::Scatter)
+                .style(theme.prompt_incorrect)
+                .data(&errors),
+        ];
+        let label = |value: f64| Span::raw(format!("{:.0}", value));
+        Chart::new(datasets)
+            .block(Block::default().title(Span::styled("WPM over Time", theme.title)))
+            .hidden_legend_constraints((Constraint::Ratio(1, 4), Constraint::Ratio(1, 2)))
+            .x_axis(
+                Axis::default()
+                    .title(Span::styled("Seconds", theme.results_chart_x))
+                    .bounds([0.0, last.end])
+                    .labels(vec![label(0.0), label(last.end)]),
+            )
+            .y_axis(
+                Axis::default()
+                    .title(Span::styled("WPM", theme.results_chart_y))
+                    .bounds([0.0, max])
+                    .labels(vec![label(0.0), label(max / 2.0), label(max)]),
+            )
+            .render(area, buf);
+    }
+}
+
+// Rows of the rhythm chart, with the width of their labels
+const RHYTHM_LANES: [&str; 3] = ["Correct", "Error", "Fix"];
+const RHYTHM_LABEL_WIDTH: u16 = 8;
+
+/// Every keystroke as a tick on a timeline, in lanes by correctness, so hesitations stand out
+struct Rhythm<'a>(&'a [results::Keystroke]);
+
+impl ThemedWidget for Rhythm<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let block = Block::default().title(Span::styled("Rhythm", theme.title));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let Some(last) = self.0.last() else {
+            return;
+        };
+        let width = inner.width.saturating_sub(RHYTHM_LABEL_WIDTH) as usize;
+        if width == 0 || inner.height < RHYTHM_LANES.len() as u16 + 1 {
+            return;
+        }
+
+        // ticks per column of each lane
+        let mut lanes = vec![vec![0usize; width]; RHYTHM_LANES.len()];
+        for keystroke in self.0 {
+            let lane = match (keystroke.key.code, keystroke.correct) {
+                (KeyCode::Backspace, _) | (_, None) => 2,
+                (_, Some(true)) => 0,
+                (_, Some(false)) => 1,
+            };
+            let column = if last.time > 0.0 {
+                (keystroke.time / last.time * (width - 1) as f64).round() as usize
+            } else {
+                0
+            };
+            lanes[lane][column] += 1;
+        }
+
+        let styles = [
+            theme.prompt_correct,
+            theme.prompt_incorrect,
+            theme.prompt_untyped,
+        ];
+        for (i, (lane, ticks)) in RHYTHM_LANES.iter().zip(&lanes).enumerate() {
+            let y = inner.y + i as u16;
+            buf.set_span(
+                inner.x,
+                y,
+                &Span::styled(*lane, theme.results_chart_y),
+                RHYTHM_LABEL_WIDTH,
+            );
+            for (x, &count) in (inner.x + RHYTHM_LABEL_WIDTH..).zip(ticks) {
+                let tick = match count {
+                    0 => continue,
+                    1 => "│",
+                    _ => "┃",
+                };
+                buf.set_string(x, y, tick, styles[i]);
+            }
+        }
+
+        let axis_y = inner.y + RHYTHM_LANES.len() as u16;
+        let end = format!("{:.1}s", last.time);
+        buf.set_string(
+            inner.x + RHYTHM_LABEL_WIDTH,
+            axis_y,
+            "0s",
+            theme.results_chart_x,
+        );
+        buf.set_string(
+            inner.right().saturating_sub(end.len() as u16),
+            axis_y,
+            end,
+            theme.results_chart_x,
+        );
+    }
+}
+
+// Width of the stats screen's list of weak words
+const WEAK_WORDS_WIDTH: u16 = 24;
+
+// Rows of the WPM histogram under the stats, with its borders, legend and axis
+const HISTOGRAM_HEIGHT: u16 = 12;
+
+/// Spread of the WPM of a group of tests, with its mean, median and one standard deviation
+/// either side of the mean marked
+struct Histogram<'a> {
+    distribution: &'a stats::Distribution,
+    /// Position of the group among those which can be switched between, counting from 1
+    position: (usize, usize),
+}
+
+impl ThemedWidget for Histogram<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let distribution = self.distribution;
+        let (position, groups) = self.position;
+        let title = if groups > 1 {
+            format!(
+                "WPM Spread: {} ({}/{})",
+                distribution.mode, position, groups
+            )
+        } else {
+            format!("WPM Spread: {}", distribution.mode)
+        };
+        let block = Block::default()
+            .title(Span::styled(title, theme.title))
+            .borders(Borders::ALL)
+            .border_type(theme.border_type)
+            .border_style(theme.stats_overview_border);
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if inner.width < 2 || inner.height < 4 {
+            return;
+        }
+
+        let (mean, median, std_dev) = (
+            distribution.mean(),
+            distribution.median(),
+            distribution.std_dev(),
+        );
+        let legend = format!(
+            "│ mean {:.1}  ┆ median {:.1}  ├┤ ±1σ {:.1}  ({} tests)",
+            mean,
+            median,
+            std_dev,
+            distribution.wpm.len()
+        );
+        buf.set_stringn(
+            inner.x,
+            inner.y,
+            legend,
+            inner.width as usize,
+            theme.stats_overview,
+        );
+
+        // one column per bin, as in `Distribution::histogram`
+        let bins = inner.width as usize;
+        let (min, max) = distribution.range();
+        let column = |wpm: f64| {
+            let bin = ((wpm - min) / (max - min) * bins as f64).max(0.0) as usize;
+            inner.x + bin.min(bins - 1) as u16
+        };
+        let counts = distribution.histogram(bins);
+        let most = counts.iter().copied().max().unwrap_or_default().max(1);
+        let bars = inner.height - 3;
+        let bottom = inner.y + bars;
+        for (x, &count) in (inner.x..).zip(&counts) {
+            // any tests at all show as at least a sliver
+            let mut eighths = (count * usize::from(bars) * 8).div_ceil(most);
+            for y in (inner.y + 1..=bottom).rev() {
+                buf.set_string(x, y, BAR_EIGHTHS[eighths.min(8)], theme.results_chart);
+                eighths = eighths.saturating_sub(8);
+            }
+        }
+        // markers go above the bars, where they'd hide nothing
+        for (wpm, marker) in [(median, "┆"), (mean, "│")] {
+            let x = column(wpm);
+            for y in inner.y + 1..=bottom {
+                if buf.get(x, y).symbol() == " " || buf.get(x, y).symbol() == "┆" {
+                    buf.set_string(x, y, marker, theme.results_chart_segments);
+                }
+            }
+        }
+
+        let axis = bottom + 1;
+        buf.set_string(inner.x, axis, "─".repeat(bins), theme.results_chart_x);
+        buf.set_string(
+            column(mean - std_dev),
+            axis,
+            "├",
+            theme.results_chart_segments,
+        );
+        buf.set_string(
+            column(mean + std_dev),
+            axis,
+            "┤",
+            theme.results_chart_segments,
+        );
+        let (low, high) = (format!("{:.0}", min), format!("{:.0} WPM", max));
+        buf.set_string(inner.x, axis + 1, &low, theme.results_chart_x);
+        if inner.width as usize > low.len() + high.len() {
+            buf.set_string(
+                inner.right() - high.len() as u16,
+                axis + 1,
+                &high,
+                theme.results_chart_x,
+            );
+        }
+    }
+}
+
+pub struct StatsView<'a> {
+    pub stats: &'a Stats,
+    pub keys: &'a Keybindings,
+    /// Which of the stats' distributions the histogram shows
+    pub shown: usize,
+}
+
+impl ThemedWidget for StatsView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let keys = self.keys;
+        let stats = self.stats;
+        buf.set_style(area, theme.default);
+        // the controls and at least one row above them
+        if area.height < 2 || area.width == 0 {
+            return;
+        }
+
+        // Chunks
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(area);
+
+        let mut actions = vec![
+            format!("'{}' to quit", keys.quit),
+            format!("'{}' to start a test", keys.next_test),
+        ];
+        if !stats.weakest_words.is_empty() {
+            actions.push(format!("'{}' to drill weak words", keys.drill));
+        }
+        if stats.distributions.len() > 1 {
+            actions.push(format!("'{}' for another mode", keys.chart));
+        }
+        let msg = match actions.split_last() {
+            Some((last, [])) => format!("Press {}", last),
+            Some((last, rest)) => format!("Press {} or {}", rest.join(", "), last),
+            None => String::new(),
+        };
+        let exit = Span::styled(msg, theme.results_restart_prompt);
+        buf.set_span(chunks[1].x, chunks[1].y, &exit, chunks[1].width);
+
+        let mut overview_area = chunks[0];
+        let distribution = stats.distributions.get(self.shown);
+        if let Some(distribution) = distribution.filter(|_| area.height > 2 * HISTOGRAM_HEIGHT) {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Length(HISTOGRAM_HEIGHT)])
+                .split(chunks[0]);
+            overview_area = rows[0];
+            let histogram = Histogram {
+                distribution,
+                position: (self.shown + 1, stats.distributions.len()),
+            };
+            histogram.render(rows[1], buf, theme);
+        }
+        if !stats.weakest_words.is_empty() {
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Min(1), Constraint::Length(WEAK_WORDS_WIDTH)])
+                .split(overview_area);
+            overview_area = columns[0];
+
+            let mut weak_text = Text::styled("", theme.stats_overview);
+            weak_text.extend(
+                stats
+                    .weakest_words
+                    .iter()
+                    .enumerate()
+                    .map(|(i, word)| Line::from(format!("{:>2}. {}", i + 1, word))),
+            );
+            Paragraph::new(weak_text)
+                .block(
+                    Block::default()
+                        .title(Span::styled("Weakest Words", theme.title))
+                        .borders(Borders::ALL)
+                        .border_type(theme.border_type)
+                        .border_style(theme.stats_overview_border),
+                )
+                .render(columns[1], buf);
+        }
+
+        let mut overview_text = Text::styled("", theme.stats_overview);
+        if stats.tests == 0 {
+            overview_text.extend([Line::from("No completed tests yet.")]);
+        } else {
+            overview_text.extend([
+                Line::from(format!("Tests completed: {}", stats.tests)),
+                Line::from(format!(
+                    "Average WPM: {:.1} (faster than ~{:.0}% of typists)",
+                    stats.average_wpm,
+                    stats::wpm_percentile(stats.average_wpm)
+                )),
+                Line::from(format!(
+                    "Best WPM: {:.1} (faster than ~{:.0}% of typists)",
+                    stats.best_wpm,
+                    stats::wpm_percentile(stats.best_wpm)
+                )),
+                Line::from(format!(
+                    "Average accuracy: {:.1}% (more accurate than ~{:.0}% of typists)",
+                    stats.average_accuracy * 100.0,
+                    stats::accuracy_percentile(stats.average_accuracy)
+                )),
+                Line::from(format!(
+                    "Best burst WPM: {}",
+                    stats
+                        .best_burst_wpm
+                        .map_or_else(|| String::from("-"), |wpm| format!("{:.1}", wpm))
+                )),
+                Line::from(format!("Rank: {}", stats::rank(stats.average_wpm))),
+                Line::from(format!(
+                    "Time spent typing: {}m {}s",
+                    stats.total_duration as u64 / 60,
+                    stats.total_duration as u64 % 60
+                )),
+            ]);
+            overview_text.extend([Line::from(format!(
+                "Streak: {}",
+                streak_summary(&stats.streak)
+            ))]);
+            if stats.daily_streak > 0 {
+                overview_text.extend([Line::from(format!(
+                    "Daily challenge streak: {}",
+                    plural(stats.daily_streak, "day")
+                ))]);
+            }
+            if let Some(language) = &stats.favorite_language {
+                overview_text.extend([Line::from(format!("Favorite language: {}", language))]);
+            }
+            if let Some((backspaces, wasted)) = stats.corrections {
+                overview_text.extend([Line::from(format!(
+                    "Corrections: {:.1} backspaces per test, {:.1}% of keystrokes wasted",
+                    backspaces,
+                    wasted * 100.0
+                ))]);
+            }
+            if let Some(alternation) = stats.alternation {
+                overview_text.extend([Line::from(alternation_summary(alternation))]);
+            }
+            if !stats.recent_notes.is_empty() {
+                overview_text.extend([Line::from(""), Line::from("Recent notes:")]);
+                overview_text.extend(
+                    stats
+                        .recent_notes
+                        .iter()
+                        .map(|(wpm, note)| Line::from(format!("  {:.1} WPM: {}", wpm, note))),
+                );
+            }
+            if !stats.keyboards.is_empty() {
+                overview_text.extend([Line::from(""), Line::from("Keyboards:")]);
+                overview_text.extend(stats.keyboards.iter().map(|keyboard| {
+                    Line::from(format!(
+                        "  {}: {} {}, {:.1} WPM, {:.1}% accuracy",
+                        keyboard.label,
+                        keyboard.tests,
+                        if keyboard.tests == 1 { "test" } else { "tests" },
+                        keyboard.average_wpm,
+                        keyboard.average_accuracy * 100.0
+                    ))
+                }));
+            }
+        }
+        let overview = Paragraph::new(overview_text).block(
+            Block::default()
+                .title(Span::styled("Stats", theme.title))
+                .borders(Borders::ALL)
+                .border_type(theme.border_type)
+                .border_style(theme.stats_overview_border),
+        );
+        overview.render(overview_area, buf);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ratatui::style::{Color, Style};
 
     mod split_words {
         use super::Status::*;
@@ -508,5 +3036,262 @@ mod tests {
                 assert_eq!(got, expected);
             }
         }
+
+        #[test]
+        fn hidden_word_split() {
+            let cases = vec![
+                TestCase {
+                    word: "monkeytype",
+                    progress: "",
+                    expected: vec![("m", Cursor), ("onkeytype", CurrentUntyped)],
+                },
+                TestCase {
+                    word: "monkeytype",
+                    progress: "monkeXt",
+                    expected: vec![
+                        ("monkeyt", CurrentUntyped),
+                        ("y", Cursor),
+                        ("pe", CurrentUntyped),
+                    ],
+                },
+                TestCase {
+                    word: "monkeytype",
+                    progress: "monkeytypes",
+                    expected: vec![("monkeytype", CurrentUntyped)],
+                },
+            ];
+
+            for case in cases {
+                let (word, expected) = setup(case);
+                let got = split_hidden_word(&word);
+                assert_eq!(got, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn draws_braille_sparklines() {
+        assert_eq!(braille_sparkline(&[], 5), "");
+        assert_eq!(braille_sparkline(&[1.0, 2.0, 3.0, 4.0], 5), "⣠⣾");
+        // values are averaged down to two per character
+        assert_eq!(braille_sparkline(&[1.0, 1.0, 4.0, 4.0], 1), "⣸");
+        assert_eq!(braille_sparkline(&[3.0, 3.0, f64::NAN], 3), "⣶");
+    }
+
+    #[test]
+    fn wraps_words_and_finds_current_line() {
+        let words: Vec<Vec<Span>> = ["one ", "two ", "three ", "four "]
+            .into_iter()
+            .map(|word| vec![Span::raw(word)])
+            .collect();
+
+        let (lines, current) = wrap_words(words.clone(), &[], 2, 9);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(current, 1);
+
+        // words wider than the area get a line of their own rather than an empty one
+        let (lines, current) = wrap_words(words, &[], 3, 2);
+        assert_eq!(lines.len(), 4);
+        assert_eq!(current, 3);
+    }
+
+    #[test]
+    fn hyphenates_words_at_line_ends() {
+        let words = vec![
+            vec![Span::raw("an ")],
+            vec![
+                Span::styled("hyph", Style::new().fg(Color::Green)),
+                Span::raw("enation "),
+            ],
+        ];
+        let breaks = [vec![], vec![2, 6, 7]];
+
+        let (lines, current) = wrap_words(words.clone(), &breaks, 1, 10);
+        let text: Vec<String> = lines
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        assert_eq!(text, ["an hyphen-\n", "ation "]);
+        assert_eq!(lines[0].spans[1].style, Style::new().fg(Color::Green));
+        assert_eq!(current, 1);
+
+        // without room for any part and the hyphen, the word moves down whole
+        let (lines, _) = wrap_words(words, &breaks, 1, 5);
+        assert_eq!(lines[1].spans[0].content, "hyph");
+    }
+
+    #[test]
+    fn soft_wraps_at_the_width() {
+        let words = vec![
+            vec![Span::raw("a ")],
+            vec![
+                Span::styled("long", Style::new().fg(Color::Green)),
+                Span::raw("_token "),
+            ],
+        ];
+        let (lines, current) = soft_wrap(words.clone(), 1, 0, 4);
+        let text: Vec<String> = lines
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        assert_eq!(text, ["a lo\n", "ng_t\n", "oken\n", " "]);
+        assert_eq!(lines[1].spans[0].style, Style::new().fg(Color::Green));
+        assert_eq!(current, 0);
+
+        // the cursor follows the typing onto the word's later lines
+        let (_, current) = soft_wrap(words, 1, 7, 4);
+        assert_eq!(current, 2);
+    }
+
+    #[test]
+    fn summarizes_streaks() {
+        let mut streak = stats::Streak {
+            days: 1,
+            ..Default::default()
+        };
+        assert_eq!(streak_summary(&streak), "1 day");
+
+        streak.days = 200;
+        streak.frozen = 3;
+        streak.freezes_left = Some(1);
+        streak.on_vacation = true;
+        assert_eq!(
+            streak_summary(&streak),
+            "200 days (on vacation, 3 days frozen, 1 freeze left this month)"
+        );
+    }
+
+    #[test]
+    fn labels_numpad_keys() {
+        let mut key = KeyEvent::from(KeyCode::Char('5'));
+        assert_eq!(key_label(&key).as_deref(), Some("5"));
+        key.state = KeyEventState::KEYPAD;
+        assert_eq!(key_label(&key).as_deref(), Some("numpad 5"));
+        assert_eq!(key_label(&KeyEvent::from(KeyCode::Backspace)), None);
+    }
+
+    #[test]
+    fn summarizes_chatter() {
+        assert_eq!(chatter_summary(&BTreeMap::new()), "none");
+        assert_eq!(
+            chatter_summary(&BTreeMap::from([('a', 1), ('e', 3), ('s', 1)])),
+            "5 (e ×3, a ×1, s ×1)"
+        );
+    }
+
+    /// Draw widgets into every area up to `max`, which panics if one draws outside its area
+    fn render_up_to<W: ThemedWidget>(max: (u16, u16), widget: impl Fn() -> W) {
+        let theme = Theme::default();
+        for width in 0..=max.0 {
+            for height in 0..=max.1 {
+                let area = Rect::new(0, 0, width, height);
+                widget().render(area, &mut Buffer::empty(area), &theme);
+            }
+        }
+    }
+
+    fn finished_test() -> Test {
+        let mut test = Test::new(vec![TestWord::from("ab"), TestWord::from("c")], true, false);
+        let start = Instant::now();
+        let keys = ['a', 'x', 'b', ' ', 'c'];
+        for (i, key) in keys.into_iter().enumerate() {
+            let time = start + std::time::Duration::from_millis(250 * i as u64);
+            test.handle_key_at(KeyEvent::from(KeyCode::Char(key)), time);
+        }
+        assert!(test.complete);
+        test
+    }
+
+    #[test]
+    fn draws_results_in_narrow_areas() {
+        let results = Results::from(&finished_test());
+        let note = ResultNote::default();
+        let grades = AccuracyGrades::default();
+        let keys = Keybindings::default();
+        let metrics = Metrics::new();
+        let max = (RESULTS_COMPACT_SIZE.0 + 5, RESULTS_COMPACT_SIZE.1 + 2);
+        for hide_speed in [false, true] {
+            render_up_to(max, || ResultsView {
+                results: &results,
+                chart: ResultsChart::Wpm,
+                note: &note,
+                grades: &grades,
+                rows: ["qwertyuiop", "asdfghjkl", "zxcvbnm"],
+                keys: &keys,
+                metrics: &metrics,
+                hide_speed,
+                viewing: false,
+            });
+        }
+    }
+
+    #[test]
+    fn draws_the_mirror_in_small_areas() {
+        let mut test = finished_test();
+        let snapshot = Snapshot::from(&test);
+        test.hide_speed = true;
+        let hidden = Snapshot::from(&test);
+        for snapshot in [None, Some(&snapshot), Some(&hidden)] {
+            render_up_to((MIRROR_FULL_SIZE.0 + 5, MIRROR_FULL_SIZE.1 + 2), || {
+                Mirror {
+                    snapshot,
+                    connected: true,
+                }
+            });
+        }
+    }
+
+    #[test]
+    fn draws_the_sidebar_in_small_areas() {
+        let test = finished_test();
+        let grades = AccuracyGrades::default();
+        render_up_to((30, 16), || Sidebar {
+            test: &test,
+            recent: &[60.0, 70.0],
+            grades: &grades,
+            rows: ["qwertyuiop", "asdfghjkl", "zxcvbnm"],
+        });
+    }
+
+    #[test]
+    fn draws_stats_in_short_areas() {
+        let stats = Stats::default();
+        let keys = Keybindings::default();
+        render_up_to((30, 4), || StatsView {
+            stats: &stats,
+            keys: &keys,
+            shown: 0,
+        });
+    }
+
+    #[test]
+    fn draws_the_classroom_in_short_areas() {
+        let rows = [StudentRow {
+            student: 0,
+            name: String::from("ada"),
+            round: 0,
+            typed: 1,
+            total: 2,
+            wpm: Some(60.0),
+            accuracy: None,
+            complete: false,
+        }];
+        render_up_to((30, 4), || ClassroomView {
+            rows: &rows,
+            round: 0,
+            port: 7878,
+        });
+    }
+
+    #[test]
+    fn grades_accuracy() {
+        let grades = AccuracyGrades {
+            good: 90.0,
+            fair: 70.0,
+        };
+        assert_eq!(grades.grade(1.0), Grade::Good);
+        assert_eq!(grades.grade(0.9), Grade::Good);
+        assert_eq!(grades.grade(0.75), Grade::Fair);
+        assert_eq!(grades.grade(0.5), Grade::Poor);
     }
 }
@@ -0,0 +1,51 @@
+//! Results of an exported test, opened again in the results screen
+
+use crate::config::Config;
+use crate::test::results::Results;
+use crate::ui::{ResultNote, ResultsChart, ResultsView};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{backend::Backend, terminal::Terminal};
+use std::io;
+
+pub fn run<B: Backend>(
+    terminal: &mut Terminal<B>,
+    config: &Config,
+    results: &Results,
+) -> io::Result<()> {
+    let keys = &config.keybindings;
+    let note = ResultNote::default();
+    let mut chart = ResultsChart::default();
+    loop {
+        terminal.draw(|f| {
+            let view = ResultsView {
+                results,
+                chart,
+                note: &note,
+                grades: &config.accuracy_grades,
+                rows: config.keyboard.rows(),
+                keys,
+                metrics: &config.metrics,
+                hide_speed: false,
+                viewing: true,
+            };
+            f.render_widget(config.theme.apply_to(view), f.size());
+        })?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        match key {
+            KeyEvent {
+                code: KeyCode::Char('c'),
+                modifiers: KeyModifiers::CONTROL,
+                kind: KeyEventKind::Press,
+                ..
+            } => break,
+            key if keys.quit.matches(&key) || keys.end.matches(&key) => break,
+            key if keys.chart.matches(&key) => chart = chart.next(),
+            _ => {}
+        }
+    }
+    Ok(())
+}